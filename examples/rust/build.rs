@@ -1,6 +1,6 @@
 fn main() {
-    futhark_bindgen::build(
-        futhark_bindgen::Backend::from_env().unwrap_or(futhark_bindgen::Backend::C),
+    futhark_bindgen::build_with(
+        &futhark_bindgen::BackendSelector::new(),
         "example.fut",
         "example.rs",
     )
@@ -0,0 +1,266 @@
+//! Proc-macro front end for `futhark-bindgen`
+//!
+//! `include_futhark!` generates Rust bindings for a Futhark program at compile time and
+//! expands to the generated items, so no `include!` from `$OUT_DIR` is needed:
+//!
+//! ```ignore
+//! mod kernels {
+//!     futhark_bindgen_macros::include_futhark!("src/kernels.fut", backend = "multicore");
+//! }
+//! ```
+//!
+//! Paths are relative to `CARGO_MANIFEST_DIR`. The following options are accepted:
+//!
+//! - `backend = "..."`: Futhark backend, `c` by default
+//! - `manifest = "..."`: use a checked-in manifest instead of running `futhark`
+//! - `naming = "..."`: naming configuration, by default `futhark-bindgen.toml` or
+//!   `[package.metadata.futhark-bindgen]` is used, see `futhark_bindgen::NamingConfig`
+//! - `compiler = "..."`: name or path of the `futhark` executable
+//! - `entry_points_within_context = true`: generate entry points as `Context` methods
+//!
+//! When no manifest is given the program is compiled into `Compiler::cache_dir`, inside
+//! `$OUT_DIR` if the crate has a build script or the target directory otherwise. Output
+//! from `futhark_bindgen::build_library` is reused, the program is only recompiled when the
+//! source file or one of its imports is newer than the manifest.
+//!
+//! The macro can't link the generated C code, a proc-macro has no way to add link search
+//! paths. Use `futhark_bindgen::build_library` in `build.rs` with the same backend.
+
+use std::path::{Path, PathBuf};
+
+use futhark_bindgen::{
+    Backend, Compiler, Config, DefaultNamer, Generate, Manifest, NamingConfig, Package, Rust,
+};
+use proc_macro2::{Span, TokenStream};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitBool, LitStr, Token};
+
+/// Generate bindings for a Futhark program, see the crate documentation for options
+#[proc_macro]
+pub fn include_futhark(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = syn::parse_macro_input!(input as Args);
+    match expand(&args) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Macro arguments
+struct Args {
+    src: LitStr,
+    backend: Option<LitStr>,
+    manifest: Option<LitStr>,
+    naming: Option<LitStr>,
+    compiler: Option<LitStr>,
+    entry_points_within_context: Option<LitBool>,
+}
+
+fn set<T>(slot: &mut Option<T>, key: &Ident, value: T) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new(
+            key.span(),
+            format!("duplicate option `{key}`"),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Args {
+            src: input.parse()?,
+            backend: None,
+            manifest: None,
+            naming: None,
+            compiler: None,
+            entry_points_within_context: None,
+        };
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "backend" => set(&mut args.backend, &key, input.parse()?)?,
+                "manifest" => set(&mut args.manifest, &key, input.parse()?)?,
+                "naming" => set(&mut args.naming, &key, input.parse()?)?,
+                "compiler" => set(&mut args.compiler, &key, input.parse()?)?,
+                "entry_points_within_context" => {
+                    set(&mut args.entry_points_within_context, &key, input.parse()?)?
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!("unknown option `{key}`"),
+                    ))
+                }
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Resolve a path relative to `CARGO_MANIFEST_DIR`, reporting missing files at `lit`
+fn existing_path(root: &Path, lit: &LitStr) -> syn::Result<PathBuf> {
+    let path = root.join(lit.value());
+    if !path.exists() {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!("file not found: {}", path.display()),
+        ));
+    }
+    Ok(path)
+}
+
+/// Make cargo rebuild the crate when `path` changes
+fn track(path: &Path) -> TokenStream {
+    let path = path.to_string_lossy();
+    format!("const _: &[u8] = include_bytes!({path:?});")
+        .parse()
+        .unwrap()
+}
+
+fn modified(path: &Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Compile `src`, reusing the output of `build_library` or of a previous expansion when the
+/// manifest is newer than the source file and everything it imports
+///
+/// The output is written to `Compiler::cache_dir` in `$OUT_DIR` when the crate has a build
+/// script, otherwise in the target directory. Returns the package and the files the
+/// expansion depends on
+fn compile(
+    root: &Path,
+    src: &Path,
+    backend: Backend,
+    compiler: Option<&LitStr>,
+) -> Result<(Package, Vec<PathBuf>), futhark_bindgen::Error> {
+    let base = std::env::var_os("OUT_DIR")
+        .or_else(|| std::env::var_os("CARGO_TARGET_DIR"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root.join("target"));
+
+    let mut futhark =
+        Compiler::new(backend, src).with_output_dir(Compiler::cache_dir(&base, backend, src));
+    if let Some(exe) = compiler {
+        futhark = futhark.with_executable_name(exe.value());
+    }
+
+    let mut deps = futhark.sources();
+    let manifest = futhark.manifest_path();
+    let newest_src = deps.iter().map(|p| modified(p)).max().flatten();
+    let pkg = match (newest_src, modified(&manifest)) {
+        (Some(src_time), Some(manifest_time)) if manifest_time >= src_time => Package {
+            manifest: Manifest::parse_file(&manifest)?,
+            c_file: manifest.with_extension("c"),
+            h_file: manifest.with_extension("h"),
+            src: src.to_path_buf(),
+        },
+        _ => futhark.compile()?,
+    };
+    deps.push(manifest);
+    Ok((pkg, deps))
+}
+
+fn expand(args: &Args) -> syn::Result<TokenStream> {
+    let root = PathBuf::from(
+        std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| syn::Error::new(Span::call_site(), "CARGO_MANIFEST_DIR is not set"))?,
+    );
+    let src = existing_path(&root, &args.src)?;
+    let mut tokens = track(&src);
+
+    let backend = match &args.backend {
+        Some(lit) => Some(Backend::from_name(&lit.value()).ok_or_else(|| {
+            syn::Error::new(lit.span(), format!("unknown backend {:?}", lit.value()))
+        })?),
+        None => None,
+    };
+
+    let pkg = match &args.manifest {
+        Some(lit) => {
+            let path = existing_path(&root, lit)?;
+            tokens.extend(track(&path));
+            let manifest = Manifest::parse_file(&path)
+                .map_err(|e| syn::Error::new(lit.span(), format!("invalid manifest: {e:?}")))?;
+            if let Some(backend) = backend.filter(|b| *b != manifest.backend) {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!(
+                        "manifest was generated using the {} backend, not {}",
+                        manifest.backend.to_str(),
+                        backend.to_str()
+                    ),
+                ));
+            }
+            Package {
+                manifest,
+                c_file: path.with_extension("c"),
+                h_file: path.with_extension("h"),
+                src: src.clone(),
+            }
+        }
+        None => {
+            let (pkg, deps) = compile(
+                &root,
+                &src,
+                backend.unwrap_or(Backend::C),
+                args.compiler.as_ref(),
+            )
+            .map_err(|e| {
+                syn::Error::new(
+                    args.src.span(),
+                    format!("futhark compilation failed: {e:?}"),
+                )
+            })?;
+            // The source file is already tracked
+            tokens.extend(deps.iter().skip(1).map(|p| track(p)));
+            pkg
+        }
+    };
+
+    let naming = match &args.naming {
+        Some(lit) => {
+            let path = existing_path(&root, lit)?;
+            tokens.extend(track(&path));
+            NamingConfig::load(&path).map_err(|e| {
+                syn::Error::new(lit.span(), format!("invalid naming configuration: {e:?}"))
+            })?
+        }
+        None => match NamingConfig::find(&root) {
+            Ok(Some((naming, path))) => {
+                tokens.extend(track(&path));
+                naming
+            }
+            Ok(None) => NamingConfig::default(),
+            Err(e) => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!("invalid naming configuration: {e:?}"),
+                ))
+            }
+        },
+    };
+
+    let namer = DefaultNamer::default().with_config(naming);
+    let mut config = Config::with_writer("lib.rs", std::io::sink(), namer);
+    if let Some(lit) = &args.entry_points_within_context {
+        config.entry_points_within_context = lit.value;
+    }
+
+    let code = Rust.generate_to_string(&pkg, &mut config).map_err(|e| {
+        syn::Error::new(Span::call_site(), format!("code generation failed: {e:?}"))
+    })?;
+    let bindings: TokenStream = code.parse().map_err(|e| {
+        syn::Error::new(Span::call_site(), format!("generated code is invalid: {e}"))
+    })?;
+    tokens.extend(bindings);
+    Ok(tokens)
+}
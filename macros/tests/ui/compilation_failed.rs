@@ -0,0 +1,7 @@
+// Paths are relative to the crate generated by trybuild in `target/tests/trybuild`
+futhark_bindgen_macros::include_futhark!(
+    "../../../../macros/tests/ui/compilation_failed.fut",
+    compiler = "false"
+);
+
+fn main() {}
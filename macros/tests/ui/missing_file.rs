@@ -0,0 +1,3 @@
+futhark_bindgen_macros::include_futhark!("src/missing.fut");
+
+fn main() {}
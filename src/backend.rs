@@ -0,0 +1,449 @@
+/// `Backend` is used to select a backend when running the `futhark` executable
+#[derive(Debug, serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy)]
+pub enum Backend {
+    /// Sequential C backend: `futhark c`
+    ///
+    /// Requires a C compiler
+    #[serde(rename = "c")]
+    C,
+
+    /// CUDA backend: `futhark cuda`
+    ///
+    /// Requires the CUDA runtime and a C compiler
+    #[serde(rename = "cuda")]
+    Cuda,
+
+    /// OpenCL backend: `futhark opencl`
+    ///
+    /// Requires OpenCL and a C compiler
+    #[serde(rename = "opencl")]
+    OpenCl,
+
+    /// Multicore C backend: `futhark multicore`
+    ///
+    /// Requires a C compiler
+    #[serde(rename = "multicore")]
+    Multicore,
+
+    /// ISPC backend: `futhark ispc`
+    ///
+    /// Requires the `ispc` compiler in your `$PATH`
+    /// and a C compiler
+    #[serde(rename = "ispc")]
+    Ispc,
+
+    /// HIP backend: `futhark hip`
+    ///
+    /// Requires a C compiler
+    #[serde(rename = "hip")]
+    Hip,
+}
+
+/// Where a backend stores array data
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Memory {
+    /// Arrays live in host memory
+    Host,
+
+    /// Arrays live in GPU memory, reading values requires a copy
+    Device,
+}
+
+/// Functions that can be used to configure a `futhark_context_config`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConfigOption {
+    /// `futhark_context_config_set_num_threads`
+    NumThreads,
+
+    /// `futhark_context_config_set_device`
+    Device,
+
+    /// `futhark_context_config_set_platform`
+    Platform,
+}
+
+impl ConfigOption {
+    /// All configuration options
+    pub const ALL: [ConfigOption; 3] = [
+        ConfigOption::NumThreads,
+        ConfigOption::Device,
+        ConfigOption::Platform,
+    ];
+
+    /// Get the name of the C function used to set the option
+    pub fn c_function(&self) -> &'static str {
+        match self {
+            ConfigOption::NumThreads => "futhark_context_config_set_num_threads",
+            ConfigOption::Device => "futhark_context_config_set_device",
+            ConfigOption::Platform => "futhark_context_config_set_platform",
+        }
+    }
+}
+
+/// Additional steps needed to compile the generated C code
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CompileStep {
+    /// Compile `<name>.kernels.ispc` using `ispc` and link the resulting object
+    Ispc,
+}
+
+/// How to check whether the toolchain for a backend is installed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Probe {
+    /// Only a C compiler is needed
+    Always,
+
+    /// An executable that should be in `$PATH`
+    Executable(&'static str),
+
+    /// An OpenCL ICD loader configuration
+    OpenClIcd,
+}
+
+impl Probe {
+    /// Returns true if the toolchain appears to be installed
+    pub fn check(&self) -> bool {
+        match self {
+            Probe::Always => true,
+            Probe::Executable(name) => find_executable(name),
+            Probe::OpenClIcd => {
+                if cfg!(target_os = "macos") {
+                    return true;
+                }
+                let vendors = std::env::var_os("OCL_ICD_VENDORS")
+                    .map(std::path::PathBuf::from)
+                    .unwrap_or_else(|| std::path::PathBuf::from("/etc/OpenCL/vendors"));
+                match std::fs::read_dir(vendors) {
+                    Ok(mut entries) => entries.next().is_some(),
+                    Err(_) => false,
+                }
+            }
+        }
+    }
+}
+
+/// Everything code generators and build scripts need to know about a backend
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    /// Backend name, as passed to the `futhark` executable
+    pub name: &'static str,
+
+    /// C libraries that need to be linked
+    pub link_libs: &'static [&'static str],
+
+    /// Libraries from `link_libs` that are linked as frameworks on macOS
+    pub macos_frameworks: &'static [&'static str],
+
+    /// Flags passed to the C compiler
+    pub c_flags: &'static [&'static str],
+
+    /// Extra compilation steps
+    pub compile_steps: &'static [CompileStep],
+
+    /// Supported context configuration functions, in addition to the ones shared by all backends
+    pub config_options: &'static [ConfigOption],
+
+    /// Where array data is stored
+    pub memory: Memory,
+
+    /// Toolchain probe
+    pub probe: Probe,
+}
+
+const C: Capabilities = Capabilities {
+    name: "c",
+    link_libs: &[],
+    macos_frameworks: &[],
+    c_flags: &["-std=c99", "-Wno-unused-parameter", "-O3"],
+    compile_steps: &[],
+    config_options: &[],
+    memory: Memory::Host,
+    probe: Probe::Always,
+};
+
+const MULTICORE: Capabilities = Capabilities {
+    name: "multicore",
+    link_libs: &["pthread", "m"],
+    macos_frameworks: &[],
+    c_flags: &["-std=c99", "-Wno-unused-parameter", "-O3"],
+    compile_steps: &[],
+    config_options: &[ConfigOption::NumThreads],
+    memory: Memory::Host,
+    probe: Probe::Always,
+};
+
+const ISPC: Capabilities = Capabilities {
+    name: "ispc",
+    link_libs: &["pthread", "m"],
+    macos_frameworks: &[],
+    c_flags: &["-fPIC", "-pthread", "-lm", "-std=c99", "-O3"],
+    compile_steps: &[CompileStep::Ispc],
+    config_options: &[ConfigOption::NumThreads],
+    memory: Memory::Host,
+    probe: Probe::Executable("ispc"),
+};
+
+const CUDA: Capabilities = Capabilities {
+    name: "cuda",
+    link_libs: &["cuda", "cudart", "nvrtc", "m"],
+    macos_frameworks: &[],
+    c_flags: &["-std=c99", "-Wno-unused-parameter", "-O3"],
+    compile_steps: &[],
+    config_options: &[ConfigOption::Device],
+    memory: Memory::Device,
+    probe: Probe::Executable("nvcc"),
+};
+
+const OPENCL: Capabilities = Capabilities {
+    name: "opencl",
+    link_libs: &["OpenCL", "m"],
+    macos_frameworks: &["OpenCL"],
+    c_flags: &["-std=c99", "-Wno-unused-parameter", "-O3"],
+    compile_steps: &[],
+    config_options: &[ConfigOption::Device, ConfigOption::Platform],
+    memory: Memory::Device,
+    probe: Probe::OpenClIcd,
+};
+
+const HIP: Capabilities = Capabilities {
+    name: "hip",
+    link_libs: &["hiprtc", "amdhip64"],
+    macos_frameworks: &[],
+    c_flags: &["-std=c99", "-Wno-unused-parameter", "-O3"],
+    compile_steps: &[],
+    config_options: &[ConfigOption::Device],
+    memory: Memory::Device,
+    probe: Probe::Executable("hipcc"),
+};
+
+impl Backend {
+    /// All backends, in the order they are preferred when probing the toolchain
+    pub const ALL: [Backend; 6] = [
+        Backend::Cuda,
+        Backend::Hip,
+        Backend::OpenCl,
+        Backend::Ispc,
+        Backend::Multicore,
+        Backend::C,
+    ];
+
+    /// Get the capabilities of a backend
+    pub fn capabilities(&self) -> &'static Capabilities {
+        match self {
+            Backend::C => &C,
+            Backend::Cuda => &CUDA,
+            Backend::OpenCl => &OPENCL,
+            Backend::Multicore => &MULTICORE,
+            Backend::Ispc => &ISPC,
+            Backend::Hip => &HIP,
+        }
+    }
+
+    /// Get the name of a backend
+    pub fn to_str(&self) -> &'static str {
+        self.capabilities().name
+    }
+
+    /// Return the backend specified by the given name if valid
+    pub fn from_name(name: &str) -> Option<Backend> {
+        let name = name.trim().to_ascii_lowercase();
+        Backend::ALL.into_iter().find(|b| b.to_str() == name)
+    }
+
+    /// Parse a comma separated list of backend names, invalid names are skipped
+    pub fn from_names(names: &str) -> Vec<Backend> {
+        names.split(',').filter_map(Backend::from_name).collect()
+    }
+
+    /// Get the backend from the `FUTHARK_BACKEND` environment variable, which may contain a
+    /// comma separated list, in which case the first valid backend is returned
+    pub fn from_env() -> Option<Backend> {
+        Backend::list_from_env().into_iter().next()
+    }
+
+    /// Get every backend listed in the `FUTHARK_BACKEND` environment variable, in order
+    pub fn list_from_env() -> Vec<Backend> {
+        match std::env::var("FUTHARK_BACKEND") {
+            Ok(names) => Backend::from_names(&names),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Get the backends enabled using `futhark-<backend>` cargo features, this reads the
+    /// `CARGO_FEATURE_FUTHARK_<BACKEND>` variables that cargo sets for build scripts
+    pub fn from_cargo_features() -> Vec<Backend> {
+        Backend::ALL
+            .into_iter()
+            .filter(|b| {
+                let var = format!("CARGO_FEATURE_FUTHARK_{}", b.to_str().to_ascii_uppercase());
+                std::env::var_os(var).is_some()
+            })
+            .collect()
+    }
+
+    /// Returns true if the toolchain needed by a backend appears to be installed
+    ///
+    /// This only checks for the presence of compilers and drivers, it doesn't guarantee
+    /// that compilation will succeed
+    pub fn probe(&self) -> bool {
+        self.capabilities().probe.check()
+    }
+
+    /// Returns the C libraries that need to be linked for a backend
+    pub fn required_c_libs(&self) -> &'static [&'static str] {
+        self.capabilities().link_libs
+    }
+
+    /// Returns the linker flags for `required_c_libs`, for example `-lm`
+    pub fn linker_flags(&self) -> Vec<String> {
+        self.required_c_libs()
+            .iter()
+            .map(|lib| format!("-l{lib}"))
+            .collect()
+    }
+}
+
+fn find_executable(name: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let exe = dir.join(name);
+        exe.is_file() || exe.with_extension("exe").is_file()
+    })
+}
+
+/// `BackendSelector` combines several sources to build an ordered list of backends to try
+///
+/// The sources are checked in the following order:
+/// - An explicit backend, set using `BackendSelector::with_backend`
+/// - `futhark-<backend>` cargo features (`CARGO_FEATURE_FUTHARK_<BACKEND>`)
+/// - The `FUTHARK_BACKEND` environment variable, which may contain a comma separated list
+/// - Backends with an installed toolchain (`nvcc`, `hipcc`, an OpenCL ICD, `ispc`), only
+///   when enabled using `BackendSelector::with_probe`
+/// - Fallback backends, `Backend::C` by default
+#[derive(Debug, Clone)]
+pub struct BackendSelector {
+    explicit: Option<Backend>,
+    cargo_features: bool,
+    env: bool,
+    probe: bool,
+    fallback: Vec<Backend>,
+}
+
+impl Default for BackendSelector {
+    fn default() -> Self {
+        BackendSelector::new()
+    }
+}
+
+impl BackendSelector {
+    /// Create a new selector using cargo features, `FUTHARK_BACKEND` and the fallback,
+    /// toolchain probing is disabled so the backend doesn't depend on the machine
+    pub fn new() -> Self {
+        BackendSelector {
+            explicit: None,
+            cargo_features: true,
+            env: true,
+            probe: false,
+            fallback: vec![Backend::C],
+        }
+    }
+
+    /// Set an explicit backend, this takes precedence over all other sources
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.explicit = Some(backend);
+        self
+    }
+
+    /// Enable or disable reading `CARGO_FEATURE_FUTHARK_<BACKEND>` variables
+    pub fn with_cargo_features(mut self, enabled: bool) -> Self {
+        self.cargo_features = enabled;
+        self
+    }
+
+    /// Enable or disable reading the `FUTHARK_BACKEND` environment variable
+    pub fn with_env(mut self, enabled: bool) -> Self {
+        self.env = enabled;
+        self
+    }
+
+    /// Enable or disable toolchain probing, disabled by default
+    pub fn with_probe(mut self, enabled: bool) -> Self {
+        self.probe = enabled;
+        self
+    }
+
+    /// Returns true if `FUTHARK_BACKEND` is consulted by `BackendSelector::preferences`
+    pub fn reads_env(&self) -> bool {
+        self.env
+    }
+
+    /// Set the backends to try after all other sources
+    pub fn with_fallback(mut self, fallback: Vec<Backend>) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Get the ordered list of backends to try, without duplicates
+    pub fn preferences(&self) -> Vec<Backend> {
+        let mut backends = Vec::new();
+
+        if let Some(b) = self.explicit {
+            backends.push(b);
+        }
+
+        if self.cargo_features {
+            backends.extend(Backend::from_cargo_features());
+        }
+
+        if self.env {
+            backends.extend(Backend::list_from_env());
+        }
+
+        if self.probe {
+            backends.extend(
+                Backend::ALL
+                    .into_iter()
+                    .filter(|b| b.capabilities().probe != Probe::Always && b.probe()),
+            );
+        }
+
+        backends.extend(self.fallback.iter().copied());
+
+        let mut out: Vec<Backend> = Vec::new();
+        for b in backends {
+            if !out.contains(&b) {
+                out.push(b);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_names_accepts_lists() {
+        assert_eq!(Backend::from_names("cuda"), vec![Backend::Cuda]);
+        assert_eq!(
+            Backend::from_names("hip, OpenCL,c"),
+            vec![Backend::Hip, Backend::OpenCl, Backend::C]
+        );
+        assert_eq!(
+            Backend::from_names("nope,multicore,"),
+            vec![Backend::Multicore]
+        );
+        assert!(Backend::from_names("").is_empty());
+    }
+
+    #[test]
+    fn probing_is_opt_in() {
+        let selector = BackendSelector::new()
+            .with_cargo_features(false)
+            .with_env(false);
+        assert_eq!(selector.preferences(), vec![Backend::C]);
+    }
+}
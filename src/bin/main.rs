@@ -3,10 +3,12 @@ use futhark_bindgen::*;
 use argh::FromArgs;
 
 fn parse_backend(s: &str) -> Result<Backend, String> {
-    let mut s = s.to_string();
-    s.make_ascii_lowercase();
-    let x = serde_json::from_str(&format!("\"{}\"", s)).expect("Invalid backend");
-    Ok(x)
+    Backend::from_name(s).ok_or_else(|| format!("Invalid backend: {s}"))
+}
+
+/// Names that aren't built in select a plugin
+fn parse_language(s: &str) -> Result<Language, String> {
+    Ok(Language::from_name(s).unwrap_or_else(|| Language::Plugin(s.to_string())))
 }
 
 #[derive(Debug, FromArgs)]
@@ -18,9 +20,34 @@ struct Main {
 
 #[derive(Debug, FromArgs)]
 #[argh(subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     Run(Run),
     Libs(Libs),
+    Templates(Templates),
+    Languages(Languages),
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(
+    name = "languages",
+    description = "List the available output languages, including plugins found on PATH",
+    subcommand
+)]
+struct Languages {}
+
+#[derive(Debug, FromArgs)]
+#[argh(
+    name = "templates",
+    description = "List the built-in templates and their placeholders",
+    subcommand
+)]
+struct Templates {
+    #[argh(
+        option,
+        description = "write the built-in templates to this directory, as a starting point for --templates"
+    )]
+    output: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, FromArgs)]
@@ -29,13 +56,12 @@ enum Commands {
     description = "List libraries for the selected backend",
     subcommand
 )]
-
 struct Libs {
     #[argh(
         option,
         default = "Backend::C",
         from_str_fn(parse_backend),
-        description = "futhark backend: c, cuda, opencl, multicore"
+        description = "futhark backend: c, cuda, opencl, multicore, ispc, hip"
     )]
     backend: Backend,
 }
@@ -46,20 +72,89 @@ struct Run {
     #[argh(positional, description = "futhark input file")]
     input: std::path::PathBuf,
 
-    #[argh(positional, description = "output file")]
-    output: std::path::PathBuf,
+    #[argh(
+        positional,
+        description = "output files, the futhark C code is written next to each of them"
+    )]
+    outputs: Vec<std::path::PathBuf>,
+
+    #[argh(
+        option,
+        long = "lang",
+        from_str_fn(parse_language),
+        description = "output language, once per output file in order, defaults to detecting it from the extension"
+    )]
+    languages: Vec<Language>,
 
     #[argh(
         option,
         default = "Backend::C",
         from_str_fn(parse_backend),
-        description = "futhark backend: c, cuda, opencl, multicore"
+        description = "futhark backend: c, cuda, opencl, multicore, ispc, hip"
     )]
     backend: Backend,
 
     #[argh(option, description = "path to futhark compiler")]
     compiler: Option<String>,
 
+    #[argh(
+        option,
+        description = "naming configuration file, defaults to futhark-bindgen.toml if it exists"
+    )]
+    naming: Option<std::path::PathBuf>,
+
+    #[argh(
+        option,
+        description = "directory containing template overrides, for example <dir>/rust/array.rs"
+    )]
+    templates: Option<std::path::PathBuf>,
+
+    #[argh(
+        switch,
+        description = "write a module tree next to the output file (mod.rs, context.rs, entries.rs, sys.rs and types/) instead of a single file"
+    )]
+    modules: bool,
+
+    #[argh(
+        switch,
+        description = "expose the raw C declarations as a public sys module, with as_raw and from_raw on each type"
+    )]
+    sys: bool,
+
+    #[argh(
+        switch,
+        description = "build a shared library next to each output, for languages that load it at runtime such as Python"
+    )]
+    shared: bool,
+
+    #[argh(
+        option,
+        long = "include-entry",
+        description = "only generate entry points matching this pattern, `*` matches anything"
+    )]
+    include_entries: Vec<String>,
+
+    #[argh(
+        option,
+        long = "exclude-entry",
+        description = "skip entry points matching this pattern"
+    )]
+    exclude_entries: Vec<String>,
+
+    #[argh(
+        option,
+        long = "include-type",
+        description = "generate types matching this pattern even if no entry point uses them"
+    )]
+    include_types: Vec<String>,
+
+    #[argh(
+        option,
+        long = "exclude-type",
+        description = "fail if an entry point uses a type matching this pattern"
+    )]
+    exclude_types: Vec<String>,
+
     #[argh(
         option,
         long = "futhark-arg",
@@ -74,26 +169,127 @@ fn main() -> Result<(), Error> {
 
     match args.command {
         Commands::Run(mut args) => {
-            if args.output.is_relative() {
-                args.output = std::path::PathBuf::from(".").join(args.output);
+            if args.outputs.is_empty() {
+                eprintln!("Error: no output file given");
+                std::process::exit(1);
+            }
+            if args.languages.len() > args.outputs.len() {
+                eprintln!(
+                    "Error: {} languages given for {} output files",
+                    args.languages.len(),
+                    args.outputs.len()
+                );
+                std::process::exit(1);
+            }
+            for output in &mut args.outputs {
+                if output.is_relative() {
+                    *output = std::path::PathBuf::from(".").join(&*output);
+                }
+            }
+            let mut out_dirs = Vec::new();
+            for output in &args.outputs {
+                let dir = output.parent().unwrap();
+                std::fs::create_dir_all(dir)?;
+                let dir = dir.canonicalize()?;
+                if !out_dirs.contains(&dir) {
+                    out_dirs.push(dir);
+                }
             }
-            let out_dir = args.output.parent().unwrap().canonicalize().unwrap();
             let mut compiler = Compiler::new(args.backend, &args.input)
                 .with_extra_args(args.futhark_args)
-                .with_output_dir(out_dir);
+                .with_output_dir(&out_dirs[0]);
             if let Some(exe) = args.compiler {
                 compiler = compiler.with_executable_name(exe);
             }
             let pkg = compiler.compile()?;
-            let mut config = Config::new(args.output, DefaultNamer::default())?;
-            let mut gen = config.detect().expect("Unable to detect output language");
-            gen.generate(&pkg, &mut config)?;
+
+            // Every output directory gets its own copy of the C code, generated code refers
+            // to it by file name
+            let mut pkgs = Vec::new();
+            for dir in &out_dirs {
+                let pkg = pkg.copy_to(dir)?;
+                if args.shared {
+                    pkg.build_shared(dir)?;
+                }
+                pkgs.push(pkg);
+            }
+            let naming = match args.naming {
+                Some(path) => NamingConfig::load(path)?,
+                None => NamingConfig::find(".")?
+                    .map(|(naming, _)| naming)
+                    .unwrap_or_default(),
+            };
+            let templates = match args.templates {
+                Some(dir) => Some(futhark_bindgen::Templates::load_dir(dir)?),
+                None => None,
+            };
+            let filter = futhark_bindgen::Filter {
+                include_entries: args.include_entries,
+                exclude_entries: args.exclude_entries,
+                include_types: args.include_types,
+                exclude_types: args.exclude_types,
+            };
+            let mut languages = args.languages.into_iter();
+            for output in args.outputs {
+                let dir = output.parent().unwrap().canonicalize()?;
+                let pkg = &pkgs[out_dirs.iter().position(|d| *d == dir).unwrap()];
+                let namer = DefaultNamer::default().with_config(naming.clone());
+                let mut config = Config::new(output, namer);
+                if let Some(language) = languages.next() {
+                    config = config.with_language(language);
+                }
+                if args.modules {
+                    config = config.with_layout(Layout::Modules);
+                }
+                config = config.with_public_sys(args.sys).with_filter(filter.clone());
+                if let Some(templates) = &templates {
+                    config = config.with_templates(templates.clone());
+                }
+                let mut gen = config.generator()?;
+                gen.generate(pkg, &mut config)?;
+            }
+        }
+        Commands::Templates(args) => {
+            for t in TEMPLATES {
+                match &args.output {
+                    Some(dir) => {
+                        let dir = dir.join(t.language);
+                        std::fs::create_dir_all(&dir)?;
+                        std::fs::write(dir.join(t.name), t.source)?;
+                    }
+                    None => println!(
+                        "{}/{}: {}\n    {}",
+                        t.language,
+                        t.name,
+                        t.description,
+                        t.placeholders.join(", ")
+                    ),
+                }
+            }
+        }
+        Commands::Languages(_) => {
+            for language in Language::available() {
+                match language.info() {
+                    Some(info) => println!(
+                        "{}: {}\n    .{}",
+                        info.name,
+                        info.description,
+                        info.extensions.join(", .")
+                    ),
+                    None => println!(
+                        "{}: plugin {}\n    --lang {}",
+                        language.name(),
+                        Plugin::executable_name(language.name()),
+                        language.name()
+                    ),
+                }
+            }
         }
         Commands::Libs(args) => {
             args.backend
-                .required_c_libs()
+                .linker_flags()
                 .iter()
-                .for_each(|x| print!("-l{x} "));
+                .for_each(|x| print!("{x} "));
             println!();
         }
     }
@@ -47,14 +47,51 @@ impl Compiler {
         self
     }
 
+    /// Directory used for the output of `src` by `build_library` and `include_futhark!`, so
+    /// both share a single compilation: `<base>/futhark-bindgen/<backend>/<name>-<hash>`
+    /// where the hash is computed from the full path of `src`
+    pub fn cache_dir(
+        base: impl AsRef<std::path::Path>,
+        backend: Backend,
+        src: impl AsRef<std::path::Path>,
+    ) -> std::path::PathBuf {
+        use std::hash::{Hash, Hasher};
+
+        let src = src.as_ref();
+        let full = src.canonicalize().unwrap_or_else(|_| src.to_path_buf());
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        full.hash(&mut hasher);
+        let name = src.file_stem().unwrap_or_default().to_string_lossy();
+        base.as_ref()
+            .join("futhark-bindgen")
+            .join(backend.to_str())
+            .join(format!("{name}-{:016x}", hasher.finish()))
+    }
+
+    /// Path of the manifest written by `compile`
+    pub fn manifest_path(&self) -> std::path::PathBuf {
+        self.output().with_extension("json")
+    }
+
+    /// The source file followed by every file it imports, see `Package::sources`
+    pub fn sources(&self) -> Vec<std::path::PathBuf> {
+        sources(&self.src)
+    }
+
+    /// Output path without extension, passed to `futhark -o`
+    fn output(&self) -> std::path::PathBuf {
+        self.output_dir
+            .join(self.src.with_extension("").file_name().unwrap())
+    }
+
     /// Compile the package
     ///
     /// This will generate a C file, C header file and manifest
     pub fn compile(&self) -> Result<Package, Error> {
+        std::fs::create_dir_all(&self.output_dir)?;
+
         // Create -o argument
-        let output = &self
-            .output_dir
-            .join(self.src.with_extension("").file_name().unwrap());
+        let output = &self.output();
 
         let ok = std::process::Command::new(&self.exe)
             .arg(self.backend.to_str())
@@ -81,3 +118,83 @@ impl Compiler {
         })
     }
 }
+
+/// Find `src` and the files it imports, all paths are canonicalized, following `import "..."` declarations relative to
+/// the importing file. Imports that can't be read, such as missing libraries, are skipped
+pub(crate) fn sources(src: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let src = src.canonicalize().unwrap_or_else(|_| src.to_path_buf());
+    let mut found = vec![src];
+    let mut i = 0;
+    while i < found.len() {
+        let file = found[i].clone();
+        i += 1;
+        let Ok(code) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        let dir = file.parent().unwrap_or(std::path::Path::new(""));
+        for line in code.lines() {
+            // Drop comments
+            let line = line.split("--").next().unwrap_or_default();
+            let mut rest = line;
+            while let Some(start) = rest.find("import") {
+                rest = rest[start + "import".len()..].trim_start();
+                let Some(path) = rest
+                    .strip_prefix('"')
+                    .and_then(|r| r.split_once('"'))
+                    .map(|(path, _)| path)
+                else {
+                    continue;
+                };
+                let Ok(import) = dir.join(format!("{path}.fut")).canonicalize() else {
+                    continue;
+                };
+                if !found.contains(&import) {
+                    found.push(import);
+                }
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sources_follow_imports() {
+        let dir =
+            std::env::temp_dir().join(format!("futhark-bindgen-sources-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(
+            dir.join("main.fut"),
+            "import \"lib/util\"\nmodule m = import \"lib/missing\"\n-- import \"other\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("lib/util.fut"), "open import \"../main\"\n").unwrap();
+        std::fs::write(dir.join("other.fut"), "").unwrap();
+
+        let dir = dir.canonicalize().unwrap();
+        let compiler = Compiler::new(Backend::C, dir.join("main.fut"));
+        assert_eq!(
+            compiler.sources(),
+            [dir.join("main.fut"), dir.join("lib/util.fut")]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cache_dir_depends_on_source_path() {
+        let a = Compiler::cache_dir("out", Backend::C, "a/kernels.fut");
+        let b = Compiler::cache_dir("out", Backend::C, "b/kernels.fut");
+        assert_ne!(a, b);
+        assert!(a.starts_with("out/futhark-bindgen/c"));
+        assert!(a
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("kernels-"));
+        assert_eq!(a, Compiler::cache_dir("out", Backend::C, "a/kernels.fut"));
+    }
+}
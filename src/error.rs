@@ -4,11 +4,62 @@ pub enum Error {
     /// Compilation failed
     CompilationFailed,
 
+    /// Building the generated C code failed
+    BuildFailed(String),
+
+    /// The manifest refers to unknown types or is missing required information
+    InvalidManifest(String),
+
+    /// A name can't be mapped to a valid identifier in the output language
+    InvalidName {
+        name: String,
+        item: String,
+        language: &'static str,
+    },
+
+    /// Generated code doesn't parse, `template` names the template that produced it
+    InvalidCode { template: String, message: String },
+
+    /// A template is invalid, or uses a placeholder that has no value
+    Template { template: String, message: String },
+
+    /// Two Futhark types, or two entry points, were given the same name
+    NameCollision {
+        name: String,
+        first: String,
+        second: String,
+    },
+
+    /// A Futhark type has no equivalent in the output language, for example `f16` in OCaml
+    UnsupportedType {
+        name: String,
+        item: String,
+        language: &'static str,
+    },
+
+    /// A type rejected by the `Filter` is used by an item that is kept, `used_by` describes
+    /// the item
+    ExcludedType { name: String, used_by: String },
+
+    /// No output language was selected and none matches the extension of the output path
+    UnknownLanguage(String),
+
+    /// A generator plugin failed or returned an invalid response, `name` is the plugin
+    /// executable
+    Plugin { name: String, message: String },
+
+    /// Several files were generated for an output that can only hold one, such as a writer
+    /// passed to `Config::with_writer`
+    MultipleFiles(Vec<std::path::PathBuf>),
+
     /// Json decoding error
     Json(serde_json::Error),
 
     /// std::io::Error
     Io(std::io::Error),
+
+    /// TOML decoding error
+    Toml(toml::de::Error),
 }
 
 impl From<serde_json::Error> for Error {
@@ -22,3 +73,9 @@ impl From<std::io::Error> for Error {
         Error::Io(e)
     }
 }
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Toml(e)
+    }
+}
@@ -1,15 +1,41 @@
 use std::{
-    collections::HashMap,
-    fs::File,
+    io::Write,
     path::{Path, PathBuf},
 };
 
 use crate::*;
 
+mod cpp;
+mod csharp;
+mod docs;
+mod filter;
+mod go;
+mod ident;
+mod julia;
+mod language;
+mod naming;
+mod ocaml;
+mod plugin;
+mod python;
 mod rust;
+mod template;
+mod zig;
 
-use convert_case::{Case, Casing};
+pub use cpp::Cpp;
+pub use csharp::CSharp;
+pub use docs::{DocFormat, Docs};
+pub use filter::Filter;
+pub use go::Go;
+pub use ident::IdentRules;
+pub use julia::Julia;
+pub use language::{Language, LanguageInfo};
+pub use naming::{DefaultNamer, EntryOverride, Namer, NamingConfig, NamingScheme, TypeOverride};
+pub use ocaml::OCaml;
+pub use plugin::{Plugin, PLUGIN_PROTOCOL};
+pub use python::Python;
 pub use rust::Rust;
+pub use template::{TemplateInfo, Templates, TEMPLATES};
+pub use zig::Zig;
 
 pub(crate) fn first_uppercase(s: &str) -> String {
     let mut s = s.to_string();
@@ -30,195 +56,401 @@ pub(crate) fn convert_struct_name(s: &str) -> &str {
         .unwrap()
 }
 
+/// Where generated code is written once generation succeeds
+enum Sink {
+    /// Replace a file atomically, leaving it untouched if the contents are unchanged
+    File,
+
+    /// Any writer, such as stdout
+    Writer(Box<dyn Write>),
+}
+
+/// How generated code is split into files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// Write everything to the output file, except for languages that need several files
+    /// such as OCaml's `.ml` and `.mli`
+    #[default]
+    SingleFile,
+
+    /// Write a module tree next to the output file, which becomes the module root. For Rust
+    /// the output should be named `mod.rs`, it declares `context.rs`, `entries.rs`, `sys.rs`
+    /// for the raw C declarations and one file per type in `types/`
+    Modules,
+}
+
 /// Code generation config
 pub struct Config {
-    /// Output file
+    /// Output file path, the extension is used to detect the output language unless
+    /// `language` is set
     pub output_path: PathBuf,
 
-    /// Path to output file
-    pub output_file: File,
+    /// Output language, see `Config::language`
+    pub language: Option<Language>,
+
+    /// Buffers used by generators, indexed by path. These are formatted and written once
+    /// generation succeeds, see `Config::output`
+    pub files: BTreeMap<PathBuf, Vec<u8>>,
 
     pub namer: Box<dyn Namer>,
-    pub type_names: HashMap<String, String>,
-    pub raw_names: HashMap<String, String>,
     pub entry_points_within_context: bool,
+
+    /// How generated code is split into files
+    pub layout: Layout,
+
+    /// Templates used by the generator
+    pub templates: Templates,
+
+    /// Entry points and types to generate
+    pub filter: Filter,
+
+    /// Expose the raw C declarations as a public `sys` module, along with `as_raw` and
+    /// `from_raw` on the generated types
+    pub public_sys: bool,
+
+    sink: Sink,
 }
 
 impl Config {
     /// Create a new config using the provided output file path
-    pub fn new(output: impl AsRef<Path>, namer: impl Namer + 'static) -> Result<Config, Error> {
-        Ok(Config {
+    ///
+    /// The file isn't modified until generation succeeds
+    pub fn new(output: impl AsRef<Path>, namer: impl Namer + 'static) -> Config {
+        Config {
             output_path: output.as_ref().to_path_buf(),
-            output_file: File::create(output)?,
+            language: None,
+            files: BTreeMap::new(),
             namer: Box::new(namer),
-            type_names: HashMap::new(),
-            raw_names: HashMap::new(),
             entry_points_within_context: false,
-        })
+            layout: Layout::SingleFile,
+            templates: Templates::default(),
+            filter: Filter::default(),
+            public_sys: false,
+            sink: Sink::File,
+        }
+    }
+
+    /// Create a new config that writes to `writer`, `name` is only used to detect the output
+    /// language, for example `lib.rs`
+    pub fn with_writer(
+        name: impl AsRef<Path>,
+        writer: impl Write + 'static,
+        namer: impl Namer + 'static,
+    ) -> Config {
+        Config {
+            output_path: name.as_ref().to_path_buf(),
+            language: None,
+            files: BTreeMap::new(),
+            namer: Box::new(namer),
+            entry_points_within_context: false,
+            layout: Layout::SingleFile,
+            templates: Templates::default(),
+            filter: Filter::default(),
+            public_sys: false,
+            sink: Sink::Writer(Box::new(writer)),
+        }
+    }
+
+    /// Generate `language` instead of detecting it from the output extension
+    pub fn with_language(mut self, language: Language) -> Config {
+        self.language = Some(language);
+        self
+    }
+
+    /// Use `templates` instead of the built-in templates
+    pub fn with_templates(mut self, templates: Templates) -> Config {
+        self.templates = templates;
+        self
+    }
+
+    /// Select how generated code is split into files
+    pub fn with_layout(mut self, layout: Layout) -> Config {
+        self.layout = layout;
+        self
+    }
+
+    /// Only generate the entry points and types selected by `filter`
+    pub fn with_filter(mut self, filter: Filter) -> Config {
+        self.filter = filter;
+        self
+    }
+
+    /// Expose the raw C declarations, see `Config::public_sys`
+    pub fn with_public_sys(mut self, public_sys: bool) -> Config {
+        self.public_sys = public_sys;
+        self
+    }
+
+    /// Get the buffer for `path`, paths are relative to the directory containing
+    /// `output_path` unless they are absolute
+    pub fn output(&mut self, path: impl AsRef<Path>) -> &mut Vec<u8> {
+        let path = self.output_file(path);
+        self.files.entry(path).or_default()
+    }
+
+    /// Resolve `path` relative to the directory containing `output_path`
+    fn output_file(&self, path: impl AsRef<Path>) -> PathBuf {
+        match self.output_path.parent() {
+            Some(dir) => dir.join(path),
+            None => path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Write generated files to the output
+    fn write(&mut self, files: &BTreeMap<PathBuf, String>) -> Result<(), Error> {
+        match &mut self.sink {
+            Sink::File => {
+                for (path, code) in files {
+                    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                        std::fs::create_dir_all(dir)?;
+                    }
+                    write_if_changed(path, code)?;
+                }
+                Ok(())
+            }
+            Sink::Writer(w) => {
+                w.write_all(single_file(files)?.as_bytes())?;
+                w.flush()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Get the only generated file, for outputs that can't hold several files
+fn single_file(files: &BTreeMap<PathBuf, String>) -> Result<&String, Error> {
+    let mut iter = files.values();
+    match (iter.next(), iter.next()) {
+        (Some(code), None) => Ok(code),
+        _ => Err(Error::MultipleFiles(files.keys().cloned().collect())),
+    }
+}
+
+/// Atomically replace the contents of `path`, files that already contain `code` are left
+/// untouched so their modification time doesn't change
+fn write_if_changed(path: &Path, code: &str) -> Result<(), Error> {
+    if let Ok(existing) = std::fs::read(path) {
+        if existing == code.as_bytes() {
+            return Ok(());
+        }
+    }
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::other(format!("invalid output path {path:?}")))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    std::fs::write(&tmp, code)?;
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e.into());
     }
+    Ok(())
 }
 
 pub trait Generate {
-    /// Iterates through the manifest and generates code
+    /// Resolves the manifest, generates code and writes it to the output configured in
+    /// `config`
     fn generate(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
-        config.namer.init(&pkg.manifest);
-        for (name, ty) in &pkg.manifest.types {
-            let tyname = config.namer.type_name(name, ty, &pkg.manifest);
-            config.type_names.insert(name.clone(), tyname);
-            config
-                .raw_names
-                .insert(name.clone(), convert_struct_name(ty.ctype()).to_string());
-        }
-        self.bindings(pkg, config)?;
-        for (name, ty) in &pkg.manifest.types {
-            match ty {
-                manifest::Type::Array(ty) => {
-                    self.array_type(pkg, config, name, ty)?;
+        let files = self.generate_files(pkg, config)?;
+        config.write(&files)
+    }
+
+    /// Resolves the manifest and returns the generated code without writing it, this fails
+    /// if more than one file is generated
+    fn generate_to_string(&mut self, pkg: &Package, config: &mut Config) -> Result<String, Error> {
+        let files = self.generate_files(pkg, config)?;
+        single_file(&files).cloned()
+    }
+
+    /// Resolves the manifest and returns the generated files without writing them
+    fn generate_files(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+    ) -> Result<BTreeMap<PathBuf, String>, Error> {
+        config.files.clear();
+        let mut lib = ir::Library::new(pkg, config.namer.as_mut())?;
+        lib.filter(&config.filter)?;
+        self.bindings(&lib, config)?;
+        for ty in lib.types_in_order() {
+            match &ty.kind {
+                ir::TypeKind::Array(a) => {
+                    self.array_type(&lib, config, ty, a)?;
                 }
-                manifest::Type::Opaque(ty) => {
-                    self.opaque_type(pkg, config, name, ty)?;
+                _ => {
+                    self.opaque_type(&lib, config, ty)?;
                 }
             }
+            if !ty.aliases.is_empty() {
+                self.type_aliases(&lib, config, ty)?;
+            }
         }
 
-        for (name, entry) in &pkg.manifest.entry_points {
-            self.entry(pkg, config, name, entry)?;
+        for entry in lib.entries.values() {
+            self.entry(&lib, config, entry)?;
         }
-        self.format(&config.output_path)?;
-        Ok(())
+        self.finish(&lib, config)?;
+        let mut files = BTreeMap::new();
+        for (path, code) in std::mem::take(&mut config.files) {
+            let code = String::from_utf8(code)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            files.insert(path.clone(), self.format(config, &path, code)?);
+        }
+        Ok(files)
     }
 
     /// Step 1: generate any setup code or low-level bindings
-    fn bindings(&mut self, _pkg: &Package, _config: &mut Config) -> Result<(), Error>;
+    fn bindings(&mut self, _lib: &ir::Library, _config: &mut Config) -> Result<(), Error>;
 
     /// Step 2: generate code for array types
     fn array_type(
         &mut self,
-        pkg: &Package,
+        lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        ty: &manifest::ArrayType,
+        ty: &ir::Type,
+        array: &ir::Array,
     ) -> Result<(), Error>;
 
     /// Step 3: generate code for opaque types
     fn opaque_type(
         &mut self,
-        pkg: &Package,
+        lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        ty: &manifest::OpaqueType,
+        ty: &ir::Type,
     ) -> Result<(), Error>;
 
-    /// Step 4: generate code for entry points
+    /// Optionally, generate deprecated aliases for a type after step 2 or 3
+    fn type_aliases(
+        &mut self,
+        _lib: &ir::Library,
+        _config: &mut Config,
+        _ty: &ir::Type,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Step 4: generate code for entry points, including deprecated aliases
     fn entry(
         &mut self,
-        pkg: &Package,
+        lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        entry: &manifest::Entry,
+        entry: &ir::Entry,
     ) -> Result<(), Error>;
 
-    /// Step 5: Optionally, run any formatting program or post-processing on the output file
-    fn format(&mut self, _output: &Path) -> Result<(), Error> {
+    /// Step 5: Optionally, generate code after all types and entry points, for example to
+    /// close a namespace
+    fn finish(&mut self, _lib: &ir::Library, _config: &mut Config) -> Result<(), Error> {
         Ok(())
     }
-}
 
-fn rust() -> Box<impl Generate> {
-    Box::<Rust>::default()
+    /// Step 6: Optionally, run any formatting program or post-processing on each generated file
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        Ok(code)
+    }
 }
 
-// fn ocaml(config: &Config) -> Box<impl Generate> {
-//     Box::new(OCaml::new(config).unwrap())
-// }
-
 impl Config {
-    /// Automatically detect output language
-    pub fn detect(&self) -> Option<Box<dyn Generate>> {
-        match self
-            .output_path
-            .extension()
-            .map(|x| x.to_str().expect("Invalid extension"))
-        {
-            Some("rs") => Some(rust()),
-            // Some("ml") => Some(ocaml(self)),
-            _ => None,
+    /// Get the output language, either the one selected using `Config::with_language` or the
+    /// one matching the extension of the output path
+    pub fn language(&self) -> Option<Language> {
+        if let Some(language) = &self.language {
+            return Some(language.clone());
         }
+        self.output_path
+            .extension()
+            .and_then(|x| x.to_str())
+            .and_then(Language::from_extension)
     }
-}
 
-pub trait Namer {
-    fn init(&mut self, manifest: &Manifest);
-    fn type_name(
-        &mut self,
-        futhark_name: &str,
-        data: &manifest::Type,
-        manifest: &Manifest,
-    ) -> String;
-    fn project_name(&mut self, futhark_name: &str, manifest: &Manifest) -> String;
-    fn new_field_name(&mut self, futhark_name: &str, manifest: &Manifest) -> String;
-}
+    /// Create the generator for the output language, fails with `Error::UnknownLanguage` if
+    /// no language was selected and the output extension isn't recognized
+    pub fn generator(&self) -> Result<Box<dyn Generate>, Error> {
+        self.language()
+            .map(|l| l.generator())
+            .ok_or_else(|| Error::UnknownLanguage(self.output_path.display().to_string()))
+    }
 
-#[derive(Default, Debug, Clone)]
-pub struct DefaultNamer {
-    ctypes: HashMap<String, String>,
+    /// Automatically detect output language, returns `None` if no language was selected and
+    /// the output extension isn't recognized
+    pub fn detect(&self) -> Option<Box<dyn Generate>> {
+        self.generator().ok()
+    }
 }
 
-fn is_valid_name(name: &str) -> bool {
-    let first = name.chars().next().expect("Empty name");
-    !['(', '{', '#', '['].contains(&first)
-}
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
 
-impl Namer for DefaultNamer {
-    fn init(&mut self, manifest: &Manifest) {
-        for (futhark_name, ty) in &manifest.types {
-            if let manifest::Type::Opaque(manifest::OpaqueType { ctype, .. }) = ty {
-                self.ctypes
-                    .insert(futhark_name.clone(), convert_struct_name(ctype).to_string());
-            }
-        }
+    use serde_json::json;
+
+    use super::*;
+    use crate::ir::tests::{entry, package};
+
+    /// Create an empty directory for a test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("futhark-bindgen-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
     }
-    fn type_name(
-        &mut self,
-        futhark_name: &str,
-        data: &manifest::Type,
-        manifest: &Manifest,
-    ) -> String {
-        if is_valid_name(futhark_name) {
-            return futhark_name.to_case(Case::Pascal);
-        }
-        match data {
-            manifest::Type::Array(array) => {
-                let elemtype = array.elemtype.to_str();
-                let elemname = first_uppercase(elemtype);
-                format!("{}Array{}d", elemname, array.rank)
-            }
-            manifest::Type::Opaque(opaque) => match &opaque.options {
-                manifest::OpaqueOptions::OpaqueArray(array)
-                | manifest::OpaqueOptions::RecordArray(array) => {
-                    let elemname =
-                        self.type_name(&array.elemtype, &manifest.types[&array.elemtype], manifest);
-                    format!("{}Array{}d", elemname, array.rank)
-                }
-                manifest::OpaqueOptions::Sum(_) | manifest::OpaqueOptions::Record(_) => {
-                    let ctype = self.ctypes.get(futhark_name).expect("Cannot find type.");
-                    "Unnamed".to_string() + ctype.strip_prefix("futhark_opaque").unwrap()
-                }
-            },
-        }
+
+    #[test]
+    fn unchanged_file_is_not_rewritten() {
+        let dir = test_dir("unchanged");
+        let path = dir.join("lib.rs");
+        std::fs::write(&path, "fn main() {}").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        write_if_changed(&path, "fn main() {}").unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), old);
+
+        write_if_changed(&path, "fn other() {}").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "fn other() {}");
+        assert_ne!(std::fs::metadata(&path).unwrap().modified().unwrap(), old);
+        assert!(!dir.join(".lib.rs.tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
     }
-    fn new_field_name(&mut self, futhark_name: &str, _manifest: &Manifest) -> String {
-        if futhark_name.chars().next().unwrap().is_ascii_digit() {
-            format!("f{}", futhark_name)
-        } else {
-            futhark_name.to_string()
-        }
+
+    #[test]
+    fn failed_rename_removes_temporary_file() {
+        let dir = test_dir("rename");
+        // Renaming a file over a non-empty directory fails
+        let path = dir.join("lib.rs");
+        std::fs::create_dir(&path).unwrap();
+        std::fs::write(path.join("keep"), "").unwrap();
+
+        assert!(matches!(
+            write_if_changed(&path, "fn main() {}"),
+            Err(Error::Io(_))
+        ));
+        assert!(!dir.join(".lib.rs.tmp").exists());
+        assert!(path.join("keep").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
     }
-    fn project_name(&mut self, futhark_name: &str, _manifest: &Manifest) -> String {
-        if futhark_name.chars().next().unwrap().is_ascii_digit() {
-            format!("f{}", futhark_name)
-        } else {
-            futhark_name.to_string()
+
+    #[test]
+    fn writer_rejects_multiple_files() {
+        let pkg = package(
+            json!({"main": entry("main", &[("x", "i32")], &["i32"])}),
+            json!({}),
+        );
+        let mut config = Config::with_writer("lib.ml", std::io::sink(), DefaultNamer::default());
+        match OCaml.generate(&pkg, &mut config) {
+            Err(Error::MultipleFiles(files)) => {
+                assert_eq!(files, [PathBuf::from("lib.ml"), PathBuf::from("lib.mli")]);
+            }
+            other => panic!("expected multiple files, got {other:?}"),
         }
     }
 }
@@ -0,0 +1,825 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::generate::IdentRules;
+use crate::ir::ElemType;
+use crate::*;
+
+/// C++ codegen, writes a header-only C++17 wrapper around the Futhark C API
+#[derive(Default)]
+pub struct Cpp;
+
+/// Names defined at namespace scope by `templates/cpp/context.hpp`, these can't be used by
+/// generated classes
+const RESERVED_CLASSES: &[&str] = &[
+    "BACKEND",
+    "Context",
+    "DEVICE_MEMORY",
+    "Error",
+    "Handle",
+    "Options",
+    "Variant",
+    "num_elements",
+];
+
+/// `Context` members, these can't be used by entry points
+const RESERVED_METHODS: &[&str] = &[
+    "Context",
+    "auto_sync",
+    "auto_sync_",
+    "check",
+    "clear_caches",
+    "config_",
+    "context_",
+    "error",
+    "get_error",
+    "pause_profiling",
+    "raw",
+    "report",
+    "sync",
+    "take_string",
+    "unpause_profiling",
+];
+
+/// Members of the generated classes, these can't be used for field accessors and sum type
+/// constructors
+const RESERVED_MEMBERS: &[&str] = &[
+    "Handle",
+    "Variant",
+    "checked_data",
+    "context",
+    "ctx_",
+    "element_type",
+    "get",
+    "ptr_",
+    "rank",
+    "raw",
+    "release",
+    "reset",
+    "restore",
+    "shape",
+    "shape_type",
+    "store",
+    "values",
+    "variant",
+];
+
+fn cpp_ident(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    IdentRules::CPP.escape(name, reserved, item)
+}
+
+/// Returns the C type used for a primitive value
+fn primitive_type(t: ElemType) -> &'static str {
+    match t {
+        ElemType::I8 => "std::int8_t",
+        ElemType::I16 => "std::int16_t",
+        ElemType::I32 => "std::int32_t",
+        ElemType::I64 => "std::int64_t",
+        ElemType::U8 => "std::uint8_t",
+        ElemType::U16 => "std::uint16_t",
+        ElemType::U32 => "std::uint32_t",
+        ElemType::U64 => "std::uint64_t",
+        // The C API stores half precision values as their bits
+        ElemType::F16 => "std::uint16_t",
+        ElemType::F32 => "float",
+        ElemType::F64 => "double",
+        ElemType::Bool => "bool",
+    }
+}
+
+/// Name of the class generated for a type
+fn class_name(ty: &ir::Type) -> Result<String, Error> {
+    cpp_ident(&ty.name, RESERVED_CLASSES, || {
+        format!("type {:?}", ty.futhark_name)
+    })
+}
+
+/// Namespace containing the generated code, derived from the output file name
+fn namespace(config: &Config) -> Result<String, Error> {
+    let stem = config
+        .output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().replace(['-', '.'], "_"))
+        .unwrap_or_default();
+    cpp_ident(&stem, &[], || {
+        format!("output file {:?}", config.output_path)
+    })
+}
+
+/// Render documentation as `///` comments
+fn doc_comment(doc: &str) -> String {
+    doc.lines()
+        .map(|line| format!("/// {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How a value is passed to the C API
+enum Value<'a> {
+    Primitive(&'static str),
+    Named(&'a ir::Type),
+}
+
+impl<'a> Value<'a> {
+    fn new(lib: &'a ir::Library, ty: &ir::TypeRef) -> Result<Self, Error> {
+        match (ty, lib.get(ty)) {
+            (ir::TypeRef::Primitive(t), _) => Ok(Value::Primitive(primitive_type(*t))),
+            (_, Some(t)) => Ok(Value::Named(t)),
+            (ir::TypeRef::Named(name), None) => {
+                Err(Error::InvalidManifest(format!("unknown type {name:?}")))
+            }
+        }
+    }
+
+    /// Type used for return values
+    fn cpp_type(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(t) => Ok(t.to_string()),
+            Value::Named(t) => class_name(t),
+        }
+    }
+
+    /// Type used for parameters
+    fn param_type(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(t) => Ok(t.to_string()),
+            Value::Named(t) => Ok(format!("const {}&", class_name(t)?)),
+        }
+    }
+
+    /// Expression passing `name` to a C function
+    fn arg(&self, name: &str) -> String {
+        match self {
+            Value::Primitive(_) => name.to_string(),
+            Value::Named(_) => format!("{name}.raw()"),
+        }
+    }
+
+    /// Declaration of the output variable `name`
+    fn out_decl(&self, name: &str) -> String {
+        match self {
+            Value::Primitive(t) => format!("{t} {name}{{}};"),
+            Value::Named(t) => format!("{}* {name} = nullptr;", t.raw_name),
+        }
+    }
+
+    /// Expression wrapping the output variable `name`
+    fn output(&self, ctx: &str, name: &str) -> Result<String, Error> {
+        match self {
+            Value::Primitive(_) => Ok(name.to_string()),
+            Value::Named(t) => Ok(format!("{}({ctx}, {name})", class_name(t)?)),
+        }
+    }
+}
+
+/// Get the header file, relative to the output directory
+fn file(config: &Config) -> PathBuf {
+    PathBuf::from(config.output_path.file_name().unwrap_or_default())
+}
+
+/// Render a template from `templates/cpp` and append it to the header
+fn render(config: &mut Config, name: &str, args: &[(&str, &dyn Display)]) -> Result<(), Error> {
+    let code = config.templates.render("cpp", name, args)?;
+    writeln!(config.output(file(config)), "{code}")?;
+    Ok(())
+}
+
+/// Parameters of a constructor taking one value per field
+struct FieldParams {
+    params: Vec<String>,
+    call_args: Vec<String>,
+}
+
+fn field_params(
+    lib: &ir::Library,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+) -> Result<FieldParams, Error> {
+    let mut params = Vec::new();
+    let mut call_args = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let name = cpp_ident(&field.name, &["ctx", "out"], || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        params.push(format!("{} {name}", value.param_type()?));
+        call_args.push(value.arg(&name));
+    }
+    Ok(FieldParams { params, call_args })
+}
+
+/// Render field accessors for records and arrays of records
+fn field_projections(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    class_name: &str,
+    fields: &[ir::Field],
+) -> Result<Vec<String>, Error> {
+    let mut members = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let project_name = cpp_ident(
+            &field.project_name,
+            &[RESERVED_MEMBERS, &[class_name]].concat(),
+            || {
+                format!(
+                    "field {:?} of type {:?}",
+                    field.futhark_name, ty.futhark_name
+                )
+            },
+        )?;
+        members.push(config.templates.render(
+            "cpp",
+            "record_project.hpp",
+            &[
+                ("field_name", &field.futhark_name),
+                ("field_type", &value.cpp_type()?),
+                ("project_name", &project_name),
+                ("project_fn", &field.project_fn),
+                ("out_decl", &value.out_decl("out")),
+                ("out_return", &value.output("*ctx_", "out")?),
+            ],
+        )?);
+    }
+    Ok(members)
+}
+
+/// Members of sum types: the `Variant` enum, constructors and payload getters
+fn sum_members(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    class_name: &str,
+    sum: &ir::Sum,
+) -> Result<Vec<String>, Error> {
+    let reserved = [RESERVED_MEMBERS, &[class_name]].concat();
+    let mut variants = Vec::new();
+    let mut members = Vec::new();
+    for variant in &sum.variants {
+        let item = || format!("variant {:?} of type {:?}", variant.name, ty.futhark_name);
+        let name = cpp_ident(&variant.name, &reserved, item)?;
+        let enumerator = cpp_ident(&variant.name, &[], item)?;
+        let payload = variant
+            .payload
+            .iter()
+            .map(|p| Value::new(lib, p))
+            .collect::<Result<Vec<_>, _>>()?;
+        let names = (0..payload.len())
+            .map(|i| format!("p{i}"))
+            .collect::<Vec<_>>();
+
+        let mut construct_params = String::new();
+        let mut construct_args = String::new();
+        for (p, name) in payload.iter().zip(&names) {
+            construct_params.push_str(&format!(", {} {name}", p.param_type()?));
+            construct_args.push_str(&format!(", {}", p.arg(name)));
+        }
+        members.push(config.templates.render(
+            "cpp",
+            "sum_variant.hpp",
+            &[
+                ("class_name", &class_name),
+                ("raw_type", &ty.raw_name),
+                ("variant_name", &variant.name),
+                ("name", &name),
+                ("construct_fn", &variant.construct_fn),
+                ("construct_params", &construct_params),
+                ("construct_args", &construct_args),
+            ],
+        )?);
+
+        if !payload.is_empty() {
+            let payload_types = payload
+                .iter()
+                .map(Value::cpp_type)
+                .collect::<Result<Vec<_>, _>>()?;
+            let destruct_decls = payload
+                .iter()
+                .zip(&names)
+                .map(|(p, name)| format!("    {}", p.out_decl(name)))
+                .collect::<Vec<_>>();
+            let destruct_args = names
+                .iter()
+                .map(|name| format!("&{name}"))
+                .collect::<Vec<_>>();
+            let destruct_return = payload
+                .iter()
+                .zip(&names)
+                .map(|(p, name)| p.output("*ctx_", name))
+                .collect::<Result<Vec<_>, _>>()?;
+            members.push(config.templates.render(
+                "cpp",
+                "sum_payload.hpp",
+                &[
+                    ("variant_name", &variant.name),
+                    ("variant", &enumerator),
+                    ("name", &name),
+                    ("destruct_fn", &variant.destruct_fn),
+                    ("payload_types", &payload_types.join(", ")),
+                    ("destruct_decls", &destruct_decls.join("\n")),
+                    ("destruct_args", &destruct_args.join(", ")),
+                    ("destruct_return", &destruct_return.join(", ")),
+                ],
+            )?);
+        }
+        variants.push(enumerator);
+    }
+
+    let sum = config.templates.render(
+        "cpp",
+        "sum.hpp",
+        &[
+            ("variants", &variants.join(", ")),
+            ("variant_fn", &sum.variant_fn),
+        ],
+    )?;
+    members.insert(0, sum);
+    Ok(members)
+}
+
+/// Signature of an entry point method
+struct Signature {
+    name: String,
+    return_type: String,
+    params: Vec<String>,
+}
+
+fn signature(lib: &ir::Library, entry: &ir::Entry) -> Result<Signature, Error> {
+    let name = cpp_ident(&entry.name, RESERVED_METHODS, || {
+        format!("entry point {:?}", entry.futhark_name)
+    })?;
+    let return_types = entry
+        .outputs
+        .iter()
+        .map(|output| Value::new(lib, &output.ty)?.cpp_type())
+        .collect::<Result<Vec<_>, _>>()?;
+    let return_type = match return_types.len() {
+        0 => "void".to_string(),
+        1 => return_types.join(""),
+        _ => format!("std::tuple<{}>", return_types.join(", ")),
+    };
+    let params = entry
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| {
+            Ok(format!(
+                "{} in{i}",
+                Value::new(lib, &input.ty)?.param_type()?
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Signature {
+        name,
+        return_type,
+        params,
+    })
+}
+
+impl Generate for Cpp {
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        let caps = lib.backend.capabilities();
+
+        let mut backend_options = Vec::new();
+        let mut configure_backend = Vec::new();
+        for option in caps.config_options {
+            let (field, line) = match option {
+                ConfigOption::NumThreads => (
+                    "  /// Number of threads, 0 selects the number of cores\n  int num_threads = 0;",
+                    "    futhark_context_config_set_num_threads(config_, options.num_threads);",
+                ),
+                ConfigOption::Device => (
+                    "  /// Device name, unused if empty\n  std::string device;",
+                    "    if (!options.device.empty()) {\n      futhark_context_config_set_device(config_, options.device.c_str());\n    }",
+                ),
+                ConfigOption::Platform => (
+                    "  /// Platform name, unused if empty\n  std::string platform;",
+                    "    if (!options.platform.empty()) {\n      futhark_context_config_set_platform(config_, options.platform.c_str());\n    }",
+                ),
+            };
+            backend_options.push(field);
+            configure_backend.push(line);
+        }
+
+        let mut forward_declarations = Vec::new();
+        for ty in lib.types_in_order() {
+            forward_declarations.push(format!("class {};", class_name(ty)?));
+        }
+
+        let mut entry_declarations = Vec::new();
+        for entry in lib.entries.values() {
+            let Signature {
+                name,
+                return_type,
+                params,
+            } = signature(lib, entry)?;
+            let doc = doc_comment(
+                entry
+                    .doc
+                    .as_deref()
+                    .unwrap_or(&format!("Entry point: {}", entry.futhark_name)),
+            );
+            entry_declarations.push(format!(
+                "\n  {}\n  {return_type} {name}({});",
+                doc.replace('\n', "\n  "),
+                params.join(", ")
+            ));
+
+            for alias in &entry.aliases {
+                let alias = cpp_ident(alias, RESERVED_METHODS, || {
+                    format!("entry point {:?}", entry.futhark_name)
+                })?;
+                entry_declarations.push(format!(
+                    "\n  [[deprecated(\"renamed to {name}\")]]\n  {return_type} {alias}({});",
+                    params.join(", ")
+                ));
+            }
+        }
+
+        let header = lib
+            .h_file
+            .file_name()
+            .map(|h| h.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        render(
+            config,
+            "context.hpp",
+            &[
+                ("header", &header),
+                ("namespace", &namespace(config)?),
+                ("backend", &lib.backend.to_str()),
+                ("device_memory", &(caps.memory == Memory::Device)),
+                ("backend_options", &backend_options.join("\n")),
+                ("configure_backend", &configure_backend.join("\n")),
+                ("forward_declarations", &forward_declarations.join("\n")),
+                ("entry_declarations", &entry_declarations.join("\n")),
+            ],
+        )
+    }
+
+    fn array_type(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+        a: &ir::Array,
+    ) -> Result<(), Error> {
+        let elemtype = primitive_type(a.elemtype);
+        // `std::vector<bool>` doesn't store its elements contiguously
+        let vector_elemtype = if a.elemtype == ElemType::Bool {
+            "std::uint8_t"
+        } else {
+            elemtype
+        };
+        let dim_args = (0..a.rank)
+            .map(|i| format!("shape[{i}]"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let index_args = (0..a.rank)
+            .map(|i| format!("index[{i}]"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        render(
+            config,
+            "array.hpp",
+            &[
+                (
+                    "doc",
+                    &doc_comment(
+                        ty.doc
+                            .as_deref()
+                            .unwrap_or(&format!("Futhark type `{}`", ty.futhark_name)),
+                    ),
+                ),
+                ("class_name", &class_name(ty)?),
+                ("raw_type", &ty.raw_name),
+                ("rank", &a.rank),
+                ("elemtype", &elemtype),
+                ("vector_elemtype", &vector_elemtype),
+                ("new_fn", &a.ops.new),
+                ("free_fn", &a.ops.free),
+                ("values_fn", &a.ops.values),
+                ("shape_fn", &a.ops.shape),
+                ("index_fn", &a.ops.index),
+                ("dim_args", &dim_args),
+                ("index_args", &index_args),
+            ],
+        )
+    }
+
+    fn opaque_type(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let item = || format!("type {:?}", ty.futhark_name);
+        let class_name = class_name(ty)?;
+        let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+
+        let mut members = Vec::new();
+        match &ty.kind {
+            ir::TypeKind::Record(record) => {
+                let FieldParams { params, call_args } = field_params(lib, ty, &record.fields)?;
+                members.push(config.templates.render(
+                    "cpp",
+                    "record.hpp",
+                    &[
+                        ("class_name", &class_name),
+                        ("raw_type", &ty.raw_name),
+                        ("new_fn", &record.new_fn),
+                        ("new_params", &params.join(", ")),
+                        ("new_call_args", &call_args.join(", ")),
+                    ],
+                )?);
+                members.extend(field_projections(
+                    lib,
+                    config,
+                    ty,
+                    &class_name,
+                    &record.fields,
+                )?);
+            }
+            ir::TypeKind::Sum(sum) => {
+                members.extend(sum_members(lib, config, ty, &class_name, sum)?);
+            }
+            ir::TypeKind::OpaqueArray(array)
+            | ir::TypeKind::RecordArray(ir::RecordArray { array, .. }) => {
+                let Value::Named(elem) = Value::new(lib, &array.elemtype)? else {
+                    return Err(Error::InvalidManifest(format!(
+                        "{} has a primitive element type",
+                        item()
+                    )));
+                };
+                let index_args = (0..array.rank)
+                    .map(|i| format!("index[{i}]"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                members.push(config.templates.render(
+                    "cpp",
+                    "opaque_array.hpp",
+                    &[
+                        ("rank", &array.rank),
+                        ("shape_fn", &array.shape_fn),
+                        ("index_fn", &array.index_fn),
+                        ("index_args", &index_args),
+                        ("elem_class", &self::class_name(elem)?),
+                        ("elem_raw_type", &elem.raw_name),
+                    ],
+                )?);
+
+                if let ir::TypeKind::RecordArray(record) = &ty.kind {
+                    let FieldParams { params, call_args } = field_params(lib, ty, &record.fields)?;
+                    members.push(config.templates.render(
+                        "cpp",
+                        "record_array.hpp",
+                        &[
+                            ("class_name", &class_name),
+                            ("raw_type", &ty.raw_name),
+                            ("zip_fn", &record.zip_fn),
+                            ("zip_params", &params.join(", ")),
+                            ("zip_call_args", &call_args.join(", ")),
+                        ],
+                    )?);
+                    members.extend(field_projections(
+                        lib,
+                        config,
+                        ty,
+                        &class_name,
+                        &record.fields,
+                    )?);
+                }
+            }
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
+        }
+
+        render(
+            config,
+            "opaque.hpp",
+            &[
+                (
+                    "doc",
+                    &doc_comment(
+                        ty.doc
+                            .as_deref()
+                            .unwrap_or(&format!("Futhark type `{}`", ty.futhark_name)),
+                    ),
+                ),
+                ("class_name", &class_name),
+                ("raw_type", &ty.raw_name),
+                ("free_fn", &ops.free),
+                ("store_fn", &ops.store),
+                ("restore_fn", &ops.restore),
+                ("members", &members.concat()),
+            ],
+        )
+    }
+
+    fn type_aliases(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let class_name = class_name(ty)?;
+        for alias in &ty.aliases {
+            let alias = cpp_ident(alias, RESERVED_CLASSES, || {
+                format!("type {:?}", ty.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "using {alias} [[deprecated(\"renamed to {class_name}\")]] = {class_name};\n"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        entry: &ir::Entry,
+    ) -> Result<(), Error> {
+        let Signature {
+            name,
+            return_type,
+            params,
+        } = signature(lib, entry)?;
+        let mut out_decl = Vec::new();
+        let mut call_args = vec!["context_".to_string()];
+        let mut entry_return = Vec::new();
+
+        for (i, output) in entry.outputs.iter().enumerate() {
+            let value = Value::new(lib, &output.ty)?;
+            let name = format!("out{i}");
+            out_decl.push(format!("  {}", value.out_decl(&name)));
+            call_args.push(format!("&{name}"));
+            entry_return.push(value.output("*this", &name)?);
+        }
+
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let value = Value::new(lib, &input.ty)?;
+            call_args.push(value.arg(&format!("in{i}")));
+        }
+
+        let entry_return = match entry_return.len() {
+            0 => String::new(),
+            1 => entry_return.join(""),
+            _ => format!("std::make_tuple({})", entry_return.join(", ")),
+        };
+
+        render(
+            config,
+            "entry.hpp",
+            &[
+                ("name", &name),
+                ("return_type", &return_type),
+                ("entry_params", &params.join(", ")),
+                ("entry_fn", &entry.cfun),
+                ("out_decl", &out_decl.join("\n")),
+                ("call_args", &call_args.join(", ")),
+                ("entry_return", &entry_return),
+            ],
+        )?;
+
+        // Aliases are defined after the types, the return type may be incomplete
+        // inside the class
+        let args = (0..params.len())
+            .map(|i| format!("in{i}"))
+            .collect::<Vec<_>>();
+        for alias in &entry.aliases {
+            let alias = cpp_ident(alias, RESERVED_METHODS, || {
+                format!("entry point {:?}", entry.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "inline {return_type} Context::{alias}({}) {{\n  return {name}({});\n}}\n",
+                params.join(", "),
+                args.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, _lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        render(config, "footer.hpp", &[("namespace", &namespace(config)?)])
+    }
+
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        Ok(format!("// Generated by futhark-bindgen\n\n{code}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+    use crate::ir::tests::{aliasing_namer, all_types, library};
+
+    /// C header declaring every function used by `code`, arguments are left unchecked
+    fn stub_header(lib: &ir::Library, code: &str) -> String {
+        let mut header = String::from(
+            "#include <stdint.h>\nstruct futhark_context;\nstruct futhark_context_config;\n",
+        );
+        for ty in lib.types.values() {
+            let raw = &ty.raw_name;
+            let free = raw.replacen("futhark_", "futhark_free_", 1);
+            header.push_str(&format!(
+                "struct {raw};\nint {free}(struct futhark_context *, struct {raw} *);\n"
+            ));
+        }
+        let mut functions = std::collections::BTreeSet::new();
+        for (i, _) in code.match_indices("futhark_") {
+            let name: String = code[i..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect();
+            if code[i + name.len()..].starts_with('(') {
+                functions.insert(name);
+            }
+        }
+        for name in functions {
+            let decl = if name.starts_with("futhark_free_") {
+                continue;
+            } else if let Some(raw) = ["futhark_new_", "futhark_restore_"]
+                .iter()
+                .find_map(|prefix| name.strip_prefix(prefix))
+                .map(|raw| format!("futhark_{raw}"))
+                .filter(|raw| lib.types.values().any(|ty| &ty.raw_name == raw))
+            {
+                format!("struct {raw} *{name}(...)")
+            } else if name.starts_with("futhark_shape_") {
+                format!("const int64_t *{name}(...)")
+            } else {
+                match name.as_str() {
+                    "futhark_context_config_new" => {
+                        format!("struct futhark_context_config *{name}(...)")
+                    }
+                    "futhark_context_new" => format!("struct futhark_context *{name}(...)"),
+                    "futhark_context_get_error" | "futhark_context_report" => {
+                        format!("char *{name}(...)")
+                    }
+                    _ => format!("int {name}(...)"),
+                }
+            };
+            header.push_str(&decl);
+            header.push_str(";\n");
+        }
+        header
+    }
+
+    #[test]
+    fn generate_all_types() {
+        let pkg = all_types();
+        let mut config = Config::with_writer("lib.hpp", std::io::sink(), aliasing_namer());
+        let code = Cpp.generate_to_string(&pkg, &mut config).unwrap();
+        for item in [
+            "std::int32_t class_(std::int32_t in0);",
+            "std::int32_t end(std::int32_t in0);",
+            "Point make_point(const F32Array1d& in0, std::int64_t in1);",
+            "[[deprecated(\"renamed to make_point\")]]\n  Point make(",
+            "inline Point Context::make(const F32Array1d& in0, std::int64_t in1) {\n  return make_point(in0, in1);\n}",
+            "using ArrayF32D1 [[deprecated(\"renamed to F32Array1d\")]] = F32Array1d;",
+            "enum class Variant { none, some };",
+            "static Option some(Context& ctx, std::int64_t p0)",
+            "std::tuple<std::int64_t> get_some() const",
+            "Point(Context& ctx, const F32Array1d& xs, double w)",
+            "F32Array1d xs() const",
+            "check(futhark_entry_noop(context_));",
+        ] {
+            assert!(code.contains(item), "missing {item:?}");
+        }
+
+        // Check the header compiles against stub declarations when a compiler is available
+        if Command::new("g++").arg("--version").output().is_err() {
+            return;
+        }
+        let lib = library(&pkg, aliasing_namer()).unwrap();
+        let dir = std::env::temp_dir().join(format!("futhark-bindgen-cpp-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("test.h"), stub_header(&lib, &code)).unwrap();
+        std::fs::write(dir.join("lib.hpp"), &code).unwrap();
+        std::fs::write(dir.join("main.cpp"), "#include \"lib.hpp\"\n").unwrap();
+        for std in ["c++17", "c++20"] {
+            let output = Command::new("g++")
+                .arg(format!("-std={std}"))
+                .args(["-fsyntax-only", "-Wall", "main.cpp"])
+                .current_dir(&dir)
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "{std}: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,926 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::generate::IdentRules;
+use crate::ir::ElemType;
+use crate::*;
+
+/// C# codegen, writes a file using `[DllImport]` to call the shared library built by
+/// `Package::build_shared`, with `SafeHandle` subclasses for the context and values
+#[derive(Default)]
+pub struct CSharp;
+
+/// Types defined by `templates/csharp/context.cs`, these can't be used by generated classes
+const RESERVED_GLOBALS: &[&str] = &[
+    "Context",
+    "FutharkException",
+    "FutharkValue",
+    "Native",
+    "Options",
+];
+
+/// Members of `Context`, these can't be used by entry points
+const RESERVED_CONTEXT: &[&str] = &[
+    "AutoSync",
+    "Backend",
+    "Check",
+    "ClearCaches",
+    "Close",
+    "Context",
+    "DangerousAddRef",
+    "DangerousGetHandle",
+    "DangerousRelease",
+    "DeviceMemory",
+    "Dispose",
+    "Equals",
+    "Error",
+    "FreeConfig",
+    "GetError",
+    "GetHashCode",
+    "GetType",
+    "IsClosed",
+    "IsInvalid",
+    "KeepString",
+    "MaybeSync",
+    "PauseProfiling",
+    "ReleaseHandle",
+    "Report",
+    "SetHandle",
+    "SetHandleAsInvalid",
+    "Sync",
+    "TakeString",
+    "ToString",
+    "UnpauseProfiling",
+];
+
+/// Members of the generated classes, these can't be used for field properties and sum type
+/// constructors
+const RESERVED_MEMBERS: &[&str] = &[
+    "Close",
+    "Context",
+    "CopyTo",
+    "DangerousAddRef",
+    "DangerousGetHandle",
+    "DangerousRelease",
+    "Dispose",
+    "Equals",
+    "Free",
+    "GetHashCode",
+    "GetType",
+    "IsClosed",
+    "IsInvalid",
+    "New",
+    "NumElements",
+    "Rank",
+    "ReleaseHandle",
+    "Restore",
+    "SetHandle",
+    "SetHandleAsInvalid",
+    "Shape",
+    "Store",
+    "ToArray",
+    "ToString",
+    "Variant",
+    "Variants",
+    "Zip",
+];
+
+/// Names used in the body of generated methods, these can't be used for parameters
+const RESERVED_LOCALS: &[&str] = &[
+    "Check",
+    "Context",
+    "MaybeSync",
+    "Native",
+    "New",
+    "Zip",
+    "ctx",
+    "ptr",
+    "result",
+    "value",
+];
+
+fn cs_ident(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    IdentRules::CSHARP.escape(name, reserved, item)
+}
+
+/// Convert a name to a C# type or member name: `binary_search` becomes `BinarySearch`
+fn cs_pascal(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    let mut pascal = String::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let mut chars = part.chars();
+        if let Some(c) = chars.next() {
+            pascal.extend(c.to_uppercase());
+            pascal.push_str(chars.as_str());
+        }
+    }
+    cs_ident(&pascal, reserved, item)
+}
+
+/// Returns the C# type used for a primitive value and the type used by the C API
+fn primitive_type(t: ElemType) -> (&'static str, &'static str) {
+    match t {
+        ElemType::I8 => ("sbyte", "sbyte"),
+        ElemType::I16 => ("short", "short"),
+        ElemType::I32 => ("int", "int"),
+        ElemType::I64 => ("long", "long"),
+        ElemType::U8 => ("byte", "byte"),
+        ElemType::U16 => ("ushort", "ushort"),
+        ElemType::U32 => ("uint", "uint"),
+        ElemType::U64 => ("ulong", "ulong"),
+        // The C API stores half precision values as their bits
+        ElemType::F16 => ("Half", "ushort"),
+        ElemType::F32 => ("float", "float"),
+        ElemType::F64 => ("double", "double"),
+        // C `bool` is a single byte, passing it as a byte keeps the declarations blittable
+        ElemType::Bool => ("bool", "byte"),
+    }
+}
+
+/// Name of the class generated for a type
+fn class_name(ty: &ir::Type) -> Result<String, Error> {
+    cs_pascal(&ty.name, RESERVED_GLOBALS, || {
+        format!("type {:?}", ty.futhark_name)
+    })
+}
+
+/// Render documentation as an XML doc comment indented by `indent`
+fn doc_comment(doc: &str, indent: &str) -> String {
+    let doc = doc
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    let lines = doc.lines().collect::<Vec<_>>();
+    if lines.len() == 1 {
+        return format!("{indent}/// <summary>{}</summary>", lines[0]);
+    }
+    let mut comment = format!("{indent}/// <summary>\n");
+    for line in lines {
+        comment.push_str(format!("{indent}/// {line}").trim_end());
+        comment.push('\n');
+    }
+    comment.push_str(&format!("{indent}/// </summary>"));
+    comment
+}
+
+/// Get the documentation for a type, using the Futhark name if the `Namer` didn't provide
+/// any
+fn type_doc(ty: &ir::Type) -> String {
+    doc_comment(
+        ty.doc
+            .as_deref()
+            .unwrap_or(&format!("Futhark type `{}`", ty.futhark_name)),
+        "",
+    )
+}
+
+/// Expression passing the span `name` to a `ref` parameter of the C type `c_type`
+fn span_ref(name: &str, cs_type: &str, c_type: &str) -> String {
+    if cs_type == c_type {
+        format!("MemoryMarshal.GetReference({name})")
+    } else {
+        format!("MemoryMarshal.GetReference(MemoryMarshal.Cast<{cs_type}, {c_type}>({name}))")
+    }
+}
+
+/// How a value is passed to the C API
+enum Value<'a> {
+    Primitive(ElemType),
+    Named(&'a ir::Type),
+}
+
+impl<'a> Value<'a> {
+    fn new(lib: &'a ir::Library, ty: &ir::TypeRef) -> Result<Self, Error> {
+        match (ty, lib.get(ty)) {
+            (ir::TypeRef::Primitive(t), _) => Ok(Value::Primitive(*t)),
+            (_, Some(t)) => Ok(Value::Named(t)),
+            (ir::TypeRef::Named(name), None) => {
+                Err(Error::InvalidManifest(format!("unknown type {name:?}")))
+            }
+        }
+    }
+
+    /// C# type used in method signatures
+    fn cs_type(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(t) => Ok(primitive_type(*t).0.to_string()),
+            Value::Named(t) => class_name(t),
+        }
+    }
+
+    /// Type of an input parameter in `[DllImport]` declarations
+    fn c_input(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(t) => Ok(primitive_type(*t).1.to_string()),
+            Value::Named(t) => class_name(t),
+        }
+    }
+
+    /// Type of an output parameter in `[DllImport]` declarations
+    fn c_output(&self) -> String {
+        match self {
+            Value::Primitive(t) => format!("out {}", primitive_type(*t).1),
+            Value::Named(_) => "out IntPtr".to_string(),
+        }
+    }
+
+    /// Expression passing `name` to a C function
+    fn arg(&self, name: &str) -> String {
+        match self {
+            Value::Primitive(ElemType::F16) => format!("BitConverter.HalfToUInt16Bits({name})"),
+            Value::Primitive(ElemType::Bool) => format!("(byte)({name} ? 1 : 0)"),
+            Value::Primitive(_) | Value::Named(_) => name.to_string(),
+        }
+    }
+
+    /// Expression wrapping the value stored in the output variable `name`
+    fn output(&self, ctx: &str, name: &str) -> Result<String, Error> {
+        match self {
+            Value::Primitive(ElemType::F16) => Ok(format!("BitConverter.UInt16BitsToHalf({name})")),
+            Value::Primitive(ElemType::Bool) => Ok(format!("{name} != 0")),
+            Value::Primitive(_) => Ok(name.to_string()),
+            Value::Named(t) => Ok(format!("new {}({ctx}, {name})", class_name(t)?)),
+        }
+    }
+}
+
+/// A single type, or a tuple of types
+fn tuple(items: &[String]) -> String {
+    match items {
+        [item] => item.clone(),
+        _ => format!("({})", items.join(", ")),
+    }
+}
+
+/// Get the output file, relative to the output directory
+fn file(config: &Config) -> PathBuf {
+    PathBuf::from(config.output_path.file_name().unwrap_or_default())
+}
+
+/// Render a template from `templates/csharp` and append it to the file
+fn render(config: &mut Config, name: &str, args: &[(&str, &dyn Display)]) -> Result<(), Error> {
+    let code = config.templates.render("csharp", name, args)?;
+    writeln!(config.output(file(config)), "{code}")?;
+    Ok(())
+}
+
+/// `[DllImport]` declaration of a C function, `params` include their names
+fn declare(name: &str, ret: &str, params: &[String]) -> String {
+    format!(
+        "\n    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]\n    internal static extern {ret} {name}({});\n",
+        params.join(", ")
+    )
+}
+
+/// `long` parameters named `prefix0`, `prefix1`, ... for each dimension
+fn dim_params(prefix: &str, rank: usize) -> Vec<String> {
+    (0..rank).map(|i| format!("long {prefix}{i}")).collect()
+}
+
+/// Arguments named `prefix0`, `prefix1`, ... for each dimension
+fn dim_args(prefix: &str, rank: usize) -> String {
+    (0..rank)
+        .map(|i| format!("{prefix}{i}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Declarations for the C functions used by a type
+fn type_declarations(lib: &ir::Library, ty: &ir::Type, out: &mut Vec<String>) -> Result<(), Error> {
+    let ctx = "Context ctx".to_string();
+    let this = format!("{} obj", class_name(ty)?);
+
+    if let ir::TypeKind::Array(a) = &ty.kind {
+        let (_, c_type) = primitive_type(a.elemtype);
+        let dims = dim_params("dim", a.rank);
+        out.push(declare(
+            &a.ops.new,
+            "IntPtr",
+            &[
+                vec![ctx.clone(), format!("ref {c_type} data")],
+                dims.clone(),
+            ]
+            .concat(),
+        ));
+        out.push(declare(
+            &a.ops.free,
+            "int",
+            &["IntPtr ctx".to_string(), "IntPtr arr".to_string()],
+        ));
+        out.push(declare(
+            &a.ops.values,
+            "int",
+            &[ctx.clone(), this.clone(), format!("ref {c_type} data")],
+        ));
+        out.push(declare(
+            &a.ops.shape,
+            "IntPtr",
+            &[ctx.clone(), this.clone()],
+        ));
+        out.push(declare(
+            &a.ops.index,
+            "int",
+            &[vec![ctx, format!("out {c_type} value"), this], dims].concat(),
+        ));
+        return Ok(());
+    }
+
+    let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+    out.push(declare(
+        &ops.free,
+        "int",
+        &["IntPtr ctx".to_string(), "IntPtr obj".to_string()],
+    ));
+    // `store` is called with a null pointer to get the size, then with a pinned buffer
+    for p in ["IntPtr p", "ref IntPtr p"] {
+        out.push(declare(
+            &ops.store,
+            "int",
+            &[
+                ctx.clone(),
+                this.clone(),
+                p.to_string(),
+                "out nuint n".to_string(),
+            ],
+        ));
+    }
+    out.push(declare(
+        &ops.restore,
+        "IntPtr",
+        &[ctx.clone(), "byte[] data".to_string()],
+    ));
+
+    let fields = |fields: &[ir::Field], new_fn: &str, out: &mut Vec<String>| {
+        let mut params = vec![ctx.clone(), "out IntPtr ptr".to_string()];
+        for (i, field) in fields.iter().enumerate() {
+            let value = Value::new(lib, &field.ty)?;
+            out.push(declare(
+                &field.project_fn,
+                "int",
+                &[
+                    ctx.clone(),
+                    format!("{} value", value.c_output()),
+                    this.clone(),
+                ],
+            ));
+            params.push(format!("{} f{i}", value.c_input()?));
+        }
+        out.push(declare(new_fn, "int", &params));
+        Ok::<_, Error>(())
+    };
+
+    let opaque_array = |a: &ir::OpaqueArray, out: &mut Vec<String>| {
+        out.push(declare(&a.shape_fn, "IntPtr", &[ctx.clone(), this.clone()]));
+        out.push(declare(
+            &a.index_fn,
+            "int",
+            &[
+                vec![ctx.clone(), "out IntPtr ptr".to_string(), this.clone()],
+                dim_params("i", a.rank),
+            ]
+            .concat(),
+        ));
+    };
+
+    match &ty.kind {
+        ir::TypeKind::Record(r) => fields(&r.fields, &r.new_fn, out)?,
+        ir::TypeKind::Sum(s) => {
+            out.push(declare(&s.variant_fn, "int", &[ctx.clone(), this.clone()]));
+            for v in &s.variants {
+                let payload = v
+                    .payload
+                    .iter()
+                    .map(|p| Value::new(lib, p))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut construct = vec![ctx.clone(), "out IntPtr ptr".to_string()];
+                let mut destruct = vec![ctx.clone()];
+                for (i, p) in payload.iter().enumerate() {
+                    construct.push(format!("{} p{i}", p.c_input()?));
+                    destruct.push(format!("{} p{i}", p.c_output()));
+                }
+                destruct.push(this.clone());
+                out.push(declare(&v.construct_fn, "int", &construct));
+                out.push(declare(&v.destruct_fn, "int", &destruct));
+            }
+        }
+        ir::TypeKind::OpaqueArray(a) => opaque_array(a, out),
+        ir::TypeKind::RecordArray(r) => {
+            opaque_array(&r.array, out);
+            fields(&r.fields, &r.zip_fn, out)?;
+        }
+        ir::TypeKind::Array(_) => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Render a constructor taking one parameter per field, used for records and arrays of
+/// records
+fn field_constructor(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+    template: &str,
+    fn_key: &str,
+    c_fn: &str,
+) -> Result<String, Error> {
+    let mut names = Vec::new();
+    let mut params = String::new();
+    let mut args = String::new();
+    let mut call_args = String::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let reserved = RESERVED_LOCALS
+            .iter()
+            .copied()
+            .chain(names.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+        let name = cs_ident(&field.name, &reserved, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        params.push_str(&format!(", {} {name}", value.cs_type()?));
+        args.push_str(&format!(", {name}"));
+        call_args.push_str(&format!(", {}", value.arg(&name)));
+        names.push(name);
+    }
+    config.templates.render(
+        "csharp",
+        template,
+        &[
+            ("class_name", &class_name(ty)?),
+            (fn_key, &c_fn),
+            ("params", &params),
+            ("args", &args),
+            ("call_args", &call_args),
+        ],
+    )
+}
+
+/// Names that can't be used for members of `ty`, a member can't have the same name as its
+/// class
+fn reserved_members(ty: &ir::Type) -> Result<Vec<String>, Error> {
+    let mut reserved = RESERVED_MEMBERS
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    reserved.push(class_name(ty)?);
+    Ok(reserved)
+}
+
+/// Render field properties for records and arrays of records
+fn field_projections(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+) -> Result<Vec<String>, Error> {
+    let reserved = reserved_members(ty)?;
+    let reserved = reserved.iter().map(String::as_str).collect::<Vec<_>>();
+    let mut members = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let project_name = cs_pascal(&field.project_name, &reserved, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        members.push(config.templates.render(
+            "csharp",
+            "record_project.cs",
+            &[
+                ("field_name", &field.futhark_name),
+                ("project_name", &project_name),
+                ("project_fn", &field.project_fn),
+                ("cs_type", &value.cs_type()?),
+                ("value_return", &value.output("Context", "value")?),
+            ],
+        )?);
+    }
+    Ok(members)
+}
+
+/// Members of sum types: the `Variants` enum, constructors and payload getters
+fn sum_members(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    sum: &ir::Sum,
+) -> Result<Vec<String>, Error> {
+    let class_name = class_name(ty)?;
+    let reserved = reserved_members(ty)?;
+    let reserved = reserved.iter().map(String::as_str).collect::<Vec<_>>();
+    let mut variants = String::new();
+    let mut members = Vec::new();
+    for variant in &sum.variants {
+        let item = || format!("variant {:?} of type {:?}", variant.name, ty.futhark_name);
+        let name = cs_pascal(&variant.name, &reserved, item)?;
+        let getter = cs_ident(&format!("Get{name}"), &reserved, item)?;
+        let payload = variant
+            .payload
+            .iter()
+            .map(|p| Value::new(lib, p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut construct_params = String::new();
+        let mut construct_args = String::new();
+        for (i, p) in payload.iter().enumerate() {
+            construct_params.push_str(&format!(", {} p{i}", p.cs_type()?));
+            construct_args.push_str(&format!(", {}", p.arg(&format!("p{i}"))));
+        }
+        members.push(config.templates.render(
+            "csharp",
+            "sum_variant.cs",
+            &[
+                ("class_name", &class_name),
+                ("variant_name", &variant.name),
+                ("name", &name),
+                ("construct_fn", &variant.construct_fn),
+                ("construct_params", &construct_params),
+                ("construct_args", &construct_args),
+            ],
+        )?);
+
+        if !payload.is_empty() {
+            let mut payload_types = Vec::new();
+            let mut destruct_args = String::new();
+            let mut destruct_return = Vec::new();
+            for (i, p) in payload.iter().enumerate() {
+                payload_types.push(p.cs_type()?);
+                destruct_args.push_str(&format!(", out var p{i}"));
+                destruct_return.push(p.output("Context", &format!("p{i}"))?);
+            }
+            members.push(config.templates.render(
+                "csharp",
+                "sum_payload.cs",
+                &[
+                    ("variant_name", &variant.name),
+                    ("variant", &name),
+                    ("name", &getter),
+                    ("payload_type", &tuple(&payload_types)),
+                    ("destruct_fn", &variant.destruct_fn),
+                    ("destruct_args", &destruct_args),
+                    ("destruct_return", &tuple(&destruct_return)),
+                ],
+            )?);
+        }
+        variants.push_str(&format!("        {name},\n"));
+    }
+
+    let sum = config.templates.render(
+        "csharp",
+        "sum.cs",
+        &[("variants", &variants), ("variant_fn", &sum.variant_fn)],
+    )?;
+    members.insert(0, sum);
+    Ok(members)
+}
+
+/// Name of the method generated for an entry point, or one of its aliases
+fn entry_name(entry: &ir::Entry, name: &str) -> Result<String, Error> {
+    cs_pascal(name, RESERVED_CONTEXT, || {
+        format!("entry point {:?}", entry.futhark_name)
+    })
+}
+
+/// Names of the parameters of an entry point, using the Futhark parameter names
+fn entry_params(entry: &ir::Entry) -> Vec<String> {
+    let mut reserved = RESERVED_LOCALS
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    reserved.extend((0..entry.outputs.len()).map(|i| format!("out{i}")));
+    let mut params = Vec::new();
+    for (i, input) in entry.inputs.iter().enumerate() {
+        let r = reserved.iter().map(String::as_str).collect::<Vec<_>>();
+        let name = cs_ident(&input.name, &r, || {
+            format!(
+                "input {:?} of entry point {:?}",
+                input.name, entry.futhark_name
+            )
+        })
+        .unwrap_or_else(|_| format!("in{i}"));
+        reserved.push(name.clone());
+        params.push(name);
+    }
+    params
+}
+
+impl Generate for CSharp {
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        let caps = lib.backend.capabilities();
+
+        let mut declarations = Vec::new();
+        let mut backend_options = Vec::new();
+        let mut configure_backend = Vec::new();
+        for option in caps.config_options {
+            let (param, field, line) = match option {
+                ConfigOption::NumThreads => (
+                    "int n",
+                    "\n    /// <summary>Number of threads, 0 selects the number of cores</summary>\n    public int NumThreads { get; set; }\n",
+                    "            Native.futhark_context_config_set_num_threads(config, options.NumThreads);\n",
+                ),
+                ConfigOption::Device => (
+                    "IntPtr device",
+                    "\n    /// <summary>Device name, unused if null</summary>\n    public string? Device { get; set; }\n",
+                    "            if (options.Device != null)\n            {\n                Native.futhark_context_config_set_device(config, KeepString(options.Device));\n            }\n",
+                ),
+                ConfigOption::Platform => (
+                    "IntPtr platform",
+                    "\n    /// <summary>Platform name, unused if null</summary>\n    public string? Platform { get; set; }\n",
+                    "            if (options.Platform != null)\n            {\n                Native.futhark_context_config_set_platform(config, KeepString(options.Platform));\n            }\n",
+                ),
+            };
+            declarations.push(declare(
+                option.c_function(),
+                "void",
+                &["IntPtr config".to_string(), param.to_string()],
+            ));
+            backend_options.push(field);
+            configure_backend.push(line);
+        }
+
+        for ty in lib.types_in_order() {
+            type_declarations(lib, ty, &mut declarations)?;
+        }
+
+        for entry in lib.entries.values() {
+            let mut params = vec!["Context ctx".to_string()];
+            for (i, output) in entry.outputs.iter().enumerate() {
+                params.push(format!(
+                    "{} out{i}",
+                    Value::new(lib, &output.ty)?.c_output()
+                ));
+            }
+            for (i, input) in entry.inputs.iter().enumerate() {
+                params.push(format!("{} in{i}", Value::new(lib, &input.ty)?.c_input()?));
+            }
+            declarations.push(declare(&entry.cfun, "int", &params));
+        }
+
+        let library = lib
+            .c_file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let namespace = config
+            .output_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        let namespace = cs_pascal(&namespace, RESERVED_GLOBALS, || {
+            format!("namespace for {}", config.output_path.display())
+        })?;
+        render(
+            config,
+            "context.cs",
+            &[
+                ("namespace", &namespace),
+                ("library", &library),
+                ("backend", &lib.backend.to_str()),
+                ("device_memory", &(caps.memory == Memory::Device)),
+                ("declarations", &declarations.concat()),
+                ("backend_options", &backend_options.concat()),
+                ("configure_backend", &configure_backend.concat()),
+            ],
+        )
+    }
+
+    fn array_type(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+        a: &ir::Array,
+    ) -> Result<(), Error> {
+        let (cs_type, c_type) = primitive_type(a.elemtype);
+        let value = Value::Primitive(a.elemtype);
+        render(
+            config,
+            "array.cs",
+            &[
+                ("doc", &type_doc(ty)),
+                ("class_name", &class_name(ty)?),
+                ("rank", &a.rank),
+                ("elem_type", &cs_type),
+                ("shape_params", &dim_params("dim", a.rank).join(", ")),
+                ("shape_args", &dim_args("dim", a.rank)),
+                (
+                    "num_elements",
+                    &dim_args("dim", a.rank).replace(", ", " * "),
+                ),
+                ("data_ref", &span_ref("data", cs_type, c_type)),
+                ("destination_ref", &span_ref("destination", cs_type, c_type)),
+                ("index_params", &dim_params("i", a.rank).join(", ")),
+                ("index_args", &dim_args("i", a.rank)),
+                ("value_return", &value.output("Context", "value")?),
+                ("new_fn", &a.ops.new),
+                ("free_fn", &a.ops.free),
+                ("values_fn", &a.ops.values),
+                ("shape_fn", &a.ops.shape),
+                ("index_fn", &a.ops.index),
+            ],
+        )
+    }
+
+    fn opaque_type(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let item = || format!("type {:?}", ty.futhark_name);
+        let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+
+        let mut members = Vec::new();
+        match &ty.kind {
+            ir::TypeKind::Record(record) => {
+                members.push(field_constructor(
+                    lib,
+                    config,
+                    ty,
+                    &record.fields,
+                    "record.cs",
+                    "new_fn",
+                    &record.new_fn,
+                )?);
+                members.extend(field_projections(lib, config, ty, &record.fields)?);
+            }
+            ir::TypeKind::Sum(sum) => members.extend(sum_members(lib, config, ty, sum)?),
+            ir::TypeKind::OpaqueArray(array)
+            | ir::TypeKind::RecordArray(ir::RecordArray { array, .. }) => {
+                let Value::Named(elem) = Value::new(lib, &array.elemtype)? else {
+                    return Err(Error::InvalidManifest(format!(
+                        "{} has a primitive element type",
+                        item()
+                    )));
+                };
+                members.push(config.templates.render(
+                    "csharp",
+                    "opaque_array.cs",
+                    &[
+                        ("rank", &array.rank),
+                        ("shape_fn", &array.shape_fn),
+                        ("index_fn", &array.index_fn),
+                        ("index_params", &dim_params("i", array.rank).join(", ")),
+                        ("index_args", &dim_args("i", array.rank)),
+                        ("elem_class", &class_name(elem)?),
+                    ],
+                )?);
+
+                if let ir::TypeKind::RecordArray(record) = &ty.kind {
+                    members.push(field_constructor(
+                        lib,
+                        config,
+                        ty,
+                        &record.fields,
+                        "record_array.cs",
+                        "zip_fn",
+                        &record.zip_fn,
+                    )?);
+                    members.extend(field_projections(lib, config, ty, &record.fields)?);
+                }
+            }
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
+        }
+
+        render(
+            config,
+            "opaque.cs",
+            &[
+                ("doc", &type_doc(ty)),
+                ("class_name", &class_name(ty)?),
+                ("free_fn", &ops.free),
+                ("store_fn", &ops.store),
+                ("restore_fn", &ops.restore),
+                ("members", &members.concat()),
+            ],
+        )
+    }
+
+    fn entry(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        entry: &ir::Entry,
+    ) -> Result<(), Error> {
+        // Entry points are methods of `Context`, the partial class is closed by `finish`
+        if lib.entries.values().next().map(|e| &e.futhark_name) == Some(&entry.futhark_name) {
+            write!(
+                config.output(file(config)),
+                "public sealed partial class Context\n{{"
+            )?;
+        }
+
+        let name = entry_name(entry, &entry.name)?;
+        let params = entry_params(entry);
+        let mut result_types = Vec::new();
+        let mut call_args = String::new();
+        let mut results = Vec::new();
+        for (i, output) in entry.outputs.iter().enumerate() {
+            let value = Value::new(lib, &output.ty)?;
+            result_types.push(value.cs_type()?);
+            call_args.push_str(&format!(", out var out{i}"));
+            results.push(value.output("this", &format!("out{i}"))?);
+        }
+
+        let mut entry_params = Vec::new();
+        for (input, param) in entry.inputs.iter().zip(&params) {
+            let value = Value::new(lib, &input.ty)?;
+            entry_params.push(format!("{} {param}", value.cs_type()?));
+            call_args.push_str(&format!(", {}", value.arg(param)));
+        }
+        let entry_params = entry_params.join(", ");
+
+        let (result_type, result_decl, entry_return) = if results.is_empty() {
+            ("void".to_string(), String::new(), String::new())
+        } else {
+            (
+                tuple(&result_types),
+                format!("        var result = {};\n", tuple(&results)),
+                "\n        return result;".to_string(),
+            )
+        };
+
+        let doc = doc_comment(
+            entry
+                .doc
+                .as_deref()
+                .unwrap_or(&format!("Entry point: {}", entry.futhark_name)),
+            "    ",
+        );
+        render(
+            config,
+            "entry.cs",
+            &[
+                ("doc", &doc),
+                ("results", &result_type),
+                ("name", &name),
+                ("entry_fn", &entry.cfun),
+                ("entry_params", &entry_params),
+                ("call_args", &call_args),
+                ("result_decl", &result_decl),
+                ("entry_return", &entry_return),
+            ],
+        )?;
+
+        let param_names = params.join(", ");
+        let call = if results.is_empty() { "" } else { "return " };
+        for alias in &entry.aliases {
+            let alias = entry_name(entry, alias)?;
+            writeln!(
+                config.output(file(config)),
+                "\n    /// <summary>Deprecated alias for <see cref=\"{name}\"/></summary>\n    [Obsolete(\"Use {name} instead\")]\n    public {result_type} {alias}({entry_params})\n    {{\n        {call}{name}({param_names});\n    }}"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        if !lib.entries.is_empty() {
+            writeln!(config.output(file(config)), "}}")?;
+        }
+        Ok(())
+    }
+
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        Ok(format!(
+            "// <auto-generated>\n// Generated by futhark-bindgen\n// </auto-generated>\n\n{}\n",
+            code.trim_end()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::tests::{aliasing_namer, all_types};
+
+    #[test]
+    fn generate_all_types() {
+        let mut config = Config::with_writer("Lib.cs", std::io::sink(), aliasing_namer());
+        let code = CSharp
+            .generate_to_string(&all_types(), &mut config)
+            .unwrap();
+        for item in [
+            "public int Class(int x)",
+            "public int Type(int x)",
+            "public Point MakePoint(F32Array1d xs, long n)",
+            "[Obsolete(\"Use MakePoint instead\")]\n    public Point Make(F32Array1d xs, long n)",
+            "public enum Variants",
+            "public Variants Variant =>",
+            "public static Option Some(Context ctx, long p0)",
+            "public long GetSome()",
+            "public Point(Context ctx, F32Array1d xs, double w)",
+            "public F32Array1d Xs",
+        ] {
+            assert!(code.contains(item), "missing {item:?}");
+        }
+    }
+}
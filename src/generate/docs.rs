@@ -0,0 +1,520 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::*;
+
+/// Output format of the `Docs` generator
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DocFormat {
+    /// A single Markdown file, anchors use inline `<a id>` tags
+    #[default]
+    Markdown,
+
+    /// A single static HTML page without external resources
+    Html,
+}
+
+/// API reference codegen, writes a single Markdown or HTML page describing every entry
+/// point and type, grouped by kind and cross-linked
+///
+/// Items are listed using their Futhark names, since those are the same for every output
+/// language, along with the names of the C functions and structs
+#[derive(Default)]
+pub struct Docs {
+    format: DocFormat,
+    title: String,
+    header: String,
+    entries: Vec<String>,
+    groups: [BTreeMap<String, String>; 5],
+}
+
+/// Type sections, in the order they appear on the page
+const GROUPS: [(&str, &str); 5] = [
+    ("section-records", "Records"),
+    ("section-sums", "Sum types"),
+    ("section-arrays", "Arrays"),
+    ("section-opaque-arrays", "Arrays of opaque values"),
+    ("section-record-arrays", "Arrays of records"),
+];
+
+const RECORDS: usize = 0;
+const SUMS: usize = 1;
+const ARRAYS: usize = 2;
+const OPAQUE_ARRAYS: usize = 3;
+const RECORD_ARRAYS: usize = 4;
+
+impl Docs {
+    /// Create a generator writing `format`
+    pub fn new(format: DocFormat) -> Docs {
+        Docs {
+            format,
+            ..Docs::default()
+        }
+    }
+
+    /// Get the output format
+    pub fn format(&self) -> DocFormat {
+        self.format
+    }
+
+    /// Name of the template directory
+    fn language(&self) -> &'static str {
+        match self.format {
+            DocFormat::Markdown => "markdown",
+            DocFormat::Html => "html",
+        }
+    }
+
+    fn render(
+        &self,
+        config: &Config,
+        name: &str,
+        args: &[(&str, &dyn Display)],
+    ) -> Result<String, Error> {
+        let ext = match self.format {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        };
+        config
+            .templates
+            .render(self.language(), &format!("{name}.{ext}"), args)
+    }
+
+    /// Escape text for the output format
+    fn text(&self, s: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => s.to_string(),
+            DocFormat::Html => s
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;"),
+        }
+    }
+
+    fn code(&self, s: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => format!("`{s}`"),
+            DocFormat::Html => format!("<code>{}</code>", self.text(s)),
+        }
+    }
+
+    fn link(&self, text: &str, anchor: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => format!("[{text}](#{anchor})"),
+            DocFormat::Html => format!("<a href=\"#{anchor}\">{text}</a>"),
+        }
+    }
+
+    /// Render a block of text, empty if `text` is `None`
+    fn paragraph(&self, text: Option<&str>) -> String {
+        match (self.format, text) {
+            (_, None) => String::new(),
+            (DocFormat::Markdown, Some(text)) => format!("{text}\n\n"),
+            (DocFormat::Html, Some(text)) => format!("<p>{}</p>\n", self.text(text)),
+        }
+    }
+
+    /// Render a titled block, `body` is already formatted
+    fn block(&self, title: &str, body: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => format!("**{title}**\n\n{body}\n"),
+            DocFormat::Html => format!("<h4>{title}</h4>\n{body}"),
+        }
+    }
+
+    fn list(&self, items: &[String]) -> String {
+        match self.format {
+            DocFormat::Markdown => items.iter().map(|i| format!("- {i}\n")).collect(),
+            DocFormat::Html => {
+                let items: String = items.iter().map(|i| format!("<li>{i}</li>\n")).collect();
+                format!("<ul>\n{items}</ul>\n")
+            }
+        }
+    }
+
+    /// Render a table, cells are already formatted
+    fn table(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        match self.format {
+            DocFormat::Markdown => {
+                let mut out = format!("| {} |\n|", headers.join(" | "));
+                out.push_str(&"---|".repeat(headers.len()));
+                out.push('\n');
+                for row in rows {
+                    out.push_str(&format!("| {} |\n", row.join(" | ")));
+                }
+                out
+            }
+            DocFormat::Html => {
+                let mut out = String::from("<table>\n<tr>");
+                for h in headers {
+                    out.push_str(&format!("<th>{h}</th>"));
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        out.push_str(&format!("<td>{cell}</td>"));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+                out
+            }
+        }
+    }
+
+    /// Render a type reference, linking to the type's section
+    fn type_ref(&self, lib: &ir::Library, ty: &ir::TypeRef) -> String {
+        let code = self.code(ty.futhark_name());
+        match ty {
+            ir::TypeRef::Primitive(_) => code,
+            ir::TypeRef::Named(name) => match lib.types.get(name) {
+                Some(t) => self.link(&code, &type_anchor(t)),
+                None => code,
+            },
+        }
+    }
+
+    fn fields(&self, lib: &ir::Library, fields: &[ir::Field]) -> String {
+        let rows = fields
+            .iter()
+            .map(|f| vec![self.code(&f.futhark_name), self.type_ref(lib, &f.ty)])
+            .collect::<Vec<_>>();
+        self.block("Fields", &self.table(&["Field", "Type"], &rows))
+    }
+
+    /// Render entry point inputs or outputs, outputs are unnamed and listed by position
+    fn params(&self, lib: &ir::Library, title: &str, params: &[ir::Param], named: bool) -> String {
+        if params.is_empty() {
+            return String::new();
+        }
+        let rows = params
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let name = if named {
+                    self.code(&p.name)
+                } else {
+                    i.to_string()
+                };
+                let unique = if p.unique { "yes" } else { "no" };
+                vec![name, self.type_ref(lib, &p.ty), unique.to_string()]
+            })
+            .collect::<Vec<_>>();
+        let name = if named { "Name" } else { "#" };
+        self.block(title, &self.table(&[name, "Type", "Unique"], &rows))
+    }
+
+    /// Render one type, `kind` is the template name
+    fn type_section(
+        &self,
+        config: &Config,
+        ty: &ir::Type,
+        kind: &str,
+        args: &[(&str, &dyn Display)],
+    ) -> Result<String, Error> {
+        let anchor = type_anchor(ty);
+        let name = self.code(&ty.futhark_name);
+        let doc = self.paragraph(ty.doc.as_deref());
+        let raw_name = self.code(&format!("struct {}", ty.raw_name));
+        let mut all: Vec<(&str, &dyn Display)> = vec![
+            ("anchor", &anchor),
+            ("name", &name),
+            ("doc", &doc),
+            ("raw_name", &raw_name),
+        ];
+        all.extend_from_slice(args);
+        self.render(config, kind, &all)
+    }
+}
+
+/// Anchor of a type section, based on the name selected by the `Namer`, which is unique
+fn type_anchor(ty: &ir::Type) -> String {
+    format!("type-{}", slug(&ty.name))
+}
+
+fn entry_anchor(entry: &ir::Entry) -> String {
+    format!("entry-{}", slug(&entry.futhark_name))
+}
+
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Get the output file, relative to the output directory
+fn file(config: &Config) -> PathBuf {
+    PathBuf::from(config.output_path.file_name().unwrap_or_default())
+}
+
+impl Generate for Docs {
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        *self = Docs::new(self.format);
+        self.title = config
+            .output_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.header = format!(
+            "Futhark {}, {} backend",
+            self.text(&lib.version),
+            lib.backend.to_str()
+        );
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+        a: &ir::Array,
+    ) -> Result<(), Error> {
+        let section = self.type_section(
+            config,
+            ty,
+            "array",
+            &[
+                ("elemtype", &self.code(a.elemtype.to_str())),
+                ("rank", &a.rank),
+            ],
+        )?;
+        self.groups[ARRAYS].insert(ty.futhark_name.clone(), section);
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let (group, section) = match &ty.kind {
+            ir::TypeKind::Record(r) => (
+                RECORDS,
+                self.type_section(
+                    config,
+                    ty,
+                    "record",
+                    &[("fields", &self.fields(lib, &r.fields))],
+                )?,
+            ),
+            ir::TypeKind::Sum(s) => {
+                let rows = s
+                    .variants
+                    .iter()
+                    .map(|v| {
+                        let payload = v
+                            .payload
+                            .iter()
+                            .map(|t| self.type_ref(lib, t))
+                            .collect::<Vec<_>>();
+                        vec![self.code(&format!("#{}", v.name)), payload.join(", ")]
+                    })
+                    .collect::<Vec<_>>();
+                let variants = self.block("Variants", &self.table(&["Variant", "Payload"], &rows));
+                (
+                    SUMS,
+                    self.type_section(config, ty, "sum", &[("variants", &variants)])?,
+                )
+            }
+            ir::TypeKind::OpaqueArray(a) => (
+                OPAQUE_ARRAYS,
+                self.type_section(
+                    config,
+                    ty,
+                    "opaque_array",
+                    &[
+                        ("elemtype", &self.type_ref(lib, &a.elemtype)),
+                        ("rank", &a.rank),
+                    ],
+                )?,
+            ),
+            ir::TypeKind::RecordArray(a) => (
+                RECORD_ARRAYS,
+                self.type_section(
+                    config,
+                    ty,
+                    "record_array",
+                    &[
+                        ("elemtype", &self.type_ref(lib, &a.array.elemtype)),
+                        ("rank", &a.array.rank),
+                        ("fields", &self.fields(lib, &a.fields)),
+                    ],
+                )?,
+            ),
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
+        };
+        self.groups[group].insert(ty.futhark_name.clone(), section);
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        entry: &ir::Entry,
+    ) -> Result<(), Error> {
+        let tuning_params = if entry.tuning_params.is_empty() {
+            String::new()
+        } else {
+            let params = entry
+                .tuning_params
+                .iter()
+                .map(|p| self.code(p))
+                .collect::<Vec<_>>();
+            self.block("Tuning parameters", &self.list(&params))
+        };
+        let section = self.render(
+            config,
+            "entry",
+            &[
+                ("anchor", &entry_anchor(entry)),
+                ("name", &self.code(&entry.futhark_name)),
+                ("doc", &self.paragraph(entry.doc.as_deref())),
+                ("cfun", &self.code(&entry.cfun)),
+                ("inputs", &self.params(lib, "Inputs", &entry.inputs, true)),
+                (
+                    "outputs",
+                    &self.params(lib, "Outputs", &entry.outputs, false),
+                ),
+                ("tuning_params", &tuning_params),
+            ],
+        )?;
+        self.entries.push(section);
+        Ok(())
+    }
+
+    fn finish(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        let heading = |anchor: &str, title: &str| match self.format {
+            DocFormat::Markdown => format!("<a id=\"{anchor}\"></a>\n## {title}\n\n"),
+            DocFormat::Html => format!("<h2 id=\"{anchor}\">{title}</h2>\n"),
+        };
+
+        let mut toc = Vec::new();
+        let mut sections = String::new();
+        if !self.entries.is_empty() {
+            let links = lib
+                .entries
+                .values()
+                .map(|e| self.link(&self.code(&e.futhark_name), &entry_anchor(e)))
+                .collect::<Vec<_>>();
+            toc.push(format!(
+                "{}\n{}",
+                self.link("Entry points", "section-entries"),
+                self.list(&links).trim_end()
+            ));
+            sections.push_str(&heading("section-entries", "Entry points"));
+            for section in &self.entries {
+                sections.push_str(section);
+            }
+        }
+        for ((anchor, title), group) in GROUPS.iter().zip(&self.groups) {
+            if group.is_empty() {
+                continue;
+            }
+            let links = group
+                .keys()
+                .map(|name| self.link(&self.code(name), &type_anchor(&lib.types[name])))
+                .collect::<Vec<_>>();
+            toc.push(format!(
+                "{}\n{}",
+                self.link(title, anchor),
+                self.list(&links).trim_end()
+            ));
+            sections.push_str(&heading(anchor, title));
+            for section in group.values() {
+                sections.push_str(section);
+            }
+        }
+        let toc = match self.format {
+            DocFormat::Markdown => toc
+                .iter()
+                .map(|t| format!("- {}\n", t.replace('\n', "\n  ")))
+                .collect::<String>(),
+            DocFormat::Html => self.list(&toc),
+        };
+
+        let page = self.render(
+            config,
+            "page",
+            &[
+                ("title", &self.text(&self.title)),
+                ("header", &self.header),
+                ("toc", &toc),
+                ("sections", &sections),
+            ],
+        )?;
+        write!(config.output(file(config)), "{page}")?;
+        Ok(())
+    }
+
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        let mut code = code.trim_end().to_string();
+        if self.format == DocFormat::Markdown {
+            // Collapse the blank lines left by empty placeholders
+            while code.contains("\n\n\n") {
+                code = code.replace("\n\n\n", "\n\n");
+            }
+        }
+        code.push('\n');
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::tests::{aliasing_namer, all_types};
+
+    #[test]
+    fn generate_all_types() {
+        let pkg = all_types();
+        for (format, output, items) in [
+            (
+                DocFormat::Markdown,
+                "api.md",
+                [
+                    "- [`class`](#entry-class)",
+                    "<a id=\"entry-class\"></a>\n### `class`",
+                    "<a id=\"type-Point\"></a>",
+                    "| `xs` | [`[]f32`](#type-F32Array1d) |",
+                    "| `#some` | `i64` |",
+                ],
+            ),
+            (
+                DocFormat::Html,
+                "api.html",
+                [
+                    "<li><a href=\"#entry-class\"><code>class</code></a></li>",
+                    "<section id=\"entry-class\">",
+                    "<section id=\"type-Point\">",
+                    "<tr><td><code>xs</code></td><td><a href=\"#type-F32Array1d\"><code>[]f32</code></a></td></tr>",
+                    "<tr><td><code>#some</code></td><td><code>i64</code></td></tr>",
+                ],
+            ),
+        ] {
+            let mut config = Config::with_writer(output, std::io::sink(), aliasing_namer());
+            let code = Docs::new(format)
+                .generate_to_string(&pkg, &mut config)
+                .unwrap();
+            for item in items {
+                assert!(code.contains(item), "missing {item:?}");
+            }
+            // Items are documented by their Futhark names, binding names and aliases are
+            // specific to each language
+            for name in ["make_point", "ArrayF32D1"] {
+                assert!(!code.contains(name), "unexpected {name:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+/// Selects the entry points and types included in the bindings
+///
+/// Patterns are matched against Futhark names and may contain `*`, which matches any
+/// sequence of characters. An empty `include_entries` includes every entry point.
+///
+/// Types are kept when they're used by a kept entry point, directly or through another
+/// type, or when they match `include_types`. A filter that doesn't select any entry points
+/// or types, such as the default one, keeps every type. Generation fails with
+/// `Error::ExcludedType` if a kept item uses a type matching `exclude_types`
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Entry points to include
+    pub include_entries: Vec<String>,
+
+    /// Entry points to exclude, takes precedence over `include_entries`
+    pub exclude_entries: Vec<String>,
+
+    /// Types to include, even if no entry point uses them
+    pub include_types: Vec<String>,
+
+    /// Types that must not be generated, takes precedence over `include_types`
+    pub exclude_types: Vec<String>,
+}
+
+/// Match `name` against a pattern where `*` matches any sequence of characters
+fn matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let Some(last) = parts.pop() else {
+        // No `*` in the pattern
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+fn any_match(patterns: &[String], name: &str) -> bool {
+    patterns.iter().any(|p| matches(p, name))
+}
+
+impl Filter {
+    /// Include entry points matching `pattern`
+    pub fn include_entry(mut self, pattern: impl Into<String>) -> Filter {
+        self.include_entries.push(pattern.into());
+        self
+    }
+
+    /// Exclude entry points matching `pattern`
+    pub fn exclude_entry(mut self, pattern: impl Into<String>) -> Filter {
+        self.exclude_entries.push(pattern.into());
+        self
+    }
+
+    /// Include types matching `pattern`
+    pub fn include_type(mut self, pattern: impl Into<String>) -> Filter {
+        self.include_types.push(pattern.into());
+        self
+    }
+
+    /// Exclude types matching `pattern`
+    pub fn exclude_type(mut self, pattern: impl Into<String>) -> Filter {
+        self.exclude_types.push(pattern.into());
+        self
+    }
+
+    /// Returns true if the entry point should be generated
+    pub fn entry(&self, futhark_name: &str) -> bool {
+        (self.include_entries.is_empty() || any_match(&self.include_entries, futhark_name))
+            && !any_match(&self.exclude_entries, futhark_name)
+    }
+
+    /// Returns true if the type may be generated
+    pub fn type_allowed(&self, futhark_name: &str) -> bool {
+        !any_match(&self.exclude_types, futhark_name)
+    }
+
+    /// Returns true if entry points or types are selected, otherwise every type is included
+    fn selects_items(&self) -> bool {
+        !self.include_entries.is_empty()
+            || !self.exclude_entries.is_empty()
+            || !self.include_types.is_empty()
+    }
+
+    /// Returns true if the type should be generated even if it isn't used
+    pub fn type_included(&self, futhark_name: &str) -> bool {
+        (!self.selects_items() || any_match(&self.include_types, futhark_name))
+            && self.type_allowed(futhark_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matching() {
+        assert!(matches("add", "add"));
+        assert!(!matches("add", "add2"));
+        assert!(!matches("add", "xadd"));
+        assert!(matches("*", ""));
+        assert!(matches("*", "anything"));
+        assert!(matches("test_*", "test_sum"));
+        assert!(!matches("test_*", "sum_test"));
+        assert!(matches("*_2d", "f32_2d"));
+        assert!(!matches("*_2d", "f32_2d_x"));
+        assert!(matches("a*b*c", "abc"));
+        assert!(matches("a*b*c", "axxbyyc"));
+        assert!(!matches("a*b*c", "acb"));
+        // The prefix and suffix can't overlap
+        assert!(!matches("ab*ba", "aba"));
+        assert!(matches("[]*", "[][]f32"));
+    }
+
+    #[test]
+    fn entry_selection() {
+        let filter = Filter::default()
+            .include_entry("test_*")
+            .exclude_entry("*_slow");
+        assert!(filter.entry("test_sum"));
+        assert!(!filter.entry("test_sum_slow"));
+        assert!(!filter.entry("main"));
+        assert!(Filter::default().entry("main"));
+    }
+
+    #[test]
+    fn default_filter_includes_every_type() {
+        assert!(Filter::default().type_included("point"));
+        assert!(!Filter::default()
+            .exclude_type("point")
+            .type_included("point"));
+        assert!(!Filter::default()
+            .include_entry("main")
+            .type_included("point"));
+        assert!(Filter::default()
+            .include_entry("main")
+            .include_type("po*")
+            .type_included("point"));
+    }
+}
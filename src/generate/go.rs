@@ -0,0 +1,792 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::generate::IdentRules;
+use crate::ir::ElemType;
+use crate::*;
+
+/// Go codegen, writes a cgo package wrapping the Futhark C API, the generated C file is
+/// compiled by cgo since it's in the same directory
+#[derive(Default)]
+pub struct Go;
+
+/// Package level names defined by `templates/go/context.go`, these can't be used by
+/// generated types
+const RESERVED_GLOBALS: &[&str] = &[
+    "Backend",
+    "Context",
+    "DeviceMemory",
+    "ErrFreed",
+    "Error",
+    "NewContext",
+    "Options",
+];
+
+/// Methods of `Context`, these can't be used by entry points
+const RESERVED_CONTEXT: &[&str] = &[
+    "ClearCaches",
+    "Free",
+    "GetError",
+    "PauseProfiling",
+    "Report",
+    "Sync",
+    "UnpauseProfiling",
+];
+
+/// Methods of the generated types, these can't be used for field accessors and sum type
+/// payload getters
+const RESERVED_MEMBERS: &[&str] = &["Free", "Index", "Shape", "Store", "Values", "Variant"];
+
+/// Names used in the body of generated functions, these can't be used for parameters
+const RESERVED_LOCALS: &[&str] = &[
+    "C", "c", "ctx", "err", "errors", "fmt", "out", "ptr", "rc", "runtime", "unsafe", "v",
+];
+
+fn go_ident(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    IdentRules::GO.escape(name, reserved, item)
+}
+
+/// Convert a name to an exported Go identifier: `binary_search` becomes `BinarySearch`
+fn go_exported(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    let mut exported = String::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let mut chars = part.chars();
+        if let Some(c) = chars.next() {
+            exported.extend(c.to_uppercase());
+            exported.push_str(chars.as_str());
+        }
+    }
+    if !exported.starts_with(char::is_uppercase) {
+        exported.insert(0, 'X');
+    }
+    go_ident(&exported, reserved, item)
+}
+
+/// Returns the Go type and C type used for a primitive value
+fn primitive_type(t: ElemType) -> (&'static str, &'static str) {
+    match t {
+        ElemType::I8 => ("int8", "int8_t"),
+        ElemType::I16 => ("int16", "int16_t"),
+        ElemType::I32 => ("int32", "int32_t"),
+        ElemType::I64 => ("int64", "int64_t"),
+        ElemType::U8 => ("uint8", "uint8_t"),
+        ElemType::U16 => ("uint16", "uint16_t"),
+        ElemType::U32 => ("uint32", "uint32_t"),
+        ElemType::U64 => ("uint64", "uint64_t"),
+        // Go has no half precision type, values are passed as their bits
+        ElemType::F16 => ("uint16", "uint16_t"),
+        ElemType::F32 => ("float32", "float"),
+        ElemType::F64 => ("float64", "double"),
+        ElemType::Bool => ("bool", "bool"),
+    }
+}
+
+/// Name of the Go type generated for a type
+fn class_name(ty: &ir::Type) -> Result<String, Error> {
+    go_exported(&ty.name, RESERVED_GLOBALS, || {
+        format!("type {:?}", ty.futhark_name)
+    })
+}
+
+/// Render documentation as a Go comment
+fn doc_comment(doc: &str) -> String {
+    doc.lines()
+        .map(|line| format!("// {line}").trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Get the documentation for a type, Go doc comments start with the name of the item
+fn type_doc(ty: &ir::Type) -> Result<String, Error> {
+    Ok(doc_comment(&ty.doc.clone().unwrap_or(format!(
+        "{} is the Futhark type `{}`",
+        class_name(ty)?,
+        ty.futhark_name
+    ))))
+}
+
+/// How a value is passed to the C API
+enum Value<'a> {
+    Primitive(ElemType),
+    Named(&'a ir::Type),
+}
+
+impl<'a> Value<'a> {
+    fn new(lib: &'a ir::Library, ty: &ir::TypeRef) -> Result<Self, Error> {
+        match (ty, lib.get(ty)) {
+            (ir::TypeRef::Primitive(t), _) => Ok(Value::Primitive(*t)),
+            (_, Some(t)) => Ok(Value::Named(t)),
+            (ir::TypeRef::Named(name), None) => {
+                Err(Error::InvalidManifest(format!("unknown type {name:?}")))
+            }
+        }
+    }
+
+    /// Go type used in function signatures
+    fn go_type(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(t) => Ok(primitive_type(*t).0.to_string()),
+            Value::Named(t) => Ok(format!("*{}", class_name(t)?)),
+        }
+    }
+
+    /// Type of the C variable used for outputs
+    fn c_type(&self) -> String {
+        match self {
+            Value::Primitive(t) => format!("C.{}", primitive_type(*t).1),
+            Value::Named(t) => format!("*C.struct_{}", t.raw_name),
+        }
+    }
+
+    /// Value returned along with an error
+    fn zero(&self) -> &'static str {
+        match self {
+            Value::Primitive(ElemType::Bool) => "false",
+            Value::Primitive(_) => "0",
+            Value::Named(_) => "nil",
+        }
+    }
+
+    /// Expression converting the C output variable `name` to a Go value
+    fn output(&self, ctx: &str, name: &str) -> Result<String, Error> {
+        match self {
+            Value::Primitive(t) => Ok(format!("{}({name})", primitive_type(*t).0)),
+            Value::Named(t) => Ok(format!("wrap{}({ctx}, {name})", class_name(t)?)),
+        }
+    }
+}
+
+/// Conversions from Go parameters to C arguments
+struct Inputs {
+    /// Statements getting the pointers wrapped by opaque values and arrays
+    decls: String,
+
+    /// Arguments, each one preceded by a comma
+    call_args: String,
+
+    /// `runtime.KeepAlive` calls for wrapped values, these must stay alive until the C
+    /// function returns
+    keep_alive: String,
+}
+
+fn inputs(values: &[(Value, String)], zero_return: &str) -> Inputs {
+    let mut decls = String::new();
+    let mut call_args = String::new();
+    let mut keep_alive = String::new();
+    for (i, (value, name)) in values.iter().enumerate() {
+        match value {
+            Value::Primitive(t) => {
+                call_args.push_str(&format!(", C.{}({name})", primitive_type(*t).1));
+            }
+            Value::Named(_) => {
+                decls.push_str(&format!(
+                    "\traw{i}, err := {name}.raw()\n\tif err != nil {{\n\t\treturn {zero_return}err\n\t}}\n"
+                ));
+                call_args.push_str(&format!(", raw{i}"));
+                keep_alive.push_str(&format!("\truntime.KeepAlive({name})\n"));
+            }
+        }
+    }
+    Inputs {
+        decls,
+        call_args,
+        keep_alive,
+    }
+}
+
+/// Names that can't be used for parameters of a function with `n` parameters and
+/// outputs
+fn reserved_locals(n: usize) -> Vec<String> {
+    let mut reserved = RESERVED_LOCALS
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    for i in 0..n {
+        reserved.extend([format!("raw{i}"), format!("out{i}"), format!("p{i}")]);
+    }
+    reserved
+}
+
+/// Result list of a function returning `types` and an error
+fn results(types: &[String]) -> String {
+    if types.is_empty() {
+        return "error".to_string();
+    }
+    format!("({}, error)", types.join(", "))
+}
+
+/// `C.int64_t` arguments for each element of the array `name`
+fn index_args(name: &str, rank: usize) -> String {
+    (0..rank)
+        .map(|i| format!("C.int64_t({name}[{i}])"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Get the package file, relative to the output directory
+fn file(config: &Config) -> PathBuf {
+    PathBuf::from(config.output_path.file_name().unwrap_or_default())
+}
+
+/// Render a template from `templates/go` and append it to the package
+fn render(config: &mut Config, name: &str, args: &[(&str, &dyn Display)]) -> Result<(), Error> {
+    let code = config.templates.render("go", name, args)?;
+    writeln!(config.output(file(config)), "{code}")?;
+    Ok(())
+}
+
+/// Render a function taking one parameter per field, used for record constructors and
+/// `zip` functions, `keys` are the placeholders for the C function, parameters, input
+/// conversions and call arguments
+fn field_constructor(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+    template: &str,
+    keys: [&str; 4],
+    c_fn: &str,
+) -> Result<String, Error> {
+    let reserved = reserved_locals(fields.len());
+    let mut names = Vec::new();
+    let mut params = String::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let r = reserved
+            .iter()
+            .chain(&names)
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let name = go_ident(&field.name, &r, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        params.push_str(&format!(", {name} {}", value.go_type()?));
+        names.push(name.clone());
+        values.push((value, name));
+    }
+    let Inputs {
+        decls,
+        call_args,
+        keep_alive,
+    } = inputs(&values, "nil, ");
+    let [fn_key, params_key, inputs_key, call_args_key] = keys;
+    config.templates.render(
+        "go",
+        template,
+        &[
+            ("class_name", &class_name(ty)?),
+            ("raw_name", &ty.raw_name),
+            (fn_key, &c_fn),
+            (params_key, &params),
+            (inputs_key, &decls),
+            (call_args_key, &call_args),
+            ("keep_alive", &keep_alive),
+        ],
+    )
+}
+
+/// Render field accessors for records and arrays of records
+fn field_projections(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+) -> Result<Vec<String>, Error> {
+    let mut members = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let project_name = go_exported(&field.project_name, RESERVED_MEMBERS, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        members.push(config.templates.render(
+            "go",
+            "record_project.go",
+            &[
+                ("class_name", &class_name(ty)?),
+                ("field_name", &field.futhark_name),
+                ("project_name", &project_name),
+                ("project_fn", &field.project_fn),
+                ("go_type", &value.go_type()?),
+                ("out_type", &value.c_type()),
+                ("zero", &value.zero()),
+                ("out_return", &value.output("v.ctx", "out")?),
+            ],
+        )?);
+    }
+    Ok(members)
+}
+
+/// Functions for sum types: the variant names, constructors and payload getters
+fn sum_members(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    sum: &ir::Sum,
+) -> Result<Vec<String>, Error> {
+    let class_name = class_name(ty)?;
+    let mut variants = Vec::new();
+    let mut members = Vec::new();
+    for variant in &sum.variants {
+        let name = go_exported(&variant.name, RESERVED_MEMBERS, || {
+            format!("variant {:?} of type {:?}", variant.name, ty.futhark_name)
+        })?;
+        let payload = variant
+            .payload
+            .iter()
+            .map(|p| Value::new(lib, p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut construct_params = String::new();
+        let mut values = Vec::new();
+        for (i, p) in payload.into_iter().enumerate() {
+            construct_params.push_str(&format!(", p{i} {}", p.go_type()?));
+            values.push((p, format!("p{i}")));
+        }
+        let Inputs {
+            decls,
+            call_args,
+            keep_alive,
+        } = inputs(&values, "nil, ");
+        members.push(config.templates.render(
+            "go",
+            "sum_variant.go",
+            &[
+                ("class_name", &class_name),
+                ("raw_name", &ty.raw_name),
+                ("variant_name", &variant.name),
+                ("name", &name),
+                ("construct_fn", &variant.construct_fn),
+                ("construct_params", &construct_params),
+                ("construct_inputs", &decls),
+                ("construct_args", &call_args),
+                ("keep_alive", &keep_alive),
+            ],
+        )?);
+
+        if !values.is_empty() {
+            let mut return_types = Vec::new();
+            let mut zero_return = String::new();
+            let mut destruct_decls = String::new();
+            let mut destruct_args = String::new();
+            let mut destruct_return = String::new();
+            for (p, name) in &values {
+                return_types.push(p.go_type()?);
+                zero_return.push_str(&format!("{}, ", p.zero()));
+                destruct_decls.push_str(&format!("\tvar {name} {}\n", p.c_type()));
+                destruct_args.push_str(&format!(", &{name}"));
+                destruct_return.push_str(&format!("{}, ", p.output("v.ctx", name)?));
+            }
+            members.push(config.templates.render(
+                "go",
+                "sum_payload.go",
+                &[
+                    ("class_name", &class_name),
+                    ("variant_name", &variant.name),
+                    ("name", &name),
+                    ("results", &results(&return_types)),
+                    ("zero_return", &zero_return),
+                    ("destruct_fn", &variant.destruct_fn),
+                    ("destruct_decls", &destruct_decls),
+                    ("destruct_args", &destruct_args),
+                    ("destruct_return", &destruct_return),
+                ],
+            )?);
+        }
+        variants.push(format!("{:?}", variant.name));
+    }
+
+    let sum = config.templates.render(
+        "go",
+        "sum.go",
+        &[
+            ("class_name", &class_name),
+            ("variants", &variants.join(", ")),
+            ("variant_fn", &sum.variant_fn),
+        ],
+    )?;
+    members.insert(0, sum);
+    Ok(members)
+}
+
+/// Signature of the method generated for an entry point
+struct Signature {
+    name: String,
+    params: Vec<(String, String)>,
+    results: String,
+}
+
+fn signature(lib: &ir::Library, entry: &ir::Entry) -> Result<Signature, Error> {
+    let name = go_exported(&entry.name, RESERVED_CONTEXT, || {
+        format!("entry point {:?}", entry.futhark_name)
+    })?;
+    let mut reserved = reserved_locals(entry.inputs.len().max(entry.outputs.len()));
+    let mut params = Vec::new();
+    for (i, input) in entry.inputs.iter().enumerate() {
+        let r = reserved.iter().map(String::as_str).collect::<Vec<_>>();
+        let param = go_ident(&input.name, &r, || {
+            format!(
+                "input {:?} of entry point {:?}",
+                input.name, entry.futhark_name
+            )
+        })
+        .unwrap_or_else(|_| format!("in{i}"));
+        reserved.push(param.clone());
+        params.push((param, Value::new(lib, &input.ty)?.go_type()?));
+    }
+    let return_types = entry
+        .outputs
+        .iter()
+        .map(|output| Value::new(lib, &output.ty)?.go_type())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Signature {
+        name,
+        params,
+        results: results(&return_types),
+    })
+}
+
+impl Generate for Go {
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        let caps = lib.backend.capabilities();
+
+        let mut backend_options = Vec::new();
+        let mut configure_backend = Vec::new();
+        for option in caps.config_options {
+            let (field, line) = match option {
+                ConfigOption::NumThreads => (
+                    "\n\t// NumThreads is the number of threads, 0 selects the number of cores\n\tNumThreads int\n",
+                    "\tC.futhark_context_config_set_num_threads(ctx.config, C.int(options.NumThreads))",
+                ),
+                ConfigOption::Device => (
+                    "\n\t// Device name, unused if empty\n\tDevice string\n",
+                    "\tif options.Device != \"\" {\n\t\tC.futhark_context_config_set_device(ctx.config, ctx.cString(options.Device))\n\t}",
+                ),
+                ConfigOption::Platform => (
+                    "\n\t// Platform name, unused if empty\n\tPlatform string\n",
+                    "\tif options.Platform != \"\" {\n\t\tC.futhark_context_config_set_platform(ctx.config, ctx.cString(options.Platform))\n\t}",
+                ),
+            };
+            backend_options.push(field);
+            configure_backend.push(line);
+        }
+
+        // `#cgo` directives, the C file is compiled by cgo and linked with the same
+        // libraries printed by the `libs` subcommand
+        let mut cgo_flags = Vec::new();
+        let c_flags = caps
+            .c_flags
+            .iter()
+            .filter(|flag| !flag.starts_with("-l"))
+            .copied()
+            .collect::<Vec<_>>();
+        if !c_flags.is_empty() {
+            cgo_flags.push(format!("#cgo CFLAGS: {}", c_flags.join(" ")));
+        }
+        let linker_flags = lib.backend.linker_flags().join(" ");
+        if caps.macos_frameworks.is_empty() {
+            if !linker_flags.is_empty() {
+                cgo_flags.push(format!("#cgo LDFLAGS: {linker_flags}"));
+            }
+        } else {
+            let darwin_flags = caps
+                .link_libs
+                .iter()
+                .map(|l| {
+                    if caps.macos_frameworks.contains(l) {
+                        format!("-framework {l}")
+                    } else {
+                        format!("-l{l}")
+                    }
+                })
+                .collect::<Vec<_>>();
+            cgo_flags.push(format!("#cgo !darwin LDFLAGS: {linker_flags}"));
+            cgo_flags.push(format!("#cgo darwin LDFLAGS: {}", darwin_flags.join(" ")));
+        }
+        let stem = lib
+            .c_file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for step in caps.compile_steps {
+            match step {
+                CompileStep::Ispc => {
+                    cgo_flags.push(format!("#cgo LDFLAGS: ${{SRCDIR}}/{stem}.kernels.o"))
+                }
+            }
+        }
+
+        let package = config
+            .output_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        let package = go_ident(&package, &[], || {
+            format!("package for {}", config.output_path.display())
+        })?;
+        let header = lib
+            .h_file
+            .file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        render(
+            config,
+            "context.go",
+            &[
+                ("package", &package),
+                ("cgo_flags", &cgo_flags.join("\n")),
+                ("header", &header),
+                ("backend", &lib.backend.to_str()),
+                ("device_memory", &(caps.memory == Memory::Device)),
+                ("backend_options", &backend_options.concat()),
+                ("configure_backend", &configure_backend.join("\n")),
+            ],
+        )
+    }
+
+    fn array_type(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+        a: &ir::Array,
+    ) -> Result<(), Error> {
+        let (go_type, ctype) = primitive_type(a.elemtype);
+        render(
+            config,
+            "array.go",
+            &[
+                ("doc", &type_doc(ty)?),
+                ("class_name", &class_name(ty)?),
+                ("raw_name", &ty.raw_name),
+                ("rank", &a.rank),
+                ("go_type", &go_type),
+                ("ctype", &ctype),
+                ("shape_args", &index_args("shape", a.rank)),
+                ("index_args", &index_args("index", a.rank)),
+                ("new_fn", &a.ops.new),
+                ("free_fn", &a.ops.free),
+                ("values_fn", &a.ops.values),
+                ("shape_fn", &a.ops.shape),
+                ("index_fn", &a.ops.index),
+            ],
+        )
+    }
+
+    fn opaque_type(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let item = || format!("type {:?}", ty.futhark_name);
+        let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+
+        let mut members = Vec::new();
+        match &ty.kind {
+            ir::TypeKind::Record(record) => {
+                members.push(field_constructor(
+                    lib,
+                    config,
+                    ty,
+                    &record.fields,
+                    "record.go",
+                    ["new_fn", "new_params", "new_inputs", "new_call_args"],
+                    &record.new_fn,
+                )?);
+                members.extend(field_projections(lib, config, ty, &record.fields)?);
+            }
+            ir::TypeKind::Sum(sum) => members.extend(sum_members(lib, config, ty, sum)?),
+            ir::TypeKind::OpaqueArray(array)
+            | ir::TypeKind::RecordArray(ir::RecordArray { array, .. }) => {
+                let Value::Named(elem) = Value::new(lib, &array.elemtype)? else {
+                    return Err(Error::InvalidManifest(format!(
+                        "{} has a primitive element type",
+                        item()
+                    )));
+                };
+                members.push(config.templates.render(
+                    "go",
+                    "opaque_array.go",
+                    &[
+                        ("class_name", &class_name(ty)?),
+                        ("rank", &array.rank),
+                        ("shape_fn", &array.shape_fn),
+                        ("index_fn", &array.index_fn),
+                        ("index_args", &index_args("index", array.rank)),
+                        ("elem_class", &class_name(elem)?),
+                        ("elem_raw_name", &elem.raw_name),
+                    ],
+                )?);
+
+                if let ir::TypeKind::RecordArray(record) = &ty.kind {
+                    members.push(field_constructor(
+                        lib,
+                        config,
+                        ty,
+                        &record.fields,
+                        "record_array.go",
+                        ["zip_fn", "zip_params", "zip_inputs", "zip_call_args"],
+                        &record.zip_fn,
+                    )?);
+                    members.extend(field_projections(lib, config, ty, &record.fields)?);
+                }
+            }
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
+        }
+
+        render(
+            config,
+            "opaque.go",
+            &[
+                ("doc", &type_doc(ty)?),
+                ("class_name", &class_name(ty)?),
+                ("raw_name", &ty.raw_name),
+                ("free_fn", &ops.free),
+                ("store_fn", &ops.store),
+                ("restore_fn", &ops.restore),
+                ("members", &members.concat()),
+            ],
+        )
+    }
+
+    fn type_aliases(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let class_name = class_name(ty)?;
+        for alias in &ty.aliases {
+            let alias = go_exported(alias, RESERVED_GLOBALS, || {
+                format!("type {:?}", ty.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "// {alias} is an alias for {class_name}\n//\n// Deprecated: use {class_name} instead.\ntype {alias} = {class_name}\n"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        entry: &ir::Entry,
+    ) -> Result<(), Error> {
+        let Signature {
+            name,
+            params,
+            results,
+        } = signature(lib, entry)?;
+
+        let mut zero_return = String::new();
+        let mut out_decl = String::new();
+        let mut out_args = String::new();
+        let mut entry_return = String::new();
+        for (i, output) in entry.outputs.iter().enumerate() {
+            let value = Value::new(lib, &output.ty)?;
+            zero_return.push_str(&format!("{}, ", value.zero()));
+            out_decl.push_str(&format!("\tvar out{i} {}\n", value.c_type()));
+            out_args.push_str(&format!(", &out{i}"));
+            entry_return.push_str(&format!("{}, ", value.output("ctx", &format!("out{i}"))?));
+        }
+
+        let mut values = Vec::new();
+        for (input, (param, _)) in entry.inputs.iter().zip(&params) {
+            values.push((Value::new(lib, &input.ty)?, param.clone()));
+        }
+        let Inputs {
+            decls,
+            call_args,
+            keep_alive,
+        } = inputs(&values, &zero_return);
+
+        let entry_params = params
+            .iter()
+            .map(|(name, t)| format!("{name} {t}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let doc = doc_comment(&entry.doc.clone().unwrap_or(format!(
+            "{name} calls the entry point `{}`",
+            entry.futhark_name
+        )));
+        render(
+            config,
+            "entry.go",
+            &[
+                ("name", &name),
+                ("doc", &doc),
+                ("entry_fn", &entry.cfun),
+                ("entry_params", &entry_params),
+                ("results", &results),
+                ("zero_return", &zero_return),
+                ("entry_inputs", &decls),
+                ("out_decl", &out_decl),
+                ("call_args", &format!("{out_args}{call_args}")),
+                ("keep_alive", &keep_alive),
+                ("entry_return", &entry_return),
+            ],
+        )?;
+
+        let param_names = params
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        for alias in &entry.aliases {
+            let alias = go_exported(alias, RESERVED_CONTEXT, || {
+                format!("entry point {:?}", entry.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "// {alias} is an alias for {name}\n//\n// Deprecated: use {name} instead.\nfunc (ctx *Context) {alias}({entry_params}) {results} {{\n\treturn ctx.{name}({param_names})\n}}\n"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        Ok(format!(
+            "// Code generated by futhark-bindgen. DO NOT EDIT.\n\n{}\n",
+            code.trim_end()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::tests::{aliasing_namer, all_types};
+
+    #[test]
+    fn generate_all_types() {
+        let mut config = Config::with_writer("lib.go", std::io::sink(), aliasing_namer());
+        let code = Go.generate_to_string(&all_types(), &mut config).unwrap();
+        for item in [
+            "func (ctx *Context) Type(x int32) (int32, error) {",
+            "func (ctx *Context) MakePoint(xs *F32Array1d, n int64) (*Point, error) {",
+            "// Deprecated: use MakePoint instead.\nfunc (ctx *Context) Make(xs *F32Array1d, n int64) (*Point, error) {",
+            "// Deprecated: use F32Array1d instead.\ntype ArrayF32D1 = F32Array1d",
+            "func (v *Option) Variant() (string, error) {",
+            "func NewOptionSome(ctx *Context, p0 int64) (*Option, error) {",
+            "func (v *Option) Some() (int64, error) {",
+            "func NewPoint(ctx *Context, xs *F32Array1d, w float64) (*Point, error) {",
+            "func (v *Point) Xs() (*F32Array1d, error) {",
+        ] {
+            assert!(code.contains(item), "missing {item:?}");
+        }
+    }
+}
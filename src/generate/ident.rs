@@ -0,0 +1,724 @@
+use crate::*;
+
+/// Identifier rules for an output language
+///
+/// Names selected by the `Namer` are passed through `IdentRules::escape` before they are
+/// used in generated code, keywords are mapped to raw identifiers where the language has
+/// them, or suffixed otherwise
+#[derive(Debug, Clone, Copy)]
+pub struct IdentRules {
+    /// Language name, used in error messages
+    pub language: &'static str,
+
+    /// Reserved words
+    pub keywords: &'static [&'static str],
+
+    /// Prefix used to turn a keyword into an identifier, `r#` for Rust
+    pub raw_prefix: Option<&'static str>,
+
+    /// Keywords that can't be used as raw identifiers
+    pub not_raw: &'static [&'static str],
+
+    /// Suffix added to keywords and reserved names that can't use `raw_prefix`
+    pub suffix: &'static str,
+
+    /// Only allow ASCII identifiers
+    pub ascii_only: bool,
+}
+
+impl IdentRules {
+    /// Rust identifiers
+    pub const RUST: IdentRules = IdentRules {
+        language: "Rust",
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match",
+            "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+            "super", "trait", "true", "type", "unsafe", "use", "where", "while", "abstract",
+            "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+            "unsized", "virtual", "yield", "_",
+        ],
+        raw_prefix: Some("r#"),
+        not_raw: &["crate", "self", "Self", "super", "_"],
+        suffix: "_",
+        ascii_only: false,
+    };
+
+    /// OCaml identifiers
+    pub const OCAML: IdentRules = IdentRules {
+        language: "OCaml",
+        keywords: &[
+            "and",
+            "as",
+            "assert",
+            "asr",
+            "begin",
+            "class",
+            "constraint",
+            "do",
+            "done",
+            "downto",
+            "else",
+            "end",
+            "exception",
+            "external",
+            "false",
+            "for",
+            "fun",
+            "function",
+            "functor",
+            "if",
+            "in",
+            "include",
+            "inherit",
+            "initializer",
+            "land",
+            "lazy",
+            "let",
+            "lor",
+            "lsl",
+            "lsr",
+            "lxor",
+            "match",
+            "method",
+            "mod",
+            "module",
+            "mutable",
+            "new",
+            "nonrec",
+            "object",
+            "of",
+            "open",
+            "or",
+            "private",
+            "rec",
+            "sig",
+            "struct",
+            "then",
+            "to",
+            "true",
+            "try",
+            "type",
+            "val",
+            "virtual",
+            "when",
+            "while",
+            "with",
+            "effect",
+            "_",
+        ],
+        raw_prefix: None,
+        not_raw: &[],
+        suffix: "_",
+        ascii_only: true,
+    };
+
+    /// C++ identifiers
+    pub const CPP: IdentRules = IdentRules {
+        language: "C++",
+        keywords: &[
+            "alignas",
+            "alignof",
+            "and",
+            "and_eq",
+            "asm",
+            "auto",
+            "bitand",
+            "bitor",
+            "bool",
+            "break",
+            "case",
+            "catch",
+            "char",
+            "char8_t",
+            "char16_t",
+            "char32_t",
+            "class",
+            "compl",
+            "concept",
+            "const",
+            "consteval",
+            "constexpr",
+            "constinit",
+            "const_cast",
+            "continue",
+            "co_await",
+            "co_return",
+            "co_yield",
+            "decltype",
+            "default",
+            "delete",
+            "do",
+            "double",
+            "dynamic_cast",
+            "else",
+            "enum",
+            "explicit",
+            "export",
+            "extern",
+            "false",
+            "float",
+            "for",
+            "friend",
+            "goto",
+            "if",
+            "inline",
+            "int",
+            "long",
+            "mutable",
+            "namespace",
+            "new",
+            "noexcept",
+            "not",
+            "not_eq",
+            "nullptr",
+            "operator",
+            "or",
+            "or_eq",
+            "private",
+            "protected",
+            "public",
+            "register",
+            "reinterpret_cast",
+            "requires",
+            "return",
+            "short",
+            "signed",
+            "sizeof",
+            "static",
+            "static_assert",
+            "static_cast",
+            "struct",
+            "switch",
+            "template",
+            "this",
+            "thread_local",
+            "throw",
+            "true",
+            "try",
+            "typedef",
+            "typeid",
+            "typename",
+            "union",
+            "unsigned",
+            "using",
+            "virtual",
+            "void",
+            "volatile",
+            "wchar_t",
+            "while",
+            "xor",
+            "xor_eq",
+            "final",
+            "override",
+            "std",
+        ],
+        raw_prefix: None,
+        not_raw: &[],
+        suffix: "_",
+        ascii_only: true,
+    };
+
+    /// C# identifiers, keywords are used as verbatim identifiers
+    pub const CSHARP: IdentRules = IdentRules {
+        language: "C#",
+        keywords: &[
+            "abstract",
+            "as",
+            "base",
+            "bool",
+            "break",
+            "byte",
+            "case",
+            "catch",
+            "char",
+            "checked",
+            "class",
+            "const",
+            "continue",
+            "decimal",
+            "default",
+            "delegate",
+            "do",
+            "double",
+            "else",
+            "enum",
+            "event",
+            "explicit",
+            "extern",
+            "false",
+            "finally",
+            "fixed",
+            "float",
+            "for",
+            "foreach",
+            "goto",
+            "if",
+            "implicit",
+            "in",
+            "int",
+            "interface",
+            "internal",
+            "is",
+            "lock",
+            "long",
+            "namespace",
+            "new",
+            "null",
+            "object",
+            "operator",
+            "out",
+            "override",
+            "params",
+            "private",
+            "protected",
+            "public",
+            "readonly",
+            "ref",
+            "return",
+            "sbyte",
+            "sealed",
+            "short",
+            "sizeof",
+            "stackalloc",
+            "static",
+            "string",
+            "struct",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "typeof",
+            "uint",
+            "ulong",
+            "unchecked",
+            "unsafe",
+            "ushort",
+            "using",
+            "virtual",
+            "void",
+            "volatile",
+            "while",
+        ],
+        raw_prefix: Some("@"),
+        not_raw: &[],
+        suffix: "_",
+        ascii_only: false,
+    };
+
+    /// Python identifiers
+    pub const PYTHON: IdentRules = IdentRules {
+        language: "Python",
+        keywords: &[
+            "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+            "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+            "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise",
+            "return", "try", "while", "with", "yield",
+        ],
+        raw_prefix: None,
+        not_raw: &[],
+        suffix: "_",
+        ascii_only: false,
+    };
+
+    /// Go identifiers, predeclared names are included since shadowing them breaks the
+    /// generated code
+    pub const GO: IdentRules = IdentRules {
+        language: "Go",
+        keywords: &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "fallthrough",
+            "for",
+            "func",
+            "go",
+            "goto",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "type",
+            "var",
+            "any",
+            "append",
+            "bool",
+            "byte",
+            "cap",
+            "clear",
+            "close",
+            "comparable",
+            "complex",
+            "complex64",
+            "complex128",
+            "copy",
+            "delete",
+            "error",
+            "false",
+            "float32",
+            "float64",
+            "imag",
+            "int",
+            "int8",
+            "int16",
+            "int32",
+            "int64",
+            "iota",
+            "len",
+            "make",
+            "max",
+            "min",
+            "new",
+            "nil",
+            "panic",
+            "print",
+            "println",
+            "real",
+            "recover",
+            "rune",
+            "string",
+            "true",
+            "uint",
+            "uint8",
+            "uint16",
+            "uint32",
+            "uint64",
+            "uintptr",
+            "_",
+        ],
+        raw_prefix: None,
+        not_raw: &[],
+        suffix: "_",
+        ascii_only: false,
+    };
+
+    /// Zig identifiers, primitive type names and values are included since they can't be
+    /// shadowed
+    pub const ZIG: IdentRules = IdentRules {
+        language: "Zig",
+        keywords: &[
+            "addrspace",
+            "align",
+            "allowzero",
+            "and",
+            "anyframe",
+            "anytype",
+            "asm",
+            "async",
+            "await",
+            "break",
+            "callconv",
+            "catch",
+            "comptime",
+            "const",
+            "continue",
+            "defer",
+            "else",
+            "enum",
+            "errdefer",
+            "error",
+            "export",
+            "extern",
+            "fn",
+            "for",
+            "if",
+            "inline",
+            "linksection",
+            "noalias",
+            "noinline",
+            "nosuspend",
+            "opaque",
+            "or",
+            "orelse",
+            "packed",
+            "pub",
+            "resume",
+            "return",
+            "struct",
+            "suspend",
+            "switch",
+            "test",
+            "threadlocal",
+            "try",
+            "union",
+            "unreachable",
+            "usingnamespace",
+            "var",
+            "volatile",
+            "while",
+            "anyerror",
+            "anyopaque",
+            "bool",
+            "c_char",
+            "c_int",
+            "c_long",
+            "c_longdouble",
+            "c_longlong",
+            "c_short",
+            "c_uint",
+            "c_ulong",
+            "c_ulonglong",
+            "c_ushort",
+            "comptime_float",
+            "comptime_int",
+            "f16",
+            "f32",
+            "f64",
+            "f80",
+            "f128",
+            "false",
+            "i8",
+            "i16",
+            "i32",
+            "i64",
+            "i128",
+            "isize",
+            "noreturn",
+            "null",
+            "true",
+            "type",
+            "u8",
+            "u16",
+            "u32",
+            "u64",
+            "u128",
+            "undefined",
+            "usize",
+            "void",
+            "_",
+        ],
+        raw_prefix: None,
+        not_raw: &[],
+        suffix: "_",
+        ascii_only: true,
+    };
+
+    /// Julia identifiers
+    pub const JULIA: IdentRules = IdentRules {
+        language: "Julia",
+        keywords: &[
+            "abstract",
+            "baremodule",
+            "begin",
+            "break",
+            "catch",
+            "const",
+            "continue",
+            "do",
+            "else",
+            "elseif",
+            "end",
+            "export",
+            "false",
+            "finally",
+            "for",
+            "function",
+            "global",
+            "if",
+            "import",
+            "in",
+            "isa",
+            "let",
+            "local",
+            "macro",
+            "module",
+            "mutable",
+            "outer",
+            "primitive",
+            "quote",
+            "return",
+            "struct",
+            "true",
+            "try",
+            "using",
+            "where",
+            "while",
+        ],
+        raw_prefix: None,
+        not_raw: &[],
+        suffix: "_",
+        ascii_only: false,
+    };
+
+    fn valid_char(&self, c: char) -> bool {
+        if self.ascii_only {
+            c.is_ascii_alphanumeric() || c == '_'
+        } else {
+            c.is_alphanumeric() || c == '_'
+        }
+    }
+
+    /// Map `name` to a valid identifier
+    ///
+    /// `reserved` lists names already used by generated code in the same scope, these are
+    /// suffixed. `item` describes the Futhark item the name came from and is only used
+    /// when the name can't be mapped, in which case `Error::InvalidName` is returned
+    pub fn escape(
+        &self,
+        name: &str,
+        reserved: &[&str],
+        item: impl FnOnce() -> String,
+    ) -> Result<String, Error> {
+        // Futhark allows primes in identifiers
+        let mut name = name.replace('\'', "_");
+
+        if name.is_empty() || !name.chars().all(|c| self.valid_char(c)) {
+            return Err(Error::InvalidName {
+                name,
+                item: item(),
+                language: self.language,
+            });
+        }
+
+        if name.chars().next().unwrap().is_ascii_digit() {
+            name = format!("_{name}");
+        }
+
+        if self.keywords.contains(&name.as_str()) {
+            match self.raw_prefix {
+                // A raw identifier names the same item as the keyword, so it can't be used if
+                // either spelling is reserved
+                Some(prefix)
+                    if !self.not_raw.contains(&name.as_str())
+                        && !reserved.contains(&name.as_str()) =>
+                {
+                    let raw = format!("{prefix}{name}");
+                    if !reserved.contains(&raw.as_str()) {
+                        return Ok(raw);
+                    }
+                    name.push_str(self.suffix);
+                }
+                _ => name.push_str(self.suffix),
+            }
+        }
+
+        while reserved.contains(&name.as_str()) {
+            name.push_str(self.suffix);
+        }
+
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escape(rules: &IdentRules, name: &str) -> Result<String, Error> {
+        rules.escape(name, &[], || "type \"t\"".to_string())
+    }
+
+    #[test]
+    fn keywords() {
+        let cases = [
+            (IdentRules::RUST, "type", "r#type"),
+            (IdentRules::RUST, "self", "self_"),
+            (IdentRules::RUST, "Self", "Self_"),
+            (IdentRules::RUST, "crate", "crate_"),
+            (IdentRules::RUST, "super", "super_"),
+            (IdentRules::RUST, "_", "__"),
+            (IdentRules::CSHARP, "class", "@class"),
+            (IdentRules::CPP, "class", "class_"),
+            (IdentRules::OCAML, "type", "type_"),
+            (IdentRules::PYTHON, "lambda", "lambda_"),
+            (IdentRules::GO, "func", "func_"),
+            (IdentRules::ZIG, "fn", "fn_"),
+            (IdentRules::JULIA, "end", "end_"),
+        ];
+        for (rules, name, expected) in cases {
+            assert_eq!(
+                escape(&rules, name).unwrap(),
+                expected,
+                "{}",
+                rules.language
+            );
+        }
+    }
+
+    #[test]
+    fn leading_digits_and_primes() {
+        for rules in [
+            IdentRules::RUST,
+            IdentRules::OCAML,
+            IdentRules::CPP,
+            IdentRules::CSHARP,
+            IdentRules::PYTHON,
+            IdentRules::GO,
+            IdentRules::ZIG,
+            IdentRules::JULIA,
+        ] {
+            assert_eq!(escape(&rules, "0").unwrap(), "_0", "{}", rules.language);
+            assert_eq!(escape(&rules, "x'").unwrap(), "x_", "{}", rules.language);
+            assert_eq!(escape(&rules, "x''").unwrap(), "x__", "{}", rules.language);
+            assert_eq!(
+                escape(&rules, "point").unwrap(),
+                "point",
+                "{}",
+                rules.language
+            );
+        }
+    }
+
+    #[test]
+    fn non_ascii() {
+        for rules in [IdentRules::RUST, IdentRules::CSHARP, IdentRules::JULIA] {
+            assert_eq!(
+                escape(&rules, "größe").unwrap(),
+                "größe",
+                "{}",
+                rules.language
+            );
+        }
+        for rules in [IdentRules::OCAML, IdentRules::CPP, IdentRules::ZIG] {
+            assert!(escape(&rules, "größe").is_err(), "{}", rules.language);
+        }
+    }
+
+    #[test]
+    fn reserved_names_are_suffixed() {
+        let rules = IdentRules::RUST;
+        let item = || String::new();
+        assert_eq!(rules.escape("new", &["new"], item).unwrap(), "new_");
+        assert_eq!(
+            rules.escape("new", &["new", "new_"], item).unwrap(),
+            "new__"
+        );
+
+        // Keywords that are also reserved can't use the raw identifier
+        assert_eq!(rules.escape("type", &["type"], item).unwrap(), "type_");
+        assert_eq!(rules.escape("type", &["r#type"], item).unwrap(), "type_");
+        let rules = IdentRules::CSHARP;
+        assert_eq!(rules.escape("class", &["class"], item).unwrap(), "class_");
+        assert_eq!(rules.escape("class", &["@class"], item).unwrap(), "class_");
+    }
+
+    #[test]
+    fn invalid_names() {
+        for name in ["", "a-b", "a b"] {
+            match escape(&IdentRules::RUST, name) {
+                Err(Error::InvalidName {
+                    name: n,
+                    item,
+                    language,
+                }) => {
+                    assert_eq!(n, name);
+                    assert_eq!(item, "type \"t\"");
+                    assert_eq!(language, "Rust");
+                }
+                other => panic!("expected an invalid name, got {other:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,755 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::generate::IdentRules;
+use crate::ir::ElemType;
+use crate::*;
+
+/// Julia codegen, writes a module calling the shared library built by
+/// `Package::build_shared` using `ccall`
+#[derive(Default)]
+pub struct Julia;
+
+/// Module level names defined by `templates/julia/context.jl`, these can't be used by
+/// generated types and entry points
+const RESERVED_GLOBALS: &[&str] = &[
+    "Array",
+    "BACKEND",
+    "Bool",
+    "Context",
+    "DEVICE_MEMORY",
+    "FutharkError",
+    "Integer",
+    "Libdl",
+    "Real",
+    "Symbol",
+    "clear_caches",
+    "free",
+    "get_error",
+    "lib",
+    "pause_profiling",
+    "payload",
+    "report",
+    "restore",
+    "store",
+    "sync",
+    "unpause_profiling",
+    "variant",
+    "variants",
+];
+
+/// Names used in the body of generated functions, these can't be used for parameters
+const RESERVED_LOCALS: &[&str] = &[
+    "C_NULL", "Cint", "Cvoid", "GC", "Ptr", "Ref", "Val", "ctx", "lib", "nothing", "out", "x",
+];
+
+/// Fields of the generated structs, these can't be used as record properties
+const RESERVED_PROPERTIES: &[&str] = &["ctx", "ptr"];
+
+fn julia_ident(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    IdentRules::JULIA.escape(name, reserved, item)
+}
+
+/// Returns the Julia type used for a primitive value and the abstract type used to
+/// annotate parameters
+fn primitive_type(t: ElemType) -> (&'static str, &'static str) {
+    match t {
+        ElemType::I8 => ("Int8", "Integer"),
+        ElemType::I16 => ("Int16", "Integer"),
+        ElemType::I32 => ("Int32", "Integer"),
+        ElemType::I64 => ("Int64", "Integer"),
+        ElemType::U8 => ("UInt8", "Integer"),
+        ElemType::U16 => ("UInt16", "Integer"),
+        ElemType::U32 => ("UInt32", "Integer"),
+        ElemType::U64 => ("UInt64", "Integer"),
+        ElemType::F16 => ("Float16", "Real"),
+        ElemType::F32 => ("Float32", "Real"),
+        ElemType::F64 => ("Float64", "Real"),
+        ElemType::Bool => ("Bool", "Bool"),
+    }
+}
+
+/// Name of the struct generated for a type
+fn class_name(ty: &ir::Type) -> Result<String, Error> {
+    julia_ident(&ty.name, RESERVED_GLOBALS, || {
+        format!("type {:?}", ty.futhark_name)
+    })
+}
+
+/// Render documentation as the body of a docstring, `$` starts an interpolation in Julia
+/// strings
+fn docstring(doc: &str) -> String {
+    doc.replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace("\"\"\"", "\\\"\\\"\\\"")
+}
+
+/// Get the documentation for a type, using the Futhark name if the `Namer` didn't provide
+/// any
+fn type_doc(ty: &ir::Type) -> String {
+    docstring(
+        ty.doc
+            .as_deref()
+            .unwrap_or(&format!("Futhark type `{}`", ty.futhark_name)),
+    )
+}
+
+/// Julia symbol literal for a name
+fn symbol(name: &str) -> String {
+    match julia_ident(name, &[], String::new) {
+        Ok(ident) if ident == name => format!(":{name}"),
+        _ => format!("Symbol({name:?})"),
+    }
+}
+
+/// Render a tuple, adding the trailing comma needed by single element tuples
+fn tuple(items: &[String]) -> String {
+    match items.len() {
+        1 => format!("({},)", items[0]),
+        _ => format!("({})", items.join(", ")),
+    }
+}
+
+/// How a value is passed to the C API
+enum Value<'a> {
+    Primitive(ElemType),
+    Named(&'a ir::Type),
+}
+
+impl<'a> Value<'a> {
+    fn new(lib: &'a ir::Library, ty: &ir::TypeRef) -> Result<Self, Error> {
+        match (ty, lib.get(ty)) {
+            (ir::TypeRef::Primitive(t), _) => Ok(Value::Primitive(*t)),
+            (_, Some(t)) => Ok(Value::Named(t)),
+            (ir::TypeRef::Named(name), None) => {
+                Err(Error::InvalidManifest(format!("unknown type {name:?}")))
+            }
+        }
+    }
+
+    /// Type used in `ccall` argument lists
+    fn ccall_type(&self) -> &'static str {
+        match self {
+            Value::Primitive(t) => primitive_type(*t).0,
+            Value::Named(_) => "Ptr{Cvoid}",
+        }
+    }
+
+    /// Type used to annotate parameters
+    fn param_type(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(t) => Ok(primitive_type(*t).1.to_string()),
+            Value::Named(t) => class_name(t),
+        }
+    }
+
+    /// Expression passing `name` to `ccall`
+    fn arg(&self, name: &str) -> String {
+        match self {
+            Value::Primitive(_) => name.to_string(),
+            Value::Named(_) => format!("_get({name})"),
+        }
+    }
+
+    /// Declaration of the `Ref` used for outputs
+    fn out_decl(&self, name: &str) -> String {
+        match self {
+            Value::Primitive(t) => format!("{name} = Ref{{{}}}()", primitive_type(*t).0),
+            Value::Named(_) => format!("{name} = Ref{{Ptr{{Cvoid}}}}(C_NULL)"),
+        }
+    }
+
+    /// Expression wrapping the value stored in the output `Ref` `name`
+    fn output(&self, ctx: &str, name: &str) -> Result<String, Error> {
+        match self {
+            Value::Primitive(_) => Ok(format!("{name}[]")),
+            Value::Named(t) => Ok(format!("{}({ctx}, {name}[])", class_name(t)?)),
+        }
+    }
+}
+
+/// Parameters and `ccall` arguments of a generated function
+#[derive(Default)]
+struct Params {
+    names: Vec<String>,
+    typed: Vec<String>,
+    arg_types: Vec<String>,
+    call_args: Vec<String>,
+
+    /// Handles that must not be garbage collected during the call
+    preserve: Vec<String>,
+}
+
+impl Params {
+    fn push(&mut self, value: &Value, name: String) -> Result<(), Error> {
+        self.typed.push(format!("{name}::{}", value.param_type()?));
+        self.arg_types.push(value.ccall_type().to_string());
+        self.call_args.push(value.arg(&name));
+        if let Value::Named(_) = value {
+            self.preserve.push(name.clone());
+        }
+        self.names.push(name);
+        Ok(())
+    }
+
+    /// `GC.@preserve` prefix for the `ccall`
+    fn preserve(&self) -> String {
+        if self.preserve.is_empty() {
+            return String::new();
+        }
+        format!("GC.@preserve {} ", self.preserve.join(" "))
+    }
+}
+
+/// Get the module file, relative to the output directory
+fn file(config: &Config) -> PathBuf {
+    PathBuf::from(config.output_path.file_name().unwrap_or_default())
+}
+
+/// Name of the module, taken from the output file
+fn module_name(config: &Config) -> Result<String, Error> {
+    let stem = config
+        .output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    julia_ident(&stem, RESERVED_GLOBALS, || {
+        format!("module for {}", config.output_path.display())
+    })
+}
+
+/// Render a template from `templates/julia` and append it to the module
+fn render(config: &mut Config, name: &str, args: &[(&str, &dyn Display)]) -> Result<(), Error> {
+    let code = config.templates.render("julia", name, args)?;
+    writeln!(config.output(file(config)), "{code}")?;
+    Ok(())
+}
+
+fn field_params(lib: &ir::Library, ty: &ir::Type, fields: &[ir::Field]) -> Result<Params, Error> {
+    let mut params = Params::default();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let reserved = [
+            RESERVED_LOCALS,
+            &params.names.iter().map(String::as_str).collect::<Vec<_>>(),
+        ]
+        .concat();
+        let name = julia_ident(&field.name, &reserved, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        params.push(&value, name)?;
+    }
+    Ok(params)
+}
+
+/// Render the field projections for records and arrays of records, and the tuple of
+/// property names
+fn field_projections(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+) -> Result<(Vec<String>, String), Error> {
+    let mut members = Vec::new();
+    let mut properties = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let project_name = julia_ident(&field.project_name, RESERVED_PROPERTIES, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        let (out_type, out_init) = match value {
+            Value::Primitive(t) => (primitive_type(t).0, ""),
+            Value::Named(_) => ("Ptr{Cvoid}", "C_NULL"),
+        };
+        members.push(config.templates.render(
+            "julia",
+            "record_project.jl",
+            &[
+                ("class_name", &class_name(ty)?),
+                ("field_symbol", &symbol(&project_name)),
+                ("project_fn", &field.project_fn),
+                ("out_type", &out_type),
+                ("out_init", &out_init),
+                ("out_return", &value.output("ctx", "out")?),
+            ],
+        )?);
+        properties.push(symbol(&project_name));
+    }
+    Ok((members, tuple(&properties)))
+}
+
+/// Render a constructor taking one value per field followed by the field projections, used
+/// for records and arrays of records
+fn field_members(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+    template: &str,
+    keys: [&str; 5],
+    c_fn: &str,
+) -> Result<Vec<String>, Error> {
+    let params = field_params(lib, ty, fields)?;
+    let (projections, properties) = field_projections(lib, config, ty, fields)?;
+    let [fn_key, params_key, typed_key, arg_types_key, call_args_key] = keys;
+    let constructor = config.templates.render(
+        "julia",
+        template,
+        &[
+            ("class_name", &class_name(ty)?),
+            (fn_key, &c_fn),
+            (params_key, &params.names.join(", ")),
+            (typed_key, &params.typed.join(", ")),
+            (arg_types_key, &params.arg_types.join(", ")),
+            (call_args_key, &params.call_args.join(", ")),
+            ("preserve", &params.preserve()),
+            ("properties", &properties),
+        ],
+    )?;
+    Ok([vec![constructor], projections].concat())
+}
+
+/// Functions for sum types: the variant names, constructors and payload getters
+fn sum_members(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    sum: &ir::Sum,
+) -> Result<Vec<String>, Error> {
+    let class_name = class_name(ty)?;
+    let mut variants = Vec::new();
+    let mut members = Vec::new();
+    for variant in &sum.variants {
+        let variant_symbol = symbol(&variant.name);
+        let payload = variant
+            .payload
+            .iter()
+            .map(|p| Value::new(lib, p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut params = Params::default();
+        for (i, p) in payload.iter().enumerate() {
+            params.push(p, format!("p{i}"))?;
+        }
+        let prefixed = |items: &[String]| {
+            items
+                .iter()
+                .map(|item| format!(", {item}"))
+                .collect::<String>()
+        };
+        members.push(config.templates.render(
+            "julia",
+            "sum_variant.jl",
+            &[
+                ("class_name", &class_name),
+                ("variant_symbol", &variant_symbol),
+                ("construct_fn", &variant.construct_fn),
+                ("construct_params", &prefixed(&params.typed)),
+                ("construct_arg_types", &prefixed(&params.arg_types)),
+                ("construct_args", &prefixed(&params.call_args)),
+                ("preserve", &params.preserve()),
+            ],
+        )?);
+
+        if !payload.is_empty() {
+            let mut destruct_decls = String::new();
+            let mut destruct_arg_types = Vec::new();
+            let mut destruct_return = Vec::new();
+            for (p, name) in payload.iter().zip(&params.names) {
+                destruct_decls.push_str(&format!("    {}\n", p.out_decl(name)));
+                destruct_arg_types.push(format!("Ptr{{{}}}", p.ccall_type()));
+                destruct_return.push(p.output("ctx", name)?);
+            }
+            members.push(config.templates.render(
+                "julia",
+                "sum_payload.jl",
+                &[
+                    ("class_name", &class_name),
+                    ("variant_symbol", &variant_symbol),
+                    ("destruct_fn", &variant.destruct_fn),
+                    ("destruct_decls", &destruct_decls),
+                    ("destruct_arg_types", &destruct_arg_types.join(", ")),
+                    ("destruct_args", &params.names.join(", ")),
+                    ("destruct_return", &destruct_return.join(", ")),
+                ],
+            )?);
+        }
+        variants.push(variant_symbol);
+    }
+
+    let example = sum
+        .variants
+        .first()
+        .map(|v| {
+            let payload = (0..v.payload.len()).map(|i| format!(", p{i}"));
+            format!("{}{}", symbol(&v.name), payload.collect::<String>())
+        })
+        .unwrap_or_default();
+    let sum = config.templates.render(
+        "julia",
+        "sum.jl",
+        &[
+            ("class_name", &class_name),
+            ("variants", &tuple(&variants)),
+            ("variant_fn", &sum.variant_fn),
+            ("example", &example),
+        ],
+    )?;
+    members.insert(0, sum);
+    Ok(members)
+}
+
+/// Names of the parameters of an entry point, using the Futhark parameter names
+fn entry_params(entry: &ir::Entry) -> Vec<String> {
+    let mut reserved = RESERVED_LOCALS
+        .iter()
+        .chain(RESERVED_GLOBALS)
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    reserved.extend((0..entry.outputs.len()).map(|i| format!("out{i}")));
+    let mut params = Vec::new();
+    for (i, input) in entry.inputs.iter().enumerate() {
+        let r = reserved.iter().map(String::as_str).collect::<Vec<_>>();
+        let name = julia_ident(&input.name, &r, || {
+            format!(
+                "input {:?} of entry point {:?}",
+                input.name, entry.futhark_name
+            )
+        })
+        .unwrap_or_else(|_| format!("in{i}"));
+        reserved.push(name.clone());
+        params.push(name);
+    }
+    params
+}
+
+impl Generate for Julia {
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        let caps = lib.backend.capabilities();
+
+        let mut extra_params = Vec::new();
+        let mut configure_backend = Vec::new();
+        for option in caps.config_options {
+            let (param, line) = match option {
+                ConfigOption::NumThreads => (
+                    ", num_threads=0",
+                    "    ccall((:futhark_context_config_set_num_threads, lib), Cvoid, (Ptr{Cvoid}, Cint), config, num_threads)",
+                ),
+                ConfigOption::Device => (
+                    ", device=nothing",
+                    "    if device !== nothing\n        push!(ctx.strings, string(device))\n        ccall((:futhark_context_config_set_device, lib), Cvoid, (Ptr{Cvoid}, Cstring), config, ctx.strings[end])\n    end",
+                ),
+                ConfigOption::Platform => (
+                    ", platform=nothing",
+                    "    if platform !== nothing\n        push!(ctx.strings, string(platform))\n        ccall((:futhark_context_config_set_platform, lib), Cvoid, (Ptr{Cvoid}, Cstring), config, ctx.strings[end])\n    end",
+                ),
+            };
+            extra_params.push(param);
+            configure_backend.push(line);
+        }
+
+        let library = lib
+            .c_file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let library_env = format!(
+            "FUTHARK_{}_LIBRARY",
+            library
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                })
+                .collect::<String>()
+        );
+        render(
+            config,
+            "context.jl",
+            &[
+                ("module", &module_name(config)?),
+                ("backend", &lib.backend.to_str()),
+                ("device_memory", &(caps.memory == Memory::Device)),
+                ("library", &library),
+                ("library_env", &library_env),
+                ("extra_params", &extra_params.concat()),
+                ("configure_backend", &configure_backend.join("\n")),
+            ],
+        )
+    }
+
+    fn array_type(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+        a: &ir::Array,
+    ) -> Result<(), Error> {
+        let shape_types = vec!["Int64"; a.rank].join(", ");
+        let shape_args = (1..=a.rank)
+            .map(|i| format!("size(data, {i})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let index_args = (1..=a.rank)
+            .map(|i| format!("index[{i}] - 1"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        render(
+            config,
+            "array.jl",
+            &[
+                ("doc", &type_doc(ty)),
+                ("class_name", &class_name(ty)?),
+                ("rank", &a.rank),
+                ("elem_type", &primitive_type(a.elemtype).0),
+                ("shape_types", &shape_types),
+                ("shape_args", &shape_args),
+                ("index_args", &index_args),
+                ("new_fn", &a.ops.new),
+                ("free_fn", &a.ops.free),
+                ("values_fn", &a.ops.values),
+                ("shape_fn", &a.ops.shape),
+                ("index_fn", &a.ops.index),
+            ],
+        )
+    }
+
+    fn opaque_type(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let item = || format!("type {:?}", ty.futhark_name);
+        let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+
+        let mut members = Vec::new();
+        match &ty.kind {
+            ir::TypeKind::Record(record) => {
+                members.extend(field_members(
+                    lib,
+                    config,
+                    ty,
+                    &record.fields,
+                    "record.jl",
+                    [
+                        "new_fn",
+                        "new_params",
+                        "new_typed_params",
+                        "new_arg_types",
+                        "new_call_args",
+                    ],
+                    &record.new_fn,
+                )?);
+            }
+            ir::TypeKind::Sum(sum) => members.extend(sum_members(lib, config, ty, sum)?),
+            ir::TypeKind::OpaqueArray(array)
+            | ir::TypeKind::RecordArray(ir::RecordArray { array, .. }) => {
+                let Value::Named(elem) = Value::new(lib, &array.elemtype)? else {
+                    return Err(Error::InvalidManifest(format!(
+                        "{} has a primitive element type",
+                        item()
+                    )));
+                };
+                let index_args = (1..=array.rank)
+                    .map(|i| format!("index[{i}] - 1"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                members.push(config.templates.render(
+                    "julia",
+                    "opaque_array.jl",
+                    &[
+                        ("class_name", &class_name(ty)?),
+                        ("rank", &array.rank),
+                        ("shape_fn", &array.shape_fn),
+                        ("index_fn", &array.index_fn),
+                        ("index_types", &vec!["Int64"; array.rank].join(", ")),
+                        ("index_args", &index_args),
+                        ("elem_class", &class_name(elem)?),
+                    ],
+                )?);
+
+                if let ir::TypeKind::RecordArray(record) = &ty.kind {
+                    members.extend(field_members(
+                        lib,
+                        config,
+                        ty,
+                        &record.fields,
+                        "record_array.jl",
+                        [
+                            "zip_fn",
+                            "zip_params",
+                            "zip_typed_params",
+                            "zip_arg_types",
+                            "zip_call_args",
+                        ],
+                        &record.zip_fn,
+                    )?);
+                }
+            }
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
+        }
+
+        render(
+            config,
+            "opaque.jl",
+            &[
+                ("doc", &type_doc(ty)),
+                ("class_name", &class_name(ty)?),
+                ("free_fn", &ops.free),
+                ("store_fn", &ops.store),
+                ("restore_fn", &ops.restore),
+                ("members", &members.join("\n")),
+            ],
+        )
+    }
+
+    fn type_aliases(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let class_name = class_name(ty)?;
+        for alias in &ty.aliases {
+            let alias = julia_ident(alias, RESERVED_GLOBALS, || {
+                format!("type {:?}", ty.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "\n\"\"\"Deprecated alias for `{class_name}`\"\"\"\nconst {alias} = {class_name}"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        entry: &ir::Entry,
+    ) -> Result<(), Error> {
+        let name = julia_ident(&entry.name, RESERVED_GLOBALS, || {
+            format!("entry point {:?}", entry.futhark_name)
+        })?;
+
+        let mut out_decl = String::new();
+        let mut params = Params::default();
+        let mut entry_return = Vec::new();
+        for (i, output) in entry.outputs.iter().enumerate() {
+            let value = Value::new(lib, &output.ty)?;
+            let name = format!("out{i}");
+            out_decl.push_str(&format!("    {}\n", value.out_decl(&name)));
+            params
+                .arg_types
+                .push(format!("Ptr{{{}}}", value.ccall_type()));
+            params.call_args.push(name.clone());
+            entry_return.push(value.output("ctx", &name)?);
+        }
+
+        let mut inputs = Params::default();
+        for (input, param) in entry.inputs.iter().zip(entry_params(entry)) {
+            inputs.push(&Value::new(lib, &input.ty)?, param)?;
+        }
+        params.arg_types.extend(inputs.arg_types);
+        params.call_args.extend(inputs.call_args);
+        let preserve = match inputs.preserve.is_empty() {
+            true => String::new(),
+            false => format!("GC.@preserve {} ", inputs.preserve.join(" ")),
+        };
+
+        let prefixed = |items: &[String]| {
+            items
+                .iter()
+                .map(|item| format!(", {item}"))
+                .collect::<String>()
+        };
+        // `(Ptr{Cvoid})` isn't a tuple, the trailing comma is needed when there are no
+        // other arguments
+        let arg_types = match params.arg_types.is_empty() {
+            true => ",".to_string(),
+            false => prefixed(&params.arg_types),
+        };
+        let entry_return = match entry_return.len() {
+            0 => "nothing".to_string(),
+            1 => entry_return.remove(0),
+            _ => format!("({})", entry_return.join(", ")),
+        };
+        let doc = docstring(
+            entry
+                .doc
+                .as_deref()
+                .unwrap_or(&format!("Entry point: {}", entry.futhark_name)),
+        );
+        render(
+            config,
+            "entry.jl",
+            &[
+                ("name", &name),
+                ("doc", &doc),
+                ("entry_fn", &entry.cfun),
+                ("entry_params", &prefixed(&inputs.names)),
+                ("typed_params", &prefixed(&inputs.typed)),
+                ("out_decl", &out_decl),
+                ("arg_types", &arg_types),
+                ("call_args", &prefixed(&params.call_args)),
+                ("preserve", &preserve),
+                ("entry_return", &entry_return),
+            ],
+        )?;
+
+        for alias in &entry.aliases {
+            let alias = julia_ident(alias, RESERVED_GLOBALS, || {
+                format!("entry point {:?}", entry.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "\nBase.@deprecate {alias}(ctx::Context, args...) {name}(ctx, args...) false"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, _lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        render(config, "footer.jl", &[("module", &module_name(config)?)])
+    }
+
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        Ok(format!(
+            "# Generated by futhark-bindgen\n\n{}\n",
+            code.trim_end()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::tests::{aliasing_namer, all_types};
+
+    #[test]
+    fn generate_all_types() {
+        let mut config = Config::with_writer("lib.jl", std::io::sink(), aliasing_namer());
+        let code = Julia.generate_to_string(&all_types(), &mut config).unwrap();
+        for item in [
+            "function end_(ctx::Context, x_::Integer)",
+            "Base.@deprecate make(ctx::Context, args...) make_point(ctx, args...) false",
+            "const ArrayF32D1 = F32Array1d",
+            "function variant(x::Option)",
+            "function Option(ctx::Context, ::Val{:some}, p0::Integer)",
+            "function payload(x::Option, ::Val{:some})",
+            "function Point(ctx::Context, xs::F32Array1d, w::Real)",
+            "function _project(x::Point, ::Val{:xs})",
+        ] {
+            assert!(code.contains(item), "missing {item:?}");
+        }
+    }
+}
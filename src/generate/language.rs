@@ -0,0 +1,179 @@
+use crate::*;
+
+/// Describes a built-in generator
+#[derive(Debug, Clone, Copy)]
+pub struct LanguageInfo {
+    /// Name accepted by `Language::from_name` and `--lang`, also the directory used by
+    /// `Templates::load_dir`
+    pub name: &'static str,
+
+    /// Output file extensions that select the language, without the leading `.`
+    pub extensions: &'static [&'static str],
+
+    /// Short description of the generated code
+    pub description: &'static str,
+}
+
+const RUST: LanguageInfo = LanguageInfo {
+    name: "rust",
+    extensions: &["rs"],
+    description: "Rust module with safe wrappers",
+};
+
+const OCAML: LanguageInfo = LanguageInfo {
+    name: "ocaml",
+    extensions: &["ml"],
+    description: "OCaml module using ctypes, with a matching .mli",
+};
+
+const CPP: LanguageInfo = LanguageInfo {
+    name: "cpp",
+    extensions: &["hpp"],
+    description: "Header-only C++17 wrapper",
+};
+
+const PYTHON: LanguageInfo = LanguageInfo {
+    name: "python",
+    extensions: &["py"],
+    description: "Python module using ctypes and NumPy",
+};
+
+const GO: LanguageInfo = LanguageInfo {
+    name: "go",
+    extensions: &["go"],
+    description: "Go package using cgo",
+};
+
+const JULIA: LanguageInfo = LanguageInfo {
+    name: "julia",
+    extensions: &["jl"],
+    description: "Julia module using ccall",
+};
+
+const ZIG: LanguageInfo = LanguageInfo {
+    name: "zig",
+    extensions: &["zig"],
+    description: "Zig file with extern declarations",
+};
+
+const CSHARP: LanguageInfo = LanguageInfo {
+    name: "csharp",
+    extensions: &["cs"],
+    description: "C# file using P/Invoke and SafeHandle",
+};
+
+const MARKDOWN: LanguageInfo = LanguageInfo {
+    name: "markdown",
+    extensions: &["md"],
+    description: "Markdown API reference",
+};
+
+const HTML: LanguageInfo = LanguageInfo {
+    name: "html",
+    extensions: &["html", "htm"],
+    description: "Static HTML API reference",
+};
+
+/// Output language, selects the generator used by `Config::generator`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    OCaml,
+    Cpp,
+    Python,
+    Go,
+    Julia,
+    Zig,
+    CSharp,
+
+    /// API reference, see `Docs`
+    Markdown,
+
+    /// API reference, see `Docs`
+    Html,
+
+    /// Generated by the `futhark-bindgen-gen-<name>` executable, see `Plugin`
+    Plugin(String),
+}
+
+impl Language {
+    /// All built-in languages
+    pub const ALL: [Language; 10] = [
+        Language::Rust,
+        Language::OCaml,
+        Language::Cpp,
+        Language::Python,
+        Language::Go,
+        Language::Julia,
+        Language::Zig,
+        Language::CSharp,
+        Language::Markdown,
+        Language::Html,
+    ];
+
+    /// Get the description of a built-in language, `None` for plugins
+    pub fn info(&self) -> Option<&'static LanguageInfo> {
+        match self {
+            Language::Rust => Some(&RUST),
+            Language::OCaml => Some(&OCAML),
+            Language::Cpp => Some(&CPP),
+            Language::Python => Some(&PYTHON),
+            Language::Go => Some(&GO),
+            Language::Julia => Some(&JULIA),
+            Language::Zig => Some(&ZIG),
+            Language::CSharp => Some(&CSHARP),
+            Language::Markdown => Some(&MARKDOWN),
+            Language::Html => Some(&HTML),
+            Language::Plugin(_) => None,
+        }
+    }
+
+    /// Get the name of a language
+    pub fn name(&self) -> &str {
+        match self {
+            Language::Plugin(name) => name,
+            _ => self.info().expect("built-in language").name,
+        }
+    }
+
+    /// Return the built-in language with the given name
+    pub fn from_name(name: &str) -> Option<Language> {
+        let name = name.trim().to_ascii_lowercase();
+        Language::ALL.into_iter().find(|l| l.name() == name)
+    }
+
+    /// Return the built-in language selected by an output file extension, plugins are never
+    /// selected by extension and have to be requested using `Language::Plugin`
+    pub fn from_extension(ext: &str) -> Option<Language> {
+        Language::ALL
+            .into_iter()
+            .find(|l| l.info().is_some_and(|i| i.extensions.contains(&ext)))
+    }
+
+    /// Every available language: the built-in ones followed by the plugins found on `PATH`
+    pub fn available() -> Vec<Language> {
+        Language::ALL
+            .into_iter()
+            .chain(Plugin::installed().into_iter().map(Language::Plugin))
+            .collect()
+    }
+
+    /// Create the generator for this language
+    pub fn generator(&self) -> Box<dyn Generate> {
+        match self {
+            Language::Rust => Box::<Rust>::default(),
+            Language::OCaml => Box::<OCaml>::default(),
+            Language::Cpp => Box::<Cpp>::default(),
+            Language::Python => Box::<Python>::default(),
+            Language::Go => Box::<Go>::default(),
+            Language::Julia => Box::<Julia>::default(),
+            Language::Zig => Box::<Zig>::default(),
+            Language::CSharp => Box::<CSharp>::default(),
+            Language::Markdown => Box::new(Docs::new(DocFormat::Markdown)),
+            Language::Html => Box::new(Docs::new(DocFormat::Html)),
+            Language::Plugin(name) => {
+                Box::new(Plugin::find(name).unwrap_or_else(|| Plugin::new(name.as_str())))
+            }
+        }
+    }
+}
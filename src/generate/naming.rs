@@ -0,0 +1,722 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use convert_case::{Case, Casing};
+use serde::Deserialize;
+
+use crate::generate::{convert_struct_name, first_uppercase};
+use crate::*;
+
+pub trait Namer {
+    fn init(&mut self, manifest: &Manifest);
+    fn type_name(
+        &mut self,
+        futhark_name: &str,
+        data: &manifest::Type,
+        manifest: &Manifest,
+    ) -> String;
+    fn project_name(&mut self, futhark_name: &str, manifest: &Manifest) -> String;
+    fn new_field_name(&mut self, futhark_name: &str, manifest: &Manifest) -> String;
+
+    /// Name of the function generated for an entry point
+    fn entry_name(&mut self, futhark_name: &str, _manifest: &Manifest) -> String {
+        futhark_name.to_string()
+    }
+
+    /// Additional derives for a generated type
+    fn type_derives(&mut self, _futhark_name: &str, _manifest: &Manifest) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Documentation for a generated type
+    fn type_doc(&mut self, _futhark_name: &str, _manifest: &Manifest) -> Option<String> {
+        None
+    }
+
+    /// Documentation for a generated entry point
+    fn entry_doc(&mut self, _futhark_name: &str, _manifest: &Manifest) -> Option<String> {
+        None
+    }
+
+    /// Previous names of a type, generated as deprecated aliases
+    fn type_aliases(
+        &mut self,
+        _futhark_name: &str,
+        _data: &manifest::Type,
+        _manifest: &Manifest,
+    ) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Previous names of an entry point, generated as deprecated functions
+    fn entry_aliases(&mut self, _futhark_name: &str, _manifest: &Manifest) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Naming scheme used by `DefaultNamer` for types that aren't renamed explicitly
+///
+/// In a `NamingConfig` this is written as `scheme = "legacy"`, `scheme = "current"` or
+/// `scheme = { pattern = "Array{elem}D{rank}" }`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NamingScheme {
+    /// Names used by futhark-bindgen 0.2: `ArrayI64D1`, anonymous records and sum types
+    /// are named `Unnamed` followed by the hash from their C type, `Unnamed_85fa2`
+    Legacy,
+
+    /// Arrays are named `I64Array1d`, anonymous tuples, records and sum types are named after
+    /// their structure
+    #[default]
+    Current,
+
+    /// Like `Current`, but arrays are named using a pattern, `{elem}` is replaced with the
+    /// element type name and `{rank}` with the number of dimensions. Both placeholders are
+    /// required
+    #[serde(deserialize_with = "array_pattern")]
+    Pattern(String),
+}
+
+/// Check that an array name pattern uses both placeholders and nothing else in braces
+fn array_pattern<'de, D>(d: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let pattern = String::deserialize(d)?;
+    let rest = pattern.replace("{elem}", "").replace("{rank}", "");
+    if !pattern.contains("{elem}") || !pattern.contains("{rank}") || rest.contains(['{', '}']) {
+        return Err(serde::de::Error::custom(format!(
+            "invalid array name pattern {pattern:?}, expected {{elem}} and {{rank}}"
+        )));
+    }
+    Ok(pattern)
+}
+
+/// Naming overrides for a single type
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypeOverride {
+    /// Generated type name
+    pub name: Option<String>,
+
+    /// Additional derives
+    #[serde(default)]
+    pub derives: Vec<String>,
+
+    /// Documentation
+    pub doc: Option<String>,
+}
+
+/// Naming overrides for a single entry point
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntryOverride {
+    /// Generated function name
+    pub name: Option<String>,
+
+    /// Documentation
+    pub doc: Option<String>,
+}
+
+/// Deserialize a map of overrides, where each value is either a name or a table
+fn name_or<'de, D, T>(d: D) -> Result<BTreeMap<String, T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + From<String>,
+{
+    struct NameOr<T>(T);
+
+    impl<'de, T: Deserialize<'de> + From<String>> Deserialize<'de> for NameOr<T> {
+        fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            struct Visitor<T>(std::marker::PhantomData<T>);
+
+            impl<'de, T: Deserialize<'de> + From<String>> serde::de::Visitor<'de> for Visitor<T> {
+                type Value = NameOr<T>;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a name or a table")
+                }
+
+                fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                    Ok(NameOr(T::from(name.to_string())))
+                }
+
+                fn visit_map<A: serde::de::MapAccess<'de>>(
+                    self,
+                    map: A,
+                ) -> Result<Self::Value, A::Error> {
+                    T::deserialize(serde::de::value::MapAccessDeserializer::new(map)).map(NameOr)
+                }
+            }
+
+            d.deserialize_any(Visitor(std::marker::PhantomData))
+        }
+    }
+
+    let map: BTreeMap<String, NameOr<T>> = Deserialize::deserialize(d)?;
+    Ok(map.into_iter().map(|(k, v)| (k, v.0)).collect())
+}
+
+impl From<String> for TypeOverride {
+    fn from(name: String) -> Self {
+        TypeOverride {
+            name: Some(name),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<String> for EntryOverride {
+    fn from(name: String) -> Self {
+        EntryOverride {
+            name: Some(name),
+            ..Default::default()
+        }
+    }
+}
+
+/// Naming configuration, loaded from `futhark-bindgen.toml` or the
+/// `[package.metadata.futhark-bindgen]` section of `Cargo.toml`
+///
+/// ```toml
+/// [types]
+/// "(i32, []f32)" = "Pair"
+/// point = { name = "Point2", derives = ["Debug"], doc = "A point in 2D space" }
+///
+/// [entries]
+/// binary_search = "search"
+///
+/// [fields]
+/// "0" = "first"
+/// ```
+///
+/// The top-level `scheme` and `aliases` keys select a `NamingScheme` and enable
+/// deprecated aliases for names that changed:
+///
+/// ```toml
+/// scheme = "current"
+/// aliases = "legacy"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamingConfig {
+    /// Overrides for types, indexed by Futhark type name
+    #[serde(default, deserialize_with = "name_or")]
+    pub types: BTreeMap<String, TypeOverride>,
+
+    /// Overrides for entry points, indexed by Futhark entry point name
+    #[serde(default, deserialize_with = "name_or")]
+    pub entries: BTreeMap<String, EntryOverride>,
+
+    /// Record field names, indexed by Futhark field name
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+
+    /// Naming scheme for types without an override
+    #[serde(default)]
+    pub scheme: NamingScheme,
+
+    /// Generate deprecated aliases using the names from this scheme for types whose name
+    /// changed, and using the Futhark name for renamed entry points
+    pub aliases: Option<NamingScheme>,
+}
+
+impl NamingConfig {
+    /// Name of the naming configuration file
+    pub const FILE_NAME: &'static str = "futhark-bindgen.toml";
+
+    /// Parse a naming configuration from a TOML string
+    pub fn parse(s: &str) -> Result<NamingConfig, Error> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// Load a naming configuration file
+    pub fn load(path: impl AsRef<Path>) -> Result<NamingConfig, Error> {
+        NamingConfig::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Find the naming configuration for the crate in `dir`, using `futhark-bindgen.toml` if it
+    /// exists and otherwise `[package.metadata.futhark-bindgen]` in `Cargo.toml`
+    ///
+    /// Returns the configuration and the path it was loaded from
+    pub fn find(
+        dir: impl AsRef<Path>,
+    ) -> Result<Option<(NamingConfig, std::path::PathBuf)>, Error> {
+        let dir = dir.as_ref();
+        let path = dir.join(NamingConfig::FILE_NAME);
+        if path.exists() {
+            return Ok(Some((NamingConfig::load(&path)?, path)));
+        }
+
+        let path = dir.join("Cargo.toml");
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let cargo: toml::Table = toml::from_str(&std::fs::read_to_string(&path)?)?;
+        let section = cargo
+            .get("package")
+            .and_then(|x| x.get("metadata"))
+            .and_then(|x| x.get("futhark-bindgen"));
+        match section {
+            Some(section) => Ok(Some((section.clone().try_into()?, path))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Default `Namer` implementation
+///
+/// Named Futhark types are converted to Pascal case, arrays are named using their element
+/// type and rank (`F32Array2d`) and anonymous tuples, records and sum types are named
+/// after their structure (`TupleF32ArrayF32D1`, `RecordXF32YF32`, `SumSomeI64None`). Names can be
+/// pinned using `DefaultNamer::with_type_name` or a `NamingConfig`, and other schemes can
+/// be selected using `DefaultNamer::with_scheme`.
+#[derive(Default, Debug, Clone)]
+pub struct DefaultNamer {
+    pinned: HashMap<String, String>,
+    config: NamingConfig,
+}
+
+impl DefaultNamer {
+    /// Use `name` for the type `futhark_name` instead of the generated name
+    pub fn with_type_name(
+        mut self,
+        futhark_name: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Self {
+        self.pinned.insert(futhark_name.into(), name.into());
+        self
+    }
+
+    /// Apply the overrides from a `NamingConfig`, this replaces any scheme or aliases set
+    /// previously
+    pub fn with_config(mut self, config: NamingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Select the naming scheme for types without an override
+    pub fn with_scheme(mut self, scheme: NamingScheme) -> Self {
+        self.config.scheme = scheme;
+        self
+    }
+
+    /// Generate deprecated aliases for names that differ from the ones `scheme` produces
+    pub fn with_aliases(mut self, scheme: NamingScheme) -> Self {
+        self.config.aliases = Some(scheme);
+        self
+    }
+
+    /// Name a type using `scheme`, ignoring overrides
+    fn scheme_name(
+        &self,
+        scheme: &NamingScheme,
+        futhark_name: &str,
+        data: &manifest::Type,
+        manifest: &Manifest,
+    ) -> String {
+        let legacy = *scheme == NamingScheme::Legacy;
+        match manifest::TypeExpr::parse(futhark_name) {
+            Ok(manifest::TypeExpr::Named(name)) => return name.to_case(Case::Pascal),
+            Ok(t) if t.is_anonymous() && !legacy => return structural_name(&t),
+            _ => (),
+        }
+
+        let array_name = |elem: String, rank: usize| match scheme {
+            NamingScheme::Legacy => format!("Array{elem}D{rank}"),
+            NamingScheme::Current => format!("{elem}Array{rank}d"),
+            NamingScheme::Pattern(p) => p
+                .replace("{elem}", &elem)
+                .replace("{rank}", &rank.to_string()),
+        };
+
+        match data {
+            manifest::Type::Array(array) => {
+                array_name(first_uppercase(array.elemtype.to_str()), array.rank)
+            }
+            manifest::Type::Opaque(opaque) => match &opaque.options {
+                manifest::OpaqueOptions::OpaqueArray(array)
+                | manifest::OpaqueOptions::RecordArray(array) => {
+                    let elemname = self.scheme_name(
+                        scheme,
+                        &array.elemtype,
+                        &manifest.types[&array.elemtype],
+                        manifest,
+                    );
+                    array_name(elemname, array.rank)
+                }
+                manifest::OpaqueOptions::Sum(_) | manifest::OpaqueOptions::Record(_) => {
+                    let ctype = convert_struct_name(&opaque.ctype);
+                    match scheme {
+                        NamingScheme::Legacy => {
+                            let hash = ctype.strip_prefix("futhark_opaque").unwrap_or(ctype);
+                            format!("Unnamed{hash}")
+                        }
+                        _ => ctype.to_case(Case::Pascal),
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Build a name from the structure of a type, this doesn't depend on the hashes Futhark
+/// uses for anonymous C types so it stays the same when the program changes
+///
+/// Field and payload types are part of the name, so records and sum types that only differ
+/// in their types get different names
+fn structural_name(t: &manifest::TypeExpr) -> String {
+    use manifest::TypeExpr;
+    match t {
+        TypeExpr::Scalar(t) => first_uppercase(t.to_str()),
+        TypeExpr::Array { rank, elem } => format!("Array{}D{rank}", structural_name(elem)),
+        TypeExpr::Tuple(elems) => {
+            "Tuple".to_string() + &elems.iter().map(structural_name).collect::<String>()
+        }
+        TypeExpr::Record(fields) => {
+            "Record".to_string()
+                + &fields
+                    .iter()
+                    .map(|(name, t)| name.to_case(Case::Pascal) + &structural_name(t))
+                    .collect::<String>()
+        }
+        TypeExpr::Sum(variants) => {
+            "Sum".to_string()
+                + &variants
+                    .iter()
+                    .map(|(name, payload)| {
+                        name.to_case(Case::Pascal)
+                            + &payload.iter().map(structural_name).collect::<String>()
+                    })
+                    .collect::<String>()
+        }
+        TypeExpr::Named(name) => name.to_case(Case::Pascal),
+    }
+}
+
+impl Namer for DefaultNamer {
+    fn init(&mut self, _manifest: &Manifest) {}
+
+    fn type_name(
+        &mut self,
+        futhark_name: &str,
+        data: &manifest::Type,
+        manifest: &Manifest,
+    ) -> String {
+        if let Some(name) = self.pinned.get(futhark_name) {
+            return name.clone();
+        }
+
+        if let Some(name) = self
+            .config
+            .types
+            .get(futhark_name)
+            .and_then(|t| t.name.as_ref())
+        {
+            return name.clone();
+        }
+
+        self.scheme_name(&self.config.scheme, futhark_name, data, manifest)
+    }
+
+    fn new_field_name(&mut self, futhark_name: &str, _manifest: &Manifest) -> String {
+        if let Some(name) = self.config.fields.get(futhark_name) {
+            return name.clone();
+        }
+
+        if futhark_name.chars().next().unwrap().is_ascii_digit() {
+            format!("f{}", futhark_name)
+        } else {
+            futhark_name.to_string()
+        }
+    }
+
+    fn project_name(&mut self, futhark_name: &str, _manifest: &Manifest) -> String {
+        if let Some(name) = self.config.fields.get(futhark_name) {
+            return name.clone();
+        }
+
+        if futhark_name.chars().next().unwrap().is_ascii_digit() {
+            format!("f{}", futhark_name)
+        } else {
+            futhark_name.to_string()
+        }
+    }
+
+    fn entry_name(&mut self, futhark_name: &str, _manifest: &Manifest) -> String {
+        self.config
+            .entries
+            .get(futhark_name)
+            .and_then(|e| e.name.clone())
+            .unwrap_or_else(|| futhark_name.to_string())
+    }
+
+    fn type_derives(&mut self, futhark_name: &str, _manifest: &Manifest) -> Vec<String> {
+        self.config
+            .types
+            .get(futhark_name)
+            .map(|t| t.derives.clone())
+            .unwrap_or_default()
+    }
+
+    fn type_doc(&mut self, futhark_name: &str, _manifest: &Manifest) -> Option<String> {
+        self.config
+            .types
+            .get(futhark_name)
+            .and_then(|t| t.doc.clone())
+    }
+
+    fn entry_doc(&mut self, futhark_name: &str, _manifest: &Manifest) -> Option<String> {
+        self.config
+            .entries
+            .get(futhark_name)
+            .and_then(|e| e.doc.clone())
+    }
+
+    fn type_aliases(
+        &mut self,
+        futhark_name: &str,
+        data: &manifest::Type,
+        manifest: &Manifest,
+    ) -> Vec<String> {
+        let Some(scheme) = &self.config.aliases else {
+            return Vec::new();
+        };
+        let old = self.scheme_name(scheme, futhark_name, data, manifest);
+        if old == self.type_name(futhark_name, data, manifest) {
+            return Vec::new();
+        }
+        vec![old]
+    }
+
+    fn entry_aliases(&mut self, futhark_name: &str, manifest: &Manifest) -> Vec<String> {
+        if self.config.aliases.is_none() || self.entry_name(futhark_name, manifest) == futhark_name
+        {
+            return Vec::new();
+        }
+        vec![futhark_name.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::ir::tests::{array, entry, library, package, record, sum};
+
+    #[test]
+    fn legacy_scheme_matches_baseline_names() {
+        let pkg = package(
+            json!({}),
+            json!({
+                "[]i64": array("i64_1d", "i64", 1),
+                "point": record("opaque_point", &[("x", "f32")]),
+                "{x: f32, y: f32}": record("opaque_85fa2", &[("x", "f32"), ("y", "f32")]),
+                "#some i64 | #none": sum("opaque_1b3c4", &[("#none", &[]), ("#some", &["i64"])]),
+            }),
+        );
+        let namer = DefaultNamer::default().with_scheme(NamingScheme::Legacy);
+        let lib = library(&pkg, namer).unwrap();
+        let name = |t: &str| lib.types[t].name.as_str();
+        assert_eq!(name("[]i64"), "ArrayI64D1");
+        assert_eq!(name("point"), "Point");
+        assert_eq!(name("{x: f32, y: f32}"), "Unnamed_85fa2");
+        assert_eq!(name("#some i64 | #none"), "Unnamed_1b3c4");
+    }
+
+    /// Create an empty directory for a test
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("futhark-bindgen-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn toml_error(s: &str) -> String {
+        match NamingConfig::parse(s) {
+            Err(Error::Toml(e)) => e.to_string(),
+            other => panic!("expected a TOML error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_config() {
+        let config = NamingConfig::parse(
+            r#"
+            scheme = { pattern = "Arr{elem}{rank}" }
+            aliases = "legacy"
+
+            [types]
+            point = { name = "Point2", derives = ["Debug"], doc = "A point" }
+            "(i32, f32)" = "Pair"
+
+            [entries]
+            binary_search = "search"
+            add = { doc = "Add two numbers" }
+
+            [fields]
+            "0" = "first"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.scheme,
+            NamingScheme::Pattern("Arr{elem}{rank}".to_string())
+        );
+        assert_eq!(config.aliases, Some(NamingScheme::Legacy));
+        let point = &config.types["point"];
+        assert_eq!(point.name.as_deref(), Some("Point2"));
+        assert_eq!(point.derives, ["Debug"]);
+        assert_eq!(point.doc.as_deref(), Some("A point"));
+        assert_eq!(config.types["(i32, f32)"].name.as_deref(), Some("Pair"));
+        assert_eq!(
+            config.entries["binary_search"].name.as_deref(),
+            Some("search")
+        );
+        assert_eq!(config.entries["add"].name, None);
+        assert_eq!(
+            config.entries["add"].doc.as_deref(),
+            Some("Add two numbers")
+        );
+        assert_eq!(config.fields["0"], "first");
+
+        let config = NamingConfig::parse("").unwrap();
+        assert_eq!(config.scheme, NamingScheme::Current);
+        assert_eq!(config.aliases, None);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(toml_error("schema = \"legacy\"").contains("unknown field `schema`"));
+        assert!(toml_error("[types]\npoint = { nme = \"P\" }").contains("unknown field `nme`"));
+        assert!(toml_error("scheme = \"modern\"").contains("modern"));
+        for pattern in [
+            "Array{elem}",
+            "Array{rank}",
+            "{elem}Array{rank}{dims}",
+            "{elem}{rank",
+        ] {
+            let error = toml_error(&format!("scheme = {{ pattern = {pattern:?} }}"));
+            assert!(
+                error.contains("invalid array name pattern"),
+                "{pattern}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn find_config() {
+        let dir = test_dir("naming-find");
+        assert!(NamingConfig::find(&dir).unwrap().is_none());
+
+        let cargo = dir.join("Cargo.toml");
+        std::fs::write(&cargo, "[package]\nname = \"test\"\n").unwrap();
+        assert!(NamingConfig::find(&dir).unwrap().is_none());
+
+        std::fs::write(
+            &cargo,
+            "[package]\nname = \"test\"\n\n[package.metadata.futhark-bindgen]\nscheme = \"legacy\"\n\n[package.metadata.futhark-bindgen.entries]\nadd = \"plus\"\n",
+        )
+        .unwrap();
+        let (config, path) = NamingConfig::find(&dir).unwrap().unwrap();
+        assert_eq!(path, cargo);
+        assert_eq!(config.scheme, NamingScheme::Legacy);
+        assert_eq!(config.entries["add"].name.as_deref(), Some("plus"));
+
+        std::fs::write(
+            &cargo,
+            "[package]\nname = \"test\"\n\n[package.metadata.futhark-bindgen]\nunknown = 1\n",
+        )
+        .unwrap();
+        assert!(matches!(NamingConfig::find(&dir), Err(Error::Toml(_))));
+
+        // futhark-bindgen.toml takes precedence over Cargo.toml
+        let file = dir.join(NamingConfig::FILE_NAME);
+        std::fs::write(&file, "scheme = \"current\"\n").unwrap();
+        let (config, path) = NamingConfig::find(&dir).unwrap().unwrap();
+        assert_eq!(path, file);
+        assert_eq!(config.scheme, NamingScheme::Current);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn overrides_package() -> Package {
+        package(
+            json!({
+                "add": entry("add", &[("x", "[]i64")], &["point"]),
+                "norm": entry("norm", &[("p", "point")], &["f32"]),
+            }),
+            json!({
+                "[]i64": array("i64_1d", "i64", 1),
+                "point": record("opaque_point", &[("0", "f32"), ("y", "f32")]),
+            }),
+        )
+    }
+
+    #[test]
+    fn type_and_entry_overrides() {
+        let config = NamingConfig::parse(
+            r#"
+            [types]
+            point = { name = "Vec2", derives = ["Debug"], doc = "A vector" }
+            "[]i64" = "Indices"
+
+            [entries]
+            add = { name = "make", doc = "Make a vector" }
+
+            [fields]
+            "0" = "x"
+            "#,
+        )
+        .unwrap();
+        let lib = library(
+            &overrides_package(),
+            DefaultNamer::default().with_config(config),
+        )
+        .unwrap();
+        let point = &lib.types["point"];
+        assert_eq!(point.name, "Vec2");
+        assert_eq!(point.derives, ["Debug"]);
+        assert_eq!(point.doc.as_deref(), Some("A vector"));
+        assert_eq!(lib.types["[]i64"].name, "Indices");
+        assert_eq!(lib.entries["add"].name, "make");
+        assert_eq!(lib.entries["add"].doc.as_deref(), Some("Make a vector"));
+        assert_eq!(lib.entries["norm"].name, "norm");
+        let ir::TypeKind::Record(r) = &point.kind else {
+            panic!("expected a record");
+        };
+        assert_eq!(r.fields[0].name, "x");
+
+        // Pinned names take precedence over the configuration
+        let namer = DefaultNamer::default()
+            .with_config(NamingConfig::parse("[types]\npoint = \"Vec2\"\n").unwrap())
+            .with_type_name("point", "Pinned");
+        let lib = library(&overrides_package(), namer).unwrap();
+        assert_eq!(lib.types["point"].name, "Pinned");
+    }
+
+    #[test]
+    fn deprecated_aliases() {
+        let config =
+            NamingConfig::parse("aliases = \"legacy\"\n[entries]\nadd = \"make\"\n").unwrap();
+        let namer = DefaultNamer::default().with_config(config);
+        let lib = library(&overrides_package(), namer.clone()).unwrap();
+        assert_eq!(lib.types["[]i64"].aliases, ["ArrayI64D1"]);
+        assert!(lib.types["point"].aliases.is_empty());
+        assert_eq!(lib.entries["add"].aliases, ["add"]);
+        assert!(lib.entries["norm"].aliases.is_empty());
+
+        let mut config = Config::with_writer("lib.rs", std::io::sink(), namer);
+        let code = Rust
+            .generate_to_string(&overrides_package(), &mut config)
+            .unwrap();
+        assert!(code.contains(
+            "/// Deprecated alias for `I64Array1d`\n#[deprecated(note = \"renamed to `I64Array1d`\")]\npub type ArrayI64D1<'a> = I64Array1d<'a>;"
+        ));
+        assert!(code.contains(
+            "/// Deprecated alias for `make`\n#[deprecated(note = \"renamed to `make`\")]\npub fn add<'a>("
+        ));
+    }
+}
@@ -1,329 +1,589 @@
+use std::fmt::Display;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use crate::generate::{convert_struct_name, first_uppercase};
+use crate::generate::{first_uppercase, IdentRules};
+use crate::ir::ElemType;
 use crate::*;
 
-/// OCaml codegen
-pub struct OCaml {
-    typemap: BTreeMap<String, String>,
-    ctypes_map: BTreeMap<String, String>,
-    ba_map: BTreeMap<String, (String, String)>,
-    mli_file: std::fs::File,
-}
-
-const OCAML_CTYPES_MAP: &[(&str, &str)] = &[
-    ("i8", "char"),
-    ("u8", "uint8_t"),
-    ("i16", "int16_t"),
-    ("u16", "uint16_t"),
-    ("i32", "int32_t"),
-    ("u32", "uint32_t"),
-    ("i64", "int64_t"),
-    ("u64", "uint64_t"),
-    ("f16", ""), // No half type in OCaml
-    ("f32", "float"),
-    ("f64", "double"),
-    ("bool", "bool"),
+/// OCaml codegen, writes the output file and the matching `.mli`
+#[derive(Default)]
+pub struct OCaml;
+
+/// Names defined in the `Bindings` module by `templates/ocaml/bindings.ml` and `ctypes`
+/// values used to declare functions
+const RESERVED_BINDINGS: &[&str] = &[
+    "bool",
+    "char",
+    "context",
+    "context_config",
+    "double",
+    "float",
+    "fn",
+    "free",
+    "int",
+    "int16_t",
+    "int32_t",
+    "int64_t",
+    "int8_t",
+    "ptr",
+    "returning",
+    "size_t",
+    "string",
+    "strlen",
+    "typedef",
+    "uint16_t",
+    "uint32_t",
+    "uint64_t",
+    "uint8_t",
+    "void",
 ];
 
-const OCAML_TYPE_MAP: &[(&str, &str)] = &[
-    ("i8", "char"),
-    ("u8", "UInt8.t"),
-    ("i16", "int"),
-    ("u16", "UInt16.t"),
-    ("i32", "int32"),
-    ("i64", "int64"),
-    ("u32", "UInt32.t"),
-    ("u64", "UInt64.t"),
-    ("f16", ""), // No half type in OCaml
-    ("f32", "float"),
-    ("f64", "float"),
-    ("bool", "bool"),
+/// Modules used by the generated code, these can't be shadowed by generated types
+const RESERVED_MODULES: &[&str] = &[
+    "Array",
+    "Array1",
+    "Bigarray",
+    "Bindings",
+    "Context",
+    "Ctypes",
+    "Ctypes_ptr",
+    "Ctypes_static",
+    "Foreign",
+    "Gc",
+    "Genarray",
+    "Int64",
+    "Obj",
+    "Option",
+    "Printexc",
+    "Printf",
+    "Signed",
+    "String",
+    "UInt16",
+    "UInt32",
+    "UInt64",
+    "UInt8",
+    "Unsigned",
 ];
 
-const OCAML_BA_TYPE_MAP: &[(&str, (&str, &str))] = &[
-    ("i8", ("int", "Bigarray.int8_signed_elt")),
-    ("u8", ("int", "Bigarray.int8_unsigned_elt")),
-    ("i16", ("int", "Bigarray.int16_signed_elt")),
-    ("u16", ("int", "Bigarray.int16_unsigned_elt")),
-    ("i32", ("int32", "Bigarray.int32_elt")),
-    ("i64", ("int64", "Bigarray.int64_elt")),
-    ("u32", ("int32", "Bigarray.int32_elt")),
-    ("u64", ("int64", "Bigarray.int64_elt")),
-    ("f16", ("", "")), // No half Bigarray kind
-    ("f32", ("float", "Bigarray.float32_elt")),
-    ("f64", ("float", "Bigarray.float64_elt")),
-    ("bool", ("int", "Bigarray.int8_unsigned_elt")),
+/// Values used by generated entry points, these can't be shadowed by an entry point
+const RESERVED_VALUES: &[&str] = &[
+    "allocate",
+    "allocate_n",
+    "bool",
+    "char",
+    "check_use_after_free",
+    "double",
+    "failwith",
+    "float",
+    "get_opaque_ptr",
+    "get_ptr",
+    "ignore",
+    "int16_t",
+    "int32_t",
+    "int64_t",
+    "int8_t",
+    "invalid_arg",
+    "is_null",
+    "null",
+    "ptr",
+    "raise",
+    "set_managed",
+    "uint16_t",
+    "uint32_t",
+    "uint64_t",
+    "uint8_t",
+    "void",
 ];
 
-fn type_is_array(t: &str) -> bool {
-    t.contains("array_f") || t.contains("array_i") || t.contains("array_u") || t.contains("array_b")
+/// Functions used inside generated type modules
+const RESERVED_GETTERS: &[&str] = &["get_ptr", "get_opaque_ptr"];
+
+/// Constructors used inside generated type modules
+const RESERVED_CONSTRUCTORS: &[&str] =
+    &["Code", "Error", "InvalidShape", "NullPtr", "UseAfterFree"];
+
+fn ocaml_ident(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    IdentRules::OCAML.escape(name, reserved, item)
+}
+
+fn unsupported(name: &str, item: String) -> Error {
+    Error::UnsupportedType {
+        name: name.to_string(),
+        item,
+        language: IdentRules::OCAML.language,
+    }
 }
 
-fn type_is_opaque(t: &str) -> bool {
-    t.contains(".t")
+/// Returns the `ctypes` type and OCaml type used for a primitive value
+fn primitive_type(t: ElemType) -> Option<(&'static str, &'static str)> {
+    Some(match t {
+        ElemType::I8 => ("int8_t", "int"),
+        ElemType::I16 => ("int16_t", "int"),
+        ElemType::I32 => ("int32_t", "int32"),
+        ElemType::I64 => ("int64_t", "int64"),
+        ElemType::U8 => ("uint8_t", "UInt8.t"),
+        ElemType::U16 => ("uint16_t", "UInt16.t"),
+        ElemType::U32 => ("uint32_t", "UInt32.t"),
+        ElemType::U64 => ("uint64_t", "UInt64.t"),
+        ElemType::F16 => return None,
+        ElemType::F32 => ("float", "float"),
+        ElemType::F64 => ("double", "float"),
+        ElemType::Bool => ("bool", "bool"),
+    })
 }
 
-fn ba_kind(t: &str) -> String {
-    let mut s = t.strip_suffix("_elt").unwrap().to_string();
+/// Returns the OCaml element type, Bigarray element type and Bigarray kind used for arrays
+fn bigarray_type(t: ElemType) -> Option<(&'static str, &'static str, &'static str)> {
+    Some(match t {
+        ElemType::I8 => ("int", "Bigarray.int8_signed_elt", "Bigarray.int8_signed"),
+        ElemType::U8 | ElemType::Bool => (
+            "int",
+            "Bigarray.int8_unsigned_elt",
+            "Bigarray.int8_unsigned",
+        ),
+        ElemType::I16 => ("int", "Bigarray.int16_signed_elt", "Bigarray.int16_signed"),
+        ElemType::U16 => (
+            "int",
+            "Bigarray.int16_unsigned_elt",
+            "Bigarray.int16_unsigned",
+        ),
+        ElemType::I32 | ElemType::U32 => ("int32", "Bigarray.int32_elt", "Bigarray.int32"),
+        ElemType::I64 | ElemType::U64 => ("int64", "Bigarray.int64_elt", "Bigarray.int64"),
+        ElemType::F16 => return None,
+        ElemType::F32 => ("float", "Bigarray.float32_elt", "Bigarray.float32"),
+        ElemType::F64 => ("float", "Bigarray.float64_elt", "Bigarray.float64"),
+    })
+}
 
-    if let Some(r) = s.get_mut(8..9) {
-        r.make_ascii_uppercase();
-    }
+/// Name of the `ctypes` type defined in `Bindings` for a named type
+///
+/// OCaml names don't use the `Namer`, they're derived from the C names so they stay
+/// compatible with previous versions: `array_f32_1d` for arrays and the C name without
+/// `futhark_opaque_` for opaque types
+fn binding_name(ty: &ir::Type) -> Result<String, Error> {
+    let name = match &ty.kind {
+        ir::TypeKind::Array(a) => format!("array_{}_{}d", a.elemtype.to_str(), a.rank),
+        _ => {
+            let name = ty
+                .raw_name
+                .strip_prefix("futhark_opaque_")
+                .unwrap_or(&ty.raw_name);
+            if name.starts_with(|c: char| c.is_ascii_digit()) || ty.futhark_name.contains(' ') {
+                format!("type_{name}")
+            } else {
+                name.to_string()
+            }
+        }
+    };
+    ocaml_ident(&name, RESERVED_BINDINGS, || {
+        format!("type {:?}", ty.futhark_name)
+    })
+}
 
-    s
+/// Name of the module generated for a type
+fn module_name(ty: &ir::Type) -> Result<String, Error> {
+    ocaml_ident(
+        &first_uppercase(&binding_name(ty)?),
+        RESERVED_MODULES,
+        || format!("type {:?}", ty.futhark_name),
+    )
 }
 
-impl OCaml {
-    /// Create new OCaml codegen instance
-    pub fn new(config: &Config) -> Result<Self, Error> {
-        let typemap = OCAML_TYPE_MAP
-            .iter()
-            .map(|(a, b)| (a.to_string(), b.to_string()))
-            .collect();
-
-        let ba_map = OCAML_BA_TYPE_MAP
-            .iter()
-            .map(|(a, (b, c))| (a.to_string(), (b.to_string(), c.to_string())))
-            .collect();
-
-        let ctypes_map = OCAML_CTYPES_MAP
-            .iter()
-            .map(|(a, b)| (a.to_string(), b.to_string()))
-            .collect();
-
-        let mli_path = config.output_path.with_extension("mli");
-        let mli_file = std::fs::File::create(mli_path)?;
-        Ok(OCaml {
-            typemap,
-            ba_map,
-            ctypes_map,
-            mli_file,
-        })
-    }
+/// How a value is passed to the C API
+enum Value<'a> {
+    Primitive(&'static str, &'static str),
+    Array(&'a ir::Type),
+    Opaque(&'a ir::Type),
+}
 
-    fn foreign_function(&mut self, name: &str, ret: &str, args: Vec<&str>) -> String {
-        format!(
-            "let {name} = fn \"{name}\" ({} @-> returning ({ret}))",
-            args.join(" @-> ")
-        )
+impl<'a> Value<'a> {
+    fn new(
+        lib: &'a ir::Library,
+        ty: &ir::TypeRef,
+        item: impl Fn() -> String,
+    ) -> Result<Self, Error> {
+        match (ty, lib.get(ty)) {
+            (ir::TypeRef::Primitive(t), _) => {
+                let (ctype, ocaml) =
+                    primitive_type(*t).ok_or_else(|| unsupported(t.to_str(), item()))?;
+                Ok(Value::Primitive(ctype, ocaml))
+            }
+            (_, Some(t)) if matches!(t.kind, ir::TypeKind::Array(_)) => Ok(Value::Array(t)),
+            (_, Some(t)) => Ok(Value::Opaque(t)),
+            (ir::TypeRef::Named(name), None) => {
+                Err(Error::InvalidManifest(format!("unknown type {name:?}")))
+            }
+        }
     }
 
-    fn get_ctype(&self, t: &str) -> String {
-        let x = self
-            .ctypes_map
-            .get(t)
-            .cloned()
-            .unwrap_or_else(|| t.to_string());
-        if x.is_empty() {
-            panic!("Unsupported type: {t}");
+    /// `ctypes` type used in `Bindings`
+    fn ctype(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(ctype, _) => Ok(ctype.to_string()),
+            Value::Array(t) | Value::Opaque(t) => binding_name(t),
         }
-        x
     }
 
-    fn get_type(&self, t: &str) -> String {
-        let x = self
-            .typemap
-            .get(t)
-            .cloned()
-            .unwrap_or_else(|| t.to_string());
-        if x.is_empty() {
-            panic!("Unsupported type: {t}");
+    /// `ctypes` type used outside of `Bindings`
+    fn qualified_ctype(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(ctype, _) => Ok(ctype.to_string()),
+            Value::Array(t) | Value::Opaque(t) => Ok(format!("Bindings.{}", binding_name(t)?)),
         }
-        x
     }
 
-    fn get_ba_type(&self, t: &str) -> (String, String) {
-        let x = self.ba_map.get(t).cloned().unwrap();
-        if x.0.is_empty() {
-            panic!("Unsupported type: {t}");
+    /// Type used in signatures
+    fn ocaml_type(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(_, ocaml) => Ok(ocaml.to_string()),
+            Value::Array(t) | Value::Opaque(t) => Ok(format!("{}.t", module_name(t)?)),
         }
-        x
     }
-}
 
-impl Generate for OCaml {
-    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
-        writeln!(self.mli_file, "(* Generated by futhark-bindgen *)\n")?;
-        writeln!(config.output_file, "(* Generated by futhark-bindgen *)\n")?;
+    /// Expression passing `name` to a C function
+    fn arg(&self, name: &str) -> String {
+        match self {
+            Value::Primitive(..) => name.to_string(),
+            Value::Array(_) => format!("(get_ptr {name})"),
+            Value::Opaque(_) => format!("(get_opaque_ptr {name})"),
+        }
+    }
 
-        let mut generated_foreign_functions = Vec::new();
-        match pkg.manifest.backend {
-            Backend::Multicore => {
-                generated_foreign_functions.push(format!(
-                    "  {}",
-                    self.foreign_function(
-                        "futhark_context_config_set_num_threads",
-                        "void",
-                        vec!["context_config", "int"]
-                    )
-                ));
-            }
-            Backend::Cuda | Backend::OpenCl => {
-                generated_foreign_functions.push(format!(
-                    "  {}",
-                    self.foreign_function(
-                        "futhark_context_config_set_device",
-                        "void",
-                        vec!["context_config", "string"]
-                    )
-                ));
+    /// Expression wrapping the value stored in the output pointer `name`
+    fn output(&self, ctx: &str, name: &str) -> Result<String, Error> {
+        match self {
+            Value::Primitive(..) => Ok(format!("!@{name}")),
+            Value::Array(t) | Value::Opaque(t) => {
+                Ok(format!("({}.of_ptr {ctx} !@{name})", module_name(t)?))
             }
-            _ => (),
         }
+    }
+}
 
-        for (name, ty) in &pkg.manifest.types {
-            match ty {
-                manifest::Type::Array(a) => {
-                    let elemtype = a.elemtype.to_str().to_string();
-                    let ctypes_elemtype = self.get_ctype(&elemtype);
-                    let rank = a.rank;
-                    let ocaml_name = format!("array_{elemtype}_{rank}d");
-                    self.typemap.insert(name.clone(), ocaml_name.clone());
-                    self.ctypes_map.insert(name.clone(), ocaml_name.clone());
-                    let elem_ptr = format!("ptr {ctypes_elemtype}");
-                    generated_foreign_functions.push(format!(
-                        "  let {ocaml_name} = typedef (ptr void) \"{ocaml_name}\""
-                    ));
-                    let mut new_args = vec!["context", &elem_ptr];
-                    new_args.resize(rank as usize + 2, "int64_t");
-                    generated_foreign_functions.push(format!(
-                        "  {}",
-                        self.foreign_function(&a.ops.new, &ocaml_name, new_args)
-                    ));
-                    generated_foreign_functions.push(format!(
-                        "  {}",
-                        self.foreign_function(
-                            &a.ops.values,
-                            "int",
-                            vec!["context", &ocaml_name, &elem_ptr]
-                        )
-                    ));
-                    generated_foreign_functions.push(format!(
-                        "  {}",
-                        self.foreign_function(&a.ops.free, "int", vec!["context", &ocaml_name])
-                    ));
-                    generated_foreign_functions.push(format!(
-                        "  {}",
-                        self.foreign_function(
-                            &a.ops.shape,
-                            "ptr int64_t",
-                            vec!["context", &ocaml_name]
-                        )
-                    ));
-                }
-                manifest::Type::Opaque(ty) => {
-                    let futhark_name = convert_struct_name(&ty.ctype);
-                    let mut ocaml_name = futhark_name
-                        .strip_prefix("futhark_opaque_")
-                        .unwrap()
-                        .to_string();
-                    if ocaml_name.chars().next().unwrap().is_numeric() || name.contains(' ') {
-                        ocaml_name = format!("type_{ocaml_name}");
-                    }
+fn foreign_function(name: &str, ret: &str, args: &[String]) -> String {
+    format!(
+        "  let {name} = fn \"{name}\" ({} @-> returning ({ret}))",
+        args.join(" @-> ")
+    )
+}
 
-                    self.typemap
-                        .insert(name.clone(), format!("{}.t", first_uppercase(&ocaml_name)));
-                    self.ctypes_map.insert(name.to_string(), ocaml_name.clone());
-                    generated_foreign_functions.push(format!(
-                        "  let {ocaml_name} = typedef (ptr void) \"{futhark_name}\""
-                    ));
+/// Get the `.ml` and `.mli` files, relative to the output directory
+fn files(config: &Config) -> (PathBuf, PathBuf) {
+    let ml = PathBuf::from(config.output_path.file_name().unwrap_or_default());
+    let mli = ml.with_extension("mli");
+    (ml, mli)
+}
 
-                    let free_fn = &ty.ops.free;
-                    generated_foreign_functions.push(format!(
-                        "  {}",
-                        self.foreign_function(free_fn, "int", vec!["context", &ocaml_name])
-                    ));
+/// Render a template from `templates/ocaml` and append it to `file`
+fn render(
+    config: &mut Config,
+    file: &Path,
+    name: &str,
+    args: &[(&str, &dyn Display)],
+) -> Result<(), Error> {
+    let code = config.templates.render("ocaml", name, args)?;
+    writeln!(config.output(file), "{code}")?;
+    Ok(())
+}
 
-                    let record = match &ty.record {
-                        Some(r) => r,
-                        None => continue,
-                    };
-
-                    let new_fn = &record.new;
-                    let mut args = vec!["context".to_string(), format!("ptr {ocaml_name}")];
-                    for f in record.fields.iter() {
-                        let cty = self
-                            .ctypes_map
-                            .get(&f.r#type)
-                            .cloned()
-                            .unwrap_or_else(|| f.r#type.clone());
-
-                        // project function
-                        generated_foreign_functions.push(format!(
-                            "  {}",
-                            self.foreign_function(
-                                &f.project,
-                                "int",
-                                vec!["context", &format!("ptr {cty}"), &ocaml_name]
-                            )
-                        ));
+/// Declarations for the C functions used by a type
+fn type_bindings(lib: &ir::Library, ty: &ir::Type, out: &mut Vec<String>) -> Result<(), Error> {
+    let item = || format!("type {:?}", ty.futhark_name);
+    let name = binding_name(ty)?;
+    let context = "context".to_string();
+    let ptr_name = format!("ptr {name}");
+    let index_args = |rank: usize| vec!["int64_t".to_string(); rank];
+
+    if let ir::TypeKind::Array(a) = &ty.kind {
+        let (ctype, _) =
+            primitive_type(a.elemtype).ok_or_else(|| unsupported(a.elemtype.to_str(), item()))?;
+        let elem_ptr = format!("ptr {ctype}");
+        let new_args = [context.clone(), elem_ptr.clone()]
+            .into_iter()
+            .chain(index_args(a.rank))
+            .collect::<Vec<_>>();
+        out.push(foreign_function(&a.ops.new, &name, &new_args));
+        out.push(foreign_function(
+            &a.ops.values,
+            "int",
+            &[context.clone(), name.clone(), elem_ptr],
+        ));
+        out.push(foreign_function(
+            &a.ops.free,
+            "int",
+            &[context.clone(), name.clone()],
+        ));
+        out.push(foreign_function(
+            &a.ops.shape,
+            "ptr int64_t",
+            &[context, name],
+        ));
+        return Ok(());
+    }
 
-                        args.push(cty);
-                    }
-                    let args = args.iter().map(|x| x.as_str()).collect();
-                    generated_foreign_functions
-                        .push(format!("  {}", self.foreign_function(new_fn, "int", args)));
+    let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+    out.push(foreign_function(
+        &ops.free,
+        "int",
+        &[context.clone(), name.clone()],
+    ));
+
+    let field_bindings = |fields: &[ir::Field], out: &mut Vec<String>| {
+        let mut ctypes = Vec::new();
+        for field in fields {
+            let ctype = Value::new(lib, &field.ty, item)?.ctype()?;
+            out.push(foreign_function(
+                &field.project_fn,
+                "int",
+                &[context.clone(), format!("ptr {ctype}"), name.clone()],
+            ));
+            ctypes.push(ctype);
+        }
+        Ok::<_, Error>(ctypes)
+    };
+
+    let opaque_array = |a: &ir::OpaqueArray, out: &mut Vec<String>| {
+        let elem = Value::new(lib, &a.elemtype, item)?.ctype()?;
+        out.push(foreign_function(
+            &a.shape_fn,
+            "ptr int64_t",
+            &[context.clone(), name.clone()],
+        ));
+        let args = [context.clone(), format!("ptr {elem}"), name.clone()]
+            .into_iter()
+            .chain(index_args(a.rank))
+            .collect::<Vec<_>>();
+        out.push(foreign_function(&a.index_fn, "int", &args));
+        Ok::<_, Error>(())
+    };
+
+    match &ty.kind {
+        ir::TypeKind::Record(r) => {
+            let ctypes = field_bindings(&r.fields, out)?;
+            let args = [context.clone(), ptr_name]
+                .into_iter()
+                .chain(ctypes)
+                .collect::<Vec<_>>();
+            out.push(foreign_function(&r.new_fn, "int", &args));
+        }
+        ir::TypeKind::Sum(s) => {
+            out.push(foreign_function(
+                &s.variant_fn,
+                "int",
+                &[context.clone(), name.clone()],
+            ));
+            for v in &s.variants {
+                let ctypes = v
+                    .payload
+                    .iter()
+                    .map(|p| Value::new(lib, p, item)?.ctype())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let construct = [context.clone(), ptr_name.clone()]
+                    .into_iter()
+                    .chain(ctypes.iter().cloned())
+                    .collect::<Vec<_>>();
+                out.push(foreign_function(&v.construct_fn, "int", &construct));
+                if ctypes.is_empty() {
+                    // Nothing to destruct, an unused binding would be a warning
+                    continue;
                 }
+                let destruct = std::iter::once(context.clone())
+                    .chain(ctypes.iter().map(|t| format!("ptr {t}")))
+                    .chain([name.clone()])
+                    .collect::<Vec<_>>();
+                out.push(foreign_function(&v.destruct_fn, "int", &destruct));
             }
         }
+        ir::TypeKind::OpaqueArray(a) => opaque_array(a, out)?,
+        ir::TypeKind::RecordArray(r) => {
+            opaque_array(&r.array, out)?;
+            let ctypes = field_bindings(&r.fields, out)?;
+            let args = [context.clone(), ptr_name]
+                .into_iter()
+                .chain(ctypes)
+                .collect::<Vec<_>>();
+            out.push(foreign_function(&r.zip_fn, "int", &args));
+        }
+        ir::TypeKind::Array(_) => unreachable!(),
+    }
+    Ok(())
+}
 
-        for entry in pkg.manifest.entry_points.values() {
-            let mut args = vec!["context".to_string()];
+/// Render field projections for records and arrays of records
+fn field_projections(
+    lib: &ir::Library,
+    config: &mut Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+) -> Result<(), Error> {
+    let (ml, mli) = files(config);
+    for field in fields {
+        let item = || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        };
+        let value = Value::new(lib, &field.ty, item)?;
+        let name = ocaml_ident(
+            &format!("get_{}", field.futhark_name),
+            RESERVED_GETTERS,
+            item,
+        )?;
+        let name = name.strip_prefix("get_").unwrap_or(&name);
+        render(
+            config,
+            &ml,
+            "record_project.ml",
+            &[
+                ("name", &name),
+                ("s", &value.qualified_ctype()?),
+                ("project", &field.project_fn),
+                ("out", &value.output("t.opaque_ctx", "out")?),
+            ],
+        )?;
+        render(
+            config,
+            &mli,
+            "record_project.mli",
+            &[("name", &name), ("out_type", &value.ocaml_type()?)],
+        )?;
+    }
+    Ok(())
+}
 
-            for out in &entry.outputs {
-                let t = self.get_ctype(&out.r#type);
+/// Parameters of a constructor taking one value per field
+struct FieldParams {
+    params: Vec<String>,
+    call_args: Vec<String>,
+    arg_types: Vec<String>,
+}
 
-                args.push(format!("ptr {t}"));
-            }
+fn field_params(
+    lib: &ir::Library,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+) -> Result<FieldParams, Error> {
+    let mut params = Vec::new();
+    let mut call_args = Vec::new();
+    let mut arg_types = Vec::new();
+    for field in fields {
+        let item = || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        };
+        let value = Value::new(lib, &field.ty, item)?;
+        let param = ocaml_ident(&format!("field{}", field.futhark_name), &[], item)?;
+        call_args.push(value.arg(&param));
+        arg_types.push(value.ocaml_type()?);
+        params.push(param);
+    }
+    Ok(FieldParams {
+        params,
+        call_args,
+        arg_types,
+    })
+}
 
-            for input in &entry.inputs {
-                let t = self.get_ctype(&input.r#type);
-                args.push(t);
-            }
+impl Generate for OCaml {
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        let (ml, mli) = files(config);
+        let caps = lib.backend.capabilities();
+
+        let mut generated_foreign_functions = Vec::new();
+        for option in caps.config_options {
+            let arg = match option {
+                ConfigOption::NumThreads => "int",
+                ConfigOption::Device | ConfigOption::Platform => "string",
+            };
+            generated_foreign_functions.push(foreign_function(
+                option.c_function(),
+                "void",
+                &["context_config".to_string(), arg.to_string()],
+            ));
+        }
 
-            let args = args.iter().map(|x| x.as_str()).collect();
+        let types = lib.types_in_order();
+        for ty in &types {
             generated_foreign_functions.push(format!(
-                "  {}",
-                self.foreign_function(&entry.cfun, "int", args)
+                "  let {} = typedef (ptr void) \"{}\"",
+                binding_name(ty)?,
+                ty.raw_name
             ));
         }
+        for ty in &types {
+            type_bindings(lib, ty, &mut generated_foreign_functions)?;
+        }
 
-        let generated_foreign_functions = generated_foreign_functions.join("\n");
+        for entry in lib.entries.values() {
+            let item = || format!("entry point {:?}", entry.futhark_name);
+            let mut args = vec!["context".to_string()];
+            for output in &entry.outputs {
+                args.push(format!(
+                    "ptr {}",
+                    Value::new(lib, &output.ty, item)?.ctype()?
+                ));
+            }
+            for input in &entry.inputs {
+                args.push(Value::new(lib, &input.ty, item)?.ctype()?);
+            }
+            generated_foreign_functions.push(foreign_function(&entry.cfun, "int", &args));
+        }
 
-        writeln!(
-            config.output_file,
-            include_str!("templates/ocaml/bindings.ml"),
-            generated_foreign_functions = generated_foreign_functions
+        render(
+            config,
+            &ml,
+            "bindings.ml",
+            &[(
+                "generated_foreign_functions",
+                &generated_foreign_functions.join("\n"),
+            )],
         )?;
+        render(config, &mli, "bindings.mli", &[])?;
+
+        let mut extra_params = Vec::new();
+        let mut extra_lines = Vec::new();
+        let mut extra_mlis = Vec::new();
+        for option in caps.config_options {
+            let (param, line, mli) = match option {
+                ConfigOption::NumThreads => (
+                    "?(num_threads = 0)",
+                    "    Bindings.futhark_context_config_set_num_threads config num_threads;",
+                    "?num_threads:int ->",
+                ),
+                ConfigOption::Device => (
+                    "?device",
+                    "    Option.iter (Bindings.futhark_context_config_set_device config) device;",
+                    "?device:string ->",
+                ),
+                ConfigOption::Platform => (
+                    "?platform",
+                    "    Option.iter (Bindings.futhark_context_config_set_platform config) platform;",
+                    "?platform:string ->",
+                ),
+            };
+            extra_params.push(param);
+            extra_lines.push(line);
+            extra_mlis.push(mli);
+        }
 
-        writeln!(self.mli_file, include_str!("templates/ocaml/bindings.mli"))?;
-
-        let (extra_param, extra_line, extra_mli) = match pkg.manifest.backend {
-            Backend::Multicore => (
-                "?(num_threads = 0)",
-                "    Bindings.futhark_context_config_set_num_threads config num_threads;",
-                "?num_threads:int ->",
-            ),
-
-            Backend::Cuda | Backend::OpenCl => (
-                "?device",
-                "    Option.iter (Bindings.futhark_context_config_set_device config) device;",
-                "?device:string ->",
-            ),
-            _ => ("", "", ""),
-        };
-
-        writeln!(
-            config.output_file,
-            include_str!("templates/ocaml/context.ml"),
-            extra_param = extra_param,
-            extra_line = extra_line
+        render(
+            config,
+            &ml,
+            "context.ml",
+            &[
+                ("extra_param", &extra_params.join(" ")),
+                ("extra_line", &extra_lines.join("\n")),
+            ],
         )?;
-        writeln!(
-            self.mli_file,
-            include_str!("templates/ocaml/context.mli"),
-            extra_mli = extra_mli
+        render(
+            config,
+            &mli,
+            "context.mli",
+            &[("extra_mli", &extra_mlis.join(" "))],
         )?;
 
         Ok(())
@@ -331,42 +591,50 @@ impl Generate for OCaml {
 
     fn array_type(
         &mut self,
-        _pkg: &Package,
+        _lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        ty: &manifest::ArrayType,
+        ty: &ir::Type,
+        a: &ir::Array,
     ) -> Result<(), Error> {
-        let rank = ty.rank;
-        let elemtype = ty.elemtype.to_str().to_string();
-        let ocaml_name = self.typemap.get(name).unwrap();
-        let module_name = first_uppercase(ocaml_name);
-        let mut dim_args = Vec::new();
-        for i in 0..rank {
-            dim_args.push(format!("(Int64.of_int dims.({i}))"));
-        }
-
-        let (ocaml_elemtype, ba_elemtype) = self.get_ba_type(&elemtype);
-        let ocaml_ctype = self.get_ctype(&elemtype);
-
-        writeln!(
-            config.output_file,
-            include_str!("templates/ocaml/array.ml"),
-            module_name = module_name,
-            elemtype = elemtype,
-            rank = rank,
-            dim_args = dim_args.join(" "),
-            ocaml_elemtype = ocaml_elemtype,
-            ba_elemtype = ba_elemtype,
-            ba_kind = ba_kind(&ba_elemtype),
-            ocaml_ctype = ocaml_ctype,
+        let (ml, mli) = files(config);
+        let item = || format!("type {:?}", ty.futhark_name);
+        let module_name = module_name(ty)?;
+        let (ocaml_elemtype, ba_elemtype, ba_kind) =
+            bigarray_type(a.elemtype).ok_or_else(|| unsupported(a.elemtype.to_str(), item()))?;
+        let (ocaml_ctype, _) =
+            primitive_type(a.elemtype).ok_or_else(|| unsupported(a.elemtype.to_str(), item()))?;
+        let dim_args = (0..a.rank)
+            .map(|i| format!("(Int64.of_int dims.({i}))"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        render(
+            config,
+            &ml,
+            "array.ml",
+            &[
+                ("module_name", &module_name),
+                ("rank", &a.rank),
+                ("ocaml_elemtype", &ocaml_elemtype),
+                ("ocaml_ctype", &ocaml_ctype),
+                ("ba_elemtype", &ba_elemtype),
+                ("ba_kind", &ba_kind),
+                ("dim_args", &dim_args),
+                ("new_fn", &a.ops.new),
+                ("free_fn", &a.ops.free),
+                ("values_fn", &a.ops.values),
+                ("shape_fn", &a.ops.shape),
+            ],
         )?;
-
-        writeln!(
-            self.mli_file,
-            include_str!("templates/ocaml/array.mli"),
-            module_name = module_name,
-            ocaml_elemtype = ocaml_elemtype,
-            ba_elemtype = ba_elemtype,
+        render(
+            config,
+            &mli,
+            "array.mli",
+            &[
+                ("module_name", &module_name),
+                ("ocaml_elemtype", &ocaml_elemtype),
+                ("ba_elemtype", &ba_elemtype),
+            ],
         )?;
 
         Ok(())
@@ -374,134 +642,206 @@ impl Generate for OCaml {
 
     fn opaque_type(
         &mut self,
-        _pkg: &Package,
+        lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        ty: &manifest::OpaqueType,
+        ty: &ir::Type,
     ) -> Result<(), Error> {
-        let futhark_name = convert_struct_name(&ty.ctype);
-        let mut ocaml_name = futhark_name
-            .strip_prefix("futhark_opaque_")
-            .unwrap()
-            .to_string();
-        if ocaml_name.chars().next().unwrap().is_numeric() || name.contains(' ') {
-            ocaml_name = format!("type_{ocaml_name}");
-        }
-        let module_name = first_uppercase(&ocaml_name);
-        self.typemap
-            .insert(ocaml_name.clone(), format!("{module_name}.t"));
-
-        let free_fn = &ty.ops.free;
-
-        writeln!(config.output_file, "module {module_name} = struct")?;
-        writeln!(self.mli_file, "module {module_name} : sig")?;
-
-        writeln!(
-            config.output_file,
-            include_str!("templates/ocaml/opaque.ml"),
-            free_fn = free_fn,
-            name = ocaml_name,
+        let (ml, mli) = files(config);
+        let item = || format!("type {:?}", ty.futhark_name);
+        let name = module_name(ty)?;
+        let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+
+        writeln!(config.output(&ml), "module {name} = struct")?;
+        writeln!(config.output(&mli), "module {name} : sig")?;
+
+        render(
+            config,
+            &ml,
+            "opaque.ml",
+            &[("name", &binding_name(ty)?), ("free_fn", &ops.free)],
         )?;
-        writeln!(self.mli_file, include_str!("templates/ocaml/opaque.mli"),)?;
-
-        let record = match &ty.record {
-            Some(r) => r,
-            None => {
-                writeln!(config.output_file, "end")?;
-                writeln!(self.mli_file, "end")?;
-                return Ok(());
+        render(config, &mli, "opaque.mli", &[])?;
+
+        match &ty.kind {
+            ir::TypeKind::Record(record) => {
+                let FieldParams {
+                    params,
+                    call_args,
+                    arg_types,
+                } = field_params(lib, ty, &record.fields)?;
+                render(
+                    config,
+                    &ml,
+                    "record.ml",
+                    &[
+                        ("new_fn", &record.new_fn),
+                        ("new_params", &params.join(" ")),
+                        ("new_call_args", &call_args.join(" ")),
+                    ],
+                )?;
+                render(
+                    config,
+                    &mli,
+                    "record.mli",
+                    &[("new_arg_types", &arg_types.join(" -> "))],
+                )?;
+                field_projections(lib, config, ty, &record.fields)?;
             }
-        };
+            ir::TypeKind::Sum(sum) => {
+                let mut constructors = Vec::new();
+                let mut construct_cases = Vec::new();
+                let mut destruct_cases = Vec::new();
+                for (i, variant) in sum.variants.iter().enumerate() {
+                    let constructor = ocaml_ident(
+                        &first_uppercase(&variant.name),
+                        RESERVED_CONSTRUCTORS,
+                        || format!("variant {:?} of type {:?}", variant.name, ty.futhark_name),
+                    )?;
+                    let payload = variant
+                        .payload
+                        .iter()
+                        .map(|p| Value::new(lib, p, item))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    if payload.is_empty() {
+                        constructors.push(constructor.clone());
+                        construct_cases.push(format!(
+                            "      | {constructor} -> Bindings.{} ctx.Context.handle ptr",
+                            variant.construct_fn
+                        ));
+                        destruct_cases.push(format!("    | {i} -> {constructor}"));
+                        continue;
+                    }
 
-        let mut new_params = Vec::new();
-        let mut new_call_args = Vec::new();
-        let mut new_arg_types = Vec::new();
-        for f in record.fields.iter() {
-            let t = self.get_type(&f.r#type);
+                    let types = payload
+                        .iter()
+                        .map(Value::ocaml_type)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    constructors.push(format!("{constructor} of {}", types.join(" * ")));
+
+                    let names = (0..payload.len())
+                        .map(|j| format!("p{j}"))
+                        .collect::<Vec<_>>();
+                    let args = payload
+                        .iter()
+                        .zip(&names)
+                        .map(|(p, name)| p.arg(name))
+                        .collect::<Vec<_>>();
+                    construct_cases.push(format!(
+                        "      | {constructor} ({}) -> Bindings.{} ctx.Context.handle ptr {}",
+                        names.join(", "),
+                        variant.construct_fn,
+                        args.join(" ")
+                    ));
 
-            new_params.push(format!("field{}", f.name));
+                    let mut case = vec![format!("    | {i} ->")];
+                    let mut outputs = Vec::new();
+                    for (p, name) in payload.iter().zip(&names) {
+                        case.push(format!(
+                            "      let {name} = allocate_n ~count:1 {} in",
+                            p.qualified_ctype()?
+                        ));
+                        outputs.push(p.output("t.opaque_ctx", name)?);
+                    }
+                    case.push(format!(
+                        "      let rc = Bindings.{} t.opaque_ctx.Context.handle {} (get_opaque_ptr t) in",
+                        variant.destruct_fn,
+                        names.join(" ")
+                    ));
+                    case.push("      if rc <> 0 then raise (Error (Code rc));".to_string());
+                    case.push("      Context.auto_sync t.opaque_ctx;".to_string());
+                    case.push(format!("      {constructor} ({})", outputs.join(", ")));
+                    destruct_cases.push(case.join("\n"));
+                }
 
-            if type_is_array(&t) {
-                new_call_args.push(format!("(get_ptr field{})", f.name));
-                new_arg_types.push(format!("{}.t", first_uppercase(&t)));
-            } else if type_is_opaque(&t) {
-                new_call_args.push(format!("(get_opaque_ptr field{})", f.name));
-                new_arg_types.push(t.to_string());
-            } else {
-                new_call_args.push(format!("field{}", f.name));
-                new_arg_types.push(t.to_string());
+                let constructors = constructors.join(" | ");
+                render(
+                    config,
+                    &ml,
+                    "sum.ml",
+                    &[
+                        ("constructors", &constructors),
+                        ("construct_cases", &construct_cases.join("\n")),
+                        ("destruct_cases", &destruct_cases.join("\n")),
+                        ("variant_fn", &sum.variant_fn),
+                    ],
+                )?;
+                render(config, &mli, "sum.mli", &[("constructors", &constructors)])?;
             }
+            ir::TypeKind::OpaqueArray(array)
+            | ir::TypeKind::RecordArray(ir::RecordArray { array, .. }) => {
+                let Value::Opaque(elem) = Value::new(lib, &array.elemtype, item)? else {
+                    return Err(Error::InvalidManifest(format!(
+                        "{} has a primitive element type",
+                        item()
+                    )));
+                };
+                let elem_module = module_name(elem)?;
+                let index_args = (0..array.rank)
+                    .map(|i| format!("(Int64.of_int index.({i}))"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                render(
+                    config,
+                    &ml,
+                    "opaque_array.ml",
+                    &[
+                        ("shape_fn", &array.shape_fn),
+                        ("index_fn", &array.index_fn),
+                        ("rank", &array.rank),
+                        ("index_args", &index_args),
+                        ("elem_module", &elem_module),
+                    ],
+                )?;
+                render(
+                    config,
+                    &mli,
+                    "opaque_array.mli",
+                    &[("elem_module", &elem_module)],
+                )?;
+
+                if let ir::TypeKind::RecordArray(record) = &ty.kind {
+                    let FieldParams {
+                        params,
+                        call_args,
+                        arg_types,
+                    } = field_params(lib, ty, &record.fields)?;
+                    render(
+                        config,
+                        &ml,
+                        "record_array.ml",
+                        &[
+                            ("zip_fn", &record.zip_fn),
+                            ("zip_params", &params.join(" ")),
+                            ("zip_call_args", &call_args.join(" ")),
+                        ],
+                    )?;
+                    render(
+                        config,
+                        &mli,
+                        "record_array.mli",
+                        &[("zip_arg_types", &arg_types.join(" -> "))],
+                    )?;
+                    field_projections(lib, config, ty, &record.fields)?;
+                }
+            }
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
         }
 
-        writeln!(
-            config.output_file,
-            include_str!("templates/ocaml/record.ml"),
-            new_params = new_params.join(" "),
-            new_fn = record.new,
-            new_call_args = new_call_args.join(" "),
-        )?;
-
-        writeln!(
-            self.mli_file,
-            include_str!("templates/ocaml/record.mli"),
-            new_arg_types = new_arg_types.join(" -> ")
-        )?;
-
-        for f in record.fields.iter() {
-            let t = self.get_type(&f.r#type);
-            let name = &f.name;
-            let project = &f.project;
-
-            let (out, out_type) = if type_is_opaque(&t) {
-                let call = t.replace(".t", ".of_ptr");
-                (format!("{call} t.opaque_ctx !@out"), t.to_string())
-            } else if type_is_array(&t) {
-                let array = first_uppercase(&t);
-                (
-                    format!("{array}.of_ptr t.opaque_ctx !@out"),
-                    format!("{}.t", first_uppercase(&t)),
-                )
-            } else {
-                ("!@out".to_string(), t.to_string())
-            };
-
-            let alloc_type = if type_is_array(&t) {
-                format!("Bindings.{t}")
-            } else if type_is_opaque(&t) {
-                t
-            } else {
-                self.get_ctype(&f.r#type)
-            };
-
-            writeln!(
-                config.output_file,
-                include_str!("templates/ocaml/record_project.ml"),
-                name = name,
-                s = alloc_type,
-                project = project,
-                out = out
-            )?;
-            writeln!(
-                self.mli_file,
-                include_str!("templates/ocaml/record_project.mli"),
-                name = name,
-                out_type = out_type
-            )?;
-        }
-
-        writeln!(config.output_file, "end\n")?;
-        writeln!(self.mli_file, "end\n")?;
+        writeln!(config.output(&ml), "end\n")?;
+        writeln!(config.output(&mli), "end\n")?;
 
         Ok(())
     }
 
     fn entry(
         &mut self,
-        _pkg: &Package,
+        lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        entry: &manifest::Entry,
+        entry: &ir::Entry,
     ) -> Result<(), Error> {
+        let (ml, mli) = files(config);
+        let item = || format!("entry point {:?}", entry.futhark_name);
         let mut arg_types = Vec::new();
         let mut return_type = Vec::new();
         let mut entry_params = Vec::new();
@@ -509,90 +849,126 @@ impl Generate for OCaml {
         let mut out_return = Vec::new();
         let mut out_decl = Vec::new();
 
-        for (i, out) in entry.outputs.iter().enumerate() {
-            let t = self.get_type(&out.r#type);
-            let ct = self.get_ctype(&out.r#type);
-
-            let mut ocaml_elemtype = t.clone();
-
-            // Transform into `Module.t`
-            if ocaml_elemtype.contains("array_") {
-                ocaml_elemtype = first_uppercase(&ocaml_elemtype) + ".t"
-            }
-
-            return_type.push(ocaml_elemtype);
-
-            let i = if entry.outputs.len() == 1 {
-                String::new()
-            } else {
-                i.to_string()
-            };
-
-            if type_is_array(&t) || type_is_opaque(&t) {
-                out_decl.push(format!("  let out{i}_ptr = allocate (ptr void) null in"));
-            } else {
-                out_decl.push(format!("  let out{i}_ptr = allocate_n {ct} ~count:1 in"));
-            }
-
-            call_args.push(format!("out{i}_ptr"));
-
-            if type_is_array(&t) {
-                let m = first_uppercase(&t);
-                out_return.push(format!("({m}.of_ptr ctx !@out{i}_ptr)"));
-            } else if type_is_opaque(&t) {
-                let m = first_uppercase(&t);
-                let m = m.strip_suffix(".t").unwrap_or(&m);
-                out_return.push(format!("({m}.of_ptr ctx !@out{i}_ptr)"));
-            } else {
-                out_return.push(format!("!@out{i}_ptr"));
+        for (i, output) in entry.outputs.iter().enumerate() {
+            let value = Value::new(lib, &output.ty, item)?;
+            let name = format!("out{i}");
+            match value {
+                Value::Primitive(ctype, _) => {
+                    out_decl.push(format!("  let {name} = allocate_n {ctype} ~count:1 in"))
+                }
+                _ => out_decl.push(format!("  let {name} = allocate (ptr void) null in")),
             }
+            return_type.push(value.ocaml_type()?);
+            out_return.push(value.output("ctx", &name)?);
+            call_args.push(name);
         }
 
         for (i, input) in entry.inputs.iter().enumerate() {
-            entry_params.push(format!("input{i}"));
-
-            let mut ocaml_elemtype = self.get_type(&input.r#type);
-
-            // Transform into `Module.t`
-            if type_is_array(&ocaml_elemtype) {
-                ocaml_elemtype = first_uppercase(&ocaml_elemtype) + ".t"
-            }
-
-            arg_types.push(ocaml_elemtype);
-
-            let t = self.get_type(&input.r#type);
-            if type_is_array(&t) {
-                call_args.push(format!("(get_ptr input{i})"));
-            } else if type_is_opaque(&t) {
-                call_args.push(format!("(get_opaque_ptr input{i})"));
-            } else {
-                call_args.push(format!("input{i}"));
-            }
+            let value = Value::new(lib, &input.ty, item)?;
+            let name = format!("input{i}");
+            arg_types.push(value.ocaml_type()?);
+            call_args.push(value.arg(&name));
+            entry_params.push(name);
         }
 
-        writeln!(
-            config.output_file,
-            include_str!("templates/ocaml/entry.ml"),
-            name = name,
-            entry_params = entry_params.join(" "),
-            out_decl = out_decl.join("\n"),
-            call_args = call_args.join(" "),
-            out_return = out_return.join(", ")
-        )?;
-
+        if entry_params.is_empty() {
+            entry_params.push("()".to_string());
+            arg_types.push("unit".to_string());
+        }
         let return_type = if return_type.is_empty() {
             "unit".to_string()
         } else {
             return_type.join(" * ")
         };
-        writeln!(
-            self.mli_file,
-            include_str!("templates/ocaml/entry.mli"),
-            name = name,
-            arg_types = arg_types.join(" -> "),
-            return_type = return_type,
+
+        let name = ocaml_ident(&entry.name, RESERVED_VALUES, item)?;
+        render(
+            config,
+            &ml,
+            "entry.ml",
+            &[
+                ("name", &name),
+                ("entry_fn", &entry.cfun),
+                ("entry_params", &entry_params.join(" ")),
+                ("out_decl", &out_decl.join("\n")),
+                ("call_args", &call_args.join(" ")),
+                ("out_return", &out_return.join(", ")),
+            ],
         )?;
+        render(
+            config,
+            &mli,
+            "entry.mli",
+            &[
+                ("name", &name),
+                ("arg_types", &arg_types.join(" -> ")),
+                ("return_type", &return_type),
+            ],
+        )?;
+
+        for alias in &entry.aliases {
+            let alias = ocaml_ident(alias, RESERVED_VALUES, item)?;
+            let deprecated = format!("[@@ocaml.deprecated \"renamed to {name}\"]");
+            writeln!(config.output(&ml), "let {alias} = {name} {deprecated}\n")?;
+            writeln!(
+                config.output(&mli),
+                "val {alias}: Context.t -> {} -> ({return_type}) {deprecated}\n",
+                arg_types.join(" -> ")
+            )?;
+        }
 
         Ok(())
     }
+
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        Ok(format!("(* Generated by futhark-bindgen *)\n\n{code}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::tests::{aliasing_namer, all_types};
+
+    #[test]
+    fn generate_all_types() {
+        let mut config = Config::with_writer("lib.ml", std::io::sink(), aliasing_namer());
+        let files = OCaml.generate_files(&all_types(), &mut config).unwrap();
+        assert_eq!(files.len(), 2);
+        for (file, items) in [
+            (
+                "lib.ml",
+                [
+                    "let class_ ctx input0 =",
+                    "let make = make_point [@@ocaml.deprecated \"renamed to make_point\"]",
+                    "module Option_ = struct",
+                    "module Point = struct",
+                    "module Arr_point_1d = struct",
+                ]
+                .as_slice(),
+            ),
+            (
+                "lib.mli",
+                [
+                    "val class_: Context.t -> int32 -> (int32)",
+                    "val type_: Context.t -> int32 -> (int32)",
+                    "val end_: Context.t -> int32 -> (int32)",
+                    "val make: Context.t -> Array_f32_1d.t -> int64 -> (Point.t) [@@ocaml.deprecated \"renamed to make_point\"]",
+                    "module Option_ : sig",
+                    "  type value = None | Some of int64",
+                    "  val get: t -> value",
+                    "module Point : sig",
+                    "  val get_xs: t -> Array_f32_1d.t",
+                    "  val zip: Context.t -> Array_f32_2d.t -> Array_f64_1d.t -> t",
+                    "module Array_f32_1d: sig",
+                ]
+                .as_slice(),
+            ),
+        ] {
+            let code = &files[Path::new(file)];
+            for item in items {
+                assert!(code.contains(item), "{file}: missing {item:?}");
+            }
+        }
+    }
 }
@@ -0,0 +1,331 @@
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::*;
+
+/// Version of the JSON protocol spoken with plugins, sent as `protocol` in every request
+pub const PLUGIN_PROTOCOL: u32 = 1;
+
+/// Runs an external generator, `futhark-bindgen-gen-<language>`, to support languages that
+/// aren't built into futhark-bindgen
+///
+/// The resolved and filtered `ir::Library` is written to the plugin's stdin as a single JSON
+/// object along with the raw manifest, the plugin replies on stdout with the files to write:
+/// `{"files": {"path": "contents"}}`. Paths are relative to the directory containing the
+/// output path and may not leave it. Anything written to stderr is passed through
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    language: String,
+    executable: PathBuf,
+}
+
+/// Request written to the plugin's stdin, this is part of the public plugin protocol, version
+/// `PLUGIN_PROTOCOL`. Fields are only added in new versions, changing or removing one
+/// increments the version
+#[derive(serde::Serialize)]
+struct Request<'a> {
+    /// Protocol version, `PLUGIN_PROTOCOL`
+    protocol: u32,
+
+    /// Language name, the plugin executable is `futhark-bindgen-gen-<language>`
+    language: &'a str,
+
+    /// Output path given by the user, returned files are relative to its directory
+    output: &'a Path,
+
+    /// Manifest written by the Futhark compiler
+    manifest: &'a Manifest,
+
+    /// Resolved library after naming and filtering, see `ir::Library`
+    library: &'a ir::Library,
+}
+
+/// Response read from the plugin's stdout
+#[derive(serde::Deserialize)]
+struct Response {
+    /// Generated files indexed by path, relative to the directory containing `output`
+    files: BTreeMap<String, String>,
+}
+
+impl Plugin {
+    /// Name of the executable implementing `language`
+    pub fn executable_name(language: &str) -> String {
+        format!(
+            "futhark-bindgen-gen-{language}{}",
+            std::env::consts::EXE_SUFFIX
+        )
+    }
+
+    /// Create a plugin for `language`, the executable is looked up on `PATH` when generating
+    pub fn new(language: impl Into<String>) -> Plugin {
+        let language = language.into();
+        let executable = PathBuf::from(Plugin::executable_name(&language));
+        Plugin {
+            language,
+            executable,
+        }
+    }
+
+    /// Find the plugin for `language` on `PATH`, returns `None` if it isn't installed
+    pub fn find(language: &str) -> Option<Plugin> {
+        let name = Plugin::executable_name(language);
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(&name))
+            .find(|exe| exe.is_file())
+            .map(|exe| Plugin::new(language).with_executable(exe))
+    }
+
+    /// Languages of the plugins found on `PATH`, sorted by name
+    pub fn installed() -> Vec<String> {
+        let prefix = Plugin::executable_name("");
+        let prefix = prefix
+            .strip_suffix(std::env::consts::EXE_SUFFIX)
+            .unwrap_or(&prefix);
+        let mut languages = std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                let language = name
+                    .strip_prefix(prefix)?
+                    .strip_suffix(std::env::consts::EXE_SUFFIX)?;
+                let is_file = entry.path().is_file();
+                (is_file && !language.is_empty()).then(|| language.to_string())
+            })
+            .collect::<Vec<_>>();
+        languages.sort();
+        languages.dedup();
+        languages
+    }
+
+    /// Run `executable` instead of looking up the plugin on `PATH`
+    pub fn with_executable(mut self, executable: impl AsRef<Path>) -> Plugin {
+        self.executable = executable.as_ref().to_path_buf();
+        self
+    }
+
+    /// Language implemented by the plugin
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::Plugin {
+            name: self.executable.display().to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Send `request` to the plugin and decode its response
+    fn run(&self, request: &Request) -> Result<Response, Error> {
+        let input = serde_json::to_vec(request)?;
+        let mut child = Command::new(&self.executable)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| self.error(format!("unable to run plugin: {e}")))?;
+
+        // Write from another thread so a plugin that starts replying before reading all of
+        // its input can't deadlock
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let (written, output) = std::thread::scope(|s| {
+            let writer = s.spawn(move || stdin.write_all(&input));
+            let output = child.wait_with_output();
+            (writer.join().expect("writer thread panicked"), output)
+        });
+        let output = output?;
+        if !output.status.success() {
+            return Err(self.error(format!("plugin failed: {}", output.status)));
+        }
+        written?;
+
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| self.error(format!("invalid response: {e}")))
+    }
+}
+
+/// Check that `path` stays within the output directory
+fn is_relative(path: &Path) -> bool {
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        && path.components().any(|c| matches!(c, Component::Normal(_)))
+}
+
+impl Generate for Plugin {
+    fn generate_files(
+        &mut self,
+        pkg: &Package,
+        config: &mut Config,
+    ) -> Result<BTreeMap<PathBuf, String>, Error> {
+        config.files.clear();
+        let mut lib = ir::Library::new(pkg, config.namer.as_mut())?;
+        lib.filter(&config.filter)?;
+
+        let response = self.run(&Request {
+            protocol: PLUGIN_PROTOCOL,
+            language: &self.language,
+            output: &config.output_path,
+            manifest: &pkg.manifest,
+            library: &lib,
+        })?;
+
+        let mut files = BTreeMap::new();
+        for (path, code) in response.files {
+            if !is_relative(Path::new(&path)) {
+                return Err(self.error(format!("invalid output path {path:?}")));
+            }
+            files.insert(config.output_file(&path), code);
+        }
+        Ok(files)
+    }
+
+    fn bindings(&mut self, _lib: &ir::Library, _config: &mut Config) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn array_type(
+        &mut self,
+        _lib: &ir::Library,
+        _config: &mut Config,
+        _ty: &ir::Type,
+        _array: &ir::Array,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn opaque_type(
+        &mut self,
+        _lib: &ir::Library,
+        _config: &mut Config,
+        _ty: &ir::Type,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        _lib: &ir::Library,
+        _config: &mut Config,
+        _entry: &ir::Entry,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::ir::tests::{entry, package};
+
+    /// Write a plugin that runs `script` to a new directory, the directory is returned along
+    /// with the plugin
+    fn stub(name: &str, script: &str) -> (PathBuf, Plugin) {
+        let dir = std::env::temp_dir().join(format!(
+            "futhark-bindgen-plugin-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join(Plugin::executable_name("test"));
+        std::fs::write(&exe, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+        (dir, Plugin::new("test").with_executable(exe))
+    }
+
+    fn generate(plugin: &mut Plugin, dir: &Path) -> Result<BTreeMap<PathBuf, String>, Error> {
+        let pkg = package(
+            json!({"add": entry("add", &[("x", "i32")], &["i32"])}),
+            json!({}),
+        );
+        let mut config = Config::new(dir.join("lib.test"), DefaultNamer::default());
+        plugin.generate_files(&pkg, &mut config)
+    }
+
+    fn plugin_error(result: Result<BTreeMap<PathBuf, String>, Error>) -> String {
+        match result {
+            Err(Error::Plugin { message, .. }) => message,
+            other => panic!("expected a plugin error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn request_and_response() {
+        let (dir, mut plugin) = stub(
+            "request",
+            "cat > \"$(dirname \"$0\")/request.json\"\necho '{\"files\": {\"lib.test\": \"code\"}}'",
+        );
+        let files = generate(&mut plugin, &dir).unwrap();
+        assert_eq!(
+            files.into_iter().collect::<Vec<_>>(),
+            [(dir.join("lib.test"), "code".to_string())]
+        );
+
+        let request: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("request.json")).unwrap())
+                .unwrap();
+        let mut keys = request.as_object().unwrap().keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(
+            keys,
+            ["language", "library", "manifest", "output", "protocol"]
+        );
+        assert_eq!(request["protocol"], PLUGIN_PROTOCOL);
+        assert_eq!(request["language"], "test");
+        assert_eq!(request["output"], dir.join("lib.test").to_str().unwrap());
+        assert_eq!(request["manifest"]["backend"], "c");
+        assert_eq!(request["library"]["entries"]["add"]["name"], "add");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn paths_stay_in_output_directory() {
+        for path in ["lib.test", "./lib.test", "src/lib.test"] {
+            assert!(is_relative(Path::new(path)), "{path}");
+        }
+        for path in [
+            "",
+            ".",
+            "..",
+            "../lib.test",
+            "src/../../lib.test",
+            "/tmp/lib.test",
+        ] {
+            assert!(!is_relative(Path::new(path)), "{path}");
+        }
+
+        let (dir, mut plugin) = stub(
+            "paths",
+            "cat > /dev/null\necho '{\"files\": {\"../lib.test\": \"\"}}'",
+        );
+        assert!(plugin_error(generate(&mut plugin, &dir)).contains("invalid output path"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failures_are_reported() {
+        let (dir, mut plugin) = stub("exit", "cat > /dev/null\nexit 3");
+        assert!(plugin_error(generate(&mut plugin, &dir)).contains("plugin failed"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let (dir, mut plugin) = stub("json", "cat > /dev/null\necho '{\"files\": '");
+        assert!(plugin_error(generate(&mut plugin, &dir)).contains("invalid response"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut plugin =
+            Plugin::new("test").with_executable("/nonexistent/futhark-bindgen-gen-test");
+        assert!(
+            plugin_error(generate(&mut plugin, Path::new("."))).contains("unable to run plugin")
+        );
+    }
+}
@@ -0,0 +1,745 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::generate::IdentRules;
+use crate::ir::ElemType;
+use crate::*;
+
+/// Python codegen, writes a module using `ctypes` and NumPy that loads the shared library
+/// built by `Package::build_shared`
+#[derive(Default)]
+pub struct Python;
+
+/// Module level names defined by `templates/python/context.py`, these can't be used by
+/// generated classes and entry points
+const RESERVED_GLOBALS: &[&str] = &[
+    "BACKEND",
+    "Context",
+    "DEVICE_MEMORY",
+    "Error",
+    "ctypes",
+    "np",
+    "os",
+    "sys",
+    "warnings",
+];
+
+/// Members of the generated classes, these can't be used for field accessors and sum type
+/// constructors
+const RESERVED_MEMBERS: &[&str] = &[
+    "dtype", "free", "numpy", "rank", "restore", "shape", "store", "variant", "variants",
+];
+
+fn python_ident(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    IdentRules::PYTHON.escape(name, reserved, item)
+}
+
+/// Returns the `ctypes` type and NumPy dtype used for a primitive value
+fn primitive_type(t: ElemType) -> (&'static str, &'static str) {
+    match t {
+        ElemType::I8 => ("ctypes.c_int8", "np.int8"),
+        ElemType::I16 => ("ctypes.c_int16", "np.int16"),
+        ElemType::I32 => ("ctypes.c_int32", "np.int32"),
+        ElemType::I64 => ("ctypes.c_int64", "np.int64"),
+        ElemType::U8 => ("ctypes.c_uint8", "np.uint8"),
+        ElemType::U16 => ("ctypes.c_uint16", "np.uint16"),
+        ElemType::U32 => ("ctypes.c_uint32", "np.uint32"),
+        ElemType::U64 => ("ctypes.c_uint64", "np.uint64"),
+        // The C API stores half precision values as their bits
+        ElemType::F16 => ("ctypes.c_uint16", "np.float16"),
+        ElemType::F32 => ("ctypes.c_float", "np.float32"),
+        ElemType::F64 => ("ctypes.c_double", "np.float64"),
+        ElemType::Bool => ("ctypes.c_bool", "np.bool_"),
+    }
+}
+
+/// Name of the class generated for a type
+fn class_name(ty: &ir::Type) -> Result<String, Error> {
+    python_ident(&ty.name, RESERVED_GLOBALS, || {
+        format!("type {:?}", ty.futhark_name)
+    })
+}
+
+/// Render documentation as the body of a docstring indented by `indent`
+fn docstring(doc: &str, indent: &str) -> String {
+    doc.replace('\\', "\\\\")
+        .replace("\"\"\"", "\\\"\\\"\\\"")
+        .lines()
+        .collect::<Vec<_>>()
+        .join(&format!("\n{indent}"))
+}
+
+/// Get the documentation for a type, using the Futhark name if the `Namer` didn't provide
+/// any
+fn type_doc(ty: &ir::Type) -> String {
+    docstring(
+        ty.doc
+            .as_deref()
+            .unwrap_or(&format!("Futhark type `{}`", ty.futhark_name)),
+        "    ",
+    )
+}
+
+/// How a value is passed to the C API
+enum Value<'a> {
+    Primitive(ElemType),
+    Named(&'a ir::Type),
+}
+
+impl<'a> Value<'a> {
+    fn new(lib: &'a ir::Library, ty: &ir::TypeRef) -> Result<Self, Error> {
+        match (ty, lib.get(ty)) {
+            (ir::TypeRef::Primitive(t), _) => Ok(Value::Primitive(*t)),
+            (_, Some(t)) => Ok(Value::Named(t)),
+            (ir::TypeRef::Named(name), None) => {
+                Err(Error::InvalidManifest(format!("unknown type {name:?}")))
+            }
+        }
+    }
+
+    /// `ctypes` type used in function declarations
+    fn ctype(&self) -> &'static str {
+        match self {
+            Value::Primitive(t) => primitive_type(*t).0,
+            Value::Named(_) => "ctypes.c_void_p",
+        }
+    }
+
+    /// Expression passing `name` to a C function
+    fn arg(&self, name: &str) -> String {
+        match self {
+            Value::Primitive(ElemType::F16) => format!("_f16_bits({name})"),
+            Value::Primitive(_) => name.to_string(),
+            Value::Named(_) => format!("{name}._get()"),
+        }
+    }
+
+    /// Expression wrapping the value stored in the `ctypes` output variable `name`
+    fn output(&self, ctx: &str, name: &str) -> Result<String, Error> {
+        match self {
+            Value::Primitive(ElemType::F16) => Ok(format!("_f16_value({name}.value)")),
+            Value::Primitive(_) => Ok(format!("{name}.value")),
+            Value::Named(t) => Ok(format!("{}._from_ptr({ctx}, {name}.value)", class_name(t)?)),
+        }
+    }
+}
+
+/// Get the module file, relative to the output directory
+fn file(config: &Config) -> PathBuf {
+    PathBuf::from(config.output_path.file_name().unwrap_or_default())
+}
+
+/// Render a template from `templates/python` and append it to the module
+fn render(config: &mut Config, name: &str, args: &[(&str, &dyn Display)]) -> Result<(), Error> {
+    let code = config.templates.render("python", name, args)?;
+    writeln!(config.output(file(config)), "{code}")?;
+    Ok(())
+}
+
+/// Declaration of a C function, setting its `restype` and `argtypes`
+fn declare(name: &str, restype: &str, args: &[&str]) -> String {
+    let mut decl = format!("_fn(\"{name}\", {restype}");
+    for arg in args {
+        decl.push_str(", ");
+        decl.push_str(arg);
+    }
+    decl.push(')');
+    decl
+}
+
+const PTR: &str = "ctypes.c_void_p";
+const OUT_PTR: &str = "ctypes.POINTER(ctypes.c_void_p)";
+const INT: &str = "ctypes.c_int";
+const SHAPE: &str = "ctypes.POINTER(ctypes.c_int64)";
+const INDEX: &str = "ctypes.c_int64";
+
+/// Declarations for the C functions used by a type
+fn type_declarations(lib: &ir::Library, ty: &ir::Type, out: &mut Vec<String>) -> Result<(), Error> {
+    let index = |rank: usize| vec![INDEX; rank];
+    let out_type = |value: &Value| format!("ctypes.POINTER({})", value.ctype());
+
+    if let ir::TypeKind::Array(a) = &ty.kind {
+        let (ctype, _) = primitive_type(a.elemtype);
+        let elem_ptr = format!("ctypes.POINTER({ctype})");
+        out.push(declare(
+            &a.ops.new,
+            PTR,
+            &[&[PTR, PTR][..], &index(a.rank)].concat(),
+        ));
+        out.push(declare(&a.ops.free, INT, &[PTR, PTR]));
+        out.push(declare(&a.ops.values, INT, &[PTR, PTR, PTR]));
+        out.push(declare(&a.ops.shape, SHAPE, &[PTR, PTR]));
+        out.push(declare(
+            &a.ops.index,
+            INT,
+            &[&[PTR, elem_ptr.as_str(), PTR][..], &index(a.rank)].concat(),
+        ));
+        return Ok(());
+    }
+
+    let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+    out.push(declare(&ops.free, INT, &[PTR, PTR]));
+    out.push(declare(
+        &ops.store,
+        INT,
+        &[PTR, PTR, OUT_PTR, "ctypes.POINTER(ctypes.c_size_t)"],
+    ));
+    out.push(declare(&ops.restore, PTR, &[PTR, "ctypes.c_char_p"]));
+
+    let fields = |fields: &[ir::Field], new_fn: &str, out: &mut Vec<String>| {
+        let mut ctypes = Vec::new();
+        for field in fields {
+            let value = Value::new(lib, &field.ty)?;
+            out.push(declare(
+                &field.project_fn,
+                INT,
+                &[PTR, &out_type(&value), PTR],
+            ));
+            ctypes.push(value.ctype());
+        }
+        out.push(declare(
+            new_fn,
+            INT,
+            &[&[PTR, OUT_PTR][..], &ctypes].concat(),
+        ));
+        Ok::<_, Error>(())
+    };
+
+    let opaque_array = |a: &ir::OpaqueArray, out: &mut Vec<String>| {
+        out.push(declare(&a.shape_fn, SHAPE, &[PTR, PTR]));
+        out.push(declare(
+            &a.index_fn,
+            INT,
+            &[&[PTR, OUT_PTR, PTR][..], &index(a.rank)].concat(),
+        ));
+    };
+
+    match &ty.kind {
+        ir::TypeKind::Record(r) => fields(&r.fields, &r.new_fn, out)?,
+        ir::TypeKind::Sum(s) => {
+            out.push(declare(&s.variant_fn, INT, &[PTR, PTR]));
+            for v in &s.variants {
+                let payload = v
+                    .payload
+                    .iter()
+                    .map(|p| Value::new(lib, p))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let ctypes = payload.iter().map(Value::ctype).collect::<Vec<_>>();
+                out.push(declare(
+                    &v.construct_fn,
+                    INT,
+                    &[&[PTR, OUT_PTR][..], &ctypes].concat(),
+                ));
+                let out_types = payload.iter().map(out_type).collect::<Vec<_>>();
+                let out_types = out_types.iter().map(String::as_str).collect::<Vec<_>>();
+                out.push(declare(
+                    &v.destruct_fn,
+                    INT,
+                    &[&[PTR][..], &out_types, &[PTR]].concat(),
+                ));
+            }
+        }
+        ir::TypeKind::OpaqueArray(a) => opaque_array(a, out),
+        ir::TypeKind::RecordArray(r) => {
+            opaque_array(&r.array, out);
+            fields(&r.fields, &r.zip_fn, out)?;
+        }
+        ir::TypeKind::Array(_) => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Parameters of a constructor taking one value per field
+struct FieldParams {
+    params: Vec<String>,
+    call_args: Vec<String>,
+}
+
+fn field_params(
+    lib: &ir::Library,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+) -> Result<FieldParams, Error> {
+    let mut params = Vec::new();
+    let mut call_args = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let reserved = [
+            &["self", "ctx", "out"][..],
+            &params.iter().map(String::as_str).collect::<Vec<_>>(),
+        ]
+        .concat();
+        let name = python_ident(&field.name, &reserved, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        call_args.push(value.arg(&name));
+        params.push(name);
+    }
+    Ok(FieldParams { params, call_args })
+}
+
+/// Render field properties for records and arrays of records
+fn field_projections(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+) -> Result<Vec<String>, Error> {
+    let mut members = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let project_name = python_ident(&field.project_name, RESERVED_MEMBERS, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        members.push(config.templates.render(
+            "python",
+            "record_project.py",
+            &[
+                ("field_name", &field.futhark_name),
+                ("project_name", &project_name),
+                ("project_fn", &field.project_fn),
+                ("out_type", &value.ctype()),
+                ("out_return", &value.output("self._ctx", "out")?),
+            ],
+        )?);
+    }
+    Ok(members)
+}
+
+/// Members of sum types: the variant names, constructors and payload getters
+fn sum_members(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    sum: &ir::Sum,
+) -> Result<Vec<String>, Error> {
+    let mut variants = Vec::new();
+    let mut members = Vec::new();
+    for variant in &sum.variants {
+        let name = python_ident(&variant.name, RESERVED_MEMBERS, || {
+            format!("variant {:?} of type {:?}", variant.name, ty.futhark_name)
+        })?;
+        let payload = variant
+            .payload
+            .iter()
+            .map(|p| Value::new(lib, p))
+            .collect::<Result<Vec<_>, _>>()?;
+        let names = (0..payload.len())
+            .map(|i| format!("p{i}"))
+            .collect::<Vec<_>>();
+
+        let mut construct_params = String::new();
+        let mut construct_args = String::new();
+        for (p, name) in payload.iter().zip(&names) {
+            construct_params.push_str(&format!(", {name}"));
+            construct_args.push_str(&format!(", {}", p.arg(name)));
+        }
+        members.push(config.templates.render(
+            "python",
+            "sum_variant.py",
+            &[
+                ("variant_name", &variant.name),
+                ("name", &name),
+                ("construct_fn", &variant.construct_fn),
+                ("construct_params", &construct_params),
+                ("construct_args", &construct_args),
+            ],
+        )?);
+
+        if !payload.is_empty() {
+            let destruct_decls = payload
+                .iter()
+                .zip(&names)
+                .map(|(p, name)| format!("        {name} = {}()", p.ctype()))
+                .collect::<Vec<_>>();
+            let destruct_args = names
+                .iter()
+                .map(|name| format!("ctypes.byref({name})"))
+                .collect::<Vec<_>>();
+            let destruct_return = payload
+                .iter()
+                .zip(&names)
+                .map(|(p, name)| p.output("self._ctx", name))
+                .collect::<Result<Vec<_>, _>>()?;
+            members.push(config.templates.render(
+                "python",
+                "sum_payload.py",
+                &[
+                    ("variant_name", &variant.name),
+                    ("name", &name),
+                    ("destruct_fn", &variant.destruct_fn),
+                    ("destruct_decls", &destruct_decls.join("\n")),
+                    ("destruct_args", &destruct_args.join(", ")),
+                    ("destruct_return", &destruct_return.join(", ")),
+                ],
+            )?);
+        }
+        variants.push(format!("{:?}", variant.name));
+    }
+
+    // A single element tuple needs a trailing comma
+    let variants = match variants.len() {
+        1 => format!("{},", variants[0]),
+        _ => variants.join(", "),
+    };
+    let sum = config.templates.render(
+        "python",
+        "sum.py",
+        &[("variants", &variants), ("variant_fn", &sum.variant_fn)],
+    )?;
+    members.insert(0, sum);
+    Ok(members)
+}
+
+/// Names of the parameters of an entry point, using the Futhark parameter names
+fn entry_params(entry: &ir::Entry) -> Vec<String> {
+    let mut reserved = vec!["ctx".to_string()];
+    reserved.extend((0..entry.outputs.len()).map(|i| format!("out{i}")));
+    reserved.extend(RESERVED_GLOBALS.iter().map(|s| s.to_string()));
+    let mut params = Vec::new();
+    for (i, input) in entry.inputs.iter().enumerate() {
+        let r = reserved.iter().map(String::as_str).collect::<Vec<_>>();
+        let name = python_ident(&input.name, &r, || {
+            format!(
+                "input {:?} of entry point {:?}",
+                input.name, entry.futhark_name
+            )
+        })
+        .unwrap_or_else(|_| format!("in{i}"));
+        reserved.push(name.clone());
+        params.push(name);
+    }
+    params
+}
+
+impl Generate for Python {
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        let caps = lib.backend.capabilities();
+
+        let mut generated_functions = Vec::new();
+        let mut extra_params = Vec::new();
+        let mut configure_backend = Vec::new();
+        for option in caps.config_options {
+            let (arg, param, line) = match option {
+                ConfigOption::NumThreads => (
+                    "ctypes.c_int",
+                    ", num_threads=0",
+                    "        _lib.futhark_context_config_set_num_threads(self._config, num_threads)",
+                ),
+                ConfigOption::Device => (
+                    "ctypes.c_char_p",
+                    ", device=None",
+                    "        if device is not None:\n            _lib.futhark_context_config_set_device(self._config, device.encode())",
+                ),
+                ConfigOption::Platform => (
+                    "ctypes.c_char_p",
+                    ", platform=None",
+                    "        if platform is not None:\n            _lib.futhark_context_config_set_platform(self._config, platform.encode())",
+                ),
+            };
+            generated_functions.push(declare(option.c_function(), "None", &[PTR, arg]));
+            extra_params.push(param);
+            configure_backend.push(line);
+        }
+
+        for ty in lib.types_in_order() {
+            type_declarations(lib, ty, &mut generated_functions)?;
+        }
+
+        for entry in lib.entries.values() {
+            let mut args = vec![PTR.to_string()];
+            for output in &entry.outputs {
+                args.push(format!(
+                    "ctypes.POINTER({})",
+                    Value::new(lib, &output.ty)?.ctype()
+                ));
+            }
+            for input in &entry.inputs {
+                args.push(Value::new(lib, &input.ty)?.ctype().to_string());
+            }
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            generated_functions.push(declare(&entry.cfun, INT, &args));
+        }
+
+        let library = lib
+            .c_file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let library_env = format!(
+            "FUTHARK_{}_LIBRARY",
+            library
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                })
+                .collect::<String>()
+        );
+        render(
+            config,
+            "context.py",
+            &[
+                ("backend", &lib.backend.to_str()),
+                (
+                    "device_memory",
+                    &if caps.memory == Memory::Device {
+                        "True"
+                    } else {
+                        "False"
+                    },
+                ),
+                ("library", &library),
+                ("library_env", &library_env),
+                ("generated_functions", &generated_functions.join("\n")),
+                ("extra_params", &extra_params.concat()),
+                ("configure_backend", &configure_backend.join("\n")),
+            ],
+        )
+    }
+
+    fn array_type(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+        a: &ir::Array,
+    ) -> Result<(), Error> {
+        let (ctype, dtype) = primitive_type(a.elemtype);
+        render(
+            config,
+            "array.py",
+            &[
+                ("doc", &type_doc(ty)),
+                ("class_name", &class_name(ty)?),
+                ("rank", &a.rank),
+                ("dtype", &dtype),
+                ("ctype", &ctype),
+                ("new_fn", &a.ops.new),
+                ("free_fn", &a.ops.free),
+                ("values_fn", &a.ops.values),
+                ("shape_fn", &a.ops.shape),
+                ("index_fn", &a.ops.index),
+            ],
+        )
+    }
+
+    fn opaque_type(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let item = || format!("type {:?}", ty.futhark_name);
+        let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+
+        let mut members = Vec::new();
+        match &ty.kind {
+            ir::TypeKind::Record(record) => {
+                let FieldParams { params, call_args } = field_params(lib, ty, &record.fields)?;
+                members.push(config.templates.render(
+                    "python",
+                    "record.py",
+                    &[
+                        ("new_fn", &record.new_fn),
+                        ("new_params", &params.join(", ")),
+                        ("new_call_args", &call_args.join(", ")),
+                    ],
+                )?);
+                members.extend(field_projections(lib, config, ty, &record.fields)?);
+            }
+            ir::TypeKind::Sum(sum) => members.extend(sum_members(lib, config, ty, sum)?),
+            ir::TypeKind::OpaqueArray(array)
+            | ir::TypeKind::RecordArray(ir::RecordArray { array, .. }) => {
+                let Value::Named(elem) = Value::new(lib, &array.elemtype)? else {
+                    return Err(Error::InvalidManifest(format!(
+                        "{} has a primitive element type",
+                        item()
+                    )));
+                };
+                members.push(config.templates.render(
+                    "python",
+                    "opaque_array.py",
+                    &[
+                        ("rank", &array.rank),
+                        ("shape_fn", &array.shape_fn),
+                        ("index_fn", &array.index_fn),
+                        ("elem_class", &class_name(elem)?),
+                    ],
+                )?);
+
+                if let ir::TypeKind::RecordArray(record) = &ty.kind {
+                    let FieldParams { params, call_args } = field_params(lib, ty, &record.fields)?;
+                    members.push(config.templates.render(
+                        "python",
+                        "record_array.py",
+                        &[
+                            ("zip_fn", &record.zip_fn),
+                            ("zip_params", &params.join(", ")),
+                            ("zip_call_args", &call_args.join(", ")),
+                        ],
+                    )?);
+                    members.extend(field_projections(lib, config, ty, &record.fields)?);
+                }
+            }
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
+        }
+
+        render(
+            config,
+            "opaque.py",
+            &[
+                ("doc", &type_doc(ty)),
+                ("class_name", &class_name(ty)?),
+                ("free_fn", &ops.free),
+                ("store_fn", &ops.store),
+                ("restore_fn", &ops.restore),
+                ("members", &members.concat()),
+            ],
+        )
+    }
+
+    fn type_aliases(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let class_name = class_name(ty)?;
+        for alias in &ty.aliases {
+            let alias = python_ident(alias, RESERVED_GLOBALS, || {
+                format!("type {:?}", ty.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "\n# Deprecated alias for {class_name}\n{alias} = {class_name}\n"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        entry: &ir::Entry,
+    ) -> Result<(), Error> {
+        let name = python_ident(&entry.name, RESERVED_GLOBALS, || {
+            format!("entry point {:?}", entry.futhark_name)
+        })?;
+        let params = entry_params(entry);
+        let mut out_decl = Vec::new();
+        let mut call_args = Vec::new();
+        let mut entry_return = Vec::new();
+
+        for (i, output) in entry.outputs.iter().enumerate() {
+            let value = Value::new(lib, &output.ty)?;
+            let name = format!("out{i}");
+            out_decl.push(format!("    {name} = {}()", value.ctype()));
+            call_args.push(format!("ctypes.byref({name})"));
+            entry_return.push(value.output("ctx", &name)?);
+        }
+
+        for (input, param) in entry.inputs.iter().zip(&params) {
+            call_args.push(Value::new(lib, &input.ty)?.arg(param));
+        }
+
+        let doc = docstring(
+            entry
+                .doc
+                .as_deref()
+                .unwrap_or(&format!("Entry point: {}", entry.futhark_name)),
+            "    ",
+        );
+        render(
+            config,
+            "entry.py",
+            &[
+                ("name", &name),
+                ("doc", &doc),
+                ("entry_fn", &entry.cfun),
+                ("entry_params", &params.join(", ")),
+                ("out_decl", &out_decl.join("\n")),
+                ("call_args", &call_args.join(", ")),
+                ("entry_return", &entry_return.join(", ")),
+            ],
+        )?;
+
+        for alias in &entry.aliases {
+            let alias = python_ident(alias, RESERVED_GLOBALS, || {
+                format!("entry point {:?}", entry.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "\ndef {alias}(*args, **kwargs):\n    \"\"\"Deprecated alias for `{name}`\"\"\"\n    warnings.warn(\"{alias} was renamed to {name}\", DeprecationWarning, stacklevel=2)\n    return {name}(*args, **kwargs)\n"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        Ok(format!("# Generated by futhark-bindgen\n\n{code}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::{Command, Stdio};
+
+    use super::*;
+    use crate::ir::tests::{aliasing_namer, all_types};
+
+    #[test]
+    fn generate_all_types() {
+        let mut config = Config::with_writer("lib.py", std::io::sink(), aliasing_namer());
+        let code = Python
+            .generate_to_string(&all_types(), &mut config)
+            .unwrap();
+        for item in [
+            "def class_(ctx, x):",
+            "def end(ctx, x):",
+            "def make_point(ctx, xs, n):",
+            "def make(*args, **kwargs):",
+            "warnings.warn(\"make was renamed to make_point\", DeprecationWarning, stacklevel=2)",
+            "ArrayF32D1 = F32Array1d",
+            "class Option(_Handle):",
+            "    def variant(self):",
+            "    def some(cls, ctx, p0):",
+            "    def get_some(self):",
+            "class Point(_Handle):",
+            "    def xs(self):",
+        ] {
+            assert!(code.contains(item), "missing {item:?}");
+        }
+
+        // Check the module parses when an interpreter is available
+        let Ok(mut child) = Command::new("python3")
+            .args(["-c", "import ast, sys; ast.parse(sys.stdin.read())"])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        else {
+            return;
+        };
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(code.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
@@ -1,209 +1,765 @@
-use crate::*;
+use std::fmt::Display;
 use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use convert_case::{Boundary, Case, Casing};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+
+use crate::generate::{IdentRules, Layout};
+use crate::ir::ElemType;
+use crate::*;
 
 /// Rust codegen
 #[derive(Default)]
 pub struct Rust;
 
-fn is_primitive(a: &str, manifest: &Manifest) -> bool {
-    !manifest.types.contains_key(a)
+/// Returns the extern declaration, `Options` method, configuration code and placeholder
+/// used when the option isn't supported by the backend
+fn config_option(option: ConfigOption) -> [TokenStream; 4] {
+    match option {
+        ConfigOption::NumThreads => [
+            quote! {
+                fn futhark_context_config_set_num_threads(_: *mut futhark_context_config, _: core::ffi::c_int);
+            },
+            quote! {
+                /// Set the number of threads
+                pub fn threads(mut self, n: u32) -> Options {
+                    self.num_threads = n;
+                    self
+                }
+            },
+            quote! {
+                futhark_context_config_set_num_threads(config, options.num_threads as core::ffi::c_int);
+            },
+            quote! {
+                let _ = &options.num_threads;
+            },
+        ],
+        ConfigOption::Device => [
+            quote! {
+                fn futhark_context_config_set_device(_: *mut futhark_context_config, _: *const core::ffi::c_char);
+            },
+            quote! {
+                /// Select the device by name
+                pub fn device(mut self, s: impl AsRef<str>) -> Options {
+                    self.device = Some(std::ffi::CString::new(s.as_ref()).expect("Invalid device"));
+                    self
+                }
+            },
+            quote! {
+                if let Some(d) = &options.device {
+                    futhark_context_config_set_device(config, d.as_ptr());
+                }
+            },
+            quote! {
+                let _ = &options.device;
+            },
+        ],
+        ConfigOption::Platform => [
+            quote! {
+                fn futhark_context_config_set_platform(_: *mut futhark_context_config, _: *const core::ffi::c_char);
+            },
+            quote! {
+                /// Select the platform by name
+                pub fn platform(mut self, s: impl AsRef<str>) -> Options {
+                    self.platform = Some(std::ffi::CString::new(s.as_ref()).expect("Invalid platform"));
+                    self
+                }
+            },
+            quote! {
+                if let Some(p) = &options.platform {
+                    futhark_context_config_set_platform(config, p.as_ptr());
+                }
+            },
+            quote! {
+                let _ = &options.platform;
+            },
+        ],
+    }
 }
-fn primitive_type_name(a: &str) -> &str {
+
+fn primitive_type_name(a: ElemType) -> &'static str {
     match a {
-        "f16" => "half::f16",
-        _ => a,
+        ElemType::F16 => "half::f16",
+        _ => a.to_str(),
+    }
+}
+
+/// Get the Rust type used for a primitive Futhark type
+fn primitive_type(a: ElemType) -> TokenStream {
+    syn::parse_str::<syn::Type>(primitive_type_name(a))
+        .expect("Invalid primitive type")
+        .into_token_stream()
+}
+
+/// Integer literal without a type suffix, such as an array rank
+fn unsuffixed(n: usize) -> proc_macro2::Literal {
+    proc_macro2::Literal::usize_unsuffixed(n)
+}
+
+/// Render documentation as `#[doc]` attributes, equivalent to `///` comments
+fn doc_comment(doc: &str) -> TokenStream {
+    let lines = doc.lines().map(|line| format!(" {line}"));
+    quote! {
+        #(#[doc = #lines])*
+    }
+}
+
+/// Render the derives for a type
+fn derives(ty: &ir::Type) -> Result<TokenStream, Error> {
+    if ty.derives.is_empty() {
+        return Ok(TokenStream::new());
+    }
+    let paths = ty
+        .derives
+        .iter()
+        .map(|d| {
+            syn::parse_str::<syn::Path>(d).map_err(|_| Error::InvalidName {
+                name: d.clone(),
+                item: format!("derive for type {:?}", ty.futhark_name),
+                language: IdentRules::RUST.language,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(quote! {
+        #[derive(#(#paths),*)]
+    })
+}
+
+/// Get the documentation for a type, using `default` if the `Namer` didn't provide any
+fn type_doc(ty: &ir::Type, default: &str) -> TokenStream {
+    doc_comment(ty.doc.as_deref().unwrap_or(default))
+}
+
+/// Items defined by the generated code that can't be used as type names
+const RESERVED_TYPES: &[&str] = &[
+    "Context",
+    "Options",
+    "Error",
+    "FutharkArray",
+    "Result",
+    "Vec",
+    "String",
+    "Default",
+    "Drop",
+];
+
+/// Local variables used by generated constructors
+const RESERVED_LOCALS: &[&str] = &["ctx", "out", "rc"];
+
+/// Methods defined on generated records and record arrays
+const RESERVED_METHODS: &[&str] = &[
+    "new",
+    "new_checked",
+    "zip",
+    "zip_checked",
+    "from_ptr",
+    "shape",
+    "get",
+    "get_checked",
+    "as_raw",
+    "from_raw",
+];
+
+/// Functions and `Context` methods defined by the generated code
+const RESERVED_FUNCTIONS: &[&str] = &[
+    "free",
+    "new",
+    "new_with_options",
+    "sync",
+    "auto_sync",
+    "clear_caches",
+    "pause_profiling",
+    "unpause_profiling",
+    "get_error",
+    "report",
+    "as_raw",
+    "as_raw_config",
+    "from_raw",
+];
+
+/// Escape `name` using `IdentRules::RUST` and check that the result is a valid identifier
+fn rust_ident(
+    name: &str,
+    reserved: &[&str],
+    item: impl Fn() -> String,
+) -> Result<syn::Ident, Error> {
+    let escaped = IdentRules::RUST.escape(name, reserved, &item)?;
+    syn::parse_str(&escaped).map_err(|_| Error::InvalidName {
+        name: escaped,
+        item: item(),
+        language: IdentRules::RUST.language,
+    })
+}
+
+/// Get the identifier for a name from the C API, such as a function or struct name
+fn raw_ident(name: &str) -> Result<syn::Ident, Error> {
+    rust_ident(name, &[], || format!("C name {name:?}"))
+}
+
+/// Get the Rust identifier for a type
+fn type_ident(ty: &ir::Type) -> Result<syn::Ident, Error> {
+    rust_ident(&ty.name, RESERVED_TYPES, || {
+        format!("type {:?}", ty.futhark_name)
+    })
+}
+
+/// Get the Rust identifiers for a record field: the constructor parameter, the projection
+/// method and its `_checked` variant
+fn field_idents(
+    ty: &ir::Type,
+    field: &ir::Field,
+) -> Result<(syn::Ident, syn::Ident, syn::Ident), Error> {
+    let item = || {
+        format!(
+            "field {:?} of type {:?}",
+            field.futhark_name, ty.futhark_name
+        )
+    };
+    Ok((
+        rust_ident(&field.name, RESERVED_LOCALS, item)?,
+        rust_ident(&field.project_name, RESERVED_METHODS, item)?,
+        rust_ident(
+            &format!("{}_checked", field.project_name),
+            RESERVED_METHODS,
+            item,
+        )?,
+    ))
+}
+
+/// Part of the bindings, used to select the output file
+enum Module<'a> {
+    Context,
+    Type(&'a ir::Type),
+    Entries,
+}
+
+/// Get the module name for a type when using `Layout::Modules`
+fn type_module(ty: &ir::Type) -> Result<(syn::Ident, String), Error> {
+    let name = type_ident(ty)?.to_string();
+    let name = name
+        .strip_prefix("r#")
+        .unwrap_or(&name)
+        .with_boundaries(&[
+            Boundary::Underscore,
+            Boundary::LowerUpper,
+            Boundary::DigitUpper,
+            Boundary::Acronym,
+        ])
+        .to_case(Case::Snake);
+    let ident = rust_ident(&name, &[], || {
+        format!("module for type {:?}", ty.futhark_name)
+    })?;
+    Ok((ident, name))
+}
+
+/// Get the file a part of the bindings is written to, relative to the output directory
+fn module_file(config: &Config, module: Module) -> Result<PathBuf, Error> {
+    if config.layout == Layout::SingleFile {
+        return Ok(PathBuf::from(
+            config.output_path.file_name().unwrap_or_default(),
+        ));
+    }
+    Ok(match module {
+        Module::Context => PathBuf::from("context.rs"),
+        Module::Type(ty) => PathBuf::from("types").join(format!("{}.rs", type_module(ty)?.1)),
+        Module::Entries => PathBuf::from("entries.rs"),
+    })
+}
+
+/// Write the module declarations and imports for `Layout::Modules`
+fn module_tree(lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+    let root = PathBuf::from(config.output_path.file_name().unwrap_or_default());
+    let sys = if config.public_sys {
+        quote! {
+            #[allow(dead_code)]
+            pub mod sys;
+        }
+    } else {
+        quote!(
+            mod sys;
+        )
+    };
+    emit_tokens(
+        config,
+        &root,
+        quote! {
+            mod context;
+            mod entries;
+            #sys
+            mod types;
+            pub use context::*;
+            pub use entries::*;
+            pub use types::*;
+        },
+    )?;
+
+    let imports = quote! {
+        #[allow(unused_imports)]
+        use super::{sys::*, *};
+    };
+    emit_tokens(config, Path::new("context.rs"), imports.clone())?;
+    emit_tokens(config, Path::new("entries.rs"), imports)?;
+    emit_tokens(config, Path::new("sys.rs"), quote! {})?;
+
+    let mut modules = std::collections::BTreeSet::new();
+    for ty in lib.types.values() {
+        let (ident, name) = type_module(ty)?;
+        emit_tokens(
+            config,
+            &Path::new("types").join(format!("{name}.rs")),
+            quote! {
+                #[allow(unused_imports)]
+                use super::super::{sys::*, *};
+            },
+        )?;
+        modules.insert(ident.to_string());
+    }
+    let modules = modules
+        .iter()
+        .map(|m| syn::parse_str::<syn::Ident>(m).unwrap())
+        .collect::<Vec<_>>();
+    emit_tokens(
+        config,
+        Path::new("types/mod.rs"),
+        quote! {
+            #(mod #modules;)*
+            #(pub use #modules::*;)*
+        },
+    )
+}
+
+/// Returns true for raw C declarations, which are moved to `sys.rs` when using
+/// `Layout::Modules`
+fn is_sys_item(item: &syn::Item) -> bool {
+    match item {
+        syn::Item::ForeignMod(_) => true,
+        syn::Item::Struct(s) => s.ident.to_string().starts_with("futhark_"),
+        _ => false,
+    }
+}
+
+/// Make raw C declarations public and merge them into one `extern` block, dropping
+/// duplicates
+fn public_sys_items(items: Vec<syn::Item>) -> Result<Vec<syn::Item>, Error> {
+    let mut seen = std::collections::BTreeSet::new();
+    let mut structs = Vec::new();
+    let mut functions = Vec::new();
+    for item in items {
+        match item {
+            syn::Item::Struct(mut s) => {
+                if seen.insert(s.ident.to_string()) {
+                    s.vis = syn::parse_quote!(pub);
+                    structs.push(s);
+                }
+            }
+            syn::Item::ForeignMod(m) => {
+                for mut f in m.items {
+                    let (ident, vis) = match &mut f {
+                        syn::ForeignItem::Fn(f) => (&f.sig.ident, &mut f.vis),
+                        syn::ForeignItem::Static(s) => (&s.ident, &mut s.vis),
+                        _ => continue,
+                    };
+                    if seen.insert(ident.to_string()) {
+                        *vis = syn::parse_quote!(pub);
+                        functions.push(f);
+                    }
+                }
+            }
+            _ => unreachable!("Not a raw C declaration"),
+        }
+    }
+    let file = parse::<syn::File>(
+        "sys",
+        &quote! {
+            #(#structs)*
+            extern "C" {
+                #(#functions)*
+            }
+        }
+        .to_string(),
+    )?;
+    Ok(file.items)
+}
+
+/// Add `as_raw` and `from_raw` to a type when `Config::public_sys` is set
+fn raw_methods(
+    config: &mut Config,
+    file: &Path,
+    rust_type: &syn::Ident,
+    raw_type: &syn::Ident,
+) -> Result<(), Error> {
+    if !config.public_sys {
+        return Ok(());
+    }
+    emit_tokens(
+        config,
+        file,
+        quote! {
+            impl<'a> #rust_type<'a> {
+                /// Get the raw pointer, which is still owned by this value
+                pub fn as_raw(&self) -> *mut #raw_type {
+                    self.ptr
+                }
+
+                /// Take ownership of a raw pointer, it's freed when the returned value is dropped
+                ///
+                /// # Safety
+                ///
+                /// `ptr` must be a valid value created using `ctx` that isn't owned by anything else
+                pub unsafe fn from_raw(ctx: &'a Context, ptr: *mut #raw_type) -> Self {
+                    Self::from_ptr(ctx, ptr)
+                }
+            }
+        },
+    )
+}
+
+/// Make private items visible to the other generated modules
+fn crate_visible(item: &mut syn::Item) {
+    fn update(vis: &mut syn::Visibility) {
+        if matches!(vis, syn::Visibility::Inherited) {
+            *vis = syn::parse_quote!(pub(crate));
+        }
+    }
+
+    match item {
+        syn::Item::Struct(s) => {
+            update(&mut s.vis);
+            s.fields.iter_mut().for_each(|f| update(&mut f.vis));
+        }
+        syn::Item::Enum(e) => update(&mut e.vis),
+        syn::Item::Fn(f) => update(&mut f.vis),
+        syn::Item::Const(c) => update(&mut c.vis),
+        syn::Item::Static(s) => update(&mut s.vis),
+        syn::Item::Type(t) => update(&mut t.vis),
+        syn::Item::Impl(i) if i.trait_.is_none() => {
+            for item in &mut i.items {
+                match item {
+                    syn::ImplItem::Fn(f) => update(&mut f.vis),
+                    syn::ImplItem::Const(c) => update(&mut c.vis),
+                    _ => (),
+                }
+            }
+        }
+        syn::Item::ForeignMod(m) => {
+            for item in &mut m.items {
+                match item {
+                    syn::ForeignItem::Fn(f) => update(&mut f.vis),
+                    syn::ForeignItem::Static(s) => update(&mut s.vis),
+                    _ => (),
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Append generated items to `file`
+///
+/// With `Layout::Modules` raw C declarations are moved to `sys.rs`
+fn emit(config: &mut Config, file: &Path, items: syn::File) -> Result<(), Error> {
+    if config.layout == Layout::SingleFile {
+        return emit_tokens(config, file, items.into_token_stream());
+    }
+
+    for mut item in items.items {
+        crate_visible(&mut item);
+        let dest = if is_sys_item(&item) {
+            Path::new("sys.rs")
+        } else {
+            file
+        };
+        emit_tokens(config, dest, item.into_token_stream())?;
+    }
+    Ok(())
+}
+
+/// Parse generated code, `template` is used for error reporting
+fn parse<T: syn::parse::Parse>(template: &str, code: &str) -> Result<T, Error> {
+    syn::parse_str(code).map_err(|e| Error::InvalidCode {
+        template: template.to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Append the items generated for `templates/rust/<name>` to `file`
+///
+/// The template is rendered using `args`, which are built from validated identifiers and
+/// tokens, and parsed so syntax errors in overridden templates are reported during
+/// generation rather than when the bindings are compiled
+fn render(
+    config: &mut Config,
+    file: &Path,
+    name: &str,
+    args: &[(&str, &dyn Display)],
+) -> Result<(), Error> {
+    let code = config.templates.render("rust", name, args)?;
+    let items = parse::<syn::File>(&format!("rust/{name}"), &code)?;
+    emit(config, file, items)
+}
+
+/// Append tokens to `file`
+fn emit_tokens(config: &mut Config, file: &Path, tokens: TokenStream) -> Result<(), Error> {
+    writeln!(config.output(file), "{tokens}")?;
+    Ok(())
+}
+
+/// Get the Rust type, without lifetime parameters
+fn rust_type_name(lib: &ir::Library, ty: &ir::TypeRef) -> Result<TokenStream, Error> {
+    match (ty, lib.get(ty)) {
+        (ir::TypeRef::Primitive(t), _) => Ok(primitive_type(*t)),
+        (_, Some(t)) => Ok(type_ident(t)?.into_token_stream()),
+        (ir::TypeRef::Named(name), None) => {
+            Err(Error::InvalidManifest(format!("unknown type {name:?}")))
+        }
     }
 }
 
+/// Get the type used in C declarations: primitives are passed as-is, other types by
+/// pointer to their C struct
+fn raw_type_name(lib: &ir::Library, ty: &ir::TypeRef) -> Result<TokenStream, Error> {
+    match ty {
+        ir::TypeRef::Primitive(t) => Ok(primitive_type(*t)),
+        _ => Ok(raw_ident(lib.raw_name(ty))?.into_token_stream()),
+    }
+}
+
+/// Argument lists used to pass a multi-dimensional index or shape to C: the Rust
+/// parameters declared by the C function and the arguments converted from `array`
+fn dim_args(rank: usize, param: &str, array: &str) -> (TokenStream, TokenStream) {
+    let params = (0..rank).map(|i| format_ident!("{param}{i}"));
+    let array = format_ident!("{array}");
+    let args = (0..rank).map(unsuffixed);
+    (quote!(#(#params: i64),*), quote!(#(#array[#args] as i64),*))
+}
+
 impl Generate for Rust {
     fn array_type(
         &mut self,
-        _pkg: &Package,
+        _lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        a: &manifest::ArrayType,
+        ty: &ir::Type,
+        a: &ir::Array,
     ) -> Result<(), Error> {
-        let elemtype = a.elemtype.to_str();
-
-        let raw_type = config.raw_names[name].clone();
-        let rust_type = config.type_names[name].clone();
-
-        let dim_params = (0..a.rank)
-            .map(|i| format!("dims[{i}] as i64"))
-            .collect::<Vec<_>>()
-            .join(", ");
-        let new_dim_args = (0..a.rank)
-            .map(|i| format!("dim{i}: i64"))
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        let index_params = (0..a.rank)
-            .map(|i| format!("index[{i}] as i64"))
-            .collect::<Vec<_>>()
-            .join(", ");
-        let index_args = (0..a.rank)
-            .map(|i| format!("i{i}: i64"))
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        writeln!(
-            config.output_file,
-            include_str!("templates/rust/array.rs"),
-            raw_type = raw_type,
-            rust_type = rust_type,
-            rank = a.rank,
-            elemtype = elemtype,
-            new_fn = a.ops.new,
-            free_fn = a.ops.free,
-            values_fn = a.ops.values,
-            shape_fn = a.ops.shape,
-            index_fn = a.ops.index,
-            dim_params = dim_params,
-            new_dim_args = new_dim_args,
-            index_params = index_params,
-            index_args = index_args,
+        let file = module_file(config, Module::Type(ty))?;
+        let doc = type_doc(
+            ty,
+            &format!(
+                "Array type with {} dimensions and {} elements",
+                a.rank,
+                primitive_type_name(a.elemtype)
+            ),
+        );
+        let derives = derives(ty)?;
+        let raw_type = raw_ident(&ty.raw_name)?;
+        let rust_type = type_ident(ty)?;
+        let rank = unsuffixed(a.rank);
+        let elemtype = primitive_type(a.elemtype);
+        let new_fn = raw_ident(&a.ops.new)?;
+        let free_fn = raw_ident(&a.ops.free)?;
+        let values_fn = raw_ident(&a.ops.values)?;
+        let shape_fn = raw_ident(&a.ops.shape)?;
+        let index_fn = raw_ident(&a.ops.index)?;
+        let (new_dim_args, dim_params) = dim_args(a.rank, "dim", "dims");
+        let (index_args, index_params) = dim_args(a.rank, "i", "index");
+
+        render(
+            config,
+            &file,
+            "array.rs",
+            &[
+                ("doc", &doc),
+                ("derives", &derives),
+                ("raw_type", &raw_type),
+                ("rust_type", &rust_type),
+                ("rank", &rank),
+                ("elemtype", &elemtype),
+                ("new_fn", &new_fn),
+                ("free_fn", &free_fn),
+                ("values_fn", &values_fn),
+                ("shape_fn", &shape_fn),
+                ("index_fn", &index_fn),
+                ("dim_params", &dim_params),
+                ("new_dim_args", &new_dim_args),
+                ("index_params", &index_params),
+                ("index_args", &index_args),
+            ],
         )?;
+        raw_methods(config, &file, &rust_type, &raw_type)?;
 
         Ok(())
     }
 
+    fn type_aliases(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let file = module_file(config, Module::Type(ty))?;
+        let rust_type = type_ident(ty)?;
+        for alias in &ty.aliases {
+            let alias = rust_ident(alias, RESERVED_TYPES, || {
+                format!("type {:?}", ty.futhark_name)
+            })?;
+            let doc = format!(" Deprecated alias for `{rust_type}`");
+            let note = format!("renamed to `{rust_type}`");
+            emit_tokens(
+                config,
+                &file,
+                quote! {
+                    #[doc = #doc]
+                    #[deprecated(note = #note)]
+                    pub type #alias<'a> = #rust_type<'a>;
+                },
+            )?;
+        }
+        Ok(())
+    }
+
     fn opaque_type(
         &mut self,
-        pkg: &Package,
+        lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        ty: &manifest::OpaqueType,
+        ty: &ir::Type,
     ) -> Result<(), Error> {
-        let raw_type = config.raw_names[name].clone();
-        let rust_type = config.type_names[name].clone();
-
-        match &ty.options {
-            manifest::OpaqueOptions::Record(record) => {
-                writeln!(
-                    config.output_file,
-                    include_str!("templates/rust/opaque.rs"),
-                    raw_type = raw_type,
-                    rust_type = rust_type,
-                    free_fn = ty.ops.free,
-                    store_fn = ty.ops.store,
-                    restore_fn = ty.ops.restore,
+        let file = module_file(config, Module::Type(ty))?;
+        let raw_type = raw_ident(&ty.raw_name)?;
+        let rust_type = type_ident(ty)?;
+        let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+        let doc = type_doc(ty, &format!("Futhark type `{}`", ty.futhark_name));
+        let derives = derives(ty)?;
+        let free_fn = raw_ident(&ops.free)?;
+        let store_fn = raw_ident(&ops.store)?;
+        let restore_fn = raw_ident(&ops.restore)?;
+
+        match &ty.kind {
+            ir::TypeKind::Record(_) | ir::TypeKind::Sum(_) => {
+                render(
+                    config,
+                    &file,
+                    "opaque.rs",
+                    &[
+                        ("doc", &doc),
+                        ("derives", &derives),
+                        ("raw_type", &raw_type),
+                        ("rust_type", &rust_type),
+                        ("free_fn", &free_fn),
+                        ("store_fn", &store_fn),
+                        ("restore_fn", &restore_fn),
+                    ],
                 )?;
+                raw_methods(config, &file, &rust_type, &raw_type)?;
+
+                let ir::TypeKind::Record(record) = &ty.kind else {
+                    return Ok(());
+                };
 
                 let mut new_call_args = vec![];
                 let mut new_params = vec![];
                 let mut new_extern_params = vec![];
                 for field in record.fields.iter() {
                     // Build new function
-                    let field_type = field.r#type.clone();
-                    let prim = is_primitive(&field_type, &pkg.manifest);
-
-                    let field_name = config.namer.new_field_name(&field.name, &pkg.manifest);
-                    let rust_field_type = if prim {
-                        primitive_type_name(&field_type).to_string()
-                    } else {
-                        config.type_names[&field_type].clone()
-                    };
-                    let raw_field_type = if prim {
-                        rust_field_type.clone()
-                    } else {
-                        config.raw_names[&field_type].clone()
-                    };
+                    let prim = field.ty.is_primitive();
+                    let (field_name, project_name, checked_name) = field_idents(ty, field)?;
+                    let rust_field_type = rust_type_name(lib, &field.ty)?;
+                    let raw_field_type = raw_type_name(lib, &field.ty)?;
+                    let extern_name = raw_ident(&format!("f_{}", field.futhark_name))?;
 
                     if prim {
-                        new_call_args.push(field_name.clone());
-                        new_extern_params.push(format!(
-                            "f_{}: {}",
-                            field.name,
-                            primitive_type_name(&raw_field_type)
-                        ));
+                        new_call_args.push(quote!(#field_name));
+                        new_extern_params.push(quote!(#extern_name: #raw_field_type));
                     } else {
-                        new_call_args.push(format!("{}.ptr", field_name));
-                        new_extern_params
-                            .push(format!("f_{}: *const {}", field.name, raw_field_type));
+                        new_call_args.push(quote!(#field_name.ptr));
+                        new_extern_params.push(quote!(#extern_name: *const #raw_field_type));
                     }
 
-                    new_params.push(format!("{}: {}", field_name, rust_field_type));
+                    new_params.push(quote!(#field_name: #rust_field_type));
 
                     // Implement get function
 
                     // If the output type is an array or opaque type then we need to wrap the return value
-                    let (output, raw_arg_type) = if is_primitive(&field_type, &pkg.manifest) {
-                        ("Ok(out)".to_string(), raw_field_type)
+                    let (output, raw_arg_type) = if prim {
+                        (quote!(Ok(out)), raw_field_type)
                     } else {
                         (
-                            format!("Ok({rust_field_type}::from_ptr(self.ctx, out))"),
-                            format!("*mut {raw_field_type}"),
+                            quote!(Ok(#rust_field_type::from_ptr(self.ctx, out))),
+                            quote!(*mut #raw_field_type),
                         )
                     };
 
-                    writeln!(
-                        config.output_file,
-                        include_str!("templates/rust/record_project.rs"),
-                        project_fn = field.project,
-                        rust_type = rust_type,
-                        raw_type = raw_type,
-                        field_name = field.name,
-                        project_name = config.namer.project_name(&field.name, &pkg.manifest),
-                        raw_arg_type = raw_arg_type,
-                        rust_field_type = rust_field_type,
-                        output = output
+                    let project_fn = raw_ident(&field.project_fn)?;
+                    render(
+                        config,
+                        &file,
+                        "record_project.rs",
+                        &[
+                            ("project_fn", &project_fn),
+                            ("rust_type", &rust_type),
+                            ("raw_type", &raw_type),
+                            ("field_name", &field.futhark_name),
+                            ("project_name", &project_name),
+                            ("checked_name", &checked_name),
+                            ("raw_arg_type", &raw_arg_type),
+                            ("rust_field_type", &rust_field_type),
+                            ("output", &output),
+                        ],
                     )?;
                 }
 
-                writeln!(
-                    config.output_file,
-                    include_str!("templates/rust/record.rs"),
-                    rust_type = rust_type,
-                    raw_type = raw_type,
-                    new_fn = record.new,
-                    new_params = new_params.join(", "),
-                    new_call_args = new_call_args.join(", "),
-                    new_extern_params = new_extern_params.join(", "),
-                )?;
-            }
-            manifest::OpaqueOptions::Sum(_sum) => {
-                writeln!(
-                    config.output_file,
-                    include_str!("templates/rust/opaque.rs"),
-                    raw_type = raw_type,
-                    rust_type = rust_type,
-                    free_fn = ty.ops.free,
-                    store_fn = ty.ops.store,
-                    restore_fn = ty.ops.restore,
+                let new_fn = raw_ident(&record.new_fn)?;
+                let new_params = quote!(#(#new_params),*);
+                let new_call_args = quote!(#(#new_call_args),*);
+                let new_extern_params = quote!(#(#new_extern_params),*);
+                render(
+                    config,
+                    &file,
+                    "record.rs",
+                    &[
+                        ("rust_type", &rust_type),
+                        ("raw_type", &raw_type),
+                        ("new_fn", &new_fn),
+                        ("new_params", &new_params),
+                        ("new_call_args", &new_call_args),
+                        ("new_extern_params", &new_extern_params),
+                    ],
                 )?;
             }
-            manifest::OpaqueOptions::OpaqueArray(array)
-            | manifest::OpaqueOptions::RecordArray(array) => {
-                let rust_elemtype = config.type_names[&array.elemtype].clone();
-                let raw_elemtype = config.raw_names[&array.elemtype].clone();
-
-                let index_args = (0..array.rank)
-                    .map(|i| format!("i{i}: i64"))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                let index_params = (0..array.rank)
-                    .map(|i| format!("index[{i}] as i64"))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-
-                writeln!(
-                    config.output_file,
-                    include_str!("templates/rust/opaque_array.rs"),
-                    raw_type = raw_type,
-                    rust_type = rust_type,
-                    free_fn = ty.ops.free,
-                    store_fn = ty.ops.store,
-                    restore_fn = ty.ops.restore,
-                    shape_fn = array.shape,
-                    index_fn = array.index,
-                    rank = array.rank,
-                    rust_elemtype = rust_elemtype,
-                    raw_elemtype = raw_elemtype,
-                    index_args = index_args,
-                    index_params = index_params,
+            ir::TypeKind::OpaqueArray(array)
+            | ir::TypeKind::RecordArray(ir::RecordArray { array, .. }) => {
+                let rust_elemtype = rust_type_name(lib, &array.elemtype)?;
+                let raw_elemtype = raw_type_name(lib, &array.elemtype)?;
+                let rank = unsuffixed(array.rank);
+                let shape_fn = raw_ident(&array.shape_fn)?;
+                let index_fn = raw_ident(&array.index_fn)?;
+                let (index_args, index_params) = dim_args(array.rank, "i", "index");
+
+                render(
+                    config,
+                    &file,
+                    "opaque_array.rs",
+                    &[
+                        ("doc", &doc),
+                        ("derives", &derives),
+                        ("raw_type", &raw_type),
+                        ("rust_type", &rust_type),
+                        ("free_fn", &free_fn),
+                        ("store_fn", &store_fn),
+                        ("restore_fn", &restore_fn),
+                        ("shape_fn", &shape_fn),
+                        ("index_fn", &index_fn),
+                        ("rank", &rank),
+                        ("rust_elemtype", &rust_elemtype),
+                        ("raw_elemtype", &raw_elemtype),
+                        ("index_args", &index_args),
+                        ("index_params", &index_params),
+                    ],
                 )?;
+                raw_methods(config, &file, &rust_type, &raw_type)?;
 
-                let Some(record) = &array.record else {
+                let ir::TypeKind::RecordArray(record) = &ty.kind else {
                     return Ok(());
                 };
 
@@ -212,53 +768,62 @@ impl Generate for Rust {
                 let mut zip_extern_params = vec![];
 
                 for field in record.fields.iter() {
-                    let field_type = field.r#type.clone();
-
-                    let field_name = config.namer.new_field_name(&field.name, &pkg.manifest);
-                    let rust_field_type = config.type_names[&field_type].clone();
-                    let raw_field_type = config.raw_names[&field_type].clone();
-
-                    zip_call_args.push(format!("{}.ptr", field_name));
-                    zip_extern_params.push(format!("f_{}: *const {}", field.name, raw_field_type));
-                    zip_params.push(format!("{}: &{}", field_name, rust_field_type));
-
-                    let project_name = config.namer.project_name(&field.name, &pkg.manifest);
-                    let project_fn = field.project.clone();
-
-                    writeln!(
-                        config.output_file,
-                        include_str!("templates/rust/record_array_project.rs"),
-                        project_fn = project_fn,
-                        rust_type = rust_type,
-                        raw_type = raw_type,
-                        project_name = project_name,
-                        raw_field_type = raw_field_type,
-                        rust_field_type = rust_field_type,
+                    let (field_name, project_name, checked_name) = field_idents(ty, field)?;
+                    let rust_field_type = rust_type_name(lib, &field.ty)?;
+                    let raw_field_type = raw_type_name(lib, &field.ty)?;
+                    let extern_name = raw_ident(&format!("f_{}", field.futhark_name))?;
+
+                    zip_call_args.push(quote!(#field_name.ptr));
+                    zip_extern_params.push(quote!(#extern_name: *const #raw_field_type));
+                    zip_params.push(quote!(#field_name: &#rust_field_type));
+
+                    let project_fn = raw_ident(&field.project_fn)?;
+                    render(
+                        config,
+                        &file,
+                        "record_array_project.rs",
+                        &[
+                            ("project_fn", &project_fn),
+                            ("rust_type", &rust_type),
+                            ("raw_type", &raw_type),
+                            ("project_name", &project_name),
+                            ("checked_name", &checked_name),
+                            ("raw_field_type", &raw_field_type),
+                            ("rust_field_type", &rust_field_type),
+                        ],
                     )?;
                 }
 
-                writeln!(
-                    config.output_file,
-                    include_str!("templates/rust/record_array.rs"),
-                    rust_type = rust_type,
-                    raw_type = raw_type,
-                    zip_params = zip_params.join(", "),
-                    zip_call_args = zip_call_args.join(", "),
-                    zip_extern_params = zip_extern_params.join(", "),
-                    zip_fn = record.zip,
+                let zip_fn = raw_ident(&record.zip_fn)?;
+                let zip_params = quote!(#(#zip_params),*);
+                let zip_call_args = quote!(#(#zip_call_args),*);
+                let zip_extern_params = quote!(#(#zip_extern_params),*);
+                render(
+                    config,
+                    &file,
+                    "record_array.rs",
+                    &[
+                        ("rust_type", &rust_type),
+                        ("raw_type", &raw_type),
+                        ("zip_params", &zip_params),
+                        ("zip_call_args", &zip_call_args),
+                        ("zip_extern_params", &zip_extern_params),
+                        ("zip_fn", &zip_fn),
+                    ],
                 )?;
             }
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
         }
         Ok(())
     }
 
     fn entry(
         &mut self,
-        pkg: &Package,
+        lib: &ir::Library,
         config: &mut Config,
-        name: &str,
-        entry: &manifest::Entry,
+        entry: &ir::Entry,
     ) -> Result<(), Error> {
+        let file = module_file(config, Module::Entries)?;
         let mut call_args = Vec::new();
         let mut entry_params = Vec::new();
         let mut return_type = Vec::new();
@@ -268,154 +833,325 @@ impl Generate for Rust {
 
         // Output arguments
         for (i, arg) in entry.outputs.iter().enumerate() {
-            let futhark_type = arg.r#type.clone();
-            let name = format!("out{i}");
-            let prim = is_primitive(&futhark_type, &pkg.manifest);
-            let raw_type = if prim {
-                primitive_type_name(&futhark_type)
-            } else {
-                &config.raw_names[&futhark_type]
-            };
-            let rust_type = if prim {
-                primitive_type_name(&futhark_type).to_string()
-            } else {
-                config.type_names[&futhark_type].to_string()
-            };
+            let name = format_ident!("out{i}");
+            let rust_type = rust_type_name(lib, &arg.ty)?;
 
-            if prim {
-                futhark_entry_params.push(format!("{name}: *mut {raw_type}"));
-                entry_return.push(format!("{name}.assume_init()"));
-                return_type.push(rust_type.clone());
-            } else {
-                futhark_entry_params.push(format!("{name}: *mut *mut {raw_type}"));
-                entry_return.push(format!("{rust_type}::from_ptr(ctx, {name}.assume_init())",));
-                return_type.push(format!("{rust_type}<'a>"));
+            match arg.abi {
+                ir::Abi::OutPointer => {
+                    let raw_type = raw_type_name(lib, &arg.ty)?;
+                    futhark_entry_params.push(quote!(#name: *mut *mut #raw_type));
+                    entry_return.push(quote!(#rust_type::from_ptr(ctx, #name.assume_init())));
+                    return_type.push(quote!(#rust_type<'a>));
+                }
+                _ => {
+                    futhark_entry_params.push(quote!(#name: *mut #rust_type));
+                    entry_return.push(quote!(#name.assume_init()));
+                    return_type.push(rust_type);
+                }
             }
 
-            out_decl.push(format!("let mut {name} = std::mem::MaybeUninit::zeroed();"));
-            call_args.push(format!("{name}.as_mut_ptr()"));
+            out_decl.push(quote!(let mut #name = std::mem::MaybeUninit::zeroed();));
+            call_args.push(quote!(#name.as_mut_ptr()));
         }
 
         // Input arguments
         for (i, arg) in entry.inputs.iter().enumerate() {
-            let futhark_type = arg.r#type.clone();
-            let name = format!("in{i}");
+            let name = format_ident!("in{i}");
+            let rust_type = rust_type_name(lib, &arg.ty)?;
 
-            let prim = is_primitive(&futhark_type, &pkg.manifest);
+            match arg.abi {
+                ir::Abi::Pointer => {
+                    let raw_type = raw_type_name(lib, &arg.ty)?;
+                    futhark_entry_params.push(quote!(#name: *const #raw_type));
+                    entry_params.push(quote!(#name: &#rust_type<'a>));
+                    call_args.push(quote!(#name.ptr as *mut _));
+                }
+                _ => {
+                    futhark_entry_params.push(quote!(#name: #rust_type));
+                    entry_params.push(quote!(#name: #rust_type));
+                    call_args.push(quote!(#name));
+                }
+            }
+        }
 
-            let raw_type = if prim {
-                primitive_type_name(&futhark_type)
-            } else {
-                &config.raw_names[&futhark_type]
-            };
-            let rust_type = if prim {
-                primitive_type_name(&futhark_type).to_string()
+        let (entry_return_type, entry_return) = match entry.outputs.len() {
+            1 => (return_type.remove(0), entry_return.remove(0)),
+            _ => (quote!((#(#return_type),*)), quote!((#(#entry_return),*))),
+        };
+
+        let escape = |name: &str| {
+            rust_ident(name, RESERVED_FUNCTIONS, || {
+                format!("entry point {:?}", entry.futhark_name)
+            })
+        };
+        let entry_name = escape(&entry.name)?;
+        let entry_fn = raw_ident(&entry.cfun)?;
+
+        let doc = doc_comment(
+            entry
+                .doc
+                .as_deref()
+                .unwrap_or(&format!("Entry point: {}", entry.futhark_name)),
+        );
+
+        let params = quote!(#(#entry_params),*);
+        let call = quote!(#(#call_args),*);
+        let out_decl = quote!(#(#out_decl)*);
+        let extern_params = quote!(#(#futhark_entry_params),*);
+        let name = if config.entry_points_within_context {
+            "context_entry.rs"
+        } else {
+            "entry.rs"
+        };
+        render(
+            config,
+            &file,
+            name,
+            &[
+                ("entry_fn", &entry_fn),
+                ("entry_name", &entry_name),
+                ("doc", &doc),
+                ("entry_params", &params),
+                ("entry_return_type", &entry_return_type),
+                ("out_decl", &out_decl),
+                ("call_args", &call),
+                ("entry_return", &entry_return),
+                ("futhark_entry_params", &extern_params),
+            ],
+        )?;
+
+        let args = (0..entry.inputs.len())
+            .map(|i| format_ident!("in{i}"))
+            .collect::<Vec<_>>();
+        for alias in &entry.aliases {
+            let alias = escape(alias)?;
+            let doc = format!(" Deprecated alias for `{entry_name}`");
+            let note = format!("renamed to `{entry_name}`");
+            let tokens = if config.entry_points_within_context {
+                quote! {
+                    impl Context {
+                        #[doc = #doc]
+                        #[deprecated(note = #note)]
+                        pub fn #alias<'a>(&'a self, #params) -> Result<#entry_return_type, Error> {
+                            self.#entry_name(#(#args),*)
+                        }
+                    }
+                }
             } else {
-                config.type_names[&futhark_type].to_string()
+                quote! {
+                    #[doc = #doc]
+                    #[deprecated(note = #note)]
+                    pub fn #alias<'a>(ctx: &'a Context, #params) -> Result<#entry_return_type, Error> {
+                        #entry_name(ctx, #(#args),*)
+                    }
+                }
             };
+            emit_tokens(config, &file, tokens)?;
+        }
+
+        Ok(())
+    }
 
-            if prim {
-                futhark_entry_params.push(format!("{name}: {raw_type}"));
-                entry_params.push(format!("{name}: {rust_type}"));
-                call_args.push(name);
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        if config.layout == Layout::Modules {
+            module_tree(lib, config)?;
+        }
+
+        let file = module_file(config, Module::Context)?;
+        let caps = lib.backend.capabilities();
+
+        let mut backend_extern_functions = Vec::new();
+        let mut backend_options = Vec::new();
+        let mut configure_backend = Vec::new();
+        for option in ConfigOption::ALL {
+            let [extern_fn, builder, configure, unused] = config_option(option);
+            if caps.config_options.contains(&option) {
+                backend_extern_functions.push(extern_fn);
+                backend_options.push(builder);
+                configure_backend.push(configure);
             } else {
-                futhark_entry_params.push(format!("{name}: *const {raw_type}"));
-                entry_params.push(format!("{name}: &{rust_type}<'a>"));
-                call_args.push(format!("{name}.ptr as *mut _"));
+                configure_backend.push(unused);
             }
         }
 
-        let (entry_return_type, entry_return) = match entry.outputs.len() {
-            0 => ("()".to_string(), "()".to_string()),
-            1 => (return_type.join(", "), entry_return.join(", ")),
-            _ => (
-                format!("({})", return_type.join(", ")),
-                format!("({})", entry_return.join(", ")),
-            ),
-        };
+        let backend = caps.name;
+        let device_memory = caps.memory == Memory::Device;
+        let backend_options = quote!(#(#backend_options)*);
+        let configure_backend = quote!(#(#configure_backend)*);
+        let backend_extern_functions = quote!(#(#backend_extern_functions)*);
+        render(
+            config,
+            &file,
+            "context.rs",
+            &[
+                ("backend", &backend),
+                ("device_memory", &device_memory),
+                ("backend_options", &backend_options),
+                ("configure_backend", &configure_backend),
+                ("backend_extern_functions", &backend_extern_functions),
+            ],
+        )?;
 
-        if config.entry_points_within_context {
-            writeln!(
-                config.output_file,
-                include_str!("templates/rust/context_entry.rs"),
-                entry_fn = entry.cfun,
-                entry_name = name,
-                entry_params = entry_params.join(", "),
-                entry_return_type = entry_return_type,
-                out_decl = out_decl.join(";\n"),
-                call_args = call_args.join(", "),
-                entry_return = entry_return,
-                futhark_entry_params = futhark_entry_params.join(", "),
-            )?;
-        } else {
-            writeln!(
-                config.output_file,
-                include_str!("templates/rust/entry.rs"),
-                entry_fn = entry.cfun,
-                entry_name = name,
-                entry_params = entry_params.join(", "),
-                entry_return_type = entry_return_type,
-                out_decl = out_decl.join(";\n"),
-                call_args = call_args.join(", "),
-                entry_return = entry_return,
-                futhark_entry_params = futhark_entry_params.join(", "),
+        if config.public_sys {
+            emit_tokens(
+                config,
+                &file,
+                quote! {
+                    impl Context {
+                        /// Get the raw context pointer, which is still owned by this value
+                        pub fn as_raw(&self) -> *mut futhark_context {
+                            self.context
+                        }
+
+                        /// Get the raw pointer to the config the context was created with
+                        pub fn as_raw_config(&self) -> *mut futhark_context_config {
+                            self.config
+                        }
+
+                        /// Take ownership of a raw context and the config it was created with, both
+                        /// are freed when the returned value is dropped
+                        ///
+                        /// # Safety
+                        ///
+                        /// `context` must be a valid context created using `config`, neither can be
+                        /// owned by anything else
+                        pub unsafe fn from_raw(
+                            config: *mut futhark_context_config,
+                            context: *mut futhark_context,
+                        ) -> Context {
+                            Context {
+                                config,
+                                context,
+                                auto_sync: true,
+                                _cache_file: None,
+                            }
+                        }
+                    }
+                },
             )?;
         }
 
         Ok(())
     }
 
-    fn bindings(&mut self, pkg: &Package, config: &mut Config) -> Result<(), Error> {
-        writeln!(config.output_file, "// Generated by futhark-bindgen\n")?;
-        let backend_extern_functions = match &pkg.manifest.backend {
-            Backend::Multicore => {
-                "fn futhark_context_config_set_num_threads(_: *mut futhark_context_config, _: core::ffi::c_int);"
-            }
-            Backend::OpenCl | Backend::Cuda => {
-                "fn futhark_context_config_set_device(_: *mut futhark_context_config, _: *const core::ffi::c_char);"
+    fn format(
+        &mut self,
+        config: &Config,
+        path: &std::path::Path,
+        code: String,
+    ) -> Result<String, Error> {
+        let mut file = parse::<syn::File>(&path.display().to_string(), &code)?;
+        if config.public_sys {
+            match config.layout {
+                Layout::SingleFile => {
+                    let (sys, mut items): (Vec<_>, Vec<_>) =
+                        file.items.into_iter().partition(is_sys_item);
+                    let sys = public_sys_items(sys)?;
+                    items.splice(
+                        0..0,
+                        [
+                            syn::parse_quote! {
+                                /// Raw C declarations
+                                #[allow(dead_code)]
+                                pub mod sys {
+                                    #(#sys)*
+                                }
+                            },
+                            syn::parse_quote!(
+                                use self::sys::*;
+                            ),
+                        ],
+                    );
+                    file.items = items;
+                }
+                Layout::Modules if path == config.output_path.with_file_name("sys.rs") => {
+                    file.items = public_sys_items(file.items)?;
+                }
+                Layout::Modules => (),
             }
-            _ => "",
-        };
+        }
+        Ok(format!(
+            "// Generated by futhark-bindgen\n\n{}",
+            prettyplease::unparse(&file)
+        ))
+    }
+}
 
-        let backend_options = match pkg.manifest.backend {
-            Backend::Multicore => {
-                "pub fn threads(mut self, n: u32) -> Options { self.num_threads = n as u32; self }"
-            }
-            Backend::Cuda | Backend::OpenCl => {
-                "pub fn device(mut self, s: impl AsRef<str>) -> Options { self.device = Some(std::ffi::CString::new(s.as_ref()).expect(\"Invalid device\")); self }"
-            }
-            _ => "",
-        };
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
 
-        let configure_num_threads = if pkg.manifest.backend == Backend::Multicore {
-            "futhark_context_config_set_num_threads(config, options.num_threads as core::ffi::c_int);"
-        } else {
-            "let _ = &options.num_threads;"
-        };
+    use super::*;
+    use crate::ir::tests::{all_types, entry, package, record};
 
-        let configure_set_device = if matches!(
-            pkg.manifest.backend,
-            Backend::Cuda | Backend::OpenCl
-        ) {
-            "if let Some(d) = &options.device { futhark_context_config_set_device(config, d.as_ptr()); }"
-        } else {
-            "let _ = &options.device;"
-        };
+    #[test]
+    fn builtin_templates_generate_valid_code() {
+        let pkg = all_types();
+        for within_context in [false, true] {
+            let mut config =
+                Config::with_writer("lib.rs", std::io::sink(), DefaultNamer::default());
+            config.entry_points_within_context = within_context;
+            let code = Rust.generate_to_string(&pkg, &mut config).unwrap();
+            syn::parse_file(&code).unwrap();
+            for item in [
+                "pub struct F32Array1d<'a>",
+                "pub struct Point<'a>",
+                "pub fn xs(&self) -> F32Array1d {",
+                "pub fn xs_checked(&self) -> Result<F32Array1d, Error> {",
+                "pub struct Option<'a>",
+                "pub struct PointArray1d<'a>",
+                "pub const BACKEND: &str = \"c\";",
+            ] {
+                assert!(code.contains(item), "missing {item:?}");
+            }
+            let entry = if within_context {
+                "pub fn make<'a>(\n        &'a self,\n        in0: &F32Array1d<'a>,"
+            } else {
+                "pub fn make<'a>(\n    ctx: &'a Context,\n    in0: &F32Array1d<'a>,"
+            };
+            assert!(code.contains(entry), "missing {entry:?}");
+        }
+    }
 
-        writeln!(
-            config.output_file,
-            include_str!("templates/rust/context.rs"),
-            backend_options = backend_options,
-            configure_num_threads = configure_num_threads,
-            configure_set_device = configure_set_device,
-            backend_extern_functions = backend_extern_functions,
-        )?;
+    #[test]
+    fn overridden_template_is_used() {
+        let templates = Templates::default()
+            .with_override(
+                "rust",
+                "entry.rs",
+                "/// Custom\npub fn {entry_name}() {{}}\n",
+            )
+            .unwrap();
+        let mut config = Config::with_writer("lib.rs", std::io::sink(), DefaultNamer::default())
+            .with_templates(templates);
+        let code = Rust.generate_to_string(&all_types(), &mut config).unwrap();
+        assert!(code.contains("/// Custom\npub fn noop() {}"));
+    }
 
-        Ok(())
+    #[test]
+    fn invalid_override_is_reported() {
+        let templates = Templates::default()
+            .with_override("rust", "entry.rs", "pub fn {entry_name}( {{}}")
+            .unwrap();
+        let mut config = Config::with_writer("lib.rs", std::io::sink(), DefaultNamer::default())
+            .with_templates(templates);
+        match Rust.generate_to_string(&all_types(), &mut config) {
+            Err(Error::InvalidCode { template, .. }) => assert_eq!(template, "rust/entry.rs"),
+            other => panic!("expected invalid code, got {other:?}"),
+        }
     }
 
-    fn format(&mut self, path: &std::path::Path) -> Result<(), Error> {
-        let _ = std::process::Command::new("rustfmt").arg(path).status();
-        Ok(())
+    #[test]
+    fn checked_projection_of_keyword_field() {
+        let pkg = package(
+            json!({"main": entry("main", &[("x", "t")], &["f32"])}),
+            json!({"t": record("opaque_t", &[("ref", "f32")])}),
+        );
+        let mut config = Config::with_writer("lib.rs", std::io::sink(), DefaultNamer::default());
+        let code = Rust.generate_to_string(&pkg, &mut config).unwrap();
+        assert!(code.contains("pub fn r#ref(&self)"));
+        assert!(code.contains("pub fn ref_checked(&self)"));
+        assert!(code.contains("self.ref_checked().unwrap()"));
+        assert!(!code.contains("r#ref_checked"));
     }
 }
@@ -0,0 +1,1545 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::*;
+
+/// A built-in template
+///
+/// Templates use the same syntax as `format!`: `{name}` is replaced with the value of a
+/// placeholder and `{{`/`}}` produce literal braces
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateInfo {
+    /// Output language, also the name of the directory used by `Templates::load_dir`
+    pub language: &'static str,
+
+    /// File name, for example `array.rs`
+    pub name: &'static str,
+
+    /// What the template generates
+    pub description: &'static str,
+
+    /// Placeholders the template may use
+    pub placeholders: &'static [&'static str],
+
+    /// Default template source
+    pub source: &'static str,
+}
+
+macro_rules! template {
+    ($language:literal, $name:literal, $description:literal, [$($placeholder:literal),* $(,)?]) => {
+        TemplateInfo {
+            language: $language,
+            name: $name,
+            description: $description,
+            placeholders: &[$($placeholder),*],
+            source: include_str!(concat!("templates/", $language, "/", $name)),
+        }
+    };
+}
+
+/// All built-in templates
+pub const TEMPLATES: &[TemplateInfo] = &[
+    template!(
+        "rust",
+        "context.rs",
+        "`Error`, `Options`, `Context` and the shared FFI declarations",
+        [
+            "backend",
+            "device_memory",
+            "backend_options",
+            "configure_backend",
+            "backend_extern_functions",
+        ]
+    ),
+    template!(
+        "rust",
+        "array.rs",
+        "Arrays of primitive values",
+        [
+            "doc",
+            "derives",
+            "raw_type",
+            "rust_type",
+            "rank",
+            "elemtype",
+            "new_fn",
+            "free_fn",
+            "values_fn",
+            "shape_fn",
+            "index_fn",
+            "dim_params",
+            "new_dim_args",
+            "index_params",
+            "index_args",
+        ]
+    ),
+    template!(
+        "rust",
+        "opaque.rs",
+        "Opaque types, records and sum types",
+        [
+            "doc",
+            "derives",
+            "raw_type",
+            "rust_type",
+            "free_fn",
+            "store_fn",
+            "restore_fn",
+        ]
+    ),
+    template!(
+        "rust",
+        "record.rs",
+        "Record constructors",
+        [
+            "rust_type",
+            "raw_type",
+            "new_fn",
+            "new_params",
+            "new_call_args",
+            "new_extern_params",
+        ]
+    ),
+    template!(
+        "rust",
+        "record_project.rs",
+        "Record field projections",
+        [
+            "project_fn",
+            "rust_type",
+            "raw_type",
+            "field_name",
+            "project_name",
+            "checked_name",
+            "rust_field_type",
+            "raw_arg_type",
+            "output",
+        ]
+    ),
+    template!(
+        "rust",
+        "opaque_array.rs",
+        "Arrays of opaque values",
+        [
+            "doc",
+            "derives",
+            "raw_type",
+            "rust_type",
+            "rank",
+            "raw_elemtype",
+            "rust_elemtype",
+            "free_fn",
+            "store_fn",
+            "restore_fn",
+            "shape_fn",
+            "index_fn",
+            "index_params",
+            "index_args",
+        ]
+    ),
+    template!(
+        "rust",
+        "record_array.rs",
+        "Arrays of records, `zip`",
+        [
+            "rust_type",
+            "raw_type",
+            "zip_fn",
+            "zip_params",
+            "zip_call_args",
+            "zip_extern_params",
+        ]
+    ),
+    template!(
+        "rust",
+        "record_array_project.rs",
+        "Field projections for arrays of records",
+        [
+            "project_fn",
+            "rust_type",
+            "raw_type",
+            "project_name",
+            "checked_name",
+            "rust_field_type",
+            "raw_field_type",
+        ]
+    ),
+    template!(
+        "rust",
+        "entry.rs",
+        "Entry points, as functions",
+        [
+            "doc",
+            "entry_fn",
+            "entry_name",
+            "entry_params",
+            "entry_return_type",
+            "out_decl",
+            "call_args",
+            "entry_return",
+            "futhark_entry_params",
+        ]
+    ),
+    template!(
+        "rust",
+        "context_entry.rs",
+        "Entry points, as `Context` methods",
+        [
+            "doc",
+            "entry_fn",
+            "entry_name",
+            "entry_params",
+            "entry_return_type",
+            "out_decl",
+            "call_args",
+            "entry_return",
+            "futhark_entry_params",
+        ]
+    ),
+    template!(
+        "ocaml",
+        "bindings.ml",
+        "Foreign function declarations",
+        ["generated_foreign_functions"]
+    ),
+    template!("ocaml", "bindings.mli", "Foreign function signatures", []),
+    template!(
+        "ocaml",
+        "context.ml",
+        "`Error`, `Context` and shared helpers",
+        ["extra_param", "extra_line"]
+    ),
+    template!("ocaml", "context.mli", "Context signature", ["extra_mli"]),
+    template!(
+        "ocaml",
+        "array.ml",
+        "Arrays of primitive values",
+        [
+            "module_name",
+            "rank",
+            "ocaml_elemtype",
+            "ocaml_ctype",
+            "ba_elemtype",
+            "ba_kind",
+            "dim_args",
+            "new_fn",
+            "free_fn",
+            "values_fn",
+            "shape_fn",
+        ]
+    ),
+    template!(
+        "ocaml",
+        "array.mli",
+        "Array signatures",
+        ["module_name", "ocaml_elemtype", "ba_elemtype"]
+    ),
+    template!("ocaml", "opaque.ml", "Opaque types", ["name", "free_fn"]),
+    template!("ocaml", "opaque.mli", "Opaque type signatures", []),
+    template!(
+        "ocaml",
+        "record.ml",
+        "Record constructors",
+        ["new_fn", "new_params", "new_call_args"]
+    ),
+    template!(
+        "ocaml",
+        "record.mli",
+        "Record constructor signatures",
+        ["new_arg_types"]
+    ),
+    template!(
+        "ocaml",
+        "record_project.ml",
+        "Record field projections",
+        ["name", "s", "project", "out"]
+    ),
+    template!(
+        "ocaml",
+        "record_project.mli",
+        "Record field projection signatures",
+        ["name", "out_type"]
+    ),
+    template!(
+        "ocaml",
+        "sum.ml",
+        "Sum types",
+        [
+            "constructors",
+            "construct_cases",
+            "destruct_cases",
+            "variant_fn"
+        ]
+    ),
+    template!("ocaml", "sum.mli", "Sum type signatures", ["constructors"]),
+    template!(
+        "ocaml",
+        "opaque_array.ml",
+        "Arrays of opaque values",
+        ["shape_fn", "index_fn", "rank", "index_args", "elem_module"]
+    ),
+    template!(
+        "ocaml",
+        "opaque_array.mli",
+        "Signatures for arrays of opaque values",
+        ["elem_module"]
+    ),
+    template!(
+        "ocaml",
+        "record_array.ml",
+        "Arrays of records, `zip`",
+        ["zip_fn", "zip_params", "zip_call_args"]
+    ),
+    template!(
+        "ocaml",
+        "record_array.mli",
+        "Signatures for arrays of records",
+        ["zip_arg_types"]
+    ),
+    template!(
+        "ocaml",
+        "entry.ml",
+        "Entry points",
+        [
+            "name",
+            "entry_fn",
+            "entry_params",
+            "out_decl",
+            "call_args",
+            "out_return"
+        ]
+    ),
+    template!(
+        "ocaml",
+        "entry.mli",
+        "Entry point signatures",
+        ["name", "arg_types", "return_type"]
+    ),
+    template!(
+        "cpp",
+        "context.hpp",
+        "Includes, `Error`, `Options`, `Context` and the `Handle` base class",
+        [
+            "header",
+            "namespace",
+            "backend",
+            "device_memory",
+            "backend_options",
+            "configure_backend",
+            "forward_declarations",
+            "entry_declarations",
+        ]
+    ),
+    template!(
+        "cpp",
+        "array.hpp",
+        "Arrays of primitive values",
+        [
+            "doc",
+            "class_name",
+            "raw_type",
+            "rank",
+            "elemtype",
+            "vector_elemtype",
+            "new_fn",
+            "free_fn",
+            "values_fn",
+            "shape_fn",
+            "index_fn",
+            "dim_args",
+            "index_args",
+        ]
+    ),
+    template!(
+        "cpp",
+        "opaque.hpp",
+        "Opaque types, `members` holds the code generated by the other opaque templates",
+        [
+            "doc",
+            "class_name",
+            "raw_type",
+            "free_fn",
+            "store_fn",
+            "restore_fn",
+            "members",
+        ]
+    ),
+    template!(
+        "cpp",
+        "record.hpp",
+        "Record constructors",
+        [
+            "class_name",
+            "raw_type",
+            "new_fn",
+            "new_params",
+            "new_call_args",
+        ]
+    ),
+    template!(
+        "cpp",
+        "record_project.hpp",
+        "Record field accessors",
+        [
+            "field_name",
+            "field_type",
+            "project_name",
+            "project_fn",
+            "out_decl",
+            "out_return",
+        ]
+    ),
+    template!(
+        "cpp",
+        "sum.hpp",
+        "Sum types, the `Variant` enum",
+        ["variants", "variant_fn"]
+    ),
+    template!(
+        "cpp",
+        "sum_variant.hpp",
+        "Sum type constructors",
+        [
+            "class_name",
+            "raw_type",
+            "variant_name",
+            "name",
+            "construct_fn",
+            "construct_params",
+            "construct_args",
+        ]
+    ),
+    template!(
+        "cpp",
+        "sum_payload.hpp",
+        "Sum type payload getters",
+        [
+            "variant_name",
+            "variant",
+            "name",
+            "destruct_fn",
+            "payload_types",
+            "destruct_decls",
+            "destruct_args",
+            "destruct_return",
+        ]
+    ),
+    template!(
+        "cpp",
+        "opaque_array.hpp",
+        "Arrays of opaque values",
+        [
+            "rank",
+            "shape_fn",
+            "index_fn",
+            "index_args",
+            "elem_class",
+            "elem_raw_type",
+        ]
+    ),
+    template!(
+        "cpp",
+        "record_array.hpp",
+        "Arrays of records, `zip` constructors",
+        [
+            "class_name",
+            "raw_type",
+            "zip_fn",
+            "zip_params",
+            "zip_call_args",
+        ]
+    ),
+    template!(
+        "cpp",
+        "entry.hpp",
+        "Entry point definitions, declared in `Context`",
+        [
+            "name",
+            "return_type",
+            "entry_params",
+            "entry_fn",
+            "out_decl",
+            "call_args",
+            "entry_return",
+        ]
+    ),
+    template!("cpp", "footer.hpp", "Closes the namespace", ["namespace"]),
+    template!(
+        "python",
+        "context.py",
+        "Library loading, C function declarations, `Error`, `Context` and the `_Handle` base class",
+        [
+            "backend",
+            "device_memory",
+            "library",
+            "library_env",
+            "generated_functions",
+            "extra_params",
+            "configure_backend",
+        ]
+    ),
+    template!(
+        "python",
+        "array.py",
+        "Arrays of primitive values",
+        [
+            "doc",
+            "class_name",
+            "rank",
+            "dtype",
+            "ctype",
+            "new_fn",
+            "free_fn",
+            "values_fn",
+            "shape_fn",
+            "index_fn",
+        ]
+    ),
+    template!(
+        "python",
+        "opaque.py",
+        "Opaque types, `members` holds the code generated by the other opaque templates",
+        [
+            "doc",
+            "class_name",
+            "free_fn",
+            "store_fn",
+            "restore_fn",
+            "members",
+        ]
+    ),
+    template!(
+        "python",
+        "record.py",
+        "Record constructors",
+        ["new_fn", "new_params", "new_call_args"]
+    ),
+    template!(
+        "python",
+        "record_project.py",
+        "Record field properties",
+        [
+            "field_name",
+            "project_name",
+            "project_fn",
+            "out_type",
+            "out_return",
+        ]
+    ),
+    template!(
+        "python",
+        "sum.py",
+        "Sum types, `variant`",
+        ["variants", "variant_fn"]
+    ),
+    template!(
+        "python",
+        "sum_variant.py",
+        "Sum type constructors",
+        [
+            "variant_name",
+            "name",
+            "construct_fn",
+            "construct_params",
+            "construct_args",
+        ]
+    ),
+    template!(
+        "python",
+        "sum_payload.py",
+        "Sum type payload getters",
+        [
+            "variant_name",
+            "name",
+            "destruct_fn",
+            "destruct_decls",
+            "destruct_args",
+            "destruct_return",
+        ]
+    ),
+    template!(
+        "python",
+        "opaque_array.py",
+        "Arrays of opaque values",
+        ["rank", "shape_fn", "index_fn", "elem_class"]
+    ),
+    template!(
+        "python",
+        "record_array.py",
+        "Arrays of records, `zip` constructors",
+        ["zip_fn", "zip_params", "zip_call_args"]
+    ),
+    template!(
+        "python",
+        "entry.py",
+        "Entry points",
+        [
+            "name",
+            "doc",
+            "entry_fn",
+            "entry_params",
+            "out_decl",
+            "call_args",
+            "entry_return",
+        ]
+    ),
+    template!(
+        "go",
+        "context.go",
+        "Package clause, cgo preamble, `Error`, `Options` and `Context`",
+        [
+            "package",
+            "cgo_flags",
+            "header",
+            "backend",
+            "device_memory",
+            "backend_options",
+            "configure_backend",
+        ]
+    ),
+    template!(
+        "go",
+        "array.go",
+        "Arrays of primitive values",
+        [
+            "doc",
+            "class_name",
+            "raw_name",
+            "rank",
+            "go_type",
+            "ctype",
+            "shape_args",
+            "index_args",
+            "new_fn",
+            "free_fn",
+            "values_fn",
+            "shape_fn",
+            "index_fn",
+        ]
+    ),
+    template!(
+        "go",
+        "opaque.go",
+        "Opaque types, `members` holds the code generated by the other opaque templates",
+        [
+            "doc",
+            "class_name",
+            "raw_name",
+            "free_fn",
+            "store_fn",
+            "restore_fn",
+            "members",
+        ]
+    ),
+    template!(
+        "go",
+        "record.go",
+        "Record constructors",
+        [
+            "class_name",
+            "raw_name",
+            "new_fn",
+            "new_params",
+            "new_inputs",
+            "new_call_args",
+            "keep_alive",
+        ]
+    ),
+    template!(
+        "go",
+        "record_project.go",
+        "Record field accessors",
+        [
+            "class_name",
+            "field_name",
+            "project_name",
+            "project_fn",
+            "go_type",
+            "out_type",
+            "zero",
+            "out_return",
+        ]
+    ),
+    template!(
+        "go",
+        "sum.go",
+        "Sum types, `Variant`",
+        ["class_name", "variants", "variant_fn"]
+    ),
+    template!(
+        "go",
+        "sum_variant.go",
+        "Sum type constructors",
+        [
+            "class_name",
+            "raw_name",
+            "variant_name",
+            "name",
+            "construct_fn",
+            "construct_params",
+            "construct_inputs",
+            "construct_args",
+            "keep_alive",
+        ]
+    ),
+    template!(
+        "go",
+        "sum_payload.go",
+        "Sum type payload getters",
+        [
+            "class_name",
+            "variant_name",
+            "name",
+            "results",
+            "zero_return",
+            "destruct_fn",
+            "destruct_decls",
+            "destruct_args",
+            "destruct_return",
+        ]
+    ),
+    template!(
+        "go",
+        "opaque_array.go",
+        "Arrays of opaque values",
+        [
+            "class_name",
+            "rank",
+            "shape_fn",
+            "index_fn",
+            "index_args",
+            "elem_class",
+            "elem_raw_name",
+        ]
+    ),
+    template!(
+        "go",
+        "record_array.go",
+        "Arrays of records, `zip` constructors",
+        [
+            "class_name",
+            "raw_name",
+            "zip_fn",
+            "zip_params",
+            "zip_inputs",
+            "zip_call_args",
+            "keep_alive",
+        ]
+    ),
+    template!(
+        "go",
+        "entry.go",
+        "Entry points, methods of `Context`",
+        [
+            "name",
+            "doc",
+            "entry_fn",
+            "entry_params",
+            "results",
+            "zero_return",
+            "entry_inputs",
+            "out_decl",
+            "call_args",
+            "keep_alive",
+            "entry_return",
+        ]
+    ),
+    template!(
+        "julia",
+        "context.jl",
+        "Module header, library path, `FutharkError` and `Context`",
+        [
+            "module",
+            "backend",
+            "device_memory",
+            "library",
+            "library_env",
+            "extra_params",
+            "configure_backend",
+        ]
+    ),
+    template!(
+        "julia",
+        "array.jl",
+        "Arrays of primitive values, converted to and from `Array{T,N}`",
+        [
+            "doc",
+            "class_name",
+            "rank",
+            "elem_type",
+            "shape_types",
+            "shape_args",
+            "index_args",
+            "new_fn",
+            "free_fn",
+            "values_fn",
+            "shape_fn",
+            "index_fn",
+        ]
+    ),
+    template!(
+        "julia",
+        "opaque.jl",
+        "Opaque types, `members` holds the code generated by the other opaque templates",
+        [
+            "doc",
+            "class_name",
+            "free_fn",
+            "store_fn",
+            "restore_fn",
+            "members",
+        ]
+    ),
+    template!(
+        "julia",
+        "record.jl",
+        "Record constructors and properties",
+        [
+            "class_name",
+            "new_fn",
+            "new_params",
+            "new_typed_params",
+            "new_arg_types",
+            "new_call_args",
+            "preserve",
+            "properties",
+        ]
+    ),
+    template!(
+        "julia",
+        "record_project.jl",
+        "Record field accessors",
+        [
+            "class_name",
+            "field_symbol",
+            "project_fn",
+            "out_type",
+            "out_init",
+            "out_return",
+        ]
+    ),
+    template!(
+        "julia",
+        "sum.jl",
+        "Sum types, `variants` and `variant`",
+        ["class_name", "variants", "variant_fn", "example"]
+    ),
+    template!(
+        "julia",
+        "sum_variant.jl",
+        "Sum type constructors",
+        [
+            "class_name",
+            "variant_symbol",
+            "construct_fn",
+            "construct_params",
+            "construct_arg_types",
+            "construct_args",
+            "preserve",
+        ]
+    ),
+    template!(
+        "julia",
+        "sum_payload.jl",
+        "Sum type payload getters",
+        [
+            "class_name",
+            "variant_symbol",
+            "destruct_fn",
+            "destruct_decls",
+            "destruct_arg_types",
+            "destruct_args",
+            "destruct_return",
+        ]
+    ),
+    template!(
+        "julia",
+        "opaque_array.jl",
+        "Arrays of opaque values",
+        [
+            "class_name",
+            "rank",
+            "shape_fn",
+            "index_fn",
+            "index_types",
+            "index_args",
+            "elem_class",
+        ]
+    ),
+    template!(
+        "julia",
+        "record_array.jl",
+        "Arrays of records, `zip` constructors",
+        [
+            "class_name",
+            "zip_fn",
+            "zip_params",
+            "zip_typed_params",
+            "zip_arg_types",
+            "zip_call_args",
+            "preserve",
+            "properties",
+        ]
+    ),
+    template!(
+        "julia",
+        "entry.jl",
+        "Entry points, functions taking a `Context`",
+        [
+            "name",
+            "doc",
+            "entry_fn",
+            "entry_params",
+            "typed_params",
+            "out_decl",
+            "arg_types",
+            "call_args",
+            "preserve",
+            "entry_return",
+        ]
+    ),
+    template!("julia", "footer.jl", "Closes the module", ["module"]),
+    template!(
+        "zig",
+        "context.zig",
+        "C API declarations for the context, `Error`, `Options` and `Context`",
+        [
+            "backend",
+            "device_memory",
+            "backend_externs",
+            "backend_options",
+            "configure_backend",
+        ]
+    ),
+    template!(
+        "zig",
+        "array.zig",
+        "Arrays of primitive values, created from slices",
+        [
+            "doc",
+            "class_name",
+            "raw_name",
+            "externs",
+            "rank",
+            "elem_type",
+            "c_type",
+            "shape_args",
+            "index_args",
+            "out_return",
+            "new_fn",
+            "free_fn",
+            "values_fn",
+            "shape_fn",
+            "index_fn",
+        ]
+    ),
+    template!(
+        "zig",
+        "opaque.zig",
+        "Opaque types, `members` holds the code generated by the other opaque templates",
+        [
+            "doc",
+            "class_name",
+            "raw_name",
+            "externs",
+            "free_fn",
+            "store_fn",
+            "restore_fn",
+            "members",
+        ]
+    ),
+    template!(
+        "zig",
+        "record.zig",
+        "Record constructors",
+        ["class_name", "raw_name", "new_fn", "fields", "call_args"]
+    ),
+    template!(
+        "zig",
+        "record_project.zig",
+        "Record field accessors",
+        [
+            "class_name",
+            "field_name",
+            "project_name",
+            "project_fn",
+            "zig_type",
+            "out_type",
+            "out_wrap",
+            "out_return",
+        ]
+    ),
+    template!(
+        "zig",
+        "sum.zig",
+        "Sum types, `Variant` and `variant`",
+        ["class_name", "variants", "variant_fn"]
+    ),
+    template!(
+        "zig",
+        "sum_variant.zig",
+        "Sum type constructors",
+        [
+            "class_name",
+            "raw_name",
+            "variant_name",
+            "name",
+            "construct_fn",
+            "construct_params",
+            "construct_args",
+        ]
+    ),
+    template!(
+        "zig",
+        "sum_payload.zig",
+        "Sum type payload getters",
+        [
+            "class_name",
+            "variant_name",
+            "variant_field",
+            "name",
+            "payload_type",
+            "destruct_fn",
+            "destruct_decls",
+            "destruct_args",
+            "destruct_wrap",
+            "destruct_return",
+        ]
+    ),
+    template!(
+        "zig",
+        "opaque_array.zig",
+        "Arrays of opaque values",
+        [
+            "class_name",
+            "rank",
+            "shape_fn",
+            "index_fn",
+            "index_args",
+            "elem_class",
+            "elem_raw_name",
+        ]
+    ),
+    template!(
+        "zig",
+        "record_array.zig",
+        "Arrays of records, `zip` constructors",
+        ["class_name", "raw_name", "zip_fn", "fields", "call_args"]
+    ),
+    template!(
+        "zig",
+        "entry.zig",
+        "Entry points, functions taking a `Context` and returning an error union",
+        [
+            "extern_decl",
+            "doc",
+            "name",
+            "entry_fn",
+            "entry_params",
+            "results",
+            "out_decl",
+            "out_wrap",
+            "call_args",
+            "entry_return",
+        ]
+    ),
+    template!(
+        "csharp",
+        "context.cs",
+        "`[DllImport]` declarations, `FutharkException`, `Options`, `Context` and `FutharkValue`",
+        [
+            "namespace",
+            "library",
+            "backend",
+            "device_memory",
+            "declarations",
+            "backend_options",
+            "configure_backend",
+        ]
+    ),
+    template!(
+        "csharp",
+        "array.cs",
+        "Arrays of primitive values, converted to and from spans",
+        [
+            "doc",
+            "class_name",
+            "rank",
+            "elem_type",
+            "shape_params",
+            "shape_args",
+            "num_elements",
+            "data_ref",
+            "destination_ref",
+            "index_params",
+            "index_args",
+            "value_return",
+            "new_fn",
+            "free_fn",
+            "values_fn",
+            "shape_fn",
+            "index_fn",
+        ]
+    ),
+    template!(
+        "csharp",
+        "opaque.cs",
+        "Opaque types, `members` holds the code generated by the other opaque templates",
+        [
+            "doc",
+            "class_name",
+            "free_fn",
+            "store_fn",
+            "restore_fn",
+            "members",
+        ]
+    ),
+    template!(
+        "csharp",
+        "record.cs",
+        "Record constructors",
+        ["class_name", "new_fn", "params", "args", "call_args"]
+    ),
+    template!(
+        "csharp",
+        "record_project.cs",
+        "Record field properties",
+        [
+            "field_name",
+            "project_name",
+            "project_fn",
+            "cs_type",
+            "value_return",
+        ]
+    ),
+    template!(
+        "csharp",
+        "sum.cs",
+        "Sum types, `Variants` and `Variant`",
+        ["variants", "variant_fn"]
+    ),
+    template!(
+        "csharp",
+        "sum_variant.cs",
+        "Sum type constructors",
+        [
+            "class_name",
+            "variant_name",
+            "name",
+            "construct_fn",
+            "construct_params",
+            "construct_args",
+        ]
+    ),
+    template!(
+        "csharp",
+        "sum_payload.cs",
+        "Sum type payload getters",
+        [
+            "variant_name",
+            "variant",
+            "name",
+            "payload_type",
+            "destruct_fn",
+            "destruct_args",
+            "destruct_return",
+        ]
+    ),
+    template!(
+        "csharp",
+        "opaque_array.cs",
+        "Arrays of opaque values",
+        [
+            "rank",
+            "shape_fn",
+            "index_fn",
+            "index_params",
+            "index_args",
+            "elem_class",
+        ]
+    ),
+    template!(
+        "csharp",
+        "record_array.cs",
+        "Arrays of records, constructors taking one array per field",
+        ["class_name", "zip_fn", "params", "args", "call_args"]
+    ),
+    template!(
+        "csharp",
+        "entry.cs",
+        "Entry points, methods of `Context`",
+        [
+            "doc",
+            "results",
+            "name",
+            "entry_fn",
+            "entry_params",
+            "call_args",
+            "result_decl",
+            "entry_return",
+        ]
+    ),
+    template!(
+        "markdown",
+        "page.md",
+        "Page layout, including the table of contents",
+        ["title", "header", "toc", "sections"]
+    ),
+    template!(
+        "markdown",
+        "entry.md",
+        "Entry point reference",
+        [
+            "anchor",
+            "name",
+            "doc",
+            "cfun",
+            "inputs",
+            "outputs",
+            "tuning_params"
+        ]
+    ),
+    template!(
+        "markdown",
+        "record.md",
+        "Record reference",
+        ["anchor", "name", "doc", "raw_name", "fields"]
+    ),
+    template!(
+        "markdown",
+        "sum.md",
+        "Sum type reference",
+        ["anchor", "name", "doc", "raw_name", "variants"]
+    ),
+    template!(
+        "markdown",
+        "array.md",
+        "Array reference",
+        ["anchor", "name", "doc", "raw_name", "elemtype", "rank"]
+    ),
+    template!(
+        "markdown",
+        "opaque_array.md",
+        "Reference for arrays of opaque values",
+        ["anchor", "name", "doc", "raw_name", "elemtype", "rank"]
+    ),
+    template!(
+        "markdown",
+        "record_array.md",
+        "Reference for arrays of records",
+        ["anchor", "name", "doc", "raw_name", "elemtype", "rank", "fields"]
+    ),
+    template!(
+        "html",
+        "page.html",
+        "Page layout, including the table of contents",
+        ["title", "header", "toc", "sections"]
+    ),
+    template!(
+        "html",
+        "entry.html",
+        "Entry point reference",
+        [
+            "anchor",
+            "name",
+            "doc",
+            "cfun",
+            "inputs",
+            "outputs",
+            "tuning_params"
+        ]
+    ),
+    template!(
+        "html",
+        "record.html",
+        "Record reference",
+        ["anchor", "name", "doc", "raw_name", "fields"]
+    ),
+    template!(
+        "html",
+        "sum.html",
+        "Sum type reference",
+        ["anchor", "name", "doc", "raw_name", "variants"]
+    ),
+    template!(
+        "html",
+        "array.html",
+        "Array reference",
+        ["anchor", "name", "doc", "raw_name", "elemtype", "rank"]
+    ),
+    template!(
+        "html",
+        "opaque_array.html",
+        "Reference for arrays of opaque values",
+        ["anchor", "name", "doc", "raw_name", "elemtype", "rank"]
+    ),
+    template!(
+        "html",
+        "record_array.html",
+        "Reference for arrays of records",
+        ["anchor", "name", "doc", "raw_name", "elemtype", "rank", "fields"]
+    ),
+];
+
+/// Part of a parsed template
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Split a template into literal text and placeholders
+fn parse<'a>(template: &str, source: &'a str) -> Result<Vec<Part<'a>>, Error> {
+    let error = |message: String| Error::Template {
+        template: template.to_string(),
+        message,
+    };
+
+    let mut parts = Vec::new();
+    let mut rest = source;
+    while let Some(i) = rest.find(['{', '}']) {
+        if i > 0 {
+            parts.push(Part::Text(&rest[..i]));
+        }
+        let brace = &rest[i..i + 1];
+        rest = &rest[i + 1..];
+
+        if rest.starts_with(brace) {
+            parts.push(Part::Text(brace));
+            rest = &rest[1..];
+            continue;
+        }
+
+        if brace == "}" {
+            return Err(error(
+                "unmatched `}`, use `}}` for a literal brace".to_string(),
+            ));
+        }
+
+        let end = rest.find('}').ok_or_else(|| {
+            error("unterminated placeholder, use `{{` for a literal brace".to_string())
+        })?;
+        let name = &rest[..end];
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(error(format!(
+                "invalid placeholder `{{{name}}}`, use `{{{{` for a literal brace"
+            )));
+        }
+        parts.push(Part::Placeholder(name));
+        rest = &rest[end + 1..];
+    }
+
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    Ok(parts)
+}
+
+/// Templates used by the code generators
+///
+/// By default the built-in templates listed in `TEMPLATES` are used, any of them can be
+/// replaced using `Templates::with_override` or `Templates::load_dir`. Overrides are
+/// checked when they are added: they must parse and only use the placeholders listed in
+/// `TemplateInfo::placeholders`
+#[derive(Debug, Clone, Default)]
+pub struct Templates {
+    overrides: BTreeMap<(String, String), String>,
+}
+
+impl Templates {
+    /// Get a built-in template
+    pub fn info(language: &str, name: &str) -> Option<&'static TemplateInfo> {
+        TEMPLATES
+            .iter()
+            .find(|t| t.language == language && t.name == name)
+    }
+
+    fn info_or_error(language: &str, name: &str) -> Result<&'static TemplateInfo, Error> {
+        Templates::info(language, name).ok_or_else(|| Error::Template {
+            template: format!("{language}/{name}"),
+            message: "unknown template".to_string(),
+        })
+    }
+
+    /// Replace the template `name` for `language`
+    pub fn with_override(
+        mut self,
+        language: &str,
+        name: &str,
+        source: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let info = Templates::info_or_error(language, name)?;
+        let source = source.into();
+        let template = format!("{language}/{name}");
+        for part in parse(&template, &source)? {
+            if let Part::Placeholder(p) = part {
+                if !info.placeholders.contains(&p) {
+                    return Err(Error::Template {
+                        template,
+                        message: format!(
+                            "unknown placeholder `{{{p}}}`, expected one of: {}",
+                            info.placeholders.join(", ")
+                        ),
+                    });
+                }
+            }
+        }
+        self.overrides
+            .insert((language.to_string(), name.to_string()), source);
+        Ok(self)
+    }
+
+    /// Load overrides from `dir`, which contains one directory per language using the
+    /// same layout as the built-in templates, for example `dir/rust/array.rs`
+    ///
+    /// Missing files use the built-in template, files that don't match a built-in template
+    /// are reported as errors
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Templates, Error> {
+        let mut templates = Templates::default();
+        for language in std::fs::read_dir(dir)? {
+            let language = language?;
+            if !language.file_type()?.is_dir() {
+                continue;
+            }
+            let language_name = language.file_name().to_string_lossy().into_owned();
+            for file in std::fs::read_dir(language.path())? {
+                let file = file?;
+                let name = file.file_name().to_string_lossy().into_owned();
+                let source = std::fs::read_to_string(file.path())?;
+                templates = templates.with_override(&language_name, &name, source)?;
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Returns true if the template `name` for `language` has been replaced
+    pub fn is_overridden(&self, language: &str, name: &str) -> bool {
+        self.overrides
+            .contains_key(&(language.to_string(), name.to_string()))
+    }
+
+    /// Get the source of a template, using the override if there is one
+    pub fn source(&self, language: &str, name: &str) -> Result<&str, Error> {
+        let info = Templates::info_or_error(language, name)?;
+        Ok(self
+            .overrides
+            .get(&(language.to_string(), name.to_string()))
+            .map(String::as_str)
+            .unwrap_or(info.source))
+    }
+
+    /// Render a template, every placeholder it uses must be in `args`
+    pub fn render(
+        &self,
+        language: &str,
+        name: &str,
+        args: &[(&str, &dyn Display)],
+    ) -> Result<String, Error> {
+        let template = format!("{language}/{name}");
+        let mut out = String::new();
+        for part in parse(&template, self.source(language, name)?)? {
+            match part {
+                Part::Text(s) => out.push_str(s),
+                Part::Placeholder(p) => {
+                    let Some((_, value)) = args.iter().find(|(k, _)| *k == p) else {
+                        return Err(Error::Template {
+                            template,
+                            message: format!("no value for placeholder `{{{p}}}`"),
+                        });
+                    };
+                    out.push_str(&value.to_string());
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template_error(result: Result<impl std::fmt::Debug, Error>) -> (String, String) {
+        match result {
+            Err(Error::Template { template, message }) => (template, message),
+            other => panic!("expected a template error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn builtin_templates_use_listed_placeholders() {
+        for t in TEMPLATES {
+            Templates::default()
+                .with_override(t.language, t.name, t.source)
+                .unwrap_or_else(|e| panic!("{}/{}: {e:?}", t.language, t.name));
+        }
+    }
+
+    #[test]
+    fn unknown_placeholder() {
+        let (template, message) = template_error(Templates::default().with_override(
+            "rust",
+            "entry.rs",
+            "pub fn {entry_name}() {{ {body} }}",
+        ));
+        assert_eq!(template, "rust/entry.rs");
+        assert!(message.starts_with("unknown placeholder `{body}`"));
+    }
+
+    #[test]
+    fn missing_placeholder_value() {
+        let templates = Templates::default()
+            .with_override("ocaml", "entry.ml", "let {name} = {entry_fn}")
+            .unwrap();
+        let (template, message) =
+            template_error(templates.render("ocaml", "entry.ml", &[("name", &"f")]));
+        assert_eq!(template, "ocaml/entry.ml");
+        assert_eq!(message, "no value for placeholder `{entry_fn}`");
+    }
+
+    #[test]
+    fn escaped_braces() {
+        let templates = Templates::default()
+            .with_override("rust", "entry.rs", "fn {entry_name}() {{ {{}} }}")
+            .unwrap();
+        let code = templates
+            .render("rust", "entry.rs", &[("entry_name", &"f")])
+            .unwrap();
+        assert_eq!(code, "fn f() { {} }");
+    }
+
+    #[test]
+    fn unterminated_placeholder() {
+        let (_, message) = template_error(Templates::default().with_override(
+            "rust",
+            "entry.rs",
+            "fn {entry_name() {}",
+        ));
+        assert!(message.starts_with("invalid placeholder"));
+
+        let (_, message) =
+            template_error(Templates::default().with_override("rust", "entry.rs", "fn f() {"));
+        assert!(message.starts_with("unterminated placeholder"));
+
+        let (_, message) =
+            template_error(Templates::default().with_override("rust", "entry.rs", "fn f() }"));
+        assert!(message.starts_with("unmatched `}`"));
+    }
+
+    #[test]
+    fn load_dir_rejects_unknown_files() {
+        let dir =
+            std::env::temp_dir().join(format!("futhark-bindgen-templates-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("rust")).unwrap();
+        std::fs::write(dir.join("rust/entry.rs"), "fn {entry_name}() {{}}").unwrap();
+        let templates = Templates::load_dir(&dir).unwrap();
+        assert!(templates.is_overridden("rust", "entry.rs"));
+        assert!(!templates.is_overridden("rust", "array.rs"));
+
+        std::fs::write(dir.join("rust/entries.rs"), "").unwrap();
+        let (template, message) = template_error(Templates::load_dir(&dir));
+        assert_eq!(template, "rust/entries.rs");
+        assert_eq!(message, "unknown template");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,77 @@
+{doc}
+class {class_name} : public Handle<{raw_type}, {free_fn}> {{
+public:
+  using element_type = {elemtype};
+  using shape_type = std::array<std::int64_t, {rank}>;
+  static constexpr std::size_t rank = {rank};
+
+  using Handle::Handle;
+
+  /// Create a new array of shape `shape` and initialize it with `data`, which must hold
+  /// `num_elements(shape)` values
+  {class_name}(Context& ctx, const element_type* data, const shape_type& shape)
+      : Handle(ctx, {new_fn}(ctx.raw(), data, {dim_args})) {{
+    ctx.auto_sync();
+  }}
+
+  /// Create a new array of shape `shape` and initialize it with `data`
+  {class_name}(Context& ctx, const std::vector<{vector_elemtype}>& data, const shape_type& shape)
+      : {class_name}(ctx, checked_data(data.data(), data.size(), shape), shape) {{}}
+
+#if __cplusplus >= 202002L
+  /// Create a new array of shape `shape` and initialize it with `data`
+  {class_name}(Context& ctx, std::span<const element_type> data, const shape_type& shape)
+      : {class_name}(ctx, checked_data(data.data(), data.size(), shape), shape) {{}}
+#endif
+
+  /// Get the array shape
+  shape_type shape() const {{
+    const std::int64_t* dims = {shape_fn}(ctx_->raw(), ptr_);
+    shape_type shape;
+    for (std::size_t i = 0; i < rank; i++) {{
+      shape[i] = dims[i];
+    }}
+    return shape;
+  }}
+
+  /// Copy the values into `data`, which must hold `num_elements(shape())` values
+  void values(element_type* data) const {{
+    ctx_->check({values_fn}(ctx_->raw(), ptr_, data));
+    ctx_->auto_sync();
+  }}
+
+  /// Copy the values into a new vector
+  std::vector<{vector_elemtype}> values() const {{
+    std::vector<{vector_elemtype}> data(num_elements(shape()));
+    values(reinterpret_cast<element_type*>(data.data()));
+    return data;
+  }}
+
+#if __cplusplus >= 202002L
+  /// Copy the values into `data`
+  void values(std::span<element_type> data) const {{
+    if (data.size() != num_elements(shape())) {{
+      throw std::invalid_argument("data size doesn't match the array shape");
+    }}
+    values(data.data());
+  }}
+#endif
+
+  /// Get the value at `index`
+  element_type get(const shape_type& index) const {{
+    element_type out{{}};
+    ctx_->check({index_fn}(ctx_->raw(), &out, ptr_, {index_args}));
+    ctx_->auto_sync();
+    return out;
+  }}
+
+private:
+  template <typename T>
+  static const element_type* checked_data(const T* data, std::size_t size, const shape_type& shape) {{
+    static_assert(sizeof(T) == sizeof(element_type), "invalid element type");
+    if (size != num_elements(shape)) {{
+      throw std::invalid_argument("data size doesn't match the array shape");
+    }}
+    return reinterpret_cast<const element_type*>(data);
+  }}
+}};
@@ -0,0 +1,204 @@
+#pragma once
+
+#include <array>
+#include <cstddef>
+#include <cstdint>
+#include <cstdlib>
+#include <stdexcept>
+#include <string>
+#include <tuple>
+#include <utility>
+#include <vector>
+#if __cplusplus >= 202002L
+#include <span>
+#endif
+
+extern "C" {{
+#include "{header}"
+}}
+
+namespace {namespace} {{
+
+/// Futhark backend used to compile the library
+inline constexpr const char* BACKEND = "{backend}";
+
+/// True when arrays are stored in device memory, reading values requires a copy
+inline constexpr bool DEVICE_MEMORY = {device_memory};
+
+/// Error raised when a Futhark function fails, the message is taken from
+/// `futhark_context_get_error`
+class Error : public std::runtime_error {{
+public:
+  Error(int code, const std::string& message) : std::runtime_error(message), code_(code) {{}}
+
+  /// Error code returned by Futhark, `-1` when a function returned a null pointer
+  int code() const noexcept {{ return code_; }}
+
+private:
+  int code_;
+}};
+
+/// Context options
+struct Options {{
+  bool debug = false;
+  bool profile = false;
+  bool logging = false;
+  /// Sync the context after each operation
+  bool auto_sync = true;
+  /// Futhark cache file, unused if empty
+  std::string cache_file;
+{backend_options}
+}};
+
+{forward_declarations}
+
+/// Futhark context, every value holds a reference to the context it was created with so
+/// contexts can't be copied or moved
+class Context {{
+public:
+  /// Create a new context
+  explicit Context(const Options& options = Options()) : auto_sync_(options.auto_sync) {{
+    config_ = futhark_context_config_new();
+    if (config_ == nullptr) {{
+      throw Error(-1, "futhark_context_config_new failed");
+    }}
+    futhark_context_config_set_debugging(config_, options.debug);
+    futhark_context_config_set_profiling(config_, options.profile);
+    futhark_context_config_set_logging(config_, options.logging);
+    if (!options.cache_file.empty()) {{
+      futhark_context_config_set_cache_file(config_, options.cache_file.c_str());
+    }}
+{configure_backend}
+    context_ = futhark_context_new(config_);
+    if (context_ == nullptr) {{
+      futhark_context_config_free(config_);
+      throw Error(-1, "futhark_context_new failed");
+    }}
+  }}
+
+  ~Context() {{
+    futhark_context_sync(context_);
+    futhark_context_free(context_);
+    futhark_context_config_free(config_);
+  }}
+
+  Context(const Context&) = delete;
+  Context& operator=(const Context&) = delete;
+
+  /// Get the raw context pointer
+  futhark_context* raw() const noexcept {{ return context_; }}
+
+  /// Sync the context, if `auto_sync` is enabled this shouldn't be needed
+  void sync() const {{ check(futhark_context_sync(context_)); }}
+
+  /// Sync if `auto_sync` is enabled, otherwise this is a noop
+  void auto_sync() const {{
+    if (auto_sync_) {{
+      sync();
+    }}
+  }}
+
+  /// Clear Futhark caches
+  void clear_caches() const {{ check(futhark_context_clear_caches(context_)); }}
+
+  /// Pause Futhark profiling
+  void pause_profiling() const {{ futhark_context_pause_profiling(context_); }}
+
+  /// Resume profiling
+  void unpause_profiling() const {{ futhark_context_unpause_profiling(context_); }}
+
+  /// Get the profiling report
+  std::string report() const {{ return take_string(futhark_context_report(context_)); }}
+
+  /// Get and clear the last error message, empty if there is none
+  std::string get_error() const {{ return take_string(futhark_context_get_error(context_)); }}
+
+  /// Build an `Error` for `code` using the last error message
+  Error error(int code) const {{ return Error(code, get_error()); }}
+
+  /// Throw an `Error` if `code` isn't zero
+  void check(int code) const {{
+    if (code != 0) {{
+      throw error(code);
+    }}
+  }}
+
+  /// Throw an `Error` if `ptr` is null
+  template <typename T>
+  T* check(T* ptr) const {{
+    if (ptr == nullptr) {{
+      throw error(-1);
+    }}
+    return ptr;
+  }}
+{entry_declarations}
+
+private:
+  static std::string take_string(char* s) {{
+    if (s == nullptr) {{
+      return std::string();
+    }}
+    std::string r(s);
+    std::free(s);
+    return r;
+  }}
+
+  futhark_context_config* config_;
+  futhark_context* context_;
+  bool auto_sync_;
+}};
+
+/// Number of elements in an array of shape `shape`
+template <std::size_t N>
+std::size_t num_elements(const std::array<std::int64_t, N>& shape) {{
+  std::size_t n = 1;
+  for (std::int64_t d : shape) {{
+    n *= static_cast<std::size_t>(d);
+  }}
+  return n;
+}}
+
+/// Base class for values owned by a context, values can be moved but not copied and are
+/// freed by the destructor
+template <typename Raw, int (*Free)(futhark_context*, Raw*)>
+class Handle {{
+public:
+  /// Take ownership of `ptr`
+  Handle(Context& ctx, Raw* ptr) : ctx_(&ctx), ptr_(ctx.check(ptr)) {{}}
+
+  ~Handle() {{ reset(); }}
+
+  Handle(const Handle&) = delete;
+  Handle& operator=(const Handle&) = delete;
+
+  Handle(Handle&& other) noexcept : ctx_(other.ctx_), ptr_(std::exchange(other.ptr_, nullptr)) {{}}
+
+  Handle& operator=(Handle&& other) noexcept {{
+    if (this != &other) {{
+      reset();
+      ctx_ = other.ctx_;
+      ptr_ = std::exchange(other.ptr_, nullptr);
+    }}
+    return *this;
+  }}
+
+  /// Get the raw pointer, which is still owned by this value
+  Raw* raw() const noexcept {{ return ptr_; }}
+
+  /// Release ownership of the raw pointer, it must be freed by the caller
+  Raw* release() noexcept {{ return std::exchange(ptr_, nullptr); }}
+
+  /// Get the context used to create the value
+  Context& context() const noexcept {{ return *ctx_; }}
+
+protected:
+  void reset() noexcept {{
+    if (ptr_ != nullptr) {{
+      Free(ctx_->raw(), ptr_);
+      ptr_ = nullptr;
+    }}
+  }}
+
+  Context* ctx_;
+  Raw* ptr_;
+}};
@@ -0,0 +1,6 @@
+inline {return_type} Context::{name}({entry_params}) {{
+{out_decl}
+  check({entry_fn}({call_args}));
+  auto_sync();
+  return {entry_return};
+}}
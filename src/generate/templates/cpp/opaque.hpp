@@ -0,0 +1,20 @@
+{doc}
+class {class_name} : public Handle<{raw_type}, {free_fn}> {{
+public:
+  using Handle::Handle;
+
+  /// Serialize the value
+  std::vector<unsigned char> store() const {{
+    void* data = nullptr;
+    std::size_t size = 0;
+    ctx_->check({store_fn}(ctx_->raw(), ptr_, &data, &size));
+    std::vector<unsigned char> bytes(static_cast<unsigned char*>(data), static_cast<unsigned char*>(data) + size);
+    std::free(data);
+    return bytes;
+  }}
+
+  /// Restore a value serialized with `store`
+  static {class_name} restore(Context& ctx, const std::vector<unsigned char>& bytes) {{
+    return {class_name}(ctx, {restore_fn}(ctx.raw(), bytes.data()));
+  }}
+{members}}};
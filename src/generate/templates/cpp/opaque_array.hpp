@@ -0,0 +1,21 @@
+
+  using shape_type = std::array<std::int64_t, {rank}>;
+  static constexpr std::size_t rank = {rank};
+
+  /// Get the array shape
+  shape_type shape() const {{
+    const std::int64_t* dims = {shape_fn}(ctx_->raw(), ptr_);
+    shape_type shape;
+    for (std::size_t i = 0; i < rank; i++) {{
+      shape[i] = dims[i];
+    }}
+    return shape;
+  }}
+
+  /// Get the value at `index`
+  {elem_class} get(const shape_type& index) const {{
+    {elem_raw_type}* out = nullptr;
+    ctx_->check({index_fn}(ctx_->raw(), &out, ptr_, {index_args}));
+    ctx_->auto_sync();
+    return {elem_class}(*ctx_, out);
+  }}
@@ -0,0 +1,10 @@
+
+  /// Create a new record from its fields
+  {class_name}(Context& ctx, {new_params})
+      : Handle(ctx, [&] {{
+          {raw_type}* out = nullptr;
+          ctx.check({new_fn}(ctx.raw(), &out, {new_call_args}));
+          return out;
+        }}()) {{
+    ctx.auto_sync();
+  }}
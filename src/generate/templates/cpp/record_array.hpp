@@ -0,0 +1,10 @@
+
+  /// Create a new array from one array per field
+  {class_name}(Context& ctx, {zip_params})
+      : Handle(ctx, [&] {{
+          {raw_type}* out = nullptr;
+          ctx.check({zip_fn}(ctx.raw(), &out, {zip_call_args}));
+          return out;
+        }}()) {{
+    ctx.auto_sync();
+  }}
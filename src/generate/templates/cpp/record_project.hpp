@@ -0,0 +1,8 @@
+
+  /// Get the `{field_name}` field
+  {field_type} {project_name}() const {{
+    {out_decl}
+    ctx_->check({project_fn}(ctx_->raw(), &out, ptr_));
+    ctx_->auto_sync();
+    return {out_return};
+  }}
@@ -0,0 +1,8 @@
+
+  /// Sum type variants
+  enum class Variant {{ {variants} }};
+
+  /// Get the variant of the value
+  Variant variant() const {{
+    return static_cast<Variant>({variant_fn}(ctx_->raw(), ptr_));
+  }}
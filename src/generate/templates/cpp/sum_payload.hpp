@@ -0,0 +1,8 @@
+
+  /// Get the payload of the `#{variant_name}` variant, `variant()` must be `Variant::{variant}`
+  std::tuple<{payload_types}> get_{name}() const {{
+{destruct_decls}
+    ctx_->check({destruct_fn}(ctx_->raw(), {destruct_args}, ptr_));
+    ctx_->auto_sync();
+    return std::make_tuple({destruct_return});
+  }}
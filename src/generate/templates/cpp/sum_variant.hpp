@@ -0,0 +1,8 @@
+
+  /// Construct the `#{variant_name}` variant
+  static {class_name} {name}(Context& ctx{construct_params}) {{
+    {raw_type}* out = nullptr;
+    ctx.check({construct_fn}(ctx.raw(), &out{construct_args}));
+    ctx.auto_sync();
+    return {class_name}(ctx, out);
+  }}
@@ -0,0 +1,71 @@
+{doc}
+public sealed class {class_name} : FutharkValue
+{{
+    /// <summary>Number of dimensions</summary>
+    public const int Rank = {rank};
+
+    internal {class_name}(Context ctx, IntPtr ptr) : base(ctx, ptr)
+    {{
+    }}
+
+    /// <summary>Create a new array from values in row-major order, <c>data</c> must hold one value per element</summary>
+    public {class_name}(Context ctx, ReadOnlySpan<{elem_type}> data, {shape_params}) : base(ctx, New(ctx, data, {shape_args}))
+    {{
+        ctx.MaybeSync();
+    }}
+
+    private static IntPtr New(Context ctx, ReadOnlySpan<{elem_type}> data, {shape_params})
+    {{
+        if (data.Length != {num_elements})
+        {{
+            throw new ArgumentException("the length of data doesn't match the shape", nameof(data));
+        }}
+        return Native.{new_fn}(ctx, ref {data_ref}, {shape_args});
+    }}
+
+    private protected override int Free(IntPtr ctx, IntPtr ptr)
+    {{
+        return Native.{free_fn}(ctx, ptr);
+    }}
+
+    /// <summary>Array shape</summary>
+    public long[] Shape
+    {{
+        get
+        {{
+            var shape = new long[Rank];
+            Marshal.Copy(Native.{shape_fn}(Context, this), shape, 0, Rank);
+            return shape;
+        }}
+    }}
+
+    /// <summary>Copy the values into a new array, in row-major order</summary>
+    public {elem_type}[] ToArray()
+    {{
+        var data = new {elem_type}[checked((int)NumElements(Shape))];
+        CopyTo(data);
+        return data;
+    }}
+
+    /// <summary>Copy the values into <c>destination</c> in row-major order, it must hold at least one value per element</summary>
+    public void CopyTo(Span<{elem_type}> destination)
+    {{
+        if (destination.Length < NumElements(Shape))
+        {{
+            throw new ArgumentException("destination is shorter than the array", nameof(destination));
+        }}
+        Context.Check(Native.{values_fn}(Context, this, ref {destination_ref}));
+        Context.Sync();
+    }}
+
+    /// <summary>Get a single value</summary>
+    public {elem_type} this[{index_params}]
+    {{
+        get
+        {{
+            Context.Check(Native.{index_fn}(Context, out var value, this, {index_args}));
+            Context.MaybeSync();
+            return {value_return};
+        }}
+    }}
+}}
@@ -0,0 +1,307 @@
+#nullable enable
+using System;
+using System.Collections.Generic;
+using System.Runtime.InteropServices;
+
+namespace {namespace};
+
+/// <summary>Declarations of the Futhark C API</summary>
+internal static class Native
+{{
+    internal const string Library = "{library}";
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern IntPtr futhark_context_config_new();
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern void futhark_context_config_free(IntPtr config);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern void futhark_context_config_set_debugging(IntPtr config, int flag);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern void futhark_context_config_set_profiling(IntPtr config, int flag);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern void futhark_context_config_set_logging(IntPtr config, int flag);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern void futhark_context_config_set_cache_file(IntPtr config, IntPtr path);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern IntPtr futhark_context_new(IntPtr config);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern void futhark_context_free(IntPtr ctx);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern int futhark_context_sync(IntPtr ctx);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern int futhark_context_sync(Context ctx);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern int futhark_context_clear_caches(Context ctx);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern IntPtr futhark_context_get_error(Context ctx);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern IntPtr futhark_context_report(Context ctx);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern void futhark_context_pause_profiling(Context ctx);
+
+    [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+    internal static extern void futhark_context_unpause_profiling(Context ctx);
+
+    [DllImport("libc", EntryPoint = "free", CallingConvention = CallingConvention.Cdecl)]
+    private static extern void free_unix(IntPtr ptr);
+
+    [DllImport("msvcrt", EntryPoint = "free", CallingConvention = CallingConvention.Cdecl)]
+    private static extern void free_windows(IntPtr ptr);
+
+    /// <summary>Free memory allocated by the C library</summary>
+    internal static void Free(IntPtr ptr)
+    {{
+        if (OperatingSystem.IsWindows())
+        {{
+            free_windows(ptr);
+        }}
+        else
+        {{
+            free_unix(ptr);
+        }}
+    }}
+{declarations}}}
+
+/// <summary>Thrown when a Futhark function fails, the message is taken from the context error</summary>
+public class FutharkException : Exception
+{{
+    /// <summary>Error code returned by the C function, -1 when a function returned a null pointer</summary>
+    public int Code {{ get; }}
+
+    /// <summary>Create a new exception</summary>
+    public FutharkException(int code, string message) : base(message)
+    {{
+        Code = code;
+    }}
+}}
+
+/// <summary>Options used to create a <see cref="Context"/></summary>
+public sealed class Options
+{{
+    /// <summary>Enable debugging output</summary>
+    public bool Debug {{ get; set; }}
+
+    /// <summary>Enable profiling</summary>
+    public bool Profile {{ get; set; }}
+
+    /// <summary>Enable logging</summary>
+    public bool Logging {{ get; set; }}
+
+    /// <summary>Path of the kernel cache</summary>
+    public string? CacheFile {{ get; set; }}
+
+    /// <summary>Sync after each call, when disabled <see cref="Context.Sync"/> must be called before reading results</summary>
+    public bool AutoSync {{ get; set; }} = true;
+{backend_options}}}
+
+/// <summary>
+/// Futhark context, values keep a reference to the context used to create them so it's only
+/// freed once they are
+/// </summary>
+public sealed partial class Context : SafeHandle
+{{
+    /// <summary>Futhark backend used to compile the library</summary>
+    public const string Backend = "{backend}";
+
+    /// <summary>True when arrays are stored in device memory, reading values requires a copy</summary>
+    public const bool DeviceMemory = {device_memory};
+
+    private IntPtr config;
+    private readonly List<IntPtr> strings = new();
+
+    /// <summary>Sync after each call, when disabled <see cref="Sync"/> must be called before reading results</summary>
+    public bool AutoSync {{ get; set; }}
+
+    /// <summary>Create a new context, free it using <c>Dispose</c></summary>
+    public Context(Options? options = null) : base(IntPtr.Zero, true)
+    {{
+        options ??= new Options();
+        AutoSync = options.AutoSync;
+        config = Native.futhark_context_config_new();
+        if (config == IntPtr.Zero)
+        {{
+            throw new FutharkException(-1, "futhark_context_config_new failed");
+        }}
+        try
+        {{
+            Native.futhark_context_config_set_debugging(config, options.Debug ? 1 : 0);
+            Native.futhark_context_config_set_profiling(config, options.Profile ? 1 : 0);
+            Native.futhark_context_config_set_logging(config, options.Logging ? 1 : 0);
+            if (options.CacheFile != null)
+            {{
+                Native.futhark_context_config_set_cache_file(config, KeepString(options.CacheFile));
+            }}
+{configure_backend}            var ptr = Native.futhark_context_new(config);
+            if (ptr == IntPtr.Zero)
+            {{
+                throw new FutharkException(-1, "futhark_context_new failed");
+            }}
+            SetHandle(ptr);
+        }}
+        catch
+        {{
+            FreeConfig();
+            throw;
+        }}
+    }}
+
+    /// <inheritdoc/>
+    public override bool IsInvalid => handle == IntPtr.Zero;
+
+    /// <inheritdoc/>
+    protected override bool ReleaseHandle()
+    {{
+        Native.futhark_context_sync(handle);
+        Native.futhark_context_free(handle);
+        FreeConfig();
+        return true;
+    }}
+
+    /// <summary>Sync the context, if <see cref="AutoSync"/> is enabled this shouldn't be needed</summary>
+    public void Sync()
+    {{
+        Check(Native.futhark_context_sync(this));
+    }}
+
+    /// <summary>Clear Futhark caches</summary>
+    public void ClearCaches()
+    {{
+        Check(Native.futhark_context_clear_caches(this));
+    }}
+
+    /// <summary>Pause Futhark profiling</summary>
+    public void PauseProfiling()
+    {{
+        Native.futhark_context_pause_profiling(this);
+    }}
+
+    /// <summary>Resume profiling</summary>
+    public void UnpauseProfiling()
+    {{
+        Native.futhark_context_unpause_profiling(this);
+    }}
+
+    /// <summary>Get the profiling report</summary>
+    public string? Report()
+    {{
+        return TakeString(Native.futhark_context_report(this));
+    }}
+
+    /// <summary>Get and clear the last error message</summary>
+    public string? GetError()
+    {{
+        return TakeString(Native.futhark_context_get_error(this));
+    }}
+
+    internal FutharkException Error(int code)
+    {{
+        return new FutharkException(code, GetError() ?? "");
+    }}
+
+    internal void Check(int rc)
+    {{
+        if (rc != 0)
+        {{
+            throw Error(rc);
+        }}
+    }}
+
+    internal void MaybeSync()
+    {{
+        if (AutoSync)
+        {{
+            Sync();
+        }}
+    }}
+
+    /// <summary>Copy a string to unmanaged memory that lives as long as the context</summary>
+    private IntPtr KeepString(string s)
+    {{
+        var ptr = Marshal.StringToCoTaskMemUTF8(s);
+        strings.Add(ptr);
+        return ptr;
+    }}
+
+    private void FreeConfig()
+    {{
+        if (config != IntPtr.Zero)
+        {{
+            Native.futhark_context_config_free(config);
+            config = IntPtr.Zero;
+        }}
+        foreach (var ptr in strings)
+        {{
+            Marshal.FreeCoTaskMem(ptr);
+        }}
+        strings.Clear();
+    }}
+
+    private static string? TakeString(IntPtr ptr)
+    {{
+        if (ptr == IntPtr.Zero)
+        {{
+            return null;
+        }}
+        var s = Marshal.PtrToStringUTF8(ptr);
+        Native.Free(ptr);
+        return s;
+    }}
+}}
+
+/// <summary>
+/// Base class of the generated array and opaque types, values are freed using <c>Dispose</c>
+/// or by the garbage collector
+/// </summary>
+public abstract class FutharkValue : SafeHandle
+{{
+    /// <summary>Context used to create the value</summary>
+    public Context Context {{ get; }}
+
+    private protected FutharkValue(Context ctx, IntPtr ptr) : base(IntPtr.Zero, true)
+    {{
+        if (ptr == IntPtr.Zero)
+        {{
+            throw ctx.Error(-1);
+        }}
+        var added = false;
+        ctx.DangerousAddRef(ref added);
+        Context = ctx;
+        SetHandle(ptr);
+    }}
+
+    /// <inheritdoc/>
+    public override bool IsInvalid => handle == IntPtr.Zero;
+
+    /// <inheritdoc/>
+    protected override bool ReleaseHandle()
+    {{
+        var rc = Free(Context.DangerousGetHandle(), handle);
+        Context.DangerousRelease();
+        return rc == 0;
+    }}
+
+    private protected abstract int Free(IntPtr ctx, IntPtr ptr);
+
+    private protected static long NumElements(long[] shape)
+    {{
+        var len = 1L;
+        foreach (var dim in shape)
+        {{
+            len *= dim;
+        }}
+        return len;
+    }}
+}}
@@ -0,0 +1,7 @@
+
+{doc}
+    public {results} {name}({entry_params})
+    {{
+        Check(Native.{entry_fn}(this{call_args}));
+{result_decl}        MaybeSync();{entry_return}
+    }}
\ No newline at end of file
@@ -0,0 +1,36 @@
+{doc}
+public sealed class {class_name} : FutharkValue
+{{
+    internal {class_name}(Context ctx, IntPtr ptr) : base(ctx, ptr)
+    {{
+    }}
+
+    private protected override int Free(IntPtr ctx, IntPtr ptr)
+    {{
+        return Native.{free_fn}(ctx, ptr);
+    }}
+
+    /// <summary>Serialize the value</summary>
+    public byte[] Store()
+    {{
+        Context.Check(Native.{store_fn}(Context, this, IntPtr.Zero, out var size));
+        var data = new byte[checked((int)size)];
+        var pin = GCHandle.Alloc(data, GCHandleType.Pinned);
+        try
+        {{
+            var ptr = pin.AddrOfPinnedObject();
+            Context.Check(Native.{store_fn}(Context, this, ref ptr, out size));
+        }}
+        finally
+        {{
+            pin.Free();
+        }}
+        return data;
+    }}
+
+    /// <summary>Restore a value serialized with <see cref="Store"/></summary>
+    public static {class_name} Restore(Context ctx, byte[] data)
+    {{
+        return new {class_name}(ctx, Native.{restore_fn}(ctx, data));
+    }}
+{members}}}
@@ -0,0 +1,26 @@
+
+    /// <summary>Number of dimensions</summary>
+    public const int Rank = {rank};
+
+    /// <summary>Array shape</summary>
+    public long[] Shape
+    {{
+        get
+        {{
+            var shape = new long[Rank];
+            Marshal.Copy(Native.{shape_fn}(Context, this), shape, 0, Rank);
+            return shape;
+        }}
+    }}
+
+    /// <summary>Get a single value</summary>
+    public {elem_class} this[{index_params}]
+    {{
+        get
+        {{
+            Context.Check(Native.{index_fn}(Context, out var ptr, this, {index_args}));
+            var result = new {elem_class}(Context, ptr);
+            Context.MaybeSync();
+            return result;
+        }}
+    }}
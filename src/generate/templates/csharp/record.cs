@@ -0,0 +1,12 @@
+
+    /// <summary>Create a new record from its fields</summary>
+    public {class_name}(Context ctx{params}) : base(ctx, New(ctx{args}))
+    {{
+        ctx.MaybeSync();
+    }}
+
+    private static IntPtr New(Context ctx{params})
+    {{
+        ctx.Check(Native.{new_fn}(ctx, out var ptr{call_args}));
+        return ptr;
+    }}
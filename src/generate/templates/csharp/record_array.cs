@@ -0,0 +1,12 @@
+
+    /// <summary>Create a new array from one array per field</summary>
+    public {class_name}(Context ctx{params}) : base(ctx, Zip(ctx{args}))
+    {{
+        ctx.MaybeSync();
+    }}
+
+    private static IntPtr Zip(Context ctx{params})
+    {{
+        ctx.Check(Native.{zip_fn}(ctx, out var ptr{call_args}));
+        return ptr;
+    }}
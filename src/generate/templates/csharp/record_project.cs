@@ -0,0 +1,12 @@
+
+    /// <summary>The <c>{field_name}</c> field</summary>
+    public {cs_type} {project_name}
+    {{
+        get
+        {{
+            Context.Check(Native.{project_fn}(Context, out var value, this));
+            var result = {value_return};
+            Context.MaybeSync();
+            return result;
+        }}
+    }}
@@ -0,0 +1,8 @@
+
+    /// <summary>Variants, in the order used by the C API</summary>
+    public enum Variants
+    {{
+{variants}    }}
+
+    /// <summary>The variant of the value</summary>
+    public Variants Variant => (Variants)Native.{variant_fn}(Context, this);
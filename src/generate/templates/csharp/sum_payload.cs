@@ -0,0 +1,9 @@
+
+    /// <summary>Get the payload of the <c>#{variant_name}</c> variant, <see cref="Variant"/> must be <c>Variants.{variant}</c></summary>
+    public {payload_type} {name}()
+    {{
+        Context.Check(Native.{destruct_fn}(Context{destruct_args}, this));
+        var result = {destruct_return};
+        Context.MaybeSync();
+        return result;
+    }}
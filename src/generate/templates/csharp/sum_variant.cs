@@ -0,0 +1,9 @@
+
+    /// <summary>Construct the <c>#{variant_name}</c> variant</summary>
+    public static {class_name} {name}(Context ctx{construct_params})
+    {{
+        ctx.Check(Native.{construct_fn}(ctx, out var ptr{construct_args}));
+        var result = new {class_name}(ctx, ptr);
+        ctx.MaybeSync();
+        return result;
+    }}
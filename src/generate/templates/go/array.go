@@ -0,0 +1,100 @@
+{doc}
+type {class_name} struct {{
+	ctx *Context
+	ptr *C.struct_{raw_name}
+}}
+
+func wrap{class_name}(ctx *Context, ptr *C.struct_{raw_name}) *{class_name} {{
+	v := &{class_name}{{ctx: ctx, ptr: ptr}}
+	runtime.SetFinalizer(v, (*{class_name}).Free)
+	return v
+}}
+
+func (v *{class_name}) raw() (*C.struct_{raw_name}, error) {{
+	if v.ptr == nil || v.ctx.context == nil {{
+		return nil, ErrFreed
+	}}
+	return v.ptr, nil
+}}
+
+// Free the array, it can't be used afterwards
+func (v *{class_name}) Free() {{
+	if v.ptr != nil && v.ctx.context != nil {{
+		C.{free_fn}(v.ctx.context, v.ptr)
+	}}
+	v.ptr = nil
+	runtime.SetFinalizer(v, nil)
+}}
+
+// New{class_name} creates a new array from data in row-major order, the product of shape
+// must be len(data)
+func New{class_name}(ctx *Context, data []{go_type}, shape [{rank}]int64) (*{class_name}, error) {{
+	c, err := ctx.raw()
+	if err != nil {{
+		return nil, err
+	}}
+	if numElements(shape[:]) != int64(len(data)) {{
+		return nil, fmt.Errorf("futhark: shape %v doesn't match %d elements", shape, len(data))
+	}}
+	var p *C.{ctype}
+	if len(data) > 0 {{
+		p = (*C.{ctype})(unsafe.Pointer(&data[0]))
+	}}
+	ptr := C.{new_fn}(c, p, {shape_args})
+	if ptr == nil {{
+		return nil, ctx.lastError()
+	}}
+	v := wrap{class_name}(ctx, ptr)
+	err = ctx.maybeSync()
+	runtime.KeepAlive(data)
+	return v, err
+}}
+
+// Shape returns the array dimensions
+func (v *{class_name}) Shape() ([{rank}]int64, error) {{
+	var shape [{rank}]int64
+	ptr, err := v.raw()
+	if err != nil {{
+		return shape, err
+	}}
+	dims := unsafe.Slice(C.{shape_fn}(v.ctx.context, ptr), {rank})
+	for i, d := range dims {{
+		shape[i] = int64(d)
+	}}
+	runtime.KeepAlive(v)
+	return shape, nil
+}}
+
+// Values copies the array into a new slice, in row-major order
+func (v *{class_name}) Values() ([]{go_type}, error) {{
+	shape, err := v.Shape()
+	if err != nil {{
+		return nil, err
+	}}
+	data := make([]{go_type}, numElements(shape[:]))
+	var p *C.{ctype}
+	if len(data) > 0 {{
+		p = (*C.{ctype})(unsafe.Pointer(&data[0]))
+	}}
+	rc := C.{values_fn}(v.ctx.context, v.ptr, p)
+	runtime.KeepAlive(v)
+	if err := v.ctx.check(rc); err != nil {{
+		return nil, err
+	}}
+	return data, v.ctx.Sync()
+}}
+
+// Index returns a single value, index must have one integer per dimension
+func (v *{class_name}) Index(index [{rank}]int64) ({go_type}, error) {{
+	var out C.{ctype}
+	ptr, err := v.raw()
+	if err != nil {{
+		return {go_type}(out), err
+	}}
+	rc := C.{index_fn}(v.ctx.context, &out, ptr, {index_args})
+	runtime.KeepAlive(v)
+	if err := v.ctx.check(rc); err != nil {{
+		return {go_type}(out), err
+	}}
+	return {go_type}(out), v.ctx.maybeSync()
+}}
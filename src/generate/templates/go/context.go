@@ -0,0 +1,214 @@
+package {package}
+
+/*
+{cgo_flags}
+#include <stdlib.h>
+#include "{header}"
+*/
+import "C"
+
+import (
+	"errors"
+	"fmt"
+	"runtime"
+	"unsafe"
+)
+
+// Backend is the Futhark backend used to compile the library
+const Backend = "{backend}"
+
+// DeviceMemory is true when arrays are stored in device memory, reading values requires a copy
+const DeviceMemory = {device_memory}
+
+// ErrFreed is returned when a freed context or value is used
+var ErrFreed = errors.New("futhark: use of a freed value")
+
+// Error is returned when a Futhark function fails, the message is taken from
+// futhark_context_get_error
+type Error struct {{
+	// Code returned by Futhark, -1 when a function returned a null pointer
+	Code int
+
+	// Message returned by futhark_context_get_error
+	Message string
+}}
+
+func (e *Error) Error() string {{
+	if e.Message == "" {{
+		return fmt.Sprintf("futhark error code: %d", e.Code)
+	}}
+	return e.Message
+}}
+
+// Options used to create a Context, the zero value selects the defaults
+type Options struct {{
+	// Debug enables debugging output
+	Debug bool
+
+	// Profile enables profiling
+	Profile bool
+
+	// Logging enables logging
+	Logging bool
+
+	// CacheFile is the path of the kernel cache, unused if empty
+	CacheFile string
+
+	// NoAutoSync disables syncing after each call, Sync must be called before reading results
+	NoAutoSync bool
+{backend_options}}}
+
+// Context is a Futhark context, values keep a reference to the context used to create them
+type Context struct {{
+	config   *C.struct_futhark_context_config
+	context  *C.struct_futhark_context
+	autoSync bool
+	strings  []*C.char
+}}
+
+// NewContext creates a new context, options may be nil
+func NewContext(options *Options) (*Context, error) {{
+	if options == nil {{
+		options = &Options{{}}
+	}}
+	ctx := &Context{{autoSync: !options.NoAutoSync}}
+	ctx.config = C.futhark_context_config_new()
+	if ctx.config == nil {{
+		return nil, &Error{{Code: -1, Message: "futhark_context_config_new failed"}}
+	}}
+	C.futhark_context_config_set_debugging(ctx.config, cInt(options.Debug))
+	C.futhark_context_config_set_profiling(ctx.config, cInt(options.Profile))
+	C.futhark_context_config_set_logging(ctx.config, cInt(options.Logging))
+	if options.CacheFile != "" {{
+		C.futhark_context_config_set_cache_file(ctx.config, ctx.cString(options.CacheFile))
+	}}
+{configure_backend}
+	ctx.context = C.futhark_context_new(ctx.config)
+	if ctx.context == nil {{
+		ctx.Free()
+		return nil, &Error{{Code: -1, Message: "futhark_context_new failed"}}
+	}}
+	runtime.SetFinalizer(ctx, (*Context).Free)
+	return ctx, nil
+}}
+
+// Free the context, values created with it can't be used afterwards
+func (ctx *Context) Free() {{
+	if ctx.context != nil {{
+		C.futhark_context_sync(ctx.context)
+		C.futhark_context_free(ctx.context)
+		ctx.context = nil
+	}}
+	if ctx.config != nil {{
+		C.futhark_context_config_free(ctx.config)
+		ctx.config = nil
+	}}
+	for _, s := range ctx.strings {{
+		C.free(unsafe.Pointer(s))
+	}}
+	ctx.strings = nil
+	runtime.SetFinalizer(ctx, nil)
+}}
+
+// Sync the context, if auto sync is enabled this shouldn't be needed
+func (ctx *Context) Sync() error {{
+	c, err := ctx.raw()
+	if err != nil {{
+		return err
+	}}
+	return ctx.check(C.futhark_context_sync(c))
+}}
+
+// ClearCaches clears Futhark caches
+func (ctx *Context) ClearCaches() error {{
+	c, err := ctx.raw()
+	if err != nil {{
+		return err
+	}}
+	return ctx.check(C.futhark_context_clear_caches(c))
+}}
+
+// PauseProfiling pauses Futhark profiling
+func (ctx *Context) PauseProfiling() {{
+	if ctx.context != nil {{
+		C.futhark_context_pause_profiling(ctx.context)
+	}}
+}}
+
+// UnpauseProfiling resumes profiling
+func (ctx *Context) UnpauseProfiling() {{
+	if ctx.context != nil {{
+		C.futhark_context_unpause_profiling(ctx.context)
+	}}
+}}
+
+// Report returns the profiling report
+func (ctx *Context) Report() string {{
+	if ctx.context == nil {{
+		return ""
+	}}
+	return takeString(C.futhark_context_report(ctx.context))
+}}
+
+// GetError returns and clears the last error message, or an empty string
+func (ctx *Context) GetError() string {{
+	if ctx.context == nil {{
+		return ""
+	}}
+	return takeString(C.futhark_context_get_error(ctx.context))
+}}
+
+func (ctx *Context) raw() (*C.struct_futhark_context, error) {{
+	if ctx.context == nil {{
+		return nil, ErrFreed
+	}}
+	return ctx.context, nil
+}}
+
+// cString converts s to a C string that lives as long as the context
+func (ctx *Context) cString(s string) *C.char {{
+	cs := C.CString(s)
+	ctx.strings = append(ctx.strings, cs)
+	return cs
+}}
+
+func (ctx *Context) check(rc C.int) error {{
+	if rc != 0 {{
+		return &Error{{Code: int(rc), Message: ctx.GetError()}}
+	}}
+	return nil
+}}
+
+func (ctx *Context) lastError() error {{
+	return &Error{{Code: -1, Message: ctx.GetError()}}
+}}
+
+func (ctx *Context) maybeSync() error {{
+	if ctx.autoSync {{
+		return ctx.Sync()
+	}}
+	return nil
+}}
+
+func takeString(s *C.char) string {{
+	if s == nil {{
+		return ""
+	}}
+	defer C.free(unsafe.Pointer(s))
+	return C.GoString(s)
+}}
+
+func cInt(b bool) C.int {{
+	if b {{
+		return 1
+	}}
+	return 0
+}}
+
+func numElements(shape []int64) int64 {{
+	n := int64(1)
+	for _, d := range shape {{
+		n *= d
+	}}
+	return n
+}}
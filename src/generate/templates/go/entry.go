@@ -0,0 +1,12 @@
+{doc}
+func (ctx *Context) {name}({entry_params}) {results} {{
+	c, err := ctx.raw()
+	if err != nil {{
+		return {zero_return}err
+	}}
+{entry_inputs}{out_decl}	rc := C.{entry_fn}(c{call_args})
+{keep_alive}	if err := ctx.check(rc); err != nil {{
+		return {zero_return}err
+	}}
+	return {entry_return}ctx.maybeSync()
+}}
@@ -0,0 +1,61 @@
+{doc}
+type {class_name} struct {{
+	ctx *Context
+	ptr *C.struct_{raw_name}
+}}
+
+func wrap{class_name}(ctx *Context, ptr *C.struct_{raw_name}) *{class_name} {{
+	v := &{class_name}{{ctx: ctx, ptr: ptr}}
+	runtime.SetFinalizer(v, (*{class_name}).Free)
+	return v
+}}
+
+func (v *{class_name}) raw() (*C.struct_{raw_name}, error) {{
+	if v.ptr == nil || v.ctx.context == nil {{
+		return nil, ErrFreed
+	}}
+	return v.ptr, nil
+}}
+
+// Free the value, it can't be used afterwards
+func (v *{class_name}) Free() {{
+	if v.ptr != nil && v.ctx.context != nil {{
+		C.{free_fn}(v.ctx.context, v.ptr)
+	}}
+	v.ptr = nil
+	runtime.SetFinalizer(v, nil)
+}}
+
+// Store serializes the value, it can be restored with Restore{class_name}
+func (v *{class_name}) Store() ([]byte, error) {{
+	ptr, err := v.raw()
+	if err != nil {{
+		return nil, err
+	}}
+	var data unsafe.Pointer
+	var size C.size_t
+	rc := C.{store_fn}(v.ctx.context, ptr, &data, &size)
+	runtime.KeepAlive(v)
+	if err := v.ctx.check(rc); err != nil {{
+		return nil, err
+	}}
+	defer C.free(data)
+	return C.GoBytes(data, C.int(size)), nil
+}}
+
+// Restore{class_name} restores a value serialized with Store
+func Restore{class_name}(ctx *Context, data []byte) (*{class_name}, error) {{
+	c, err := ctx.raw()
+	if err != nil {{
+		return nil, err
+	}}
+	if len(data) == 0 {{
+		return nil, &Error{{Code: -1, Message: "futhark: no data to restore"}}
+	}}
+	ptr := C.{restore_fn}(c, unsafe.Pointer(&data[0]))
+	if ptr == nil {{
+		return nil, ctx.lastError()
+	}}
+	return wrap{class_name}(ctx, ptr), nil
+}}
+{members}
\ No newline at end of file
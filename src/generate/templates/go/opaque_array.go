@@ -0,0 +1,30 @@
+
+// Shape returns the array dimensions
+func (v *{class_name}) Shape() ([{rank}]int64, error) {{
+	var shape [{rank}]int64
+	ptr, err := v.raw()
+	if err != nil {{
+		return shape, err
+	}}
+	dims := unsafe.Slice(C.{shape_fn}(v.ctx.context, ptr), {rank})
+	for i, d := range dims {{
+		shape[i] = int64(d)
+	}}
+	runtime.KeepAlive(v)
+	return shape, nil
+}}
+
+// Index returns a single element, index must have one integer per dimension
+func (v *{class_name}) Index(index [{rank}]int64) (*{elem_class}, error) {{
+	ptr, err := v.raw()
+	if err != nil {{
+		return nil, err
+	}}
+	var out *C.struct_{elem_raw_name}
+	rc := C.{index_fn}(v.ctx.context, &out, ptr, {index_args})
+	runtime.KeepAlive(v)
+	if err := v.ctx.check(rc); err != nil {{
+		return nil, err
+	}}
+	return wrap{elem_class}(v.ctx, out), v.ctx.maybeSync()
+}}
@@ -0,0 +1,14 @@
+
+// New{class_name} creates a new record from its fields
+func New{class_name}(ctx *Context{new_params}) (*{class_name}, error) {{
+	c, err := ctx.raw()
+	if err != nil {{
+		return nil, err
+	}}
+{new_inputs}	var out *C.struct_{raw_name}
+	rc := C.{new_fn}(c, &out{new_call_args})
+{keep_alive}	if err := ctx.check(rc); err != nil {{
+		return nil, err
+	}}
+	return wrap{class_name}(ctx, out), ctx.maybeSync()
+}}
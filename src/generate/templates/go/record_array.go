@@ -0,0 +1,14 @@
+
+// New{class_name} creates a new array from one array per field
+func New{class_name}(ctx *Context{zip_params}) (*{class_name}, error) {{
+	c, err := ctx.raw()
+	if err != nil {{
+		return nil, err
+	}}
+{zip_inputs}	var out *C.struct_{raw_name}
+	rc := C.{zip_fn}(c, &out{zip_call_args})
+{keep_alive}	if err := ctx.check(rc); err != nil {{
+		return nil, err
+	}}
+	return wrap{class_name}(ctx, out), ctx.maybeSync()
+}}
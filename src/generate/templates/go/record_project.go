@@ -0,0 +1,15 @@
+
+// {project_name} returns the field {field_name}
+func (v *{class_name}) {project_name}() ({go_type}, error) {{
+	var out {out_type}
+	ptr, err := v.raw()
+	if err != nil {{
+		return {zero}, err
+	}}
+	rc := C.{project_fn}(v.ctx.context, &out, ptr)
+	runtime.KeepAlive(v)
+	if err := v.ctx.check(rc); err != nil {{
+		return {zero}, err
+	}}
+	return {out_return}, v.ctx.maybeSync()
+}}
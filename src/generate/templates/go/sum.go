@@ -0,0 +1,14 @@
+
+// {class_name}Variants lists the variant names, indexed by the value used by the C API
+var {class_name}Variants = []string{{{variants}}}
+
+// Variant returns the name of the variant
+func (v *{class_name}) Variant() (string, error) {{
+	ptr, err := v.raw()
+	if err != nil {{
+		return "", err
+	}}
+	i := C.{variant_fn}(v.ctx.context, ptr)
+	runtime.KeepAlive(v)
+	return {class_name}Variants[i], nil
+}}
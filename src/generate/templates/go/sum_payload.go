@@ -0,0 +1,14 @@
+
+// {name} returns the payload of the #{variant_name} variant, Variant must be "{variant_name}"
+func (v *{class_name}) {name}() {results} {{
+{destruct_decls}	ptr, err := v.raw()
+	if err != nil {{
+		return {zero_return}err
+	}}
+	rc := C.{destruct_fn}(v.ctx.context{destruct_args}, ptr)
+	runtime.KeepAlive(v)
+	if err := v.ctx.check(rc); err != nil {{
+		return {zero_return}err
+	}}
+	return {destruct_return}v.ctx.maybeSync()
+}}
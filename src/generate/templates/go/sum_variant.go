@@ -0,0 +1,14 @@
+
+// New{class_name}{name} constructs the #{variant_name} variant
+func New{class_name}{name}(ctx *Context{construct_params}) (*{class_name}, error) {{
+	c, err := ctx.raw()
+	if err != nil {{
+		return nil, err
+	}}
+{construct_inputs}	var out *C.struct_{raw_name}
+	rc := C.{construct_fn}(c, &out{construct_args})
+{keep_alive}	if err := ctx.check(rc); err != nil {{
+		return nil, err
+	}}
+	return wrap{class_name}(ctx, out), ctx.maybeSync()
+}}
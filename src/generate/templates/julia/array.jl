@@ -0,0 +1,62 @@
+
+"""
+    {class_name}(ctx, data::AbstractArray{{<:Any,{rank}}})
+
+{doc}
+
+Julia arrays are column-major and Futhark arrays are row-major, the dimensions are
+reordered so `x[i, j]` in Julia is `x[i-1][j-1]` in Futhark.
+"""
+mutable struct {class_name}
+    ctx::Context
+    ptr::Ptr{{Cvoid}}
+
+    function {class_name}(ctx::Context, ptr::Ptr{{Cvoid}})
+        ptr == C_NULL && throw(_error(ctx, -1))
+        x = new(ctx, ptr)
+        return finalizer(free, x)
+    end
+end
+
+function {class_name}(ctx::Context, data::AbstractArray{{<:Any,{rank}}})
+    row_major = _reverse_dims(convert(Array{{{elem_type},{rank}}}, data))
+    x = GC.@preserve row_major {class_name}(ctx, ccall((:{new_fn}, lib), Ptr{{Cvoid}}, (Ptr{{Cvoid}}, Ptr{{{elem_type}}}, {shape_types}), _get(ctx), row_major, {shape_args}))
+    GC.@preserve row_major _auto_sync(ctx)
+    return x
+end
+
+function free(x::{class_name})
+    if x.ptr != C_NULL && x.ctx.handle != C_NULL
+        ccall((:{free_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{Cvoid}}), x.ctx.handle, x.ptr)
+    end
+    x.ptr = C_NULL
+    return nothing
+end
+
+Base.eltype(::Type{{{class_name}}}) = {elem_type}
+
+Base.ndims(::Type{{{class_name}}}) = {rank}
+
+function Base.size(x::{class_name})
+    dims = GC.@preserve x ccall((:{shape_fn}, lib), Ptr{{Int64}}, (Ptr{{Cvoid}}, Ptr{{Cvoid}}), _get(x.ctx), _get(x))
+    return ntuple(i -> Int(unsafe_load(dims, i)), {rank})
+end
+
+Base.length(x::{class_name}) = prod(size(x))
+
+"""Copy the values into a new `Array`"""
+function Base.Array(x::{class_name})
+    out = Array{{{elem_type},{rank}}}(undef, reverse(size(x))...)
+    GC.@preserve x out _check(x.ctx, ccall((:{values_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{Cvoid}}, Ptr{{{elem_type}}}), _get(x.ctx), _get(x), out))
+    sync(x.ctx)
+    return _reverse_dims(out)
+end
+
+Base.collect(x::{class_name}) = Array(x)
+
+function Base.getindex(x::{class_name}, index::Vararg{{Integer,{rank}}})
+    out = Ref{{{elem_type}}}()
+    GC.@preserve x _check(x.ctx, ccall((:{index_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{{elem_type}}}, Ptr{{Cvoid}}, {shape_types}), _get(x.ctx), out, _get(x), {index_args}))
+    _auto_sync(x.ctx)
+    return out[]
+end
\ No newline at end of file
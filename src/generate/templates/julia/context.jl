@@ -0,0 +1,129 @@
+module {module}
+
+using Libdl
+
+"""Futhark backend used to compile the library"""
+const BACKEND = "{backend}"
+
+"""True when arrays are stored in device memory, reading values requires a copy"""
+const DEVICE_MEMORY = {device_memory}
+
+const lib = get(ENV, "{library_env}", joinpath(@__DIR__, (Sys.iswindows() ? "" : "lib") * "{library}." * Libdl.dlext))
+
+"""
+    FutharkError(code, message)
+
+Thrown when a Futhark function fails, the message is taken from `futhark_context_get_error`.
+`code` is -1 when a function returned a null pointer.
+"""
+struct FutharkError <: Exception
+    code::Int
+    message::String
+end
+
+Base.showerror(io::IO, e::FutharkError) = print(io, "FutharkError(", e.code, "): ", e.message)
+
+"""
+    Context(; debug=false, profile=false, logging=false, cache_file=nothing, auto_sync=true{extra_params})
+
+Futhark context, values keep a reference to the context used to create them
+"""
+mutable struct Context
+    config::Ptr{{Cvoid}}
+    handle::Ptr{{Cvoid}}
+    auto_sync::Bool
+    strings::Vector{{String}}
+end
+
+function Context(; debug=false, profile=false, logging=false, cache_file=nothing, auto_sync=true{extra_params})
+    config = ccall((:futhark_context_config_new, lib), Ptr{{Cvoid}}, ())
+    config == C_NULL && throw(FutharkError(-1, "futhark_context_config_new failed"))
+    ctx = Context(config, C_NULL, auto_sync, String[])
+    finalizer(free, ctx)
+    ccall((:futhark_context_config_set_debugging, lib), Cvoid, (Ptr{{Cvoid}}, Cint), config, debug)
+    ccall((:futhark_context_config_set_profiling, lib), Cvoid, (Ptr{{Cvoid}}, Cint), config, profile)
+    ccall((:futhark_context_config_set_logging, lib), Cvoid, (Ptr{{Cvoid}}, Cint), config, logging)
+    if cache_file !== nothing
+        push!(ctx.strings, string(cache_file))
+        ccall((:futhark_context_config_set_cache_file, lib), Cvoid, (Ptr{{Cvoid}}, Cstring), config, ctx.strings[end])
+    end
+{configure_backend}
+    ctx.handle = ccall((:futhark_context_new, lib), Ptr{{Cvoid}}, (Ptr{{Cvoid}},), config)
+    if ctx.handle == C_NULL
+        free(ctx)
+        throw(FutharkError(-1, "futhark_context_new failed"))
+    end
+    return ctx
+end
+
+"""
+    free(x)
+
+Free a context or value, it can't be used afterwards. Values are also freed when they're
+garbage collected.
+"""
+function free(ctx::Context)
+    if ctx.handle != C_NULL
+        ccall((:futhark_context_sync, lib), Cint, (Ptr{{Cvoid}},), ctx.handle)
+        ccall((:futhark_context_free, lib), Cvoid, (Ptr{{Cvoid}},), ctx.handle)
+        ctx.handle = C_NULL
+    end
+    if ctx.config != C_NULL
+        ccall((:futhark_context_config_free, lib), Cvoid, (Ptr{{Cvoid}},), ctx.config)
+        ctx.config = C_NULL
+    end
+    empty!(ctx.strings)
+    return nothing
+end
+
+function _get(ctx::Context)
+    ctx.handle == C_NULL && throw(ArgumentError("use of a freed context"))
+    return ctx.handle
+end
+
+function _get(x)
+    ptr = getfield(x, :ptr)
+    ptr == C_NULL && throw(ArgumentError("use of a freed value"))
+    return ptr
+end
+
+function _take_string(ptr::Ptr{{UInt8}})
+    ptr == C_NULL && return nothing
+    s = unsafe_string(ptr)
+    Libc.free(ptr)
+    return s
+end
+
+_error(ctx::Context, code) = FutharkError(code, something(get_error(ctx), ""))
+
+_check(ctx::Context, rc) = rc == 0 ? nothing : throw(_error(ctx, rc))
+
+"""Reverse the dimensions of an array, converting between row-major and column-major order"""
+_reverse_dims(a::AbstractArray{{T,N}}) where {{T,N}} = permutedims(a, ntuple(i -> N + 1 - i, N))
+
+"""Sync the context, if `auto_sync` is enabled this shouldn't be needed"""
+sync(ctx::Context) = _check(ctx, ccall((:futhark_context_sync, lib), Cint, (Ptr{{Cvoid}},), _get(ctx)))
+
+_auto_sync(ctx::Context) = ctx.auto_sync ? sync(ctx) : nothing
+
+"""Clear Futhark caches"""
+clear_caches(ctx::Context) = _check(ctx, ccall((:futhark_context_clear_caches, lib), Cint, (Ptr{{Cvoid}},), _get(ctx)))
+
+"""Pause Futhark profiling"""
+pause_profiling(ctx::Context) = ccall((:futhark_context_pause_profiling, lib), Cvoid, (Ptr{{Cvoid}},), _get(ctx))
+
+"""Resume profiling"""
+unpause_profiling(ctx::Context) = ccall((:futhark_context_unpause_profiling, lib), Cvoid, (Ptr{{Cvoid}},), _get(ctx))
+
+"""Get the profiling report"""
+report(ctx::Context) = _take_string(ccall((:futhark_context_report, lib), Ptr{{UInt8}}, (Ptr{{Cvoid}},), _get(ctx)))
+
+"""Get and clear the last error message, or `nothing`"""
+get_error(ctx::Context) = _take_string(ccall((:futhark_context_get_error, lib), Ptr{{UInt8}}, (Ptr{{Cvoid}},), _get(ctx)))
+
+"""
+    payload(x, variant::Symbol)
+
+Get the payload of a sum type value as a tuple, `variant(x)` must be `variant`
+"""
+payload(x, v::Symbol) = payload(x, Val(v))
\ No newline at end of file
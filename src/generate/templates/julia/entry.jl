@@ -0,0 +1,11 @@
+
+"""
+    {name}(ctx{entry_params})
+
+{doc}
+"""
+function {name}(ctx::Context{typed_params})
+{out_decl}    {preserve}_check(ctx, ccall((:{entry_fn}, lib), Cint, (Ptr{{Cvoid}}{arg_types}), _get(ctx){call_args}))
+    _auto_sync(ctx)
+    return {entry_return}
+end
\ No newline at end of file
@@ -0,0 +1,2 @@
+
+end # module {module}
\ No newline at end of file
@@ -0,0 +1,39 @@
+
+"""
+{doc}
+"""
+mutable struct {class_name}
+    ctx::Context
+    ptr::Ptr{{Cvoid}}
+
+    function {class_name}(ctx::Context, ptr::Ptr{{Cvoid}})
+        ptr == C_NULL && throw(_error(ctx, -1))
+        x = new(ctx, ptr)
+        return finalizer(free, x)
+    end
+end
+
+function free(x::{class_name})
+    if getfield(x, :ptr) != C_NULL && getfield(x, :ctx).handle != C_NULL
+        ccall((:{free_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{Cvoid}}), getfield(x, :ctx).handle, getfield(x, :ptr))
+    end
+    setfield!(x, :ptr, C_NULL)
+    return nothing
+end
+
+"""Serialize the value to a `Vector{{UInt8}}`"""
+function store(x::{class_name})
+    ctx = getfield(x, :ctx)
+    data = Ref{{Ptr{{Cvoid}}}}(C_NULL)
+    len = Ref{{Csize_t}}(0)
+    GC.@preserve x _check(ctx, ccall((:{store_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{Cvoid}}, Ptr{{Ptr{{Cvoid}}}}, Ptr{{Csize_t}}), _get(ctx), _get(x), data, len))
+    out = copy(unsafe_wrap(Vector{{UInt8}}, Ptr{{UInt8}}(data[]), len[]))
+    Libc.free(data[])
+    return out
+end
+
+"""Restore a value serialized with `store`"""
+function restore(::Type{{{class_name}}}, ctx::Context, data::Vector{{UInt8}})
+    return {class_name}(ctx, ccall((:{restore_fn}, lib), Ptr{{Cvoid}}, (Ptr{{Cvoid}}, Ptr{{UInt8}}), _get(ctx), data))
+end
+{members}
\ No newline at end of file
@@ -0,0 +1,18 @@
+
+Base.ndims(::Type{{{class_name}}}) = {rank}
+
+function Base.size(x::{class_name})
+    ctx = getfield(x, :ctx)
+    dims = GC.@preserve x ccall((:{shape_fn}, lib), Ptr{{Int64}}, (Ptr{{Cvoid}}, Ptr{{Cvoid}}), _get(ctx), _get(x))
+    return ntuple(i -> Int(unsafe_load(dims, i)), {rank})
+end
+
+Base.length(x::{class_name}) = prod(size(x))
+
+function Base.getindex(x::{class_name}, index::Vararg{{Integer,{rank}}})
+    ctx = getfield(x, :ctx)
+    out = Ref{{Ptr{{Cvoid}}}}(C_NULL)
+    GC.@preserve x _check(ctx, ccall((:{index_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{Ptr{{Cvoid}}}}, Ptr{{Cvoid}}, {index_types}), _get(ctx), out, _get(x), {index_args}))
+    _auto_sync(ctx)
+    return {elem_class}(ctx, out[])
+end
\ No newline at end of file
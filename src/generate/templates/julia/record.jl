@@ -0,0 +1,19 @@
+
+"""
+    {class_name}(ctx, {new_params})
+
+Create a new record from its fields
+"""
+function {class_name}(ctx::Context, {new_typed_params})
+    out = Ref{{Ptr{{Cvoid}}}}(C_NULL)
+    {preserve}_check(ctx, ccall((:{new_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{Ptr{{Cvoid}}}}, {new_arg_types}), _get(ctx), out, {new_call_args}))
+    _auto_sync(ctx)
+    return {class_name}(ctx, out[])
+end
+
+Base.propertynames(::{class_name}) = {properties}
+
+function Base.getproperty(x::{class_name}, name::Symbol)
+    name in {properties} && return _project(x, Val(name))
+    return getfield(x, name)
+end
\ No newline at end of file
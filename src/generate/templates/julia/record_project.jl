@@ -0,0 +1,8 @@
+
+function _project(x::{class_name}, ::Val{{{field_symbol}}})
+    ctx = getfield(x, :ctx)
+    out = Ref{{{out_type}}}({out_init})
+    GC.@preserve x _check(ctx, ccall((:{project_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{{out_type}}}, Ptr{{Cvoid}}), _get(ctx), out, _get(x)))
+    _auto_sync(ctx)
+    return {out_return}
+end
\ No newline at end of file
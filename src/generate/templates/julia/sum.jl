@@ -0,0 +1,17 @@
+
+"""Variant names, in the order used by the C API"""
+variants(::Type{{{class_name}}}) = {variants}
+
+"""Name of the variant as a `Symbol`"""
+function variant(x::{class_name})
+    ctx = getfield(x, :ctx)
+    i = GC.@preserve x ccall((:{variant_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{Cvoid}}), _get(ctx), _get(x))
+    return variants({class_name})[i + 1]
+end
+
+"""
+    {class_name}(ctx, variant::Symbol, payload...)
+
+Construct a variant, for example `{class_name}(ctx, {example})`
+"""
+{class_name}(ctx::Context, v::Symbol, payload...) = {class_name}(ctx, Val(v), payload...)
\ No newline at end of file
@@ -0,0 +1,7 @@
+
+function payload(x::{class_name}, ::Val{{{variant_symbol}}})
+    ctx = getfield(x, :ctx)
+{destruct_decls}    GC.@preserve x _check(ctx, ccall((:{destruct_fn}, lib), Cint, (Ptr{{Cvoid}}, {destruct_arg_types}, Ptr{{Cvoid}}), _get(ctx), {destruct_args}, _get(x)))
+    _auto_sync(ctx)
+    return ({destruct_return},)
+end
\ No newline at end of file
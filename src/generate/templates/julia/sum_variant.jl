@@ -0,0 +1,7 @@
+
+function {class_name}(ctx::Context, ::Val{{{variant_symbol}}}{construct_params})
+    out = Ref{{Ptr{{Cvoid}}}}(C_NULL)
+    {preserve}_check(ctx, ccall((:{construct_fn}, lib), Cint, (Ptr{{Cvoid}}, Ptr{{Ptr{{Cvoid}}}}{construct_arg_types}), _get(ctx), out{construct_args}))
+    _auto_sync(ctx)
+    return {class_name}(ctx, out[])
+end
\ No newline at end of file
@@ -8,7 +8,7 @@ module {module_name} = struct
   let free ctx ptr =
     let is_null = Ctypes.is_null ptr || Ctypes.is_null (!@ptr) in
     if not ctx.Context.context_free && not is_null then
-      let () = ignore (Bindings.futhark_free_{elemtype}_{rank}d ctx.Context.handle (!@ptr)) in
+      let () = ignore (Bindings.{free_fn} ctx.Context.handle (!@ptr)) in
       ptr <-@ Ctypes.null
 
   let cast x =
@@ -17,7 +17,7 @@ module {module_name} = struct
   let v ctx ba =
     check_use_after_free `context ctx.Context.context_free;
     let dims = Genarray.dims ba in
-    let ptr = Bindings.futhark_new_{elemtype}_{rank}d ctx.Context.handle (cast @@ bigarray_start genarray ba) {dim_args} in
+    let ptr = Bindings.{new_fn} ctx.Context.handle (cast @@ bigarray_start genarray ba) {dim_args} in
     if is_null ptr then raise (Error NullPtr);
     Context.auto_sync ctx;
     {{ ptr = Ctypes.allocate ~finalise:(free ctx) (Ctypes.ptr Ctypes.void) ptr; ctx; shape = dims }}
@@ -28,7 +28,7 @@ module {module_name} = struct
     let a = Array.fold_left ( * ) 1 t.shape in
     let b = Array.fold_left ( * ) 1 dims in
     if (a <> b) then raise (Error (InvalidShape (a, b)));
-    let rc = Bindings.futhark_values_{elemtype}_{rank}d t.ctx.Context.handle (get_ptr t) (cast @@ bigarray_start genarray ba) in
+    let rc = Bindings.{values_fn} t.ctx.Context.handle (get_ptr t) (cast @@ bigarray_start genarray ba) in
     Context.auto_sync t.ctx;
     if rc <> 0 then raise (Error (Code rc))
 
@@ -63,7 +63,7 @@ module {module_name} = struct
     of_array1 ctx dims arr
 
   let ptr_shape ctx ptr =
-    let s = Bindings.futhark_shape_{elemtype}_{rank}d ctx ptr in
+    let s = Bindings.{shape_fn} ctx ptr in
     Array.init {rank} (fun i -> Int64.to_int !@ (s +@ i))
 
   let of_ptr ctx ptr =
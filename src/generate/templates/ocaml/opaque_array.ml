@@ -0,0 +1,15 @@
+  let shape t =
+    check_use_after_free `context t.opaque_ctx.Context.context_free;
+    let s = Bindings.{shape_fn} t.opaque_ctx.Context.handle (get_opaque_ptr t) in
+    Array.init {rank} (fun i -> Int64.to_int !@ (s +@ i))
+
+  let get t index =
+    check_use_after_free `context t.opaque_ctx.Context.context_free;
+    let shape = shape t in
+    if Array.length index <> {rank} then raise (Error (InvalidShape ({rank}, Array.length index)));
+    Array.iteri (fun i x -> if x < 0 || x >= shape.(i) then invalid_arg "index out of bounds") index;
+    let out = allocate (ptr void) null in
+    let rc = Bindings.{index_fn} t.opaque_ctx.Context.handle out (get_opaque_ptr t) {index_args} in
+    if rc <> 0 then raise (Error (Code rc));
+    Context.auto_sync t.opaque_ctx;
+    {elem_module}.of_ptr t.opaque_ctx !@out
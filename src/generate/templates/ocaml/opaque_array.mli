@@ -0,0 +1,6 @@
+
+  val shape: t -> int array
+  (** Array shape *)
+
+  val get: t -> int array -> {elem_module}.t
+  (** Get the element at an index *)
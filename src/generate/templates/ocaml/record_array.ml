@@ -0,0 +1,7 @@
+  let zip ctx {zip_params} =
+    check_use_after_free `context ctx.Context.context_free;
+    let ptr = allocate ~finalise:(free' ctx) (ptr void) null in
+    let rc = Bindings.{zip_fn} ctx.Context.handle ptr {zip_call_args} in
+    if rc <> 0 then raise (Error (Code rc));
+    Context.auto_sync ctx;
+    {{ opaque_ptr = ptr; opaque_ctx = ctx }}
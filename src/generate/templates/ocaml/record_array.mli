@@ -0,0 +1,3 @@
+
+  val zip: Context.t -> {zip_arg_types} -> t
+  (** Create an array of records from one array per field *)
@@ -0,0 +1,21 @@
+  type value = {constructors}
+
+  let v ctx value =
+    check_use_after_free `context ctx.Context.context_free;
+    let ptr = allocate ~finalise:(free' ctx) (ptr void) null in
+    let rc =
+      match value with
+{construct_cases}
+    in
+    if rc <> 0 then raise (Error (Code rc));
+    Context.auto_sync ctx;
+    {{ opaque_ptr = ptr; opaque_ctx = ctx }}
+
+  let variant t =
+    check_use_after_free `context t.opaque_ctx.Context.context_free;
+    Bindings.{variant_fn} t.opaque_ctx.Context.handle (get_opaque_ptr t)
+
+  let get t =
+    match variant t with
+{destruct_cases}
+    | n -> failwith (Printf.sprintf "unknown variant %d" n)
@@ -0,0 +1,12 @@
+
+  type value = {constructors}
+  (** Variants *)
+
+  val v: Context.t -> value -> t
+  (** Create a value from a variant *)
+
+  val variant: t -> int
+  (** Index of the variant, in declaration order *)
+
+  val get: t -> value
+  (** Get the variant and its payload *)
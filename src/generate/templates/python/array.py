@@ -0,0 +1,47 @@
+
+class {class_name}(_Handle):
+    """{doc}"""
+
+    rank = {rank}
+    dtype = np.dtype({dtype})
+    _free_fn = _lib.{free_fn}
+
+    def __init__(self, ctx, data):
+        """Create a new array from anything accepted by `numpy.asarray`"""
+        data = np.ascontiguousarray(data, dtype=self.dtype)
+        if data.ndim != self.rank:
+            raise ValueError("expected an array of rank {rank}, got {{}}".format(data.ndim))
+        ptr = _lib.{new_fn}(ctx._get(), data.ctypes.data_as(ctypes.c_void_p), *data.shape)
+        super().__init__(ctx, ptr)
+        ctx.auto_sync()
+
+    @property
+    def shape(self):
+        """Array shape"""
+        dims = _lib.{shape_fn}(self._ctx._get(), self._get())
+        return tuple(dims[i] for i in range(self.rank))
+
+    def numpy(self):
+        """Copy the values into a new `numpy.ndarray`"""
+        out = np.empty(self.shape, dtype=self.dtype)
+        self._ctx._check(_lib.{values_fn}(self._ctx._get(), self._get(), out.ctypes.data_as(ctypes.c_void_p)))
+        self._ctx.sync()
+        return out
+
+    def __array__(self, dtype=None, copy=None):
+        out = self.numpy()
+        return out if dtype is None else out.astype(dtype)
+
+    def __len__(self):
+        return self.shape[0]
+
+    def __getitem__(self, index):
+        """Get a single value, `index` must have one integer per dimension"""
+        if not isinstance(index, tuple):
+            index = (index,)
+        if len(index) != self.rank:
+            raise IndexError("expected {rank} indices, got {{}}".format(len(index)))
+        out = {ctype}()
+        self._ctx._check(_lib.{index_fn}(self._ctx._get(), ctypes.byref(out), self._get(), *index))
+        self._ctx.auto_sync()
+        return np.frombuffer(out, dtype=self.dtype)[0]
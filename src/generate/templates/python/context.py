@@ -0,0 +1,199 @@
+import ctypes
+import os
+import sys
+import warnings
+
+import numpy as np
+
+#: Futhark backend used to compile the library
+BACKEND = "{backend}"
+
+#: True when arrays are stored in device memory, reading values requires a copy
+DEVICE_MEMORY = {device_memory}
+
+
+def _library_path():
+    if sys.platform == "win32":
+        name = "{library}.dll"
+    elif sys.platform == "darwin":
+        name = "lib{library}.dylib"
+    else:
+        name = "lib{library}.so"
+    return os.path.join(os.path.dirname(os.path.abspath(__file__)), name)
+
+
+_lib = ctypes.CDLL(os.environ.get("{library_env}", _library_path()))
+_libc = ctypes.cdll.msvcrt if sys.platform == "win32" else ctypes.CDLL(None)
+_libc.free.argtypes = [ctypes.c_void_p]
+_libc.free.restype = None
+
+
+def _fn(name, restype, *argtypes):
+    f = getattr(_lib, name)
+    f.restype = restype
+    f.argtypes = list(argtypes)
+
+
+_fn("futhark_context_config_new", ctypes.c_void_p)
+_fn("futhark_context_config_free", None, ctypes.c_void_p)
+_fn("futhark_context_config_set_debugging", None, ctypes.c_void_p, ctypes.c_int)
+_fn("futhark_context_config_set_profiling", None, ctypes.c_void_p, ctypes.c_int)
+_fn("futhark_context_config_set_logging", None, ctypes.c_void_p, ctypes.c_int)
+_fn("futhark_context_config_set_cache_file", None, ctypes.c_void_p, ctypes.c_char_p)
+_fn("futhark_context_new", ctypes.c_void_p, ctypes.c_void_p)
+_fn("futhark_context_free", None, ctypes.c_void_p)
+_fn("futhark_context_sync", ctypes.c_int, ctypes.c_void_p)
+_fn("futhark_context_clear_caches", ctypes.c_int, ctypes.c_void_p)
+_fn("futhark_context_pause_profiling", None, ctypes.c_void_p)
+_fn("futhark_context_unpause_profiling", None, ctypes.c_void_p)
+_fn("futhark_context_get_error", ctypes.c_void_p, ctypes.c_void_p)
+_fn("futhark_context_report", ctypes.c_void_p, ctypes.c_void_p)
+{generated_functions}
+
+
+def _take_string(ptr):
+    if not ptr:
+        return None
+    s = ctypes.string_at(ptr).decode("utf-8", "replace")
+    _libc.free(ptr)
+    return s
+
+
+def _f16_bits(x):
+    return int(np.float16(x).view(np.uint16))
+
+
+def _f16_value(bits):
+    return np.uint16(bits).view(np.float16)
+
+
+class Error(Exception):
+    """Raised when a Futhark function fails, the message is taken from
+    `futhark_context_get_error`"""
+
+    def __init__(self, code, message):
+        super().__init__(message or "Futhark error code: {{}}".format(code))
+        #: Error code returned by Futhark, -1 when a function returned a null pointer
+        self.code = code
+
+
+class Context:
+    """Futhark context, values keep a reference to the context used to create them"""
+
+    def __init__(self, debug=False, profile=False, logging=False, cache_file=None, auto_sync=True{extra_params}):
+        self._handle = None
+        self._config = _lib.futhark_context_config_new()
+        if not self._config:
+            raise Error(-1, "futhark_context_config_new failed")
+        _lib.futhark_context_config_set_debugging(self._config, int(debug))
+        _lib.futhark_context_config_set_profiling(self._config, int(profile))
+        _lib.futhark_context_config_set_logging(self._config, int(logging))
+        self._cache_file = None
+        if cache_file is not None:
+            self._cache_file = os.fsencode(cache_file)
+            _lib.futhark_context_config_set_cache_file(self._config, self._cache_file)
+{configure_backend}
+        self._handle = _lib.futhark_context_new(self._config)
+        if not self._handle:
+            _lib.futhark_context_config_free(self._config)
+            self._config = None
+            raise Error(-1, "futhark_context_new failed")
+        self._auto_sync = auto_sync
+
+    def free(self):
+        """Free the context, values created with it can't be used afterwards"""
+        if self._handle:
+            _lib.futhark_context_sync(self._handle)
+            _lib.futhark_context_free(self._handle)
+            _lib.futhark_context_config_free(self._config)
+            self._handle = None
+            self._config = None
+
+    def __del__(self):
+        self.free()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *args):
+        self.free()
+
+    def _get(self):
+        if not self._handle:
+            raise ValueError("use of a freed context")
+        return self._handle
+
+    def _check(self, rc):
+        if rc != 0:
+            raise Error(rc, self.get_error())
+
+    def _check_ptr(self, ptr):
+        if not ptr:
+            raise Error(-1, self.get_error())
+        return ptr
+
+    def sync(self):
+        """Sync the context, if `auto_sync` is enabled this shouldn't be needed"""
+        self._check(_lib.futhark_context_sync(self._get()))
+
+    def auto_sync(self):
+        """Sync if `auto_sync` is enabled, otherwise this is a noop"""
+        if self._auto_sync:
+            self.sync()
+
+    def clear_caches(self):
+        """Clear Futhark caches"""
+        self._check(_lib.futhark_context_clear_caches(self._get()))
+
+    def pause_profiling(self):
+        """Pause Futhark profiling"""
+        _lib.futhark_context_pause_profiling(self._get())
+
+    def unpause_profiling(self):
+        """Resume profiling"""
+        _lib.futhark_context_unpause_profiling(self._get())
+
+    def report(self):
+        """Get the profiling report"""
+        return _take_string(_lib.futhark_context_report(self._get()))
+
+    def get_error(self):
+        """Get and clear the last error message, or None"""
+        return _take_string(_lib.futhark_context_get_error(self._get()))
+
+
+class _Handle:
+    """Base class for values owned by a context, freed when garbage collected or by `free`"""
+
+    _free_fn = None
+
+    def __init__(self, ctx, ptr):
+        self._ptr = None
+        self._ctx = ctx
+        self._ptr = ctx._check_ptr(ptr)
+
+    @classmethod
+    def _from_ptr(cls, ctx, ptr):
+        t = cls.__new__(cls)
+        _Handle.__init__(t, ctx, ptr)
+        return t
+
+    def _get(self):
+        if not self._ptr:
+            raise ValueError("use of a freed value")
+        return self._ptr
+
+    def free(self):
+        """Free the value, it can't be used afterwards"""
+        if self._ptr and self._ctx._handle:
+            self._free_fn(self._ctx._handle, self._ptr)
+        self._ptr = None
+
+    def __del__(self):
+        self.free()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *args):
+        self.free()
@@ -0,0 +1,7 @@
+
+def {name}(ctx, {entry_params}):
+    """{doc}"""
+{out_decl}
+    ctx._check(_lib.{entry_fn}(ctx._get(), {call_args}))
+    ctx.auto_sync()
+    return {entry_return}
@@ -0,0 +1,20 @@
+
+class {class_name}(_Handle):
+    """{doc}"""
+
+    _free_fn = _lib.{free_fn}
+
+    def store(self):
+        """Serialize the value to `bytes`"""
+        data = ctypes.c_void_p()
+        size = ctypes.c_size_t()
+        self._ctx._check(_lib.{store_fn}(self._ctx._get(), self._get(), ctypes.byref(data), ctypes.byref(size)))
+        out = ctypes.string_at(data, size.value)
+        _libc.free(data)
+        return out
+
+    @classmethod
+    def restore(cls, ctx, data):
+        """Restore a value serialized with `store`"""
+        return cls._from_ptr(ctx, _lib.{restore_fn}(ctx._get(), data))
+{members}
\ No newline at end of file
@@ -0,0 +1,22 @@
+
+    rank = {rank}
+
+    @property
+    def shape(self):
+        """Array shape"""
+        dims = _lib.{shape_fn}(self._ctx._get(), self._get())
+        return tuple(dims[i] for i in range(self.rank))
+
+    def __len__(self):
+        return self.shape[0]
+
+    def __getitem__(self, index):
+        """Get a single value, `index` must have one integer per dimension"""
+        if not isinstance(index, tuple):
+            index = (index,)
+        if len(index) != self.rank:
+            raise IndexError("expected {rank} indices, got {{}}".format(len(index)))
+        out = ctypes.c_void_p()
+        self._ctx._check(_lib.{index_fn}(self._ctx._get(), ctypes.byref(out), self._get(), *index))
+        self._ctx.auto_sync()
+        return {elem_class}._from_ptr(self._ctx, out.value)
@@ -0,0 +1,7 @@
+
+    def __init__(self, ctx, {new_params}):
+        """Create a new record from its fields"""
+        out = ctypes.c_void_p()
+        ctx._check(_lib.{new_fn}(ctx._get(), ctypes.byref(out), {new_call_args}))
+        super().__init__(ctx, out.value)
+        ctx.auto_sync()
@@ -0,0 +1,7 @@
+
+    def __init__(self, ctx, {zip_params}):
+        """Create a new array from one array per field"""
+        out = ctypes.c_void_p()
+        ctx._check(_lib.{zip_fn}(ctx._get(), ctypes.byref(out), {zip_call_args}))
+        super().__init__(ctx, out.value)
+        ctx.auto_sync()
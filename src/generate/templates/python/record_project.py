@@ -0,0 +1,8 @@
+
+    @property
+    def {project_name}(self):
+        """The `{field_name}` field"""
+        out = {out_type}()
+        self._ctx._check(_lib.{project_fn}(self._ctx._get(), ctypes.byref(out), self._get()))
+        self._ctx.auto_sync()
+        return {out_return}
@@ -0,0 +1,8 @@
+
+    #: Variant names, in the order used by the C API
+    variants = ({variants})
+
+    @property
+    def variant(self):
+        """Name of the variant"""
+        return self.variants[_lib.{variant_fn}(self._ctx._get(), self._get())]
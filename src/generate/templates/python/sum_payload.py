@@ -0,0 +1,8 @@
+
+    def get_{name}(self):
+        """Get the payload of the `#{variant_name}` variant as a tuple, `variant` must be
+        `"{variant_name}"`"""
+{destruct_decls}
+        self._ctx._check(_lib.{destruct_fn}(self._ctx._get(), {destruct_args}, self._get()))
+        self._ctx.auto_sync()
+        return ({destruct_return},)
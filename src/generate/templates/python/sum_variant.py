@@ -0,0 +1,8 @@
+
+    @classmethod
+    def {name}(cls, ctx{construct_params}):
+        """Construct the `#{variant_name}` variant"""
+        out = ctypes.c_void_p()
+        ctx._check(_lib.{construct_fn}(ctx._get(), ctypes.byref(out){construct_args}))
+        ctx.auto_sync()
+        return cls._from_ptr(ctx, out.value)
@@ -4,8 +4,8 @@ struct {raw_type} {{
     _private: [u8; 0]
 }}
 
-/// Array type with {rank} dimensions and {elemtype} elements
-pub struct {rust_type}<'a> {{
+{doc}
+{derives}pub struct {rust_type}<'a> {{
     ptr: *mut {raw_type},
     shape: [usize; {rank}],
     ctx: &'a Context,
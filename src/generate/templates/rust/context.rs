@@ -1,3 +1,9 @@
+/// Futhark backend used to compile the library
+pub const BACKEND: &str = "{backend}";
+
+/// True when arrays are stored in device memory, reading values requires a copy
+pub const DEVICE_MEMORY: bool = {device_memory};
+
 #[derive(Debug)]
 pub enum Error {{
     Code(core::ffi::c_int),
@@ -27,6 +33,7 @@ pub struct Options {{
     num_threads: u32,
     cache_file: std::option::Option<std::ffi::CString>,
     device: std::option::Option<std::ffi::CString>,
+    platform: std::option::Option<std::ffi::CString>,
     auto_sync: bool,
 }}
 
@@ -46,6 +53,7 @@ impl Options {{
             num_threads: 0,
             cache_file: None,
             device: None,
+            platform: None,
             auto_sync: true,
         }}
     }}
@@ -84,6 +92,7 @@ impl Options {{
 }}
 
 /// Futhark context
+#[derive(Debug)]
 pub struct Context {{
     config: *mut futhark_context_config,
     context: *mut futhark_context,
@@ -120,8 +129,7 @@ impl Context {{
                 futhark_context_config_set_cache_file(config, c.as_ptr());
             }}
 
-            {configure_num_threads}
-            {configure_set_device}
+            {configure_backend}
 
             let context = futhark_context_new(config);
             if context.is_null() {{
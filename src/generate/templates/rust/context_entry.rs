@@ -1,11 +1,11 @@
 impl Context {{
-    /// Entry point: {entry_name}
+    {doc}
     pub fn {entry_name}<'a>(&'a self, {entry_params}) -> Result<{entry_return_type}, Error> {{
         #[allow(unused)]
         let ctx = self;
         {out_decl}
         let rc = unsafe {{
-            futhark_entry_{entry_name}(self.context, {call_args})
+            {entry_fn}(self.context, {call_args})
         }};
         if rc != 0 {{ return Err(Error::Code(rc)); }}
     
@@ -1,8 +1,8 @@
-/// Entry point: {entry_name}
+{doc}
 pub fn {entry_name}<'a>(ctx: &'a Context, {entry_params}) -> Result<{entry_return_type}, Error> {{
     {out_decl}
     let rc = unsafe {{
-        futhark_entry_{entry_name}(ctx.context, {call_args})
+        {entry_fn}(ctx.context, {call_args})
     }};
     if rc != 0 {{ return Err(Error::Code(rc)); }}
 
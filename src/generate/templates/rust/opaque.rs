@@ -4,8 +4,8 @@ struct {raw_type} {{
     _private: [u8; 0]
 }}
 
-/// Futhark type
-pub struct {rust_type}<'a> {{
+{doc}
+{derives}pub struct {rust_type}<'a> {{
     ptr: *mut {raw_type},
     ctx: &'a Context,
 }}
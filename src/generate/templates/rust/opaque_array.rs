@@ -1,5 +1,5 @@
-/// Futhark type
-pub struct {rust_type}<'a> {{
+{doc}
+{derives}pub struct {rust_type}<'a> {{
     ptr: *mut {raw_type},
     shape: [usize; {rank}],
     ctx: &'a Context,
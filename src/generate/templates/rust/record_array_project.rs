@@ -1,8 +1,8 @@
 impl<'a> {rust_type}<'a> {{
     pub fn {project_name}(&self) -> {rust_field_type} {{
-        self.{project_name}_checked().unwrap()
+        self.{checked_name}().unwrap()
     }}
-    pub fn {project_name}_checked(&self) -> Result<{rust_field_type}, Error> {{
+    pub fn {checked_name}(&self) -> Result<{rust_field_type}, Error> {{
         unsafe {{
             let mut out = std::ptr::null_mut();
             let rc = {project_fn}(self.ctx.context, &mut out, self.ptr);
@@ -1,9 +1,9 @@
 impl<'a> {rust_type}<'a> {{
     pub fn {project_name}(&self) -> {rust_field_type} {{
-        self.{project_name}_checked().unwrap()
+        self.{checked_name}().unwrap()
     }}
     /// Get the {field_name} field
-    pub fn {project_name}_checked(&self) -> Result<{rust_field_type}, Error> {{
+    pub fn {checked_name}(&self) -> Result<{rust_field_type}, Error> {{
         let mut out = std::mem::MaybeUninit::zeroed();
         let rc = unsafe {{
             {project_fn}(
@@ -0,0 +1,48 @@
+pub const {raw_name} = opaque {{}};
+{externs}
+
+{doc}
+pub const {class_name} = struct {{
+    ctx: *Context,
+    ptr: *{raw_name},
+
+    /// Create a new array from `data` in row-major order, the product of `dims` must be
+    /// `data.len`
+    pub fn init(ctx: *Context, data: []const {elem_type}, dims: [{rank}]i64) Error!{class_name} {{
+        if (numElements(&dims) != data.len) return error.ShapeMismatch;
+        const ptr = {new_fn}(ctx.handle, data.ptr, {shape_args}) orelse return error.Failure;
+        var result = {class_name}{{ .ctx = ctx, .ptr = ptr }};
+        errdefer result.deinit();
+        try ctx.autoSync();
+        return result;
+    }}
+
+    /// Free the array, it can't be used afterwards
+    pub fn deinit(self: *{class_name}) void {{
+        _ = {free_fn}(self.ctx.handle, self.ptr);
+        self.* = undefined;
+    }}
+
+    /// Get the array dimensions
+    pub fn shape(self: {class_name}) [{rank}]i64 {{
+        return {shape_fn}(self.ctx.handle, self.ptr)[0..{rank}].*;
+    }}
+
+    /// Copy the values into a new slice in row-major order, owned by the caller
+    pub fn toOwnedSlice(self: {class_name}, allocator: std.mem.Allocator) Error![]{elem_type} {{
+        const dims = self.shape();
+        const data = try allocator.alloc({elem_type}, numElements(&dims));
+        errdefer allocator.free(data);
+        try self.ctx.check({values_fn}(self.ctx.handle, self.ptr, data.ptr));
+        try self.ctx.sync();
+        return data;
+    }}
+
+    /// Get a single value, `index` has one entry per dimension
+    pub fn get(self: {class_name}, index: [{rank}]i64) Error!{elem_type} {{
+        var out: {c_type} = undefined;
+        try self.ctx.check({index_fn}(self.ctx.handle, &out, self.ptr, {index_args}));
+        try self.ctx.autoSync();
+        return {out_return};
+    }}
+}};
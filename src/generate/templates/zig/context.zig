@@ -0,0 +1,144 @@
+const std = @import("std");
+
+/// Futhark backend used to compile the library
+pub const backend = "{backend}";
+
+/// True when arrays are stored in device memory, reading values requires a copy
+pub const device_memory = {device_memory};
+
+pub const futhark_context_config = opaque {{}};
+pub const futhark_context = opaque {{}};
+pub extern fn futhark_context_config_new() ?*futhark_context_config;
+pub extern fn futhark_context_config_free(*futhark_context_config) void;
+pub extern fn futhark_context_config_set_debugging(*futhark_context_config, c_int) void;
+pub extern fn futhark_context_config_set_profiling(*futhark_context_config, c_int) void;
+pub extern fn futhark_context_config_set_logging(*futhark_context_config, c_int) void;
+pub extern fn futhark_context_config_set_cache_file(*futhark_context_config, [*:0]const u8) void;
+{backend_externs}pub extern fn futhark_context_new(*futhark_context_config) ?*futhark_context;
+pub extern fn futhark_context_free(*futhark_context) void;
+pub extern fn futhark_context_sync(*futhark_context) c_int;
+pub extern fn futhark_context_clear_caches(*futhark_context) c_int;
+pub extern fn futhark_context_get_error(*futhark_context) ?[*:0]u8;
+pub extern fn futhark_context_report(*futhark_context) ?[*:0]u8;
+pub extern fn futhark_context_pause_profiling(*futhark_context) void;
+pub extern fn futhark_context_unpause_profiling(*futhark_context) void;
+
+/// Errors returned by Futhark functions, the message is available from `Context.getError`
+pub const Error = error{{
+    /// A function failed without a more specific error code, or returned a null pointer
+    Failure,
+
+    /// Program error, for example a failed bounds check
+    ProgramError,
+
+    /// Out of memory, also returned when an allocator fails
+    OutOfMemory,
+
+    /// The shape of an array doesn't match the number of elements
+    ShapeMismatch,
+}};
+
+/// Map a non-zero Futhark error code to an error
+pub fn errorFromCode(code: c_int) Error {{
+    return switch (code) {{
+        2 => error.ProgramError,
+        3 => error.OutOfMemory,
+        else => error.Failure,
+    }};
+}}
+
+/// Options used to create a `Context`, strings must outlive the context
+pub const Options = struct {{
+    /// Enable debugging output
+    debug: bool = false,
+
+    /// Enable profiling
+    profile: bool = false,
+
+    /// Enable logging
+    logging: bool = false,
+
+    /// Path of the kernel cache
+    cache_file: ?[*:0]const u8 = null,
+
+    /// Sync after each call, when disabled `Context.sync` must be called before reading results
+    auto_sync: bool = true,
+{backend_options}}};
+
+/// Futhark context, values keep a pointer to the context used to create them so it must not
+/// be moved while they're alive
+pub const Context = struct {{
+    config: *futhark_context_config,
+    handle: *futhark_context,
+    auto_sync: bool,
+
+    /// Create a new context, free it using `deinit`
+    pub fn init(options: Options) Error!Context {{
+        const config = futhark_context_config_new() orelse return error.Failure;
+        errdefer futhark_context_config_free(config);
+        futhark_context_config_set_debugging(config, @intFromBool(options.debug));
+        futhark_context_config_set_profiling(config, @intFromBool(options.profile));
+        futhark_context_config_set_logging(config, @intFromBool(options.logging));
+        if (options.cache_file) |cache_file| futhark_context_config_set_cache_file(config, cache_file);
+{configure_backend}        const handle = futhark_context_new(config) orelse return error.Failure;
+        return .{{ .config = config, .handle = handle, .auto_sync = options.auto_sync }};
+    }}
+
+    /// Free the context, values created with it can't be used afterwards
+    pub fn deinit(self: *Context) void {{
+        _ = futhark_context_sync(self.handle);
+        futhark_context_free(self.handle);
+        futhark_context_config_free(self.config);
+        self.* = undefined;
+    }}
+
+    /// Sync the context, if `auto_sync` is enabled this shouldn't be needed
+    pub fn sync(self: *Context) Error!void {{
+        try self.check(futhark_context_sync(self.handle));
+    }}
+
+    /// Clear Futhark caches
+    pub fn clearCaches(self: *Context) Error!void {{
+        try self.check(futhark_context_clear_caches(self.handle));
+    }}
+
+    /// Pause Futhark profiling
+    pub fn pauseProfiling(self: *Context) void {{
+        futhark_context_pause_profiling(self.handle);
+    }}
+
+    /// Resume profiling
+    pub fn unpauseProfiling(self: *Context) void {{
+        futhark_context_unpause_profiling(self.handle);
+    }}
+
+    /// Get the profiling report, owned by the caller
+    pub fn report(self: *Context, allocator: std.mem.Allocator) Error!?[]u8 {{
+        return takeString(allocator, futhark_context_report(self.handle));
+    }}
+
+    /// Get and clear the last error message, owned by the caller
+    pub fn getError(self: *Context, allocator: std.mem.Allocator) Error!?[]u8 {{
+        return takeString(allocator, futhark_context_get_error(self.handle));
+    }}
+
+    fn check(_: *Context, rc: c_int) Error!void {{
+        if (rc != 0) return errorFromCode(rc);
+    }}
+
+    fn autoSync(self: *Context) Error!void {{
+        if (self.auto_sync) try self.sync();
+    }}
+}};
+
+fn takeString(allocator: std.mem.Allocator, str: ?[*:0]u8) Error!?[]u8 {{
+    const ptr = str orelse return null;
+    defer std.c.free(ptr);
+    return try allocator.dupe(u8, std.mem.span(ptr));
+}}
+
+fn numElements(dims: []const i64) usize {{
+    var len: usize = 1;
+    for (dims) |dim| len *= @as(usize, @intCast(dim));
+    return len;
+}}
@@ -0,0 +1,8 @@
+{extern_decl}
+
+{doc}
+pub fn {name}(ctx: *Context{entry_params}) Error!{results} {{
+{out_decl}    try ctx.check({entry_fn}(ctx.handle{call_args}));
+{out_wrap}    try ctx.autoSync();
+    return {entry_return};
+}}
@@ -0,0 +1,30 @@
+pub const {raw_name} = opaque {{}};
+{externs}
+
+{doc}
+pub const {class_name} = struct {{
+    ctx: *Context,
+    ptr: *{raw_name},
+
+    /// Free the value, it can't be used afterwards
+    pub fn deinit(self: *{class_name}) void {{
+        _ = {free_fn}(self.ctx.handle, self.ptr);
+        self.* = undefined;
+    }}
+
+    /// Serialize the value, the result is owned by the caller
+    pub fn store(self: {class_name}, allocator: std.mem.Allocator) Error![]u8 {{
+        var data: ?*anyopaque = null;
+        var len: usize = 0;
+        try self.ctx.check({store_fn}(self.ctx.handle, self.ptr, &data, &len));
+        defer std.c.free(data);
+        const bytes: [*]const u8 = @ptrCast(data orelse return allocator.alloc(u8, 0));
+        return allocator.dupe(u8, bytes[0..len]);
+    }}
+
+    /// Restore a value serialized with `store`
+    pub fn restore(ctx: *Context, data: []const u8) Error!{class_name} {{
+        const ptr = {restore_fn}(ctx.handle, data.ptr) orelse return error.Failure;
+        return .{{ .ctx = ctx, .ptr = ptr }};
+    }}
+{members}}};
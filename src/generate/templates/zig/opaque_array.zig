@@ -0,0 +1,15 @@
+
+    /// Get the array dimensions
+    pub fn shape(self: {class_name}) [{rank}]i64 {{
+        return {shape_fn}(self.ctx.handle, self.ptr)[0..{rank}].*;
+    }}
+
+    /// Get a single element, `index` has one entry per dimension
+    pub fn get(self: {class_name}, index: [{rank}]i64) Error!{elem_class} {{
+        var out: *{elem_raw_name} = undefined;
+        try self.ctx.check({index_fn}(self.ctx.handle, &out, self.ptr, {index_args}));
+        var result = {elem_class}{{ .ctx = self.ctx, .ptr = out }};
+        errdefer result.deinit();
+        try self.ctx.autoSync();
+        return result;
+    }}
@@ -0,0 +1,14 @@
+
+    /// Fields used to create a new record
+    pub const Fields = struct {{
+{fields}    }};
+
+    /// Create a new record from its fields
+    pub fn init(ctx: *Context, fields: Fields) Error!{class_name} {{
+        var out: *{raw_name} = undefined;
+        try ctx.check({new_fn}(ctx.handle, &out{call_args}));
+        var result = {class_name}{{ .ctx = ctx, .ptr = out }};
+        errdefer result.deinit();
+        try ctx.autoSync();
+        return result;
+    }}
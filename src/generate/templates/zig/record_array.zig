@@ -0,0 +1,15 @@
+
+    /// Arrays used to create a new array of records, one per field
+    pub const Fields = struct {{
+{fields}    }};
+
+    /// Create a new array of records from one array per field, the arrays must have the same
+    /// shape
+    pub fn zip(ctx: *Context, fields: Fields) Error!{class_name} {{
+        var out: *{raw_name} = undefined;
+        try ctx.check({zip_fn}(ctx.handle, &out{call_args}));
+        var result = {class_name}{{ .ctx = ctx, .ptr = out }};
+        errdefer result.deinit();
+        try ctx.autoSync();
+        return result;
+    }}
@@ -0,0 +1,8 @@
+
+    /// Get the `{field_name}` field
+    pub fn {project_name}(self: {class_name}) Error!{zig_type} {{
+        var out: {out_type} = undefined;
+        try self.ctx.check({project_fn}(self.ctx.handle, &out, self.ptr));
+{out_wrap}        try self.ctx.autoSync();
+        return {out_return};
+    }}
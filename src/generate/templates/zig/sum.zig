@@ -0,0 +1,9 @@
+
+    /// Variants, in the order used by the C API
+    pub const Variant = enum(c_int) {{
+{variants}    }};
+
+    /// Get the variant
+    pub fn variant(self: {class_name}) Variant {{
+        return @enumFromInt({variant_fn}(self.ctx.handle, self.ptr));
+    }}
@@ -0,0 +1,7 @@
+
+    /// Get the payload of the `#{variant_name}` variant, `variant` must be `.{variant_field}`
+    pub fn {name}(self: {class_name}) Error!{payload_type} {{
+{destruct_decls}        try self.ctx.check({destruct_fn}(self.ctx.handle{destruct_args}, self.ptr));
+{destruct_wrap}        try self.ctx.autoSync();
+        return {destruct_return};
+    }}
@@ -0,0 +1,10 @@
+
+    /// Construct the `#{variant_name}` variant
+    pub fn {name}(ctx: *Context{construct_params}) Error!{class_name} {{
+        var out: *{raw_name} = undefined;
+        try ctx.check({construct_fn}(ctx.handle, &out{construct_args}));
+        var result = {class_name}{{ .ctx = ctx, .ptr = out }};
+        errdefer result.deinit();
+        try ctx.autoSync();
+        return result;
+    }}
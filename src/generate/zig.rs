@@ -0,0 +1,854 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::generate::IdentRules;
+use crate::ir::ElemType;
+use crate::*;
+
+/// Zig codegen, writes a single file declaring the C API as `extern` functions along with
+/// wrapper structs, the C file generated by Futhark has to be compiled and linked separately
+#[derive(Default)]
+pub struct Zig;
+
+/// Container level names defined by `templates/zig/context.zig` and names used for
+/// parameters and locals in generated functions, Zig doesn't allow locals to shadow
+/// declarations so these can't be used by generated types and entry points
+const RESERVED_GLOBALS: &[&str] = &[
+    "Context",
+    "Error",
+    "Options",
+    "allocator",
+    "backend",
+    "bytes",
+    "cache_file",
+    "code",
+    "config",
+    "ctx",
+    "data",
+    "device",
+    "device_memory",
+    "dim",
+    "dims",
+    "errorFromCode",
+    "fields",
+    "handle",
+    "index",
+    "len",
+    "numElements",
+    "options",
+    "out",
+    "platform",
+    "ptr",
+    "rc",
+    "result",
+    "self",
+    "std",
+    "str",
+    "takeString",
+];
+
+/// Declarations and locals of the generated structs, these can't be used for accessors
+const RESERVED_MEMBERS: &[&str] = &[
+    "Fields",
+    "Variant",
+    "allocator",
+    "bytes",
+    "ctx",
+    "data",
+    "deinit",
+    "dims",
+    "fields",
+    "get",
+    "index",
+    "init",
+    "len",
+    "out",
+    "ptr",
+    "restore",
+    "result",
+    "self",
+    "shape",
+    "store",
+    "variant",
+    "zip",
+];
+
+fn zig_ident(name: &str, reserved: &[&str], item: impl Fn() -> String) -> Result<String, Error> {
+    IdentRules::ZIG.escape(name, reserved, item)
+}
+
+/// Convert a snake case name to camel case, optionally after `prefix`
+fn zig_camel(
+    prefix: &str,
+    name: &str,
+    reserved: &[&str],
+    item: impl Fn() -> String,
+) -> Result<String, Error> {
+    let mut camel = prefix.to_string();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let mut chars = part.chars();
+        if let Some(c) = chars.next() {
+            if camel.is_empty() {
+                camel.push(c);
+            } else {
+                camel.extend(c.to_uppercase());
+            }
+            camel.push_str(chars.as_str());
+        }
+    }
+    zig_ident(&camel, reserved, item)
+}
+
+/// Returns the Zig type used for a primitive value and the type used by the C API, `f16`
+/// values are passed as their bits
+fn primitive_type(t: ElemType) -> (&'static str, &'static str) {
+    match t {
+        ElemType::I8 => ("i8", "i8"),
+        ElemType::I16 => ("i16", "i16"),
+        ElemType::I32 => ("i32", "i32"),
+        ElemType::I64 => ("i64", "i64"),
+        ElemType::U8 => ("u8", "u8"),
+        ElemType::U16 => ("u16", "u16"),
+        ElemType::U32 => ("u32", "u32"),
+        ElemType::U64 => ("u64", "u64"),
+        ElemType::F16 => ("f16", "u16"),
+        ElemType::F32 => ("f32", "f32"),
+        ElemType::F64 => ("f64", "f64"),
+        ElemType::Bool => ("bool", "bool"),
+    }
+}
+
+/// Name of the struct generated for a type
+fn class_name(ty: &ir::Type) -> Result<String, Error> {
+    zig_ident(&ty.name, RESERVED_GLOBALS, || {
+        format!("type {:?}", ty.futhark_name)
+    })
+}
+
+/// Render documentation as `///` comments
+fn doc_comment(doc: &str) -> String {
+    doc.lines()
+        .map(|line| format!("/// {line}").trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Get the documentation for a type, using the Futhark name if the `Namer` didn't provide
+/// any
+fn type_doc(ty: &ir::Type) -> String {
+    doc_comment(
+        ty.doc
+            .as_deref()
+            .unwrap_or(&format!("Futhark type `{}`", ty.futhark_name)),
+    )
+}
+
+/// Declaration of a C function, every function takes the context as its first argument.
+/// Parameters are unnamed so they can't shadow other declarations.
+fn extern_fn(name: &str, params: &[String], ret: &str) -> String {
+    let params = std::iter::once("*futhark_context".to_string())
+        .chain(params.iter().cloned())
+        .collect::<Vec<_>>();
+    format!("pub extern fn {name}({}) {ret};\n", params.join(", "))
+}
+
+/// Dimension parameters of C functions taking a shape or an index
+fn dim_params(rank: usize) -> Vec<String> {
+    vec!["i64".to_string(); rank]
+}
+
+/// Elements of a `shape` or `index` array passed to a C function
+fn index_args(name: &str, rank: usize) -> String {
+    (0..rank)
+        .map(|i| format!("{name}[{i}]"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// How a value is passed to the C API
+enum Value<'a> {
+    Primitive(ElemType),
+    Named(&'a ir::Type),
+}
+
+impl<'a> Value<'a> {
+    fn new(lib: &'a ir::Library, ty: &ir::TypeRef) -> Result<Self, Error> {
+        match (ty, lib.get(ty)) {
+            (ir::TypeRef::Primitive(t), _) => Ok(Value::Primitive(*t)),
+            (_, Some(t)) => Ok(Value::Named(t)),
+            (ir::TypeRef::Named(name), None) => {
+                Err(Error::InvalidManifest(format!("unknown type {name:?}")))
+            }
+        }
+    }
+
+    /// Type used by the wrappers
+    fn zig_type(&self) -> Result<String, Error> {
+        match self {
+            Value::Primitive(t) => Ok(primitive_type(*t).0.to_string()),
+            Value::Named(t) => class_name(t),
+        }
+    }
+
+    /// Type used in `extern` declarations
+    fn c_type(&self) -> String {
+        match self {
+            Value::Primitive(t) => primitive_type(*t).1.to_string(),
+            Value::Named(t) => format!("*{}", t.raw_name),
+        }
+    }
+
+    /// Expression passing `expr` to the C API
+    fn arg(&self, expr: &str) -> String {
+        match self {
+            Value::Primitive(ElemType::F16) => format!("@as(u16, @bitCast({expr}))"),
+            Value::Primitive(_) => expr.to_string(),
+            Value::Named(_) => format!("{expr}.ptr"),
+        }
+    }
+
+    /// Declarations wrapping the output `out`, returns the code and the expression used
+    /// to return the value. Handles are freed if a later step fails.
+    fn wrap(
+        &self,
+        ctx: &str,
+        out: &str,
+        result: &str,
+        indent: &str,
+    ) -> Result<(String, String), Error> {
+        match self {
+            Value::Primitive(ElemType::F16) => Ok((String::new(), format!("@as(f16, @bitCast({out}))"))),
+            Value::Primitive(_) => Ok((String::new(), out.to_string())),
+            Value::Named(t) => Ok((
+                format!(
+                    "{indent}var {result} = {}{{ .ctx = {ctx}, .ptr = {out} }};\n{indent}errdefer {result}.deinit();\n",
+                    class_name(t)?
+                ),
+                result.to_string(),
+            )),
+        }
+    }
+}
+
+/// Get the output file, relative to the output directory
+fn file(config: &Config) -> PathBuf {
+    PathBuf::from(config.output_path.file_name().unwrap_or_default())
+}
+
+/// Render a template from `templates/zig` and append it to the output file
+fn render(config: &mut Config, name: &str, args: &[(&str, &dyn Display)]) -> Result<(), Error> {
+    let code = config.templates.render("zig", name, args)?;
+    writeln!(config.output(file(config)), "{code}")?;
+    Ok(())
+}
+
+/// Render a constructor taking a `Fields` struct, used for records and arrays of records,
+/// returns the code and the declaration of `c_fn`
+fn field_constructor(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+    template: &str,
+    fn_key: &str,
+    c_fn: &str,
+) -> Result<(String, String), Error> {
+    let mut struct_fields = String::new();
+    let mut call_args = String::new();
+    let mut params = vec![format!("**{}", ty.raw_name)];
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let name = zig_ident(&field.name, &[], || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        struct_fields.push_str(&format!("        {name}: {},\n", value.zig_type()?));
+        call_args.push_str(&format!(", {}", value.arg(&format!("fields.{name}"))));
+        params.push(value.c_type());
+    }
+    let code = config.templates.render(
+        "zig",
+        template,
+        &[
+            ("class_name", &class_name(ty)?),
+            ("raw_name", &ty.raw_name),
+            (fn_key, &c_fn),
+            ("fields", &struct_fields),
+            ("call_args", &call_args),
+        ],
+    )?;
+    Ok((code, extern_fn(c_fn, &params, "c_int")))
+}
+
+/// Render one accessor per field, used for records and arrays of records
+fn field_projections(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    fields: &[ir::Field],
+    externs: &mut String,
+) -> Result<Vec<String>, Error> {
+    let mut members = Vec::new();
+    for field in fields {
+        let value = Value::new(lib, &field.ty)?;
+        let project_name = zig_camel("", &field.project_name, RESERVED_MEMBERS, || {
+            format!(
+                "field {:?} of type {:?}",
+                field.futhark_name, ty.futhark_name
+            )
+        })?;
+        externs.push_str(&extern_fn(
+            &field.project_fn,
+            &[
+                format!("*{}", value.c_type()),
+                format!("*const {}", ty.raw_name),
+            ],
+            "c_int",
+        ));
+        let (out_wrap, out_return) = value.wrap("self.ctx", "out", "result", "        ")?;
+        members.push(config.templates.render(
+            "zig",
+            "record_project.zig",
+            &[
+                ("class_name", &class_name(ty)?),
+                ("field_name", &field.name),
+                ("project_name", &project_name),
+                ("project_fn", &field.project_fn),
+                ("zig_type", &value.zig_type()?),
+                ("out_type", &value.c_type()),
+                ("out_wrap", &out_wrap),
+                ("out_return", &out_return),
+            ],
+        )?);
+    }
+    Ok(members)
+}
+
+/// Type returned by a function with `types` as outputs
+fn results(types: &[String]) -> String {
+    match types {
+        [] => "void".to_string(),
+        [t] => t.clone(),
+        _ => format!("struct {{ {} }}", types.join(", ")),
+    }
+}
+
+/// Expression returning `values`, matching `results`
+fn results_return(values: &[String]) -> String {
+    match values {
+        [] => "{}".to_string(),
+        [v] => v.clone(),
+        _ => format!(".{{ {} }}", values.join(", ")),
+    }
+}
+
+/// Functions for sum types: the `Variant` enum, constructors and payload getters
+fn sum_members(
+    lib: &ir::Library,
+    config: &Config,
+    ty: &ir::Type,
+    sum: &ir::Sum,
+    externs: &mut String,
+) -> Result<Vec<String>, Error> {
+    let class_name = class_name(ty)?;
+    let item = || format!("type {:?}", ty.futhark_name);
+    externs.push_str(&extern_fn(
+        &sum.variant_fn,
+        &[format!("*const {}", ty.raw_name)],
+        "c_int",
+    ));
+
+    let mut variants = String::new();
+    let mut members = Vec::new();
+    for variant in &sum.variants {
+        let variant_field = zig_ident(&variant.name, &[], item)?;
+        variants.push_str(&format!("        {variant_field},\n"));
+        let payload = variant
+            .payload
+            .iter()
+            .map(|p| Value::new(lib, p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut construct_params = String::new();
+        let mut construct_args = String::new();
+        let mut params = vec![format!("**{}", ty.raw_name)];
+        for (i, p) in payload.iter().enumerate() {
+            construct_params.push_str(&format!(", v{i}: {}", p.zig_type()?));
+            construct_args.push_str(&format!(", {}", p.arg(&format!("v{i}"))));
+            params.push(p.c_type());
+        }
+        externs.push_str(&extern_fn(&variant.construct_fn, &params, "c_int"));
+        members.push(config.templates.render(
+            "zig",
+            "sum_variant.zig",
+            &[
+                ("class_name", &class_name),
+                ("raw_name", &ty.raw_name),
+                ("variant_name", &variant.name),
+                (
+                    "name",
+                    &zig_camel("init", &variant.name, RESERVED_MEMBERS, item)?,
+                ),
+                ("construct_fn", &variant.construct_fn),
+                ("construct_params", &construct_params),
+                ("construct_args", &construct_args),
+            ],
+        )?);
+
+        if payload.is_empty() {
+            continue;
+        }
+        let mut destruct_decls = String::new();
+        let mut destruct_args = String::new();
+        let mut destruct_wrap = String::new();
+        let mut payload_types = Vec::new();
+        let mut destruct_return = Vec::new();
+        let mut params = Vec::new();
+        for (i, p) in payload.iter().enumerate() {
+            destruct_decls.push_str(&format!(
+                "        var out{i}: {} = undefined;\n",
+                p.c_type()
+            ));
+            destruct_args.push_str(&format!(", &out{i}"));
+            params.push(format!("*{}", p.c_type()));
+            let (wrap, ret) = p.wrap(
+                "self.ctx",
+                &format!("out{i}"),
+                &format!("result{i}"),
+                "        ",
+            )?;
+            destruct_wrap.push_str(&wrap);
+            payload_types.push(p.zig_type()?);
+            destruct_return.push(ret);
+        }
+        params.push(format!("*const {}", ty.raw_name));
+        externs.push_str(&extern_fn(&variant.destruct_fn, &params, "c_int"));
+        members.push(config.templates.render(
+            "zig",
+            "sum_payload.zig",
+            &[
+                ("class_name", &class_name),
+                ("variant_name", &variant.name),
+                ("variant_field", &variant_field),
+                (
+                    "name",
+                    &zig_camel("get", &variant.name, RESERVED_MEMBERS, item)?,
+                ),
+                ("payload_type", &results(&payload_types)),
+                ("destruct_fn", &variant.destruct_fn),
+                ("destruct_decls", &destruct_decls),
+                ("destruct_args", &destruct_args),
+                ("destruct_wrap", &destruct_wrap),
+                ("destruct_return", &results_return(&destruct_return)),
+            ],
+        )?);
+    }
+
+    let sum = config.templates.render(
+        "zig",
+        "sum.zig",
+        &[
+            ("class_name", &class_name),
+            ("variants", &variants),
+            ("variant_fn", &sum.variant_fn),
+        ],
+    )?;
+    members.insert(0, sum);
+    Ok(members)
+}
+
+/// Name of the function generated for an entry point, or one of its aliases
+fn entry_name(entry: &ir::Entry, name: &str) -> Result<String, Error> {
+    zig_camel("", name, RESERVED_GLOBALS, || {
+        format!("entry point {:?}", entry.futhark_name)
+    })
+}
+
+/// Names that can't be used by entry point parameters: the container level declarations
+/// and the locals used in the function body
+fn entry_reserved(lib: &ir::Library, entry: &ir::Entry) -> Result<Vec<String>, Error> {
+    let mut reserved = RESERVED_GLOBALS
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    for ty in lib.types.values() {
+        reserved.push(class_name(ty)?);
+    }
+    for e in lib.entries.values() {
+        reserved.push(entry_name(e, &e.name)?);
+        for alias in &e.aliases {
+            reserved.push(entry_name(e, alias)?);
+        }
+    }
+    for i in 0..entry.outputs.len() {
+        reserved.push(format!("out{i}"));
+        reserved.push(format!("result{i}"));
+    }
+    Ok(reserved)
+}
+
+impl Generate for Zig {
+    fn bindings(&mut self, lib: &ir::Library, config: &mut Config) -> Result<(), Error> {
+        let caps = lib.backend.capabilities();
+
+        let mut backend_externs = String::new();
+        let mut backend_options = String::new();
+        let mut configure_backend = String::new();
+        for option in caps.config_options {
+            let (extern_decl, field, line) = match option {
+                ConfigOption::NumThreads => (
+                    "pub extern fn futhark_context_config_set_num_threads(*futhark_context_config, c_int) void;\n",
+                    "\n    /// Number of threads, 0 selects the number of cores\n    num_threads: c_int = 0,\n",
+                    "        futhark_context_config_set_num_threads(config, options.num_threads);\n",
+                ),
+                ConfigOption::Device => (
+                    "pub extern fn futhark_context_config_set_device(*futhark_context_config, [*:0]const u8) void;\n",
+                    "\n    /// Device name\n    device: ?[*:0]const u8 = null,\n",
+                    "        if (options.device) |device| futhark_context_config_set_device(config, device);\n",
+                ),
+                ConfigOption::Platform => (
+                    "pub extern fn futhark_context_config_set_platform(*futhark_context_config, [*:0]const u8) void;\n",
+                    "\n    /// Platform name\n    platform: ?[*:0]const u8 = null,\n",
+                    "        if (options.platform) |platform| futhark_context_config_set_platform(config, platform);\n",
+                ),
+            };
+            backend_externs.push_str(extern_decl);
+            backend_options.push_str(field);
+            configure_backend.push_str(line);
+        }
+
+        render(
+            config,
+            "context.zig",
+            &[
+                ("backend", &lib.backend.to_str()),
+                ("device_memory", &(caps.memory == Memory::Device)),
+                ("backend_externs", &backend_externs),
+                ("backend_options", &backend_options),
+                ("configure_backend", &configure_backend),
+            ],
+        )
+    }
+
+    fn array_type(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+        a: &ir::Array,
+    ) -> Result<(), Error> {
+        let (elem_type, c_type) = primitive_type(a.elemtype);
+        let raw = &ty.raw_name;
+        let externs = [
+            extern_fn(
+                &a.ops.new,
+                &[vec![format!("[*]const {elem_type}")], dim_params(a.rank)].concat(),
+                &format!("?*{raw}"),
+            ),
+            extern_fn(&a.ops.free, &[format!("*{raw}")], "c_int"),
+            extern_fn(
+                &a.ops.values,
+                &[format!("*{raw}"), format!("[*]{elem_type}")],
+                "c_int",
+            ),
+            extern_fn(&a.ops.shape, &[format!("*{raw}")], "[*]const i64"),
+            extern_fn(
+                &a.ops.index,
+                &[
+                    vec![format!("*{c_type}"), format!("*{raw}")],
+                    dim_params(a.rank),
+                ]
+                .concat(),
+                "c_int",
+            ),
+        ];
+        let (_, out_return) = Value::Primitive(a.elemtype).wrap("self.ctx", "out", "result", "")?;
+        render(
+            config,
+            "array.zig",
+            &[
+                ("doc", &type_doc(ty)),
+                ("class_name", &class_name(ty)?),
+                ("raw_name", raw),
+                ("externs", &externs.concat().trim_end()),
+                ("rank", &a.rank),
+                ("elem_type", &elem_type),
+                ("c_type", &c_type),
+                ("shape_args", &index_args("dims", a.rank)),
+                ("index_args", &index_args("index", a.rank)),
+                ("out_return", &out_return),
+                ("new_fn", &a.ops.new),
+                ("free_fn", &a.ops.free),
+                ("values_fn", &a.ops.values),
+                ("shape_fn", &a.ops.shape),
+                ("index_fn", &a.ops.index),
+            ],
+        )
+    }
+
+    fn opaque_type(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let item = || format!("type {:?}", ty.futhark_name);
+        let ops = ty.opaque_ops.as_ref().expect("Opaque type without ops");
+        let raw = &ty.raw_name;
+
+        let mut externs = [
+            extern_fn(&ops.free, &[format!("*{raw}")], "c_int"),
+            extern_fn(
+                &ops.store,
+                &[
+                    format!("*const {raw}"),
+                    "*?*anyopaque".to_string(),
+                    "*usize".to_string(),
+                ],
+                "c_int",
+            ),
+            extern_fn(
+                &ops.restore,
+                &["*const anyopaque".to_string()],
+                &format!("?*{raw}"),
+            ),
+        ]
+        .concat();
+
+        let mut members = Vec::new();
+        match &ty.kind {
+            ir::TypeKind::Record(record) => {
+                let (constructor, extern_decl) = field_constructor(
+                    lib,
+                    config,
+                    ty,
+                    &record.fields,
+                    "record.zig",
+                    "new_fn",
+                    &record.new_fn,
+                )?;
+                members.push(constructor);
+                externs.push_str(&extern_decl);
+                members.extend(field_projections(
+                    lib,
+                    config,
+                    ty,
+                    &record.fields,
+                    &mut externs,
+                )?);
+            }
+            ir::TypeKind::Sum(sum) => {
+                members.extend(sum_members(lib, config, ty, sum, &mut externs)?)
+            }
+            ir::TypeKind::OpaqueArray(array)
+            | ir::TypeKind::RecordArray(ir::RecordArray { array, .. }) => {
+                let Value::Named(elem) = Value::new(lib, &array.elemtype)? else {
+                    return Err(Error::InvalidManifest(format!(
+                        "{} has a primitive element type",
+                        item()
+                    )));
+                };
+                externs.push_str(&extern_fn(
+                    &array.shape_fn,
+                    &[format!("*{raw}")],
+                    "[*]const i64",
+                ));
+                externs.push_str(&extern_fn(
+                    &array.index_fn,
+                    &[
+                        vec![format!("**{}", elem.raw_name), format!("*{raw}")],
+                        dim_params(array.rank),
+                    ]
+                    .concat(),
+                    "c_int",
+                ));
+                members.push(config.templates.render(
+                    "zig",
+                    "opaque_array.zig",
+                    &[
+                        ("class_name", &class_name(ty)?),
+                        ("rank", &array.rank),
+                        ("shape_fn", &array.shape_fn),
+                        ("index_fn", &array.index_fn),
+                        ("index_args", &index_args("index", array.rank)),
+                        ("elem_class", &class_name(elem)?),
+                        ("elem_raw_name", &elem.raw_name),
+                    ],
+                )?);
+
+                if let ir::TypeKind::RecordArray(record) = &ty.kind {
+                    let (constructor, extern_decl) = field_constructor(
+                        lib,
+                        config,
+                        ty,
+                        &record.fields,
+                        "record_array.zig",
+                        "zip_fn",
+                        &record.zip_fn,
+                    )?;
+                    members.push(constructor);
+                    externs.push_str(&extern_decl);
+                    members.extend(field_projections(
+                        lib,
+                        config,
+                        ty,
+                        &record.fields,
+                        &mut externs,
+                    )?);
+                }
+            }
+            ir::TypeKind::Array(_) => unreachable!("Arrays are handled by array_type"),
+        }
+
+        render(
+            config,
+            "opaque.zig",
+            &[
+                ("doc", &type_doc(ty)),
+                ("class_name", &class_name(ty)?),
+                ("raw_name", raw),
+                ("externs", &externs.trim_end()),
+                ("free_fn", &ops.free),
+                ("store_fn", &ops.store),
+                ("restore_fn", &ops.restore),
+                ("members", &members.concat()),
+            ],
+        )
+    }
+
+    fn type_aliases(
+        &mut self,
+        _lib: &ir::Library,
+        config: &mut Config,
+        ty: &ir::Type,
+    ) -> Result<(), Error> {
+        let class_name = class_name(ty)?;
+        for alias in &ty.aliases {
+            let alias = zig_ident(alias, RESERVED_GLOBALS, || {
+                format!("type {:?}", ty.futhark_name)
+            })?;
+            writeln!(
+                config.output(file(config)),
+                "/// Deprecated alias for `{class_name}`\npub const {alias} = {class_name};\n"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn entry(
+        &mut self,
+        lib: &ir::Library,
+        config: &mut Config,
+        entry: &ir::Entry,
+    ) -> Result<(), Error> {
+        let name = entry_name(entry, &entry.name)?;
+
+        let mut params = Vec::new();
+        let mut out_decl = String::new();
+        let mut out_wrap = String::new();
+        let mut call_args = String::new();
+        let mut result_types = Vec::new();
+        let mut entry_return = Vec::new();
+        for (i, output) in entry.outputs.iter().enumerate() {
+            let value = Value::new(lib, &output.ty)?;
+            out_decl.push_str(&format!(
+                "    var out{i}: {} = undefined;\n",
+                value.c_type()
+            ));
+            call_args.push_str(&format!(", &out{i}"));
+            params.push(format!("*{}", value.c_type()));
+            let (wrap, ret) =
+                value.wrap("ctx", &format!("out{i}"), &format!("result{i}"), "    ")?;
+            out_wrap.push_str(&wrap);
+            result_types.push(value.zig_type()?);
+            entry_return.push(ret);
+        }
+
+        let mut reserved = entry_reserved(lib, entry)?;
+        let mut entry_params = String::new();
+        for (i, input) in entry.inputs.iter().enumerate() {
+            let value = Value::new(lib, &input.ty)?;
+            let r = reserved.iter().map(String::as_str).collect::<Vec<_>>();
+            let param = zig_ident(&input.name, &r, || {
+                format!(
+                    "input {:?} of entry point {:?}",
+                    input.name, entry.futhark_name
+                )
+            })
+            .unwrap_or_else(|_| format!("in{i}"));
+            entry_params.push_str(&format!(", {param}: {}", value.zig_type()?));
+            call_args.push_str(&format!(", {}", value.arg(&param)));
+            params.push(value.c_type());
+            reserved.push(param);
+        }
+
+        let doc = doc_comment(
+            entry
+                .doc
+                .as_deref()
+                .unwrap_or(&format!("Entry point: {}", entry.futhark_name)),
+        );
+        render(
+            config,
+            "entry.zig",
+            &[
+                (
+                    "extern_decl",
+                    &extern_fn(&entry.cfun, &params, "c_int").trim_end(),
+                ),
+                ("doc", &doc),
+                ("name", &name),
+                ("entry_fn", &entry.cfun),
+                ("entry_params", &entry_params),
+                ("results", &results(&result_types)),
+                ("out_decl", &out_decl),
+                ("out_wrap", &out_wrap),
+                ("call_args", &call_args),
+                ("entry_return", &results_return(&entry_return)),
+            ],
+        )?;
+
+        for alias in &entry.aliases {
+            let alias = entry_name(entry, alias)?;
+            writeln!(
+                config.output(file(config)),
+                "/// Deprecated alias for `{name}`\npub const {alias} = {name};\n"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn format(&mut self, _config: &Config, _path: &Path, code: String) -> Result<String, Error> {
+        Ok(format!(
+            "// Generated by futhark-bindgen\n\n{}\n",
+            code.trim_end()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::tests::{aliasing_namer, all_types};
+
+    #[test]
+    fn generate_all_types() {
+        let mut config = Config::with_writer("lib.zig", std::io::sink(), aliasing_namer());
+        let code = Zig.generate_to_string(&all_types(), &mut config).unwrap();
+        for item in [
+            "pub fn type_(ctx: *Context, x: i32) Error!i32 {",
+            "pub fn makePoint(ctx: *Context, xs: F32Array1d, n: i64) Error!Point {",
+            "pub const make = makePoint;",
+            "pub const ArrayF32D1 = F32Array1d;",
+            "pub fn variant(self: Option) Variant {",
+            "pub fn getSome(self: Option) Error!i64 {",
+            "pub fn xs(self: Point) Error!F32Array1d {",
+            "pub fn zip(ctx: *Context, fields: Fields) Error!PointArray1d {",
+        ] {
+            assert!(code.contains(item), "missing {item:?}");
+        }
+    }
+}
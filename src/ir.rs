@@ -0,0 +1,1014 @@
+//! Resolved representation of a manifest, shared by all code generators
+//!
+//! `Library::new` resolves every type reference in the manifest, classifies each type and
+//! computes the C ABI shape of entry point parameters, so generators don't need to
+//! re-derive this information from the raw manifest strings.
+
+use serde::Serialize;
+
+use crate::generate::{convert_struct_name, Namer};
+use crate::*;
+
+pub use manifest::{ElemType, TypeExpr};
+
+/// Reference to a type from an entry point, record field or array
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeRef {
+    /// Scalar value
+    Primitive(ElemType),
+
+    /// Array or opaque type, the value is the Futhark type name and can be used to
+    /// look up the type in `Library::types`
+    Named(String),
+}
+
+impl TypeRef {
+    /// Returns true for primitive types
+    pub fn is_primitive(&self) -> bool {
+        matches!(self, TypeRef::Primitive(_))
+    }
+
+    /// Get the Futhark name of the referenced type
+    pub fn futhark_name(&self) -> &str {
+        match self {
+            TypeRef::Primitive(t) => t.to_str(),
+            TypeRef::Named(name) => name,
+        }
+    }
+}
+
+/// How a value is passed across the C API
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Abi {
+    /// Scalar passed by value: `T`
+    Value,
+
+    /// Handle passed as a pointer: `struct T *`
+    Pointer,
+
+    /// Scalar output: `T *`
+    OutValue,
+
+    /// Handle output: `struct T **`
+    OutPointer,
+}
+
+/// Ownership of an entry point input
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ownership {
+    /// The value is borrowed for the duration of the call
+    Borrowed,
+
+    /// The value is marked as unique and consumed by the call
+    Consumed,
+}
+
+/// Entry point parameter
+#[derive(Clone, Debug, Serialize)]
+pub struct Param {
+    /// Parameter name, outputs are named `out0`, `out1`, ...
+    pub name: String,
+
+    /// Parameter type
+    pub ty: TypeRef,
+
+    /// Ownership, outputs are always `Ownership::Borrowed`
+    pub ownership: Ownership,
+
+    /// Marked as unique in the Futhark program, for inputs this matches
+    /// `Ownership::Consumed`
+    pub unique: bool,
+
+    /// C ABI shape
+    pub abi: Abi,
+}
+
+/// Entry point
+#[derive(Clone, Debug, Serialize)]
+pub struct Entry {
+    /// Name of the entry point in the Futhark program
+    pub futhark_name: String,
+
+    /// Name selected by the `Namer`
+    pub name: String,
+
+    /// Documentation selected by the `Namer`
+    pub doc: Option<String>,
+
+    /// Deprecated names selected by the `Namer`, aliases that clash with another entry
+    /// point are dropped
+    pub aliases: Vec<String>,
+
+    /// Name of the C function
+    pub cfun: String,
+
+    /// Input parameters
+    pub inputs: Vec<Param>,
+
+    /// Output parameters
+    pub outputs: Vec<Param>,
+
+    /// Tuning parameters
+    pub tuning_params: Vec<String>,
+}
+
+/// Record field
+#[derive(Clone, Debug, Serialize)]
+pub struct Field {
+    /// Name of the field in the Futhark program
+    pub futhark_name: String,
+
+    /// Name used for constructor parameters
+    pub name: String,
+
+    /// Name used for projection functions
+    pub project_name: String,
+
+    /// C function used to project the field
+    pub project_fn: String,
+
+    /// Field type
+    pub ty: TypeRef,
+}
+
+/// Array of primitive values
+#[derive(Clone, Debug, Serialize)]
+pub struct Array {
+    pub elemtype: ElemType,
+    pub rank: usize,
+    pub ops: manifest::ArrayOps,
+}
+
+/// Record type
+#[derive(Clone, Debug, Serialize)]
+pub struct Record {
+    pub new_fn: String,
+    pub fields: Vec<Field>,
+}
+
+/// Sum type variant
+#[derive(Clone, Debug, Serialize)]
+pub struct Variant {
+    /// Constructor name, without `#`
+    pub name: String,
+    pub construct_fn: String,
+    pub destruct_fn: String,
+    pub payload: Vec<TypeRef>,
+}
+
+/// Sum type
+#[derive(Clone, Debug, Serialize)]
+pub struct Sum {
+    pub variant_fn: String,
+    pub variants: Vec<Variant>,
+}
+
+/// Array of opaque values
+#[derive(Clone, Debug, Serialize)]
+pub struct OpaqueArray {
+    pub elemtype: TypeRef,
+    pub rank: usize,
+    pub index_fn: String,
+    pub shape_fn: String,
+}
+
+/// Array of records, which can also be constructed from and projected into field arrays
+#[derive(Clone, Debug, Serialize)]
+pub struct RecordArray {
+    pub array: OpaqueArray,
+    pub zip_fn: String,
+    pub fields: Vec<Field>,
+}
+
+/// Classification of a type
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeKind {
+    /// Array of primitive values
+    Array(Array),
+
+    /// Record
+    Record(Record),
+
+    /// Sum type
+    Sum(Sum),
+
+    /// Array of opaque values
+    OpaqueArray(OpaqueArray),
+
+    /// Array of records
+    RecordArray(RecordArray),
+}
+
+impl TypeKind {
+    /// Returns true for array-like types
+    pub fn is_array(&self) -> bool {
+        matches!(
+            self,
+            TypeKind::Array(_) | TypeKind::OpaqueArray(_) | TypeKind::RecordArray(_)
+        )
+    }
+
+    /// Get the rank of array-like types
+    pub fn rank(&self) -> Option<usize> {
+        match self {
+            TypeKind::Array(a) => Some(a.rank),
+            TypeKind::OpaqueArray(a) => Some(a.rank),
+            TypeKind::RecordArray(a) => Some(a.array.rank),
+            _ => None,
+        }
+    }
+}
+
+/// Array or opaque type
+#[derive(Clone, Debug, Serialize)]
+pub struct Type {
+    /// Name of the type in the Futhark program
+    pub futhark_name: String,
+
+    /// Name selected by the `Namer`
+    pub name: String,
+
+    /// Name of the C struct, without `struct` or `*`
+    pub raw_name: String,
+
+    /// Parsed Futhark type, types that can't be parsed are represented as `TypeExpr::Named`
+    #[serde(skip)]
+    pub expr: TypeExpr,
+
+    /// Additional derives selected by the `Namer`
+    pub derives: Vec<String>,
+
+    /// Documentation selected by the `Namer`
+    pub doc: Option<String>,
+
+    /// Deprecated names selected by the `Namer`, aliases that clash with another type are
+    /// dropped
+    pub aliases: Vec<String>,
+
+    /// Functions shared by all opaque types, `None` for arrays of primitive values
+    pub opaque_ops: Option<manifest::OpaqueOps>,
+
+    /// Type classification
+    pub kind: TypeKind,
+}
+
+impl Type {
+    /// Get the types used by this type, for example record fields or array elements
+    pub fn references(&self) -> Vec<&TypeRef> {
+        match &self.kind {
+            TypeKind::Array(_) => Vec::new(),
+            TypeKind::Record(r) => r.fields.iter().map(|f| &f.ty).collect(),
+            TypeKind::Sum(s) => s.variants.iter().flat_map(|v| &v.payload).collect(),
+            TypeKind::OpaqueArray(a) => vec![&a.elemtype],
+            TypeKind::RecordArray(a) => std::iter::once(&a.array.elemtype)
+                .chain(a.fields.iter().map(|f| &f.ty))
+                .collect(),
+        }
+    }
+}
+
+/// Resolved Futhark library
+#[derive(Clone, Debug, Serialize)]
+pub struct Library {
+    /// Backend used to compile the library
+    pub backend: Backend,
+
+    /// Futhark version
+    pub version: String,
+
+    /// Path to the generated C file
+    pub c_file: std::path::PathBuf,
+
+    /// Path to the generated C header file
+    pub h_file: std::path::PathBuf,
+
+    /// Types, indexed by Futhark name
+    pub types: BTreeMap<String, Type>,
+
+    /// Entry points, indexed by Futhark name
+    pub entries: BTreeMap<String, Entry>,
+}
+
+fn invalid(msg: String) -> Error {
+    Error::InvalidManifest(msg)
+}
+
+fn resolve(manifest: &Manifest, name: &str, context: &str) -> Result<TypeRef, Error> {
+    if manifest.types.contains_key(name) {
+        return Ok(TypeRef::Named(name.to_string()));
+    }
+
+    match TypeExpr::parse(name) {
+        Ok(TypeExpr::Scalar(t)) => Ok(TypeRef::Primitive(t)),
+        _ => Err(invalid(format!("unknown type {name:?} in {context}"))),
+    }
+}
+
+fn fields(
+    manifest: &Manifest,
+    namer: &mut dyn Namer,
+    fields: &[manifest::Field],
+    context: &str,
+) -> Result<Vec<Field>, Error> {
+    fields
+        .iter()
+        .map(|f| {
+            Ok(Field {
+                futhark_name: f.name.clone(),
+                name: namer.new_field_name(&f.name, manifest),
+                project_name: namer.project_name(&f.name, manifest),
+                project_fn: f.project.clone(),
+                ty: resolve(manifest, &f.r#type, context)?,
+            })
+        })
+        .collect()
+}
+
+fn opaque_array(
+    manifest: &Manifest,
+    a: &manifest::OpaqueArray,
+    context: &str,
+) -> Result<OpaqueArray, Error> {
+    Ok(OpaqueArray {
+        elemtype: resolve(manifest, &a.elemtype, context)?,
+        rank: a.rank,
+        index_fn: a.index.clone(),
+        shape_fn: a.shape.clone(),
+    })
+}
+
+/// Constructor name of a sum type variant without the leading `#`, derived from the
+/// construct function when the manifest doesn't include it
+fn variant_name(v: &manifest::Variant, ctype: &str) -> String {
+    if !v.name.is_empty() {
+        return v.name.strip_prefix('#').unwrap_or(&v.name).to_string();
+    }
+    let raw_name = convert_struct_name(ctype);
+    let prefix = format!(
+        "futhark_new_{}_",
+        raw_name.strip_prefix("futhark_").unwrap_or(raw_name)
+    );
+    v.construct
+        .strip_prefix(&prefix)
+        .unwrap_or(&v.construct)
+        .to_string()
+}
+
+impl Library {
+    /// Resolve a package, using `namer` to select type and field names
+    pub fn new(pkg: &Package, namer: &mut dyn Namer) -> Result<Library, Error> {
+        let manifest = &pkg.manifest;
+        namer.init(manifest);
+
+        let mut types = BTreeMap::new();
+        let mut names: BTreeMap<String, String> = BTreeMap::new();
+        for (futhark_name, ty) in &manifest.types {
+            let context = format!("type {futhark_name:?}");
+            let (opaque_ops, kind) = match ty {
+                manifest::Type::Array(a) => (
+                    None,
+                    TypeKind::Array(Array {
+                        elemtype: a.elemtype,
+                        rank: a.rank,
+                        ops: a.ops.clone(),
+                    }),
+                ),
+                manifest::Type::Opaque(t) => {
+                    let kind = match &t.options {
+                        manifest::OpaqueOptions::Record(r) => TypeKind::Record(Record {
+                            new_fn: r.new.clone(),
+                            fields: fields(manifest, namer, &r.fields, &context)?,
+                        }),
+                        manifest::OpaqueOptions::Sum(s) => TypeKind::Sum(Sum {
+                            variant_fn: s.variant.clone(),
+                            variants: s
+                                .variants
+                                .iter()
+                                .map(|v| {
+                                    Ok(Variant {
+                                        name: variant_name(v, t.ctype.as_str()),
+                                        construct_fn: v.construct.clone(),
+                                        destruct_fn: v.destruct.clone(),
+                                        payload: v
+                                            .payload
+                                            .iter()
+                                            .map(|p| resolve(manifest, p, &context))
+                                            .collect::<Result<_, Error>>()?,
+                                    })
+                                })
+                                .collect::<Result<_, Error>>()?,
+                        }),
+                        manifest::OpaqueOptions::OpaqueArray(a) => {
+                            TypeKind::OpaqueArray(opaque_array(manifest, a, &context)?)
+                        }
+                        manifest::OpaqueOptions::RecordArray(a) => {
+                            let Some(record) = &a.record else {
+                                return Err(invalid(format!("missing record fields in {context}")));
+                            };
+                            TypeKind::RecordArray(RecordArray {
+                                array: opaque_array(manifest, a, &context)?,
+                                zip_fn: record.zip.clone(),
+                                fields: fields(manifest, namer, &record.fields, &context)?,
+                            })
+                        }
+                    };
+                    (Some(t.ops.clone()), kind)
+                }
+            };
+
+            let name = namer.type_name(futhark_name, ty, manifest);
+            if let Some(first) = names.insert(name.clone(), futhark_name.clone()) {
+                return Err(Error::NameCollision {
+                    name,
+                    first,
+                    second: futhark_name.clone(),
+                });
+            }
+
+            types.insert(
+                futhark_name.clone(),
+                Type {
+                    futhark_name: futhark_name.clone(),
+                    name,
+                    raw_name: convert_struct_name(ty.ctype()).to_string(),
+                    expr: TypeExpr::parse(futhark_name)
+                        .unwrap_or_else(|_| TypeExpr::Named(futhark_name.clone())),
+                    derives: namer.type_derives(futhark_name, manifest),
+                    doc: namer.type_doc(futhark_name, manifest),
+                    aliases: namer.type_aliases(futhark_name, ty, manifest),
+                    opaque_ops,
+                    kind,
+                },
+            );
+        }
+
+        let mut seen = std::collections::BTreeSet::new();
+        for ty in types.values_mut() {
+            ty.aliases
+                .retain(|alias| !names.contains_key(alias) && seen.insert(alias.clone()));
+        }
+
+        let mut entries = BTreeMap::new();
+        let mut entry_names: BTreeMap<String, String> = BTreeMap::new();
+        for (futhark_name, entry) in &manifest.entry_points {
+            let context = format!("entry point {futhark_name:?}");
+            let mut outputs = Vec::new();
+            for (i, output) in entry.outputs.iter().enumerate() {
+                let ty = resolve(manifest, &output.r#type, &context)?;
+                let abi = if ty.is_primitive() {
+                    Abi::OutValue
+                } else {
+                    Abi::OutPointer
+                };
+                outputs.push(Param {
+                    name: format!("out{i}"),
+                    ty,
+                    ownership: Ownership::Borrowed,
+                    unique: output.unique,
+                    abi,
+                });
+            }
+
+            let mut inputs = Vec::new();
+            for input in &entry.inputs {
+                let ty = resolve(manifest, &input.r#type, &context)?;
+                let abi = if ty.is_primitive() {
+                    Abi::Value
+                } else {
+                    Abi::Pointer
+                };
+                inputs.push(Param {
+                    name: input.name.clone(),
+                    ty,
+                    ownership: if input.unique {
+                        Ownership::Consumed
+                    } else {
+                        Ownership::Borrowed
+                    },
+                    unique: input.unique,
+                    abi,
+                });
+            }
+
+            let name = namer.entry_name(futhark_name, manifest);
+            if let Some(first) = entry_names.insert(name.clone(), futhark_name.clone()) {
+                return Err(Error::NameCollision {
+                    name,
+                    first,
+                    second: futhark_name.clone(),
+                });
+            }
+
+            entries.insert(
+                futhark_name.clone(),
+                Entry {
+                    futhark_name: futhark_name.clone(),
+                    name,
+                    doc: namer.entry_doc(futhark_name, manifest),
+                    aliases: namer.entry_aliases(futhark_name, manifest),
+                    cfun: entry.cfun.clone(),
+                    inputs,
+                    outputs,
+                    tuning_params: entry.tuning_params.clone(),
+                },
+            );
+        }
+
+        let mut seen = std::collections::BTreeSet::new();
+        for entry in entries.values_mut() {
+            entry
+                .aliases
+                .retain(|alias| !entry_names.contains_key(alias) && seen.insert(alias.clone()));
+        }
+
+        Ok(Library {
+            backend: manifest.backend,
+            version: manifest.version.clone(),
+            c_file: pkg.c_file.clone(),
+            h_file: pkg.h_file.clone(),
+            types,
+            entries,
+        })
+    }
+
+    /// Remove the entry points and types rejected by `filter`, along with types that are no
+    /// longer used
+    pub fn filter(&mut self, filter: &Filter) -> Result<(), Error> {
+        self.entries.retain(|name, _| filter.entry(name));
+
+        // Walk the types used by the remaining items, remembering the first user of each
+        let mut used_by: BTreeMap<String, String> = BTreeMap::new();
+        let mut queue = Vec::new();
+        for entry in self.entries.values() {
+            for param in entry.inputs.iter().chain(&entry.outputs) {
+                queue.push((&param.ty, format!("entry point {:?}", entry.futhark_name)));
+            }
+        }
+        for ty in self.types.values() {
+            if filter.type_included(&ty.futhark_name) {
+                used_by.insert(ty.futhark_name.clone(), String::new());
+                queue.extend(
+                    ty.references()
+                        .into_iter()
+                        .map(|r| (r, format!("type {:?}", ty.futhark_name))),
+                );
+            }
+        }
+
+        while let Some((ty, user)) = queue.pop() {
+            let Some(ty) = self.get(ty) else {
+                continue;
+            };
+            if used_by.contains_key(&ty.futhark_name) {
+                continue;
+            }
+            if !filter.type_allowed(&ty.futhark_name) {
+                return Err(Error::ExcludedType {
+                    name: ty.futhark_name.clone(),
+                    used_by: user,
+                });
+            }
+            used_by.insert(ty.futhark_name.clone(), user);
+            queue.extend(
+                ty.references()
+                    .into_iter()
+                    .map(|r| (r, format!("type {:?}", ty.futhark_name))),
+            );
+        }
+
+        self.types.retain(|name, _| used_by.contains_key(name));
+        Ok(())
+    }
+
+    /// Get the types ordered so every type comes after the types it uses, otherwise types
+    /// are sorted by Futhark name
+    pub fn types_in_order(&self) -> Vec<&Type> {
+        fn visit<'a>(
+            lib: &'a Library,
+            ty: &'a Type,
+            seen: &mut std::collections::BTreeSet<&'a str>,
+            out: &mut Vec<&'a Type>,
+        ) {
+            if !seen.insert(&ty.futhark_name) {
+                return;
+            }
+            for r in ty.references() {
+                if let Some(t) = lib.get(r) {
+                    visit(lib, t, seen, out);
+                }
+            }
+            out.push(ty);
+        }
+
+        let mut seen = std::collections::BTreeSet::new();
+        let mut out = Vec::new();
+        for ty in self.types.values() {
+            visit(self, ty, &mut seen, &mut out);
+        }
+        out
+    }
+
+    /// Look up a named type
+    pub fn get(&self, ty: &TypeRef) -> Option<&Type> {
+        match ty {
+            TypeRef::Primitive(_) => None,
+            TypeRef::Named(name) => self.types.get(name),
+        }
+    }
+
+    /// Get the name selected by the `Namer` for a named type, or the Futhark name of a primitive type
+    pub fn type_name<'a>(&'a self, ty: &'a TypeRef) -> &'a str {
+        match self.get(ty) {
+            Some(t) => &t.name,
+            None => ty.futhark_name(),
+        }
+    }
+
+    /// Get the C struct name for a named type, or the Futhark name of a primitive type
+    pub fn raw_name<'a>(&'a self, ty: &'a TypeRef) -> &'a str {
+        match self.get(ty) {
+            Some(t) => &t.raw_name,
+            None => ty.futhark_name(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// Build a package from the `entry_points` and `types` sections of a manifest
+    pub(crate) fn package(entry_points: Value, types: Value) -> Package {
+        let manifest = json!({
+            "backend": "c",
+            "version": "0.25.0",
+            "entry_points": entry_points,
+            "types": types,
+        });
+        Package {
+            manifest: serde_json::from_value(manifest).expect("invalid test manifest"),
+            c_file: "test.c".into(),
+            h_file: "test.h".into(),
+            src: "test.fut".into(),
+        }
+    }
+
+    /// Entry point taking `inputs`, given as name and type, and returning `outputs`
+    pub(crate) fn entry(name: &str, inputs: &[(&str, &str)], outputs: &[&str]) -> Value {
+        json!({
+            "cfun": format!("futhark_entry_{name}"),
+            "inputs": inputs
+                .iter()
+                .map(|(name, ty)| json!({"name": name, "type": ty, "unique": false}))
+                .collect::<Vec<_>>(),
+            "outputs": outputs
+                .iter()
+                .map(|ty| json!({"type": ty, "unique": false}))
+                .collect::<Vec<_>>(),
+            "tuning_params": [],
+        })
+    }
+
+    fn ops(c: &str) -> Value {
+        json!({
+            "free": format!("futhark_free_{c}"),
+            "store": format!("futhark_store_{c}"),
+            "restore": format!("futhark_restore_{c}"),
+        })
+    }
+
+    /// Record with `fields`, given as name and type
+    pub(crate) fn record(c: &str, fields: &[(&str, &str)]) -> Value {
+        json!({
+            "kind": "opaque",
+            "ctype": format!("struct futhark_{c} *"),
+            "ops": ops(c),
+            "record": {
+                "new": format!("futhark_new_{c}"),
+                "fields": fields
+                    .iter()
+                    .map(|(name, ty)| json!({
+                        "name": name,
+                        "type": ty,
+                        "project": format!("futhark_project_{c}_{name}"),
+                    }))
+                    .collect::<Vec<_>>(),
+            },
+        })
+    }
+
+    /// Sum type with `variants`, given as constructor name and payload types, names are used
+    /// as-is in the manifest
+    pub(crate) fn sum(c: &str, variants: &[(&str, &[&str])]) -> Value {
+        json!({
+            "kind": "opaque",
+            "ctype": format!("struct futhark_{c} *"),
+            "ops": ops(c),
+            "sum": {
+                "variant": format!("futhark_variant_{c}"),
+                "variants": variants
+                    .iter()
+                    .map(|(name, payload)| {
+                        let c_name = name.trim_start_matches('#');
+                        json!({
+                            "name": name,
+                            "payload": payload,
+                            "construct": format!("futhark_new_{c}_{c_name}"),
+                            "destruct": format!("futhark_destruct_{c}_{c_name}"),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            },
+        })
+    }
+
+    /// Array of a primitive type, `c` is the C type name such as `i64_1d`
+    pub(crate) fn array(c: &str, elemtype: &str, rank: usize) -> Value {
+        json!({
+            "kind": "array",
+            "ctype": format!("struct futhark_{c} *"),
+            "elemtype": elemtype,
+            "rank": rank,
+            "ops": {
+                "free": format!("futhark_free_{c}"),
+                "shape": format!("futhark_shape_{c}"),
+                "values": format!("futhark_values_{c}"),
+                "new": format!("futhark_new_{c}"),
+                "index": format!("futhark_index_{c}"),
+            },
+        })
+    }
+
+    /// Array of an opaque type
+    pub(crate) fn opaque_array(c: &str, elemtype: &str, rank: usize) -> Value {
+        json!({
+            "kind": "opaque",
+            "ctype": format!("struct futhark_{c} *"),
+            "ops": ops(c),
+            "opaque_array": {
+                "rank": rank,
+                "elemtype": elemtype,
+                "index": format!("futhark_index_{c}"),
+                "shape": format!("futhark_shape_{c}"),
+            },
+        })
+    }
+
+    /// Array of records, `fields` are given as name and array type
+    pub(crate) fn record_array(
+        c: &str,
+        elemtype: &str,
+        rank: usize,
+        fields: &[(&str, &str)],
+    ) -> Value {
+        json!({
+            "kind": "opaque",
+            "ctype": format!("struct futhark_{c} *"),
+            "ops": ops(c),
+            "record_array": {
+                "rank": rank,
+                "elemtype": elemtype,
+                "index": format!("futhark_index_{c}"),
+                "shape": format!("futhark_shape_{c}"),
+                "zip": format!("futhark_zip_{c}"),
+                "fields": fields
+                    .iter()
+                    .map(|(name, ty)| json!({
+                        "name": name,
+                        "type": ty,
+                        "project": format!("futhark_project_{c}_{name}"),
+                    }))
+                    .collect::<Vec<_>>(),
+            },
+        })
+    }
+
+    /// Package using every kind of type, with entry points named after keywords of the
+    /// output languages
+    pub(crate) fn all_types() -> Package {
+        package(
+            json!({
+                "make": entry("make", &[("xs", "[]f32"), ("n", "i64")], &["point"]),
+                "split": entry("split", &[("ps", "[]point")], &["[]f32", "[]f32", "i32"]),
+                "pick": entry("pick", &[("o", "[]option"), ("i", "i64")], &["option"]),
+                "noop": entry("noop", &[], &[]),
+                "type": entry("type", &[("x", "i32")], &["i32"]),
+                "class": entry("class", &[("x", "i32")], &["i32"]),
+                "end": entry("end", &[("x", "i32")], &["i32"]),
+            }),
+            json!({
+                "[]f32": array("f32_1d", "f32", 1),
+                "[][]u8": array("u8_2d", "u8", 2),
+                "point": record("opaque_point", &[("xs", "[]f32"), ("w", "f64")]),
+                "option": sum("opaque_option", &[("#none", &[]), ("#some", &["i64"])]),
+                "[]option": opaque_array("opaque_arr_option_1d", "option", 1),
+                "[]point": record_array(
+                    "opaque_arr_point_1d",
+                    "point",
+                    1,
+                    &[("xs", "[][]f32"), ("w", "[]f64")],
+                ),
+                "[][]f32": array("f32_2d", "f32", 2),
+                "[]f64": array("f64_1d", "f64", 1),
+            }),
+        )
+    }
+
+    /// Namer generating deprecated aliases for the legacy type names and for `make`, which
+    /// is renamed to `make_point`
+    pub(crate) fn aliasing_namer() -> DefaultNamer {
+        let config =
+            NamingConfig::parse("aliases = \"legacy\"\n[entries]\nmake = \"make_point\"\n")
+                .unwrap();
+        DefaultNamer::default().with_config(config)
+    }
+
+    pub(crate) fn library(pkg: &Package, namer: impl Namer) -> Result<Library, Error> {
+        let mut namer = namer;
+        Library::new(pkg, &mut namer)
+    }
+
+    #[test]
+    fn records_differing_in_field_types_get_distinct_names() {
+        let pkg = package(
+            json!({}),
+            json!({
+                "{x: f32, y: f32}": record("opaque_1a", &[("x", "f32"), ("y", "f32")]),
+                "{x: i32, y: i32}": record("opaque_2b", &[("x", "i32"), ("y", "i32")]),
+                "#some i64 | #none": sum("opaque_3c", &[("none", &[]), ("some", &["i64"])]),
+                "#some f32 | #none": sum("opaque_4d", &[("none", &[]), ("some", &["f32"])]),
+            }),
+        );
+        let lib = library(&pkg, DefaultNamer::default()).unwrap();
+        let name = |t: &str| lib.types[t].name.as_str();
+        assert_eq!(name("{x: f32, y: f32}"), "RecordXF32YF32");
+        assert_eq!(name("{x: i32, y: i32}"), "RecordXI32YI32");
+        assert_eq!(name("#some i64 | #none"), "SumSomeI64None");
+        assert_eq!(name("#some f32 | #none"), "SumSomeF32None");
+    }
+
+    #[test]
+    fn type_name_collision() {
+        let pkg = package(
+            json!({}),
+            json!({
+                "point": record("opaque_point", &[("x", "f32")]),
+                "vec": record("opaque_vec", &[("x", "f32")]),
+            }),
+        );
+        let namer = DefaultNamer::default().with_type_name("vec", "Point");
+        match library(&pkg, namer) {
+            Err(Error::NameCollision {
+                name,
+                first,
+                second,
+            }) => {
+                assert_eq!(name, "Point");
+                assert_eq!((first.as_str(), second.as_str()), ("point", "vec"));
+            }
+            other => panic!("expected a name collision, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn entry_name_collision() {
+        let pkg = package(
+            json!({
+                "add": entry("add", &[("x", "i32")], &["i32"]),
+                "plus": entry("plus", &[("x", "i32")], &["i32"]),
+            }),
+            json!({}),
+        );
+        let naming = NamingConfig::parse("[entries]\nplus = \"add\"\n").unwrap();
+        match library(&pkg, DefaultNamer::default().with_config(naming)) {
+            Err(Error::NameCollision {
+                name,
+                first,
+                second,
+            }) => {
+                assert_eq!(name, "add");
+                assert_eq!((first.as_str(), second.as_str()), ("add", "plus"));
+            }
+            other => panic!("expected a name collision, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn variant_names() {
+        let mut unnamed = sum("opaque_option", &[("none", &[]), ("some", &["i64"])]);
+        for v in unnamed["sum"]["variants"].as_array_mut().unwrap() {
+            v.as_object_mut().unwrap().remove("name");
+        }
+        let pkg = package(
+            json!({}),
+            json!({
+                "named": sum("opaque_named", &[("#none", &[]), ("#some", &["i64"])]),
+                "unnamed": unnamed,
+            }),
+        );
+        let lib = library(&pkg, DefaultNamer::default()).unwrap();
+        for t in ["named", "unnamed"] {
+            let TypeKind::Sum(s) = &lib.types[t].kind else {
+                panic!("expected a sum type");
+            };
+            let names = s
+                .variants
+                .iter()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(names, ["none", "some"], "variants of {t:?}");
+        }
+    }
+
+    fn filter_package() -> Package {
+        package(
+            json!({
+                "main": entry("main", &[("ps", "[]point")], &["f32"]),
+                "other": entry("other", &[("o", "option")], &[]),
+            }),
+            json!({
+                "[]f32": array("f32_1d", "f32", 1),
+                "[][]f32": array("f32_2d", "f32", 2),
+                "[]f64": array("f64_1d", "f64", 1),
+                "[]u8": array("u8_1d", "u8", 1),
+                "point": record("opaque_point", &[("xs", "[]f32"), ("w", "f64")]),
+                "[]point": record_array(
+                    "opaque_arr_point_1d",
+                    "point",
+                    1,
+                    &[("xs", "[][]f32"), ("w", "[]f64")],
+                ),
+                "option": sum("opaque_option", &[("#none", &[]), ("#some", &["i64"])]),
+            }),
+        )
+    }
+
+    fn filtered(filter: &Filter) -> Result<Library, Error> {
+        let mut lib = library(&filter_package(), DefaultNamer::default())?;
+        lib.filter(filter)?;
+        Ok(lib)
+    }
+
+    #[test]
+    fn default_filter_keeps_everything() {
+        let lib = filtered(&Filter::default()).unwrap();
+        assert_eq!(lib.entries.len(), 2);
+        assert_eq!(lib.types.len(), 7);
+    }
+
+    #[test]
+    fn filter_prunes_unused_types_transitively() {
+        let lib = filtered(&Filter::default().include_entry("main")).unwrap();
+        assert_eq!(lib.entries.keys().collect::<Vec<_>>(), ["main"]);
+        assert_eq!(
+            lib.types.keys().collect::<Vec<_>>(),
+            ["[][]f32", "[]f32", "[]f64", "[]point", "point"]
+        );
+
+        let lib = filtered(
+            &Filter::default()
+                .exclude_entry("other")
+                .include_type("[]u*"),
+        )
+        .unwrap();
+        assert!(lib.types.contains_key("[]u8"));
+        assert!(!lib.types.contains_key("option"));
+    }
+
+    #[test]
+    fn filter_reports_excluded_types() {
+        match filtered(
+            &Filter::default()
+                .include_entry("main")
+                .exclude_type("[]f32"),
+        ) {
+            Err(Error::ExcludedType { name, used_by }) => {
+                assert_eq!(name, "[]f32");
+                assert_eq!(used_by, "type \"point\"");
+            }
+            other => panic!("expected an excluded type, got {other:?}"),
+        }
+
+        // Unused excluded types are dropped
+        let lib = filtered(
+            &Filter::default()
+                .exclude_type("option")
+                .exclude_entry("other"),
+        )
+        .unwrap();
+        assert!(!lib.types.contains_key("option"));
+        match filtered(&Filter::default().exclude_type("option")) {
+            Err(Error::ExcludedType { name, used_by }) => {
+                assert_eq!(name, "option");
+                assert_eq!(used_by, "entry point \"other\"");
+            }
+            other => panic!("expected an excluded type, got {other:?}"),
+        }
+    }
+}
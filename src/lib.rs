@@ -1,103 +1,26 @@
 pub(crate) use std::collections::BTreeMap;
 
+mod backend;
 mod compiler;
 mod error;
 pub(crate) mod generate;
+pub mod ir;
 pub mod manifest;
 mod package;
 
+pub use backend::{
+    Backend, BackendSelector, Capabilities, CompileStep, ConfigOption, Memory, Probe,
+};
 pub use compiler::Compiler;
 pub use error::Error;
-pub use generate::{Config, DefaultNamer, Generate, Rust};
+pub use generate::{
+    CSharp, Config, Cpp, DefaultNamer, DocFormat, Docs, EntryOverride, Filter, Generate, Go,
+    IdentRules, Julia, Language, LanguageInfo, Layout, Namer, NamingConfig, NamingScheme, OCaml,
+    Plugin, Python, Rust, TemplateInfo, Templates, TypeOverride, Zig, PLUGIN_PROTOCOL, TEMPLATES,
+};
 pub use manifest::Manifest;
 pub use package::Package;
 
-/// `Backend` is used to select a backend when running the `futhark` executable
-#[derive(Debug, serde::Deserialize, PartialEq, Eq, Clone, Copy)]
-pub enum Backend {
-    /// Sequential C backend: `futhark c`
-    ///
-    /// Requires a C compiler
-    #[serde(rename = "c")]
-    C,
-
-    /// CUDA backend: `futhark cuda`
-    ///
-    /// Requires the CUDA runtime and a C compiler
-    #[serde(rename = "cuda")]
-    Cuda,
-
-    /// OpenCL backend: `futhark opencl`
-    ///
-    /// Requires OpenCL and a C compiler
-    #[serde(rename = "opencl")]
-    OpenCl,
-
-    /// Multicore C backend: `futhark multicore`
-    ///
-    /// Requires a C compiler
-    #[serde(rename = "multicore")]
-    Multicore,
-
-    /// ISPC backend: `futhark ispc`
-    ///
-    /// Requires the `ispc` compiler in your `$PATH`
-    /// and a C compiler
-    #[serde(rename = "ispc")]
-    Ispc,
-
-    /// HIP backend: `futhark hip`
-    ///
-    /// Requires a C compiler
-    #[serde(rename = "hip")]
-    Hip,
-}
-
-impl Backend {
-    /// Get the name of a backend
-    pub fn to_str(&self) -> &'static str {
-        match self {
-            Backend::C => "c",
-            Backend::Cuda => "cuda",
-            Backend::OpenCl => "opencl",
-            Backend::Multicore => "multicore",
-            Backend::Ispc => "ispc",
-            Backend::Hip => "hip",
-        }
-    }
-
-    /// Return the backend specified by the given name if valid
-    pub fn from_name(name: &str) -> Option<Backend> {
-        match name.to_ascii_lowercase().as_str() {
-            "c" => Some(Backend::C),
-            "cuda" => Some(Backend::Cuda),
-            "opencl" => Some(Backend::OpenCl),
-            "multicore" => Some(Backend::Multicore),
-            "ispc" => Some(Backend::Ispc),
-            _ => None,
-        }
-    }
-
-    /// Get the backend from the `FUTHARK_BACKEND` environment variable
-    pub fn from_env() -> Option<Backend> {
-        match std::env::var("FUTHARK_BACKEND") {
-            Ok(name) => Backend::from_name(&name),
-            Err(_) => None,
-        }
-    }
-
-    /// Returns the C libraries that need to be linked for a backend
-    pub fn required_c_libs(&self) -> &'static [&'static str] {
-        match self {
-            Backend::Cuda => &["cuda", "cudart", "nvrtc", "m"],
-            Backend::OpenCl => &["OpenCL", "m"],
-            Backend::Multicore | Backend::Ispc => &["pthread", "m"],
-            Backend::Hip => &["hiprtc", "amdhip64"],
-            _ => &[],
-        }
-    }
-}
-
 #[cfg(feature = "build")]
 /// Generate the bindings and link the Futhark C code
 ///
@@ -111,20 +34,111 @@ pub fn build(
     backend: Backend,
     src: impl AsRef<std::path::Path>,
     dest: impl AsRef<std::path::Path>,
+) {
+    let selector = BackendSelector::new()
+        .with_backend(backend)
+        .with_cargo_features(false)
+        .with_env(false)
+        .with_probe(false)
+        .with_fallback(vec![]);
+    build_with(&selector, src, dest)
+}
+
+#[cfg(feature = "build")]
+/// Generate the bindings and link the Futhark C code, selecting the backend using `selector`
+///
+/// Each backend returned by `BackendSelector::preferences` is tried in order, if compiling
+/// or building the C code fails then the next backend is used. The selected backend is
+/// reported using `cargo:warning`
+///
+/// Naming overrides are loaded from `futhark-bindgen.toml` or the
+/// `[package.metadata.futhark-bindgen]` section of `Cargo.toml`, see `NamingConfig`
+pub fn build_with(
+    selector: &BackendSelector,
+    src: impl AsRef<std::path::Path>,
+    dest: impl AsRef<std::path::Path>,
 ) {
     use generate::DefaultNamer;
 
     let out = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
     let dest = std::path::PathBuf::from(&out).join(dest);
-    let lib = Compiler::new(backend, src)
-        .with_output_dir(out)
-        .compile()
-        .expect("Compilation failed");
+    let lib = compile_and_link(selector, src.as_ref(), |_| out.clone());
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let mut namer = DefaultNamer::default();
+    if let Some((naming, path)) =
+        NamingConfig::find(manifest_dir).expect("Invalid naming configuration")
+    {
+        println!("cargo:rerun-if-changed={}", path.display());
+        namer = namer.with_config(naming);
+    }
 
-    let mut config =
-        Config::new(&dest, DefaultNamer::default()).expect("Unable to configure codegen");
-    let mut gen = config.detect().expect("Invalid output language");
+    let mut config = Config::new(&dest, namer);
+    let mut gen = config.generator().expect("Invalid output language");
     gen.generate(&lib, &mut config)
         .expect("Code generation failed");
-    lib.link();
+}
+
+#[cfg(feature = "build")]
+/// Compile and link the Futhark C code without generating bindings
+///
+/// This is used together with `futhark-bindgen-macros`, which generates the bindings at
+/// compile time. The backend is selected using `selector` in the same way as `build_with`
+/// and returned so it can be checked against the one passed to the macro
+///
+/// The output is written to `Compiler::cache_dir` in `$OUT_DIR`, where the macro finds it
+/// instead of compiling the program again
+pub fn build_library(selector: &BackendSelector, src: impl AsRef<std::path::Path>) -> Backend {
+    let out = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let src = src.as_ref();
+    compile_and_link(selector, src, |backend| {
+        Compiler::cache_dir(&out, backend, src)
+    })
+    .manifest
+    .backend
+}
+
+#[cfg(feature = "build")]
+fn compile_and_link(
+    selector: &BackendSelector,
+    src: &std::path::Path,
+    out: impl Fn(Backend) -> std::path::PathBuf,
+) -> Package {
+    if selector.reads_env() {
+        println!("cargo:rerun-if-env-changed=FUTHARK_BACKEND");
+    }
+
+    let mut lib = None;
+    for backend in selector.preferences() {
+        let pkg = match Compiler::new(backend, src)
+            .with_output_dir(out(backend))
+            .compile()
+        {
+            Ok(pkg) => pkg,
+            Err(e) => {
+                println!(
+                    "cargo:warning=futhark-bindgen: compilation failed using the {} backend: {e:?}",
+                    backend.to_str()
+                );
+                continue;
+            }
+        };
+
+        if let Err(e) = pkg.try_link() {
+            println!(
+                "cargo:warning=futhark-bindgen: unable to build the {} backend: {e:?}",
+                backend.to_str()
+            );
+            continue;
+        }
+
+        println!(
+            "cargo:warning=futhark-bindgen: using the {} backend",
+            backend.to_str()
+        );
+        lib = Some(pkg);
+        break;
+    }
+
+    lib.expect("No usable Futhark backend")
 }
@@ -1,8 +1,8 @@
 use crate::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Scalar types
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum ElemType {
     /// Signed 8 bit integer
     #[serde(rename = "i8")]
@@ -72,20 +72,20 @@ impl ElemType {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Output {
     pub r#type: String,
     pub unique: bool,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Input {
     pub name: String,
     pub r#type: String,
     pub unique: bool,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Entry {
     pub cfun: String,
     pub outputs: Vec<Output>,
@@ -93,7 +93,7 @@ pub struct Entry {
     pub tuning_params: Vec<String>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ArrayOps {
     pub free: String,
     pub index: String,
@@ -104,7 +104,7 @@ pub struct ArrayOps {
     // pub values_raw: String,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ArrayType {
     pub ctype: String,
     pub rank: usize,
@@ -112,21 +112,21 @@ pub struct ArrayType {
     pub ops: ArrayOps,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct OpaqueOps {
     pub free: String,
     pub store: String,
     pub restore: String,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Field {
     pub name: String,
     pub project: String,
     pub r#type: String,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct OpaqueType {
     pub ctype: String,
     pub ops: OpaqueOps,
@@ -134,32 +134,35 @@ pub struct OpaqueType {
     pub options: OpaqueOptions,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Record {
     pub new: String,
     pub fields: Vec<Field>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Variant {
+    /// Constructor name, missing in older manifests
+    #[serde(default)]
+    pub name: String,
     pub construct: String,
     pub destruct: String,
     pub payload: Vec<String>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Sum {
     pub variant: String,
     pub variants: Vec<Variant>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RecordArray {
     pub zip: String,
     pub fields: Vec<Field>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct OpaqueArray {
     pub rank: usize,
     pub elemtype: String,
@@ -169,7 +172,7 @@ pub struct OpaqueArray {
     pub record: Option<RecordArray>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum OpaqueOptions {
     #[serde(rename = "record")]
     Record(Record),
@@ -182,7 +185,7 @@ pub enum OpaqueOptions {
     RecordArray(OpaqueArray),
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(tag = "kind")]
 pub enum Type {
     #[serde(rename = "array")]
@@ -200,7 +203,7 @@ impl Type {
 }
 
 /// A Rust encoding of the Futhark manifest file
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Manifest {
     pub backend: Backend,
     pub version: String,
@@ -216,3 +219,399 @@ impl Manifest {
         Ok(manifest)
     }
 }
+
+/// Parsed Futhark type, as found in manifest type names such as `[][]f32`, `(i32, [n]f32)`,
+/// `{x: f32, y: f32}` or `#some i64 | #none`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeExpr {
+    /// Scalar type
+    Scalar(ElemType),
+
+    /// Array with `rank` dimensions, `elem` is never an array
+    Array { rank: usize, elem: Box<TypeExpr> },
+
+    /// Tuple
+    Tuple(Vec<TypeExpr>),
+
+    /// Record, fields are kept in the order they appear
+    Record(Vec<(String, TypeExpr)>),
+
+    /// Sum type, each constructor has a name and a list of payload types
+    Sum(Vec<(String, Vec<TypeExpr>)>),
+
+    /// Named type, such as a type abbreviation or abstract type
+    Named(String),
+}
+
+impl TypeExpr {
+    /// Parse a Futhark type
+    pub fn parse(s: &str) -> Result<TypeExpr, Error> {
+        let mut parser = TypeParser { input: s, pos: 0 };
+        let t = parser.parse_type()?;
+        parser.skip_whitespace();
+        if parser.pos != s.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(t)
+    }
+
+    /// Returns the scalar type if `self` is a scalar
+    pub fn scalar(&self) -> Option<ElemType> {
+        match self {
+            TypeExpr::Scalar(t) => Some(*t),
+            _ => None,
+        }
+    }
+
+    /// Returns true for tuples, records and sum types
+    pub fn is_anonymous(&self) -> bool {
+        matches!(
+            self,
+            TypeExpr::Tuple(_) | TypeExpr::Record(_) | TypeExpr::Sum(_)
+        )
+    }
+}
+
+impl std::str::FromStr for TypeExpr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TypeExpr::parse(s)
+    }
+}
+
+impl std::fmt::Display for TypeExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TypeExpr::Scalar(t) => f.write_str(t.to_str()),
+            TypeExpr::Array { rank, elem } => {
+                for _ in 0..*rank {
+                    f.write_str("[]")?;
+                }
+                write!(f, "{elem}")
+            }
+            TypeExpr::Tuple(elems) => {
+                f.write_str("(")?;
+                for (i, t) in elems.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{t}")?;
+                }
+                f.write_str(")")
+            }
+            TypeExpr::Record(fields) => {
+                f.write_str("{")?;
+                for (i, (name, t)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{name}: {t}")?;
+                }
+                f.write_str("}")
+            }
+            TypeExpr::Sum(variants) => {
+                for (i, (name, payload)) in variants.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "#{name}")?;
+                    for t in payload {
+                        if matches!(t, TypeExpr::Sum(_)) {
+                            write!(f, " ({t})")?;
+                        } else {
+                            write!(f, " {t}")?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            TypeExpr::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+struct TypeParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> TypeParser<'a> {
+    fn error(&self, msg: &str) -> Error {
+        Error::InvalidManifest(format!(
+            "{msg} at position {} in type {:?}",
+            self.pos, self.input
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("expected {c:?}")));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn ident(&mut self) -> Result<&'a str, Error> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !(c.is_alphanumeric() || c == '_' || c == '\'' || c == '.') {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+        if start == self.pos {
+            return Err(self.error("expected identifier"));
+        }
+        Ok(&self.input[start..self.pos])
+    }
+
+    fn parse_type(&mut self) -> Result<TypeExpr, Error> {
+        self.skip_whitespace();
+        if self.peek() != Some('#') {
+            return self.parse_atom();
+        }
+
+        let mut variants = Vec::new();
+        loop {
+            self.expect('#')?;
+            let name = self.ident()?.to_string();
+            let mut payload = Vec::new();
+            loop {
+                self.skip_whitespace();
+                match self.peek() {
+                    None | Some('|') | Some(',') | Some(')') | Some('}') => break,
+                    _ => payload.push(self.parse_atom()?),
+                }
+            }
+            variants.push((name, payload));
+            if self.peek() != Some('|') {
+                break;
+            }
+            self.pos += 1;
+        }
+        Ok(TypeExpr::Sum(variants))
+    }
+
+    fn parse_atom(&mut self) -> Result<TypeExpr, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('*') => {
+                // Uniqueness annotations don't change the type
+                self.pos += 1;
+                self.parse_atom()
+            }
+            Some('[') => {
+                let mut rank = 0;
+                while self.peek() == Some('[') {
+                    // Skip size annotations, `[]` and `[n]` are the same type
+                    let Some(len) = self.input[self.pos..].find(']') else {
+                        return Err(self.error("unclosed '['"));
+                    };
+                    self.pos += len + 1;
+                    rank += 1;
+                    self.skip_whitespace();
+                }
+                match self.parse_atom()? {
+                    TypeExpr::Array { rank: inner, elem } => Ok(TypeExpr::Array {
+                        rank: rank + inner,
+                        elem,
+                    }),
+                    elem => Ok(TypeExpr::Array {
+                        rank,
+                        elem: Box::new(elem),
+                    }),
+                }
+            }
+            Some('(') => {
+                self.pos += 1;
+                let mut elems = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                    return Ok(TypeExpr::Tuple(elems));
+                }
+                loop {
+                    elems.push(self.parse_type()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(')') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        _ => return Err(self.error("expected ',' or ')'")),
+                    }
+                }
+                if elems.len() == 1 {
+                    // Parenthesized type
+                    return Ok(elems.remove(0));
+                }
+                Ok(TypeExpr::Tuple(elems))
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.pos += 1;
+                    return Ok(TypeExpr::Record(fields));
+                }
+                loop {
+                    let name = self.ident()?.to_string();
+                    self.expect(':')?;
+                    fields.push((name, self.parse_type()?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+                Ok(TypeExpr::Record(fields))
+            }
+            _ => {
+                let name = self.ident()?;
+                match serde_json::from_value(serde_json::Value::String(name.to_string())) {
+                    Ok(t) => Ok(TypeExpr::Scalar(t)),
+                    Err(_) => Ok(TypeExpr::Named(name.to_string())),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> TypeExpr {
+        TypeExpr::parse(s).unwrap_or_else(|e| panic!("unable to parse {s:?}: {e:?}"))
+    }
+
+    fn array(rank: usize, elem: TypeExpr) -> TypeExpr {
+        TypeExpr::Array {
+            rank,
+            elem: Box::new(elem),
+        }
+    }
+
+    #[test]
+    fn parse_arrays() {
+        assert_eq!(parse("[][]f32"), array(2, TypeExpr::Scalar(ElemType::F32)));
+        assert_eq!(parse("[n]f32"), array(1, TypeExpr::Scalar(ElemType::F32)));
+        assert_eq!(parse("[n][]f32"), parse("[][m]f32"));
+        assert_eq!(parse("[]point"), array(1, TypeExpr::Named("point".into())));
+    }
+
+    #[test]
+    fn parse_tuples() {
+        assert_eq!(
+            parse("(i32, [n]f32)"),
+            TypeExpr::Tuple(vec![
+                TypeExpr::Scalar(ElemType::I32),
+                array(1, TypeExpr::Scalar(ElemType::F32)),
+            ])
+        );
+        assert_eq!(parse("()"), TypeExpr::Tuple(vec![]));
+        assert_eq!(parse("(i32)"), TypeExpr::Scalar(ElemType::I32));
+    }
+
+    #[test]
+    fn parse_records() {
+        assert_eq!(
+            parse("{x: f32, y: f32}"),
+            TypeExpr::Record(vec![
+                ("x".into(), TypeExpr::Scalar(ElemType::F32)),
+                ("y".into(), TypeExpr::Scalar(ElemType::F32)),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_sums() {
+        assert_eq!(
+            parse("#some i64 | #none"),
+            TypeExpr::Sum(vec![
+                ("some".into(), vec![TypeExpr::Scalar(ElemType::I64)]),
+                ("none".into(), vec![]),
+            ])
+        );
+        assert_eq!(
+            parse("{a: #x i32 | #y, b: bool}"),
+            TypeExpr::Record(vec![
+                (
+                    "a".into(),
+                    TypeExpr::Sum(vec![
+                        ("x".into(), vec![TypeExpr::Scalar(ElemType::I32)]),
+                        ("y".into(), vec![]),
+                    ])
+                ),
+                ("b".into(), TypeExpr::Scalar(ElemType::Bool)),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_uniqueness() {
+        assert_eq!(parse("*[]i32"), parse("[]i32"));
+        assert_eq!(parse("(*[]i32, f32)"), parse("([]i32, f32)"));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for s in [
+            "f32",
+            "[][]f32",
+            "(i32, []f32)",
+            "{x: f32, y: f32}",
+            "#some i64 | #none",
+            "{a: #x i32 | #y, b: []bool}",
+            "#a (#b | #c) | #d",
+            "[]point",
+        ] {
+            let t = parse(s);
+            assert_eq!(t.to_string(), s);
+            assert_eq!(parse(&t.to_string()), t);
+        }
+        assert_eq!(parse("[n]f32").to_string(), "[]f32");
+    }
+
+    #[test]
+    fn parse_errors() {
+        for s in [
+            "[f32",
+            "[n",
+            "(i32, f32",
+            "{x: f32",
+            "{x f32}",
+            "f32 i32",
+            "[]f32)",
+            "#",
+            "",
+        ] {
+            assert!(
+                matches!(TypeExpr::parse(s), Err(Error::InvalidManifest(_))),
+                "{s:?} should be rejected"
+            );
+        }
+    }
+}
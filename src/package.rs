@@ -17,64 +17,179 @@ pub struct Package {
 }
 
 impl Package {
+    /// Compile `<name>.kernels.ispc` using `ispc`, returning the path to the object file
+    fn compile_ispc(&self) -> Result<std::path::PathBuf, Error> {
+        let kernels = self.c_file.with_extension("kernels.ispc");
+        let dest = kernels.with_extension("o");
+        let ok = std::process::Command::new("ispc")
+            .arg(&kernels)
+            .arg("-o")
+            .arg(&dest)
+            .arg("--pic")
+            .arg("--addressing=64")
+            .arg("--target=host")
+            .arg("-O3")
+            .status()?
+            .success();
+        if !ok {
+            return Err(Error::BuildFailed(format!(
+                "ispc failed to compile {}",
+                kernels.display()
+            )));
+        }
+        Ok(dest)
+    }
+
+    /// Copy the generated C files to `dir` and return the package using the copies, so
+    /// outputs in different directories can each include the C code by file name
+    ///
+    /// Returns the package unchanged if the files are already in `dir`
+    pub fn copy_to(&self, dir: impl AsRef<std::path::Path>) -> Result<Package, Error> {
+        let dir = dir.as_ref();
+        if self.c_file.parent() == Some(dir) {
+            return Ok(self.clone());
+        }
+
+        let copy = |file: &std::path::Path| -> Result<std::path::PathBuf, Error> {
+            let dest = dir.join(file.file_name().unwrap_or_default());
+            std::fs::copy(file, &dest)?;
+            Ok(dest)
+        };
+        let kernels = self.c_file.with_extension("kernels.ispc");
+        if kernels.exists() {
+            copy(&kernels)?;
+        }
+        Ok(Package {
+            manifest: self.manifest.clone(),
+            c_file: copy(&self.c_file)?,
+            h_file: copy(&self.h_file)?,
+            src: self.src.clone(),
+        })
+    }
+
+    /// The source file followed by every file it imports through `import "..."`, imports
+    /// that can't be found are skipped
+    pub fn sources(&self) -> Vec<std::path::PathBuf> {
+        compiler::sources(&self.src)
+    }
+
+    /// File name of the shared library built by `Package::build_shared`, for example
+    /// `libtest.so` for `test.fut` on Linux
+    pub fn shared_library_name(&self) -> String {
+        let stem = self
+            .c_file
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        format!(
+            "{}{stem}{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        )
+    }
+
+    /// Compile the package into a shared library in `dir`, named using
+    /// `Package::shared_library_name`, and return its path
+    ///
+    /// The C compiler is taken from the `CC` environment variable, defaulting to `cc`. This
+    /// is used by languages that load the library at runtime, such as Python
+    pub fn build_shared(
+        &self,
+        dir: impl AsRef<std::path::Path>,
+    ) -> Result<std::path::PathBuf, Error> {
+        let caps = self.manifest.backend.capabilities();
+        let output = dir.as_ref().join(self.shared_library_name());
+        let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        let mut cmd = std::process::Command::new(&cc);
+        cmd.args(caps.c_flags)
+            .arg("-shared")
+            .arg("-fPIC")
+            .arg("-o")
+            .arg(&output)
+            .arg(&self.c_file);
+
+        for step in caps.compile_steps {
+            match step {
+                CompileStep::Ispc => {
+                    cmd.arg(self.compile_ispc()?);
+                }
+            }
+        }
+
+        for lib in caps.link_libs {
+            if cfg!(target_os = "macos") && caps.macos_frameworks.contains(lib) {
+                cmd.arg("-framework").arg(lib);
+            } else {
+                cmd.arg(format!("-l{lib}"));
+            }
+        }
+
+        if !cmd.status()?.success() {
+            return Err(Error::BuildFailed(format!(
+                "{cc} failed to build {}",
+                output.display()
+            )));
+        }
+        Ok(output)
+    }
+
     #[cfg(feature = "build")]
-    fn build(&self, libname: &str) {
-        if self.manifest.backend == Backend::Ispc {
-            let kernels = self.c_file.with_extension("kernels.ispc");
-            let dest = kernels.with_extension("o");
-            std::process::Command::new("ispc")
-                .arg(&kernels)
-                .arg("-o")
-                .arg(&dest)
-                .arg("--pic")
-                .arg("--addressing=64")
-                .arg("--target=host")
-                .arg("-O3")
-                .status()
-                .expect("Unable to run ispc");
-
-            cc::Build::new()
-                .file(&self.c_file)
-                .object(&dest)
-                .flag("-fPIC")
-                .flag("-pthread")
-                .flag("-lm")
-                .flag("-std=c99")
-                .flag("-O3")
-                .extra_warnings(false)
-                .warnings(false)
-                .compile(libname);
-        } else {
-            cc::Build::new()
-                .flag("-std=c99")
-                .flag("-Wno-unused-parameter")
-                .flag("-O3")
-                .file(&self.c_file)
-                .extra_warnings(false)
-                .warnings(false)
-                .compile(libname);
+    fn build(&self, libname: &str) -> Result<(), Error> {
+        let caps = self.manifest.backend.capabilities();
+        let mut build = cc::Build::new();
+        build.file(&self.c_file);
+
+        for step in caps.compile_steps {
+            match step {
+                CompileStep::Ispc => {
+                    build.object(self.compile_ispc()?);
+                }
+            }
+        }
+
+        for flag in caps.c_flags {
+            build.flag(flag);
         }
+
+        build
+            .extra_warnings(false)
+            .warnings(false)
+            .try_compile(libname)
+            .map_err(|e| Error::BuildFailed(e.to_string()))
     }
+
     /// Link the package
     ///
     /// Note: This should only be used in `build.rs`
     #[cfg(feature = "build")]
     pub fn link(&self) {
+        self.try_link().expect("Unable to build Futhark package")
+    }
+
+    /// Link the package, returning an error if the C code can't be built
+    ///
+    /// Note: This should only be used in `build.rs`
+    #[cfg(feature = "build")]
+    pub fn try_link(&self) -> Result<(), Error> {
         let project = std::env::var("CARGO_PKG_NAME").unwrap();
         let name = format!("futhark_generate_{project}");
-        self.build(&name);
+        self.build(&name)?;
 
-        println!("cargo:rerun-if-changed={}", self.src.display());
+        for src in self.sources() {
+            println!("cargo:rerun-if-changed={}", src.display());
+        }
         println!("cargo:rustc-link-lib={name}");
 
-        let libs = self.manifest.backend.required_c_libs();
+        let caps = self.manifest.backend.capabilities();
 
-        for lib in libs {
-            if cfg!(target_os = "macos") && lib == &"OpenCL" {
+        for lib in caps.link_libs {
+            if cfg!(target_os = "macos") && caps.macos_frameworks.contains(lib) {
                 println!("cargo:rustc-link-lib=framework={}", lib);
             } else {
                 println!("cargo:rustc-link-lib={}", lib);
             }
         }
+
+        Ok(())
     }
 }
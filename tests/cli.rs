@@ -0,0 +1,122 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const MANIFEST: &str = r#"{
+    "backend": "c",
+    "version": "0.25.0",
+    "entry_points": {
+        "add": {
+            "cfun": "futhark_entry_add",
+            "inputs": [{"name": "x", "type": "i32", "unique": false}],
+            "outputs": [{"type": "i32", "unique": false}],
+            "tuning_params": []
+        }
+    },
+    "types": {}
+}"#;
+
+/// Create an empty directory for a test
+fn test_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("futhark-bindgen-cli-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_script(path: &Path, script: &str) {
+    std::fs::write(path, script).unwrap();
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Write `test.fut` and a fake `futhark` executable to `dir`, the compiler writes the
+/// manifest and empty C files
+fn setup(dir: &Path) -> (PathBuf, PathBuf) {
+    let src = dir.join("test.fut");
+    std::fs::write(&src, "entry add (x: i32) = x + 1\n").unwrap();
+    std::fs::write(dir.join("manifest.json"), MANIFEST).unwrap();
+    let compiler = dir.join("futhark");
+    write_script(
+        &compiler,
+        &format!(
+            "#!/bin/sh\n\
+             while [ $# -gt 0 ]; do case $1 in -o) out=$2; shift;; esac; shift; done\n\
+             cp {} \"$out.json\"\n\
+             echo '/* c */' > \"$out.c\"\n\
+             echo '/* h */' > \"$out.h\"\n",
+            dir.join("manifest.json").display()
+        ),
+    );
+    (src, compiler)
+}
+
+fn bindgen(dir: &Path, compiler: &Path, args: &[&dyn AsRef<std::ffi::OsStr>]) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_futhark-bindgen"));
+    cmd.current_dir(dir).arg("run");
+    for arg in args {
+        cmd.arg(arg);
+    }
+    cmd.arg("--compiler").arg(compiler);
+    cmd
+}
+
+#[test]
+fn outputs_in_different_directories() {
+    let dir = test_dir("outputs");
+    let (src, compiler) = setup(&dir);
+    let status = bindgen(&dir, &compiler, &[&src, &"a/lib.rs", &"b/lib.go"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    for out in ["a", "b"] {
+        for file in ["test.c", "test.h"] {
+            assert!(dir.join(out).join(file).is_file(), "missing {out}/{file}");
+        }
+    }
+    let go = std::fs::read_to_string(dir.join("b/lib.go")).unwrap();
+    assert!(go.contains("#include \"test.h\""));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn plugins_need_explicit_language() {
+    let dir = test_dir("plugin");
+    let (src, compiler) = setup(&dir);
+    let bin = dir.join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    write_script(
+        &bin.join("futhark-bindgen-gen-txt"),
+        &format!(
+            "#!/bin/sh\ntouch {}\necho '{{\"files\": {{\"out.txt\": \"generated\"}}}}'\n",
+            dir.join("ran").display()
+        ),
+    );
+    let path = std::env::join_paths(
+        std::iter::once(bin).chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+
+    let output = bindgen(&dir, &compiler, &[&src, &"out.txt"])
+        .env("PATH", &path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("UnknownLanguage"));
+    assert!(!dir.join("ran").exists());
+
+    let status = bindgen(&dir, &compiler, &[&src, &"out.txt", &"--lang", &"txt"])
+        .env("PATH", &path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(dir.join("ran").exists());
+    assert_eq!(
+        std::fs::read_to_string(dir.join("out.txt")).unwrap(),
+        "generated"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}